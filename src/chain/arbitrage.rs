@@ -0,0 +1,331 @@
+use crate::{
+    chain::{
+        compute_units::{ComputeUnitEstimator, RouteShape},
+        constants::sol_mint,
+        dex_pool::DexPool,
+        pools::{MintPoolData, PoolKind},
+        route::{RouteLeg, RoutePlan},
+    },
+    config::Config,
+    dex::quote::{SwapDirection, SwapQuote},
+};
+use solana_sdk::pubkey::Pubkey;
+
+/// Fee for the transaction's single signature
+pub const BASE_FEE_LAMPORTS: u64 = 5_000;
+
+/// Smallest trade the search considers; below this quotes round to nothing
+pub const MIN_TRADE_LAMPORTS: u64 = 100_000;
+
+const MICRO_LAMPORTS_PER_LAMPORT: u128 = 1_000_000;
+
+/// Width of the interval at which the search switches to checking every amount left
+const SEARCH_TOLERANCE: u64 = 4;
+
+/// What landing one arbitrage transaction costs regardless of its size
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExecutionCosts {
+    /// Limit the priority fee is paid on. `from_config` takes the fallback limit, and planners
+    /// swap in the limit learned for each route shape.
+    pub compute_unit_limit: u32,
+    /// Priority fee in micro-lamports per compute unit
+    pub compute_unit_price: u64,
+    pub tip_lamports: u64,
+}
+
+impl ExecutionCosts {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            compute_unit_limit: config.bot.compute_unit_limit,
+            compute_unit_price: config.spam.as_ref().map_or(1000, |s| s.compute_unit_price),
+            tip_lamports: config.bot.tip_lamports,
+        }
+    }
+
+    /// The same costs for a transaction sent with `compute_unit_limit`
+    pub fn with_compute_unit_limit(&self, compute_unit_limit: u32) -> Self {
+        Self {
+            compute_unit_limit,
+            ..*self
+        }
+    }
+
+    /// Priority fee for the full compute unit limit, rounded up as the runtime charges it
+    pub fn priority_fee_lamports(&self) -> u64 {
+        let micro_lamports = self.compute_unit_limit as u128 * self.compute_unit_price as u128;
+        micro_lamports
+            .div_ceil(MICRO_LAMPORTS_PER_LAMPORT)
+            .try_into()
+            .unwrap_or(u64::MAX)
+    }
+
+    pub fn total_lamports(&self) -> u64 {
+        BASE_FEE_LAMPORTS
+            .saturating_add(self.priority_fee_lamports())
+            .saturating_add(self.tip_lamports)
+    }
+}
+
+/// Buy the token with SOL in one pool and sell it back for SOL in another, sized for the
+/// largest net profit
+#[derive(Debug, Clone)]
+pub struct ArbitragePlan {
+    pub token_mint: Pubkey,
+    pub buy_pool: Pubkey,
+    pub buy_kind: PoolKind,
+    pub sell_pool: Pubkey,
+    pub sell_kind: PoolKind,
+    /// Lamports sold into the buy pool
+    pub amount_in: u64,
+    pub buy_quote: SwapQuote,
+    pub sell_quote: SwapQuote,
+    /// Lamports returned above `amount_in`, after both pools' fees
+    pub gross_profit: u64,
+    /// `ExecutionCosts::total_lamports` at planning time
+    pub execution_cost: u64,
+    /// Compute unit limit `execution_cost` was priced at
+    pub compute_unit_limit: u32,
+    /// `gross_profit` less `execution_cost`, in lamports
+    pub expected_profit: u64,
+}
+
+impl From<&ArbitragePlan> for RoutePlan {
+    /// The plan as a SOL route of its buy leg then its sell leg
+    fn from(plan: &ArbitragePlan) -> Self {
+        let sol_mint = sol_mint();
+        RoutePlan {
+            base_mint: sol_mint,
+            amount_in: plan.amount_in,
+            legs: vec![
+                RouteLeg {
+                    pool: plan.buy_pool,
+                    kind: plan.buy_kind,
+                    input_mint: sol_mint,
+                    output_mint: plan.token_mint,
+                    direction: SwapDirection::SolToToken,
+                    quote: plan.buy_quote,
+                },
+                RouteLeg {
+                    pool: plan.sell_pool,
+                    kind: plan.sell_kind,
+                    input_mint: plan.token_mint,
+                    output_mint: sol_mint,
+                    direction: SwapDirection::TokenToSol,
+                    quote: plan.sell_quote,
+                },
+            ],
+            gross_profit: plan.gross_profit,
+            execution_cost: plan.execution_cost,
+            compute_unit_limit: plan.compute_unit_limit,
+            expected_profit: plan.expected_profit,
+        }
+    }
+}
+
+/// Both legs of buying with `amount_in` lamports on `buy` and selling all of it on `sell`
+fn round_trip(
+    buy: &dyn DexPool,
+    sell: &dyn DexPool,
+    amount_in: u64,
+) -> Option<(SwapQuote, SwapQuote)> {
+    let buy_quote = buy.quote(amount_in, SwapDirection::SolToToken).ok()?;
+    if buy_quote.amount_out == 0 {
+        return None;
+    }
+    let sell_quote = sell
+        .quote(buy_quote.amount_out, SwapDirection::TokenToSol)
+        .ok()?;
+    Some((buy_quote, sell_quote))
+}
+
+/// Lamports gained by the round trip, or `None` when either leg cannot be quoted
+fn round_trip_profit(buy: &dyn DexPool, sell: &dyn DexPool, amount_in: u64) -> Option<i128> {
+    round_trip(buy, sell, amount_in)
+        .map(|(_, sell_quote)| sell_quote.amount_out as i128 - amount_in as i128)
+}
+
+/// The amount in `low..=high` that maximizes `profit`, by golden-section search.
+///
+/// `profit` must rise to a single peak and fall after it, which holds for a trade through any
+/// supported curve since each pays out less for every additional unit in. Amounts a pool
+/// cannot fill should score `i128::MIN`, which pulls the search back inside the liquidity.
+pub fn maximize_profit(low: u64, high: u64, profit: impl Fn(u64) -> i128) -> u64 {
+    let (mut low, mut high) = (low, high.max(low));
+    while high - low > SEARCH_TOLERANCE {
+        // 0.382 of the interval from either end
+        let step = ((high - low) as u128 * 382 / 1000) as u64;
+        let (left, right) = (low + step, high - step);
+        if profit(left) < profit(right) {
+            low = left;
+        } else {
+            high = right;
+        }
+    }
+    (low..=high)
+        .max_by_key(|&amount| profit(amount))
+        .unwrap_or(low)
+}
+
+/// Size a two-pool arbitrage between `MIN_TRADE_LAMPORTS` and `max_amount_in` with
+/// `maximize_profit`. Returns `None` unless the best size clears `costs`.
+pub fn optimize_two_pool(
+    buy: &dyn DexPool,
+    sell: &dyn DexPool,
+    max_amount_in: u64,
+    costs: &ExecutionCosts,
+) -> Option<ArbitragePlan> {
+    if max_amount_in < MIN_TRADE_LAMPORTS {
+        return None;
+    }
+    let amount_in = maximize_profit(MIN_TRADE_LAMPORTS, max_amount_in, |amount_in| {
+        round_trip_profit(buy, sell, amount_in).unwrap_or(i128::MIN)
+    });
+    let (buy_quote, sell_quote) = round_trip(buy, sell, amount_in)?;
+    let gross_profit = sell_quote.amount_out.checked_sub(amount_in)?;
+    let execution_cost = costs.total_lamports();
+    let expected_profit = gross_profit.checked_sub(execution_cost)?;
+    if expected_profit == 0 {
+        return None;
+    }
+
+    Some(ArbitragePlan {
+        token_mint: buy.token_mint(),
+        buy_pool: buy.address(),
+        buy_kind: buy.kind(),
+        sell_pool: sell.address(),
+        sell_kind: sell.kind(),
+        amount_in,
+        buy_quote,
+        sell_quote,
+        gross_profit,
+        execution_cost,
+        compute_unit_limit: costs.compute_unit_limit,
+        expected_profit,
+    })
+}
+
+/// The most profitable two-pool plan over every ordered pair of SOL pools in `pool_data`,
+/// priced at the compute unit limit of the executor instruction over those pools
+pub fn best_two_pool_plan(
+    pool_data: &MintPoolData,
+    max_amount_in: u64,
+    costs: &ExecutionCosts,
+    compute_units: &ComputeUnitEstimator,
+) -> Option<ArbitragePlan> {
+    let costs = &costs
+        .with_compute_unit_limit(compute_units.limit_or_fallback(&RouteShape::executor(pool_data)));
+    let sol_mint = sol_mint();
+    let sol_pools: Vec<&dyn DexPool> = pool_data
+        .pools()
+        .filter(|pool| pool.base_mint() == sol_mint)
+        .collect();
+
+    sol_pools
+        .iter()
+        .flat_map(|buy| sol_pools.iter().map(move |sell| (*buy, *sell)))
+        .filter(|(buy, sell)| buy.address() != sell.address())
+        .filter_map(|(buy, sell)| optimize_two_pool(buy, sell, max_amount_in, costs))
+        .max_by_key(|plan| plan.expected_profit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::dex_pool::test_pool::TestPool;
+
+    const SOL_RESERVE: u64 = 1_000_000_000_000;
+    const TOKEN_RESERVE: u64 = 1_000_000_000_000;
+    const FEE_BASIS_POINTS: u64 = 30;
+
+    /// Tokens at 1 SOL in the buy pool and 1.1 SOL in the sell pool
+    fn pools() -> (TestPool, TestPool) {
+        let token_mint = Pubkey::new_unique();
+        (
+            TestPool::new(
+                token_mint,
+                sol_mint(),
+                SOL_RESERVE,
+                TOKEN_RESERVE,
+                FEE_BASIS_POINTS,
+            ),
+            TestPool::new(
+                token_mint,
+                sol_mint(),
+                SOL_RESERVE * 11 / 10,
+                TOKEN_RESERVE,
+                FEE_BASIS_POINTS,
+            ),
+        )
+    }
+
+    #[test]
+    fn maximize_profit_finds_the_peak() {
+        let peak = 1_234_567;
+        let profit = |amount: u64| -((amount as i128 - peak as i128).pow(2));
+        assert_eq!(maximize_profit(0, 10_000_000, profit), peak);
+        assert_eq!(maximize_profit(0, 1_000, profit), 1_000);
+        // Amounts past the pool's liquidity pull the search back
+        let capped = |amount: u64| {
+            if amount > 500_000 {
+                i128::MIN
+            } else {
+                profit(amount)
+            }
+        };
+        assert_eq!(maximize_profit(0, 10_000_000, capped), 500_000);
+    }
+
+    #[test]
+    fn optimize_two_pool_matches_the_closed_form() {
+        let (buy, sell) = pools();
+        let plan =
+            optimize_two_pool(&buy, &sell, u64::MAX / 4, &ExecutionCosts::default()).unwrap();
+
+        // Both legs together pay a*x / (b + c*x) for x in, which peaks at (sqrt(a*b) - b) / c
+        let gamma = 1.0 - FEE_BASIS_POINTS as f64 / 10_000.0;
+        let (x_buy, y_buy) = (buy.base_reserve as f64, buy.token_reserve as f64);
+        let (x_sell, y_sell) = (sell.base_reserve as f64, sell.token_reserve as f64);
+        let a = gamma * gamma * y_buy * x_sell;
+        let b = x_buy * y_sell;
+        let c = gamma * y_sell + gamma * gamma * y_buy;
+        let optimum = ((a * b).sqrt() - b) / c;
+        let max_profit = a * optimum / (b + c * optimum) - optimum;
+
+        assert!(
+            (plan.amount_in as f64 - optimum).abs() < optimum * 1e-4,
+            "{} vs {}",
+            plan.amount_in,
+            optimum
+        );
+        assert!(
+            (plan.gross_profit as f64 - max_profit).abs() <= 2.0,
+            "{} vs {}",
+            plan.gross_profit,
+            max_profit
+        );
+        assert_eq!(plan.expected_profit, plan.gross_profit - BASE_FEE_LAMPORTS);
+        assert_eq!(plan.buy_pool, buy.address);
+        assert_eq!(plan.sell_pool, sell.address);
+    }
+
+    #[test]
+    fn optimize_two_pool_respects_limits_and_costs() {
+        let (buy, sell) = pools();
+        let costs = ExecutionCosts::default();
+
+        // Profit still rises at the cap, so the search ends as profitable as the cap itself
+        let plan = optimize_two_pool(&buy, &sell, 1_000_000_000, &costs).unwrap();
+        assert_eq!(
+            plan.gross_profit as i128,
+            round_trip_profit(&buy, &sell, 1_000_000_000).unwrap()
+        );
+
+        assert!(optimize_two_pool(&sell, &buy, u64::MAX / 4, &costs).is_none());
+        assert!(optimize_two_pool(&buy, &sell, MIN_TRADE_LAMPORTS - 1, &costs).is_none());
+        let costs = ExecutionCosts {
+            tip_lamports: 2_000_000_000,
+            ..costs
+        };
+        assert!(optimize_two_pool(&buy, &sell, u64::MAX / 4, &costs).is_none());
+    }
+}
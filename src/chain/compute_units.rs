@@ -0,0 +1,147 @@
+use crate::{
+    chain::{
+        pools::{MintPoolData, PoolKind},
+        route::RoutePlan,
+    },
+    config::Config,
+};
+use std::{collections::HashMap, sync::Mutex};
+
+/// Largest compute unit limit a transaction may request
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// The DEX legs a transaction swaps through. Transactions of one shape consume about the same
+/// compute whatever their amounts.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RouteShape {
+    executor: bool,
+    kinds: Vec<PoolKind>,
+}
+
+impl RouteShape {
+    /// The executor instruction, which passes through every pool of the mint
+    pub fn executor(mint_pool_data: &MintPoolData) -> Self {
+        RouteShape {
+            executor: true,
+            kinds: mint_pool_data.pools().map(|pool| pool.kind()).collect(),
+        }
+    }
+
+    /// `plan` sent as each pool's own swap instruction
+    pub fn native(plan: &RoutePlan) -> Self {
+        Self::native_legs(plan.legs.iter().map(|leg| leg.kind))
+    }
+
+    /// Swap instructions through pools of `kinds` in sequence
+    pub fn native_legs(kinds: impl IntoIterator<Item = PoolKind>) -> Self {
+        RouteShape {
+            executor: false,
+            kinds: kinds.into_iter().collect(),
+        }
+    }
+}
+
+/// Compute unit limits learned from simulating each route shape
+#[derive(Debug)]
+pub struct ComputeUnitEstimator {
+    /// Limit for a shape that has not been simulated yet
+    fallback_limit: u32,
+    /// Headroom over the simulated units, in percent
+    margin_percent: u32,
+    limits: Mutex<HashMap<RouteShape, u32>>,
+}
+
+impl ComputeUnitEstimator {
+    pub fn new(fallback_limit: u32, margin_percent: u32) -> Self {
+        ComputeUnitEstimator {
+            fallback_limit,
+            margin_percent,
+            limits: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(
+            config.bot.compute_unit_limit,
+            config.bot.compute_unit_margin_percent,
+        )
+    }
+
+    pub fn fallback_limit(&self) -> u32 {
+        self.fallback_limit
+    }
+
+    /// The limit learned for `shape`, if it has been simulated
+    pub fn limit(&self, shape: &RouteShape) -> Option<u32> {
+        self.limits.lock().unwrap().get(shape).copied()
+    }
+
+    /// The limit a transaction of `shape` would be sent with now
+    pub fn limit_or_fallback(&self, shape: &RouteShape) -> u32 {
+        self.limit(shape).unwrap_or(self.fallback_limit)
+    }
+
+    /// Learn `shape`'s limit from a simulation that consumed `units_consumed`
+    pub fn record(&self, shape: &RouteShape, units_consumed: u64) -> u32 {
+        let limit = (units_consumed * (100 + self.margin_percent as u64) / 100)
+            .min(MAX_COMPUTE_UNIT_LIMIT as u64) as u32;
+        self.limits.lock().unwrap().insert(shape.clone(), limit);
+        limit
+    }
+
+    /// Drop `shape`'s limit so the next transaction of that shape simulates it again
+    pub fn forget(&self, shape: &RouteShape) {
+        self.limits.lock().unwrap().remove(shape);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shape(kinds: &[PoolKind]) -> RouteShape {
+        RouteShape::native_legs(kinds.iter().copied())
+    }
+
+    #[test]
+    fn records_limits_with_margin() {
+        let estimator = ComputeUnitEstimator::new(600_000, 15);
+        let swap = shape(&[PoolKind::Raydium, PoolKind::Whirlpool]);
+        assert_eq!(estimator.fallback_limit(), 600_000);
+        assert_eq!(estimator.limit(&swap), None);
+        assert_eq!(estimator.limit_or_fallback(&swap), 600_000);
+
+        assert_eq!(estimator.record(&swap, 100_000), 115_000);
+        assert_eq!(estimator.limit(&swap), Some(115_000));
+        assert_eq!(estimator.limit_or_fallback(&swap), 115_000);
+        // The margin rounds down
+        assert_eq!(estimator.record(&swap, 99_999), 114_998);
+        // Other shapes, including the same pools in another order, stay unlearned
+        let reversed = shape(&[PoolKind::Whirlpool, PoolKind::Raydium]);
+        assert_eq!(estimator.limit(&reversed), None);
+    }
+
+    #[test]
+    fn caps_limits_at_the_runtime_maximum() {
+        let estimator = ComputeUnitEstimator::new(600_000, 15);
+        let swap = shape(&[PoolKind::Raydium]);
+        assert_eq!(estimator.record(&swap, 1_300_000), MAX_COMPUTE_UNIT_LIMIT);
+        assert_eq!(
+            estimator.record(&swap, u64::MAX / 200),
+            MAX_COMPUTE_UNIT_LIMIT
+        );
+        assert_eq!(estimator.limit(&swap), Some(MAX_COMPUTE_UNIT_LIMIT));
+    }
+
+    #[test]
+    fn forgets_limits() {
+        let estimator = ComputeUnitEstimator::new(600_000, 0);
+        let swap = shape(&[PoolKind::Raydium]);
+        assert_eq!(estimator.record(&swap, 80_000), 80_000);
+        estimator.forget(&swap);
+        assert_eq!(estimator.limit(&swap), None);
+        assert_eq!(estimator.limit_or_fallback(&swap), 600_000);
+        // Forgetting an unlearned shape is harmless
+        estimator.forget(&swap);
+    }
+}
@@ -6,3 +6,14 @@ pub const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
 pub fn sol_mint() -> Pubkey {
     Pubkey::from_str(SOL_MINT).unwrap()
 }
+
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    6, 221, 246, 225, 215, 101, 161, 147, 217, 203, 225, 70, 206, 235, 121, 172, 28, 180, 134, 244,
+    64, 118, 252, 1, 16, 241, 37, 236, 114, 157, 18, 16,
+]);
+
+pub const MEMO_PROGRAM_ID: &str = "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr";
+
+pub fn memo_program_id() -> Pubkey {
+    Pubkey::from_str(MEMO_PROGRAM_ID).unwrap()
+}
@@ -0,0 +1,2662 @@
+use crate::{
+    chain::{
+        constants::{memo_program_id, TOKEN_2022_PROGRAM_ID},
+        pools::{
+            DlmmPool, MeteoraDAmmPool, MeteoraDAmmV2Pool, PoolKind, PumpPool, RaydiumClmmPool,
+            RaydiumCpPool, RaydiumPool, SolfiPool, VertigoPool, WhirlpoolPool,
+        },
+        token_fetch::{mint_supply, token_account_amount, AccountMap},
+    },
+    dex::{
+        meteora::{
+            constants::{
+                damm_program_id, damm_v2_event_authority, damm_v2_pool_authority,
+                damm_v2_program_id, dlmm_event_authority, dlmm_program_id, vault_program_id,
+            },
+            damm_info::{vault_lp_mint, DammInfo, DammVaultInfo},
+            damm_quote::DammVaultSide,
+            dammv2_info::DammV2Pool,
+            dlmm_info::{bin_array_address, bin_id_to_bin_array_index, BinArray, DlmmInfo},
+            instruction::{
+                self as meteora_instruction, DammSwapAccounts, DammV2SwapAccounts, DlmmSwapAccounts,
+            },
+        },
+        pump::{
+            amm_info::{
+                fee_config_address, global_config_address, global_volume_accumulator_address,
+                pool_market_cap, user_volume_accumulator_address, PumpAmmInfo, PumpFeeConfig,
+                PumpGlobalConfig,
+            },
+            constants::{pump_event_authority, pump_program_id},
+            instruction::{self as pump_instruction, PumpSwapAccounts},
+            quote::PumpFees,
+        },
+        quote::{SwapDirection, SwapQuote},
+        raydium::{
+            amm_info::RaydiumAmmInfo,
+            clmm_info::{
+                compute_tick_array_start_index, get_tick_array_pubkey, tick_count, AmmConfig,
+                PoolState, TickArrayBitmapExtension, TickArrayState, POOL_TICK_ARRAY_BITMAP_SEED,
+            },
+            constants::*,
+            cp_amm_info::{RaydiumCpAmmConfig, RaydiumCpAmmInfo},
+            instruction::{
+                self as raydium_instruction, ClmmSwapV2Accounts, SwapBaseInAccounts,
+                SwapBaseInputAccounts,
+            },
+            market_info::MarketInfo,
+        },
+        solfi::{
+            constants::solfi_program_id,
+            info::SolfiInfo,
+            instruction::{self as solfi_instruction, SolfiSwapAccounts},
+        },
+        vertigo::{
+            constants::vertigo_program_id,
+            info::VertigoPool as VertigoState,
+            instruction::{self as vertigo_instruction, VertigoSwapAccounts},
+            utils::derive_vault_address,
+        },
+        whirlpool::{
+            constants::whirlpool_program_id,
+            instruction::{self as whirlpool_instruction, SwapV2Accounts},
+            state::{TickArray, Whirlpool as WhirlpoolState, TICK_ARRAY_SIZE},
+            tick_sequence::TickArraySequence,
+            update_tick_array_accounts_for_onchain,
+        },
+    },
+};
+use anyhow::{anyhow, Result};
+use solana_sdk::{
+    account::{from_account, Account},
+    clock::Clock,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    sysvar,
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use std::fmt;
+
+/// A pool of any supported DEX that trades the configured mint against another mint, SOL for
+/// most pools. Fields and directions named after SOL refer to that other side.
+///
+/// A pool starts out knowing only its own address and learns the rest from the accounts it is
+/// updated with. `accounts_to_fetch` names what the next `update` reads, which grows once the
+/// pool state has been decoded (vaults, tick arrays, bin arrays).
+pub trait DexPool: DexPoolClone + fmt::Debug + Send + Sync {
+    fn kind(&self) -> PoolKind;
+
+    /// Address of the pool state account
+    fn address(&self) -> Pubkey;
+
+    /// The configured mint
+    fn token_mint(&self) -> Pubkey;
+
+    /// The mint the pool trades `token_mint` against
+    fn base_mint(&self) -> Pubkey;
+
+    /// Accounts `update` reads, leaving out addresses the pool has not learned yet
+    fn accounts_to_fetch(&self) -> Vec<Pubkey>;
+
+    /// Refresh the pool from fetched accounts. Addresses decoded before a missing account is
+    /// reached are kept, so a failed update can still widen `accounts_to_fetch`.
+    fn update(&mut self, accounts: &AccountMap) -> Result<()>;
+
+    /// Quote selling exactly `amount_in` in `direction` against the last update
+    fn quote(&self, amount_in: u64, direction: SwapDirection) -> Result<SwapQuote>;
+
+    /// Accounts the executor program reads for this pool, in the order it expects them
+    fn swap_account_metas(&self, wallet: &Pubkey) -> Vec<AccountMeta>;
+
+    /// Token programs that own `token_mint` and `base_mint`
+    fn token_programs(&self) -> (Pubkey, Pubkey);
+
+    /// The pool program's own instruction selling exactly `amount_in` in `direction` from the
+    /// wallet's associated token accounts, failing unless `minimum_amount_out` comes back
+    fn swap_instruction(
+        &self,
+        wallet: &Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        direction: SwapDirection,
+    ) -> Result<Instruction>;
+}
+
+/// Lets `MintPoolData` clone its boxed pools
+pub trait DexPoolClone {
+    fn clone_box(&self) -> Box<dyn DexPool>;
+}
+
+impl<T: DexPool + Clone + 'static> DexPoolClone for T {
+    fn clone_box(&self) -> Box<dyn DexPool> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn DexPool> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// `keys` without the ones still at their default
+fn known(keys: impl IntoIterator<Item = Pubkey>) -> Vec<Pubkey> {
+    keys.into_iter()
+        .filter(|pubkey| *pubkey != Pubkey::default())
+        .collect()
+}
+
+fn account<'a>(accounts: &'a AccountMap, pubkey: &Pubkey, name: &str) -> Result<&'a Account> {
+    accounts
+        .get(pubkey)
+        .ok_or_else(|| anyhow!("{} account {} not found", name, pubkey))
+}
+
+/// The pool state account, which must be owned by the program of `kind`
+fn pool_account<'a>(
+    accounts: &'a AccountMap,
+    pool: &Pubkey,
+    kind: PoolKind,
+) -> Result<&'a Account> {
+    let account = accounts
+        .get(pool)
+        .ok_or_else(|| anyhow!("Pool account not found"))?;
+
+    let program_id = kind.program_id();
+    if account.owner != program_id {
+        return Err(anyhow!(
+            "{} pool account is not owned by {}. Actual owner: {}",
+            kind,
+            program_id,
+            account.owner
+        ));
+    }
+    Ok(account)
+}
+
+/// Token balance of `vault`
+fn vault_amount(accounts: &AccountMap, vault: &Pubkey) -> Result<u64> {
+    token_account_amount(&account(accounts, vault, "Vault")?.data)
+}
+
+fn clock(accounts: &AccountMap) -> Result<Clock> {
+    from_account(account(accounts, &sysvar::clock::ID, "Clock")?)
+        .ok_or_else(|| anyhow!("Invalid clock sysvar account"))
+}
+
+/// Token program that owns `mint`
+fn mint_program(accounts: &AccountMap, mint: &Pubkey) -> Result<Pubkey> {
+    let owner = account(accounts, mint, "Mint")?.owner;
+    if owner != spl_token::ID && owner != TOKEN_2022_PROGRAM_ID {
+        return Err(anyhow!(
+            "Mint {} is owned by {}, not a token program",
+            mint,
+            owner
+        ));
+    }
+    Ok(owner)
+}
+
+/// The wallet's associated token accounts for `token_mint` and `base_mint`
+fn wallet_token_accounts(pool: &dyn DexPool, wallet: &Pubkey) -> Result<(Pubkey, Pubkey)> {
+    if pool.base_mint() == Pubkey::default() {
+        return Err(anyhow!(
+            "{} pool {} has not been loaded",
+            pool.kind(),
+            pool.address()
+        ));
+    }
+    let (token_program, base_program) = pool.token_programs();
+    Ok((
+        get_associated_token_address_with_program_id(wallet, &pool.token_mint(), &token_program),
+        get_associated_token_address_with_program_id(wallet, &pool.base_mint(), &base_program),
+    ))
+}
+
+/// `(input, output)` of a swap in `direction` between the token side and the base side
+fn in_out<T>(direction: SwapDirection, token_side: T, base_side: T) -> (T, T) {
+    match direction {
+        SwapDirection::SolToToken => (base_side, token_side),
+        SwapDirection::TokenToSol => (token_side, base_side),
+    }
+}
+
+/// Order a token side and a base side the way the pool does, given whether the token is
+/// the pool's first mint
+fn pool_order<T>(token_is_first: bool, token_side: T, base_side: T) -> (T, T) {
+    if token_is_first {
+        (token_side, base_side)
+    } else {
+        (base_side, token_side)
+    }
+}
+
+/// Whether a swap in `direction` sells the pool's first mint
+fn sells_first(direction: SwapDirection, token_is_first: bool) -> bool {
+    (direction == SwapDirection::TokenToSol) == token_is_first
+}
+
+/// Check that a pool trades the configured mint
+fn ensure_pair(
+    mint: &Pubkey,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+    kind: PoolKind,
+    pool: &Pubkey,
+) -> Result<()> {
+    if mint != mint_a && mint != mint_b {
+        return Err(anyhow!(
+            "Mint {} is not present in {} pool {}",
+            mint,
+            kind,
+            pool
+        ));
+    }
+
+    Ok(())
+}
+
+impl DexPool for RaydiumPool {
+    fn kind(&self) -> PoolKind {
+        PoolKind::Raydium
+    }
+
+    fn address(&self) -> Pubkey {
+        self.pool
+    }
+
+    fn token_mint(&self) -> Pubkey {
+        self.token_mint
+    }
+
+    fn base_mint(&self) -> Pubkey {
+        self.base_mint
+    }
+
+    fn accounts_to_fetch(&self) -> Vec<Pubkey> {
+        known([self.pool, self.token_vault, self.sol_vault, self.market])
+    }
+
+    /// Takes both vault balances net of the pool's pending PnL
+    fn update(&mut self, accounts: &AccountMap) -> Result<()> {
+        let data = &pool_account(accounts, &self.pool, self.kind())?.data;
+        let amm_info = RaydiumAmmInfo::load_checked(data)?;
+        ensure_pair(
+            &self.token_mint,
+            &amm_info.coin_mint,
+            &amm_info.pc_mint,
+            self.kind(),
+            &self.pool,
+        )?;
+        if !amm_info.swap_enabled() {
+            return Err(anyhow!("Swaps are disabled (status {})", amm_info.status));
+        }
+
+        self.token_is_coin = self.token_mint == amm_info.coin_mint;
+        (self.token_vault, self.sol_vault) = if self.token_is_coin {
+            (amm_info.coin_vault, amm_info.pc_vault)
+        } else {
+            (amm_info.pc_vault, amm_info.coin_vault)
+        };
+        (self.token_mint, self.base_mint) = if self.token_is_coin {
+            (amm_info.coin_mint, amm_info.pc_mint)
+        } else {
+            (amm_info.pc_mint, amm_info.coin_mint)
+        };
+        self.swap_fee_numerator = amm_info.swap_fee_numerator;
+        self.swap_fee_denominator = amm_info.swap_fee_denominator;
+        self.open_orders = amm_info.open_orders;
+        self.market = amm_info.market;
+        // Only swaps need the market, so a pool whose market cannot be loaded is still quoted
+        self.market_accounts = accounts
+            .get(&amm_info.market)
+            .and_then(|account| MarketInfo::load_checked(&account.data).ok())
+            .and_then(|market| {
+                market
+                    .swap_accounts(&amm_info.market, &amm_info.market_program)
+                    .ok()
+            });
+
+        let (coin_reserve, pc_reserve) = amm_info.effective_reserves(
+            vault_amount(accounts, &amm_info.coin_vault)?,
+            vault_amount(accounts, &amm_info.pc_vault)?,
+        )?;
+        (self.token_reserve, self.sol_reserve) = if self.token_is_coin {
+            (coin_reserve, pc_reserve)
+        } else {
+            (pc_reserve, coin_reserve)
+        };
+        Ok(())
+    }
+
+    fn quote(&self, amount_in: u64, direction: SwapDirection) -> Result<SwapQuote> {
+        self.quote_exact_in(amount_in, direction)
+    }
+
+    fn swap_account_metas(&self, _wallet: &Pubkey) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(raydium_program_id(), false),
+            AccountMeta::new_readonly(self.base_mint, false),
+            AccountMeta::new_readonly(raydium_authority(), false),
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new(self.token_vault, false),
+            AccountMeta::new(self.sol_vault, false),
+        ]
+    }
+
+    fn token_programs(&self) -> (Pubkey, Pubkey) {
+        (spl_token::ID, spl_token::ID)
+    }
+
+    /// `swap_base_in`, which passes through the pool's OpenBook market
+    fn swap_instruction(
+        &self,
+        wallet: &Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        direction: SwapDirection,
+    ) -> Result<Instruction> {
+        let market = self
+            .market_accounts
+            .clone()
+            .ok_or_else(|| anyhow!("Market of Raydium pool {} is not loaded", self.pool))?;
+        let (token_account, base_account) = wallet_token_accounts(self, wallet)?;
+        let (user_source, user_destination) = in_out(direction, token_account, base_account);
+        let (pool_coin_vault, pool_pc_vault) =
+            pool_order(self.token_is_coin, self.token_vault, self.sol_vault);
+
+        Ok(raydium_instruction::swap_base_in(
+            &SwapBaseInAccounts {
+                amm: self.pool,
+                amm_open_orders: self.open_orders,
+                pool_coin_vault,
+                pool_pc_vault,
+                market,
+                user_source,
+                user_destination,
+                user_owner: *wallet,
+            },
+            amount_in,
+            minimum_amount_out,
+        ))
+    }
+}
+
+impl DexPool for RaydiumCpPool {
+    fn kind(&self) -> PoolKind {
+        PoolKind::RaydiumCp
+    }
+
+    fn address(&self) -> Pubkey {
+        self.pool
+    }
+
+    fn token_mint(&self) -> Pubkey {
+        self.token_mint
+    }
+
+    fn base_mint(&self) -> Pubkey {
+        self.base_mint
+    }
+
+    fn accounts_to_fetch(&self) -> Vec<Pubkey> {
+        known([self.pool, self.amm_config, self.token_vault, self.sol_vault])
+    }
+
+    /// Takes the fee tier from the AMM config and both vault balances net of accrued fees
+    fn update(&mut self, accounts: &AccountMap) -> Result<()> {
+        let data = &pool_account(accounts, &self.pool, self.kind())?.data;
+        let amm_info = RaydiumCpAmmInfo::load_checked(data)?;
+        ensure_pair(
+            &self.token_mint,
+            &amm_info.token_0_mint,
+            &amm_info.token_1_mint,
+            self.kind(),
+            &self.pool,
+        )?;
+        if !amm_info.swap_enabled() {
+            return Err(anyhow!(
+                "Swaps are disabled (status {:#04b})",
+                amm_info.status
+            ));
+        }
+
+        let token_is_0 = self.token_mint == amm_info.token_0_mint;
+        (self.token_vault, self.sol_vault) = if token_is_0 {
+            (amm_info.token_0_vault, amm_info.token_1_vault)
+        } else {
+            (amm_info.token_1_vault, amm_info.token_0_vault)
+        };
+        (self.token_mint, self.base_mint) = if token_is_0 {
+            (amm_info.token_0_mint, amm_info.token_1_mint)
+        } else {
+            (amm_info.token_1_mint, amm_info.token_0_mint)
+        };
+        (self.token_program, self.base_token_program) = if token_is_0 {
+            (amm_info.token_0_program, amm_info.token_1_program)
+        } else {
+            (amm_info.token_1_program, amm_info.token_0_program)
+        };
+        self.amm_config = amm_info.amm_config;
+        self.observation = amm_info.observation_key;
+        self.creator_fee_on = amm_info.creator_fee_on;
+
+        let amm_config = account(accounts, &amm_info.amm_config, "AmmConfig")?;
+        let amm_config = RaydiumCpAmmConfig::load_checked(&amm_config.data)?;
+        self.trade_fee_rate = amm_config.trade_fee_rate;
+        self.protocol_fee_rate = amm_config.protocol_fee_rate;
+        self.fund_fee_rate = amm_config.fund_fee_rate;
+        self.creator_fee_rate = if amm_info.enable_creator_fee {
+            amm_config.creator_fee_rate
+        } else {
+            0
+        };
+
+        let (reserve_0, reserve_1) = amm_info.vault_amounts_without_fees(
+            vault_amount(accounts, &amm_info.token_0_vault)?,
+            vault_amount(accounts, &amm_info.token_1_vault)?,
+        )?;
+        (self.token_reserve, self.sol_reserve) = if token_is_0 {
+            (reserve_0, reserve_1)
+        } else {
+            (reserve_1, reserve_0)
+        };
+        Ok(())
+    }
+
+    fn quote(&self, amount_in: u64, direction: SwapDirection) -> Result<SwapQuote> {
+        self.quote_exact_in(amount_in, direction)
+    }
+
+    fn swap_account_metas(&self, _wallet: &Pubkey) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(raydium_cp_program_id(), false),
+            AccountMeta::new_readonly(self.base_mint, false),
+            AccountMeta::new_readonly(raydium_cp_authority(), false),
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new_readonly(self.amm_config, false),
+            AccountMeta::new(self.token_vault, false),
+            AccountMeta::new(self.sol_vault, false),
+            AccountMeta::new(self.observation, false),
+        ]
+    }
+
+    fn token_programs(&self) -> (Pubkey, Pubkey) {
+        (self.token_program, self.base_token_program)
+    }
+
+    /// `swap_base_input`
+    fn swap_instruction(
+        &self,
+        wallet: &Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        direction: SwapDirection,
+    ) -> Result<Instruction> {
+        let (token_account, base_account) = wallet_token_accounts(self, wallet)?;
+        let (input_token_account, output_token_account) =
+            in_out(direction, token_account, base_account);
+        let (input_vault, output_vault) = in_out(direction, self.token_vault, self.sol_vault);
+        let (input_token_program, output_token_program) =
+            in_out(direction, self.token_program, self.base_token_program);
+        let (input_token_mint, output_token_mint) =
+            in_out(direction, self.token_mint, self.base_mint);
+
+        Ok(raydium_instruction::swap_base_input(
+            &SwapBaseInputAccounts {
+                payer: *wallet,
+                amm_config: self.amm_config,
+                pool_state: self.pool,
+                input_token_account,
+                output_token_account,
+                input_vault,
+                output_vault,
+                input_token_program,
+                output_token_program,
+                input_token_mint,
+                output_token_mint,
+                observation_state: self.observation,
+            },
+            amount_in,
+            minimum_amount_out,
+        ))
+    }
+}
+
+impl DexPool for PumpPool {
+    fn kind(&self) -> PoolKind {
+        PoolKind::Pump
+    }
+
+    fn address(&self) -> Pubkey {
+        self.pool
+    }
+
+    fn token_mint(&self) -> Pubkey {
+        self.token_mint
+    }
+
+    fn base_mint(&self) -> Pubkey {
+        self.base_mint
+    }
+
+    fn accounts_to_fetch(&self) -> Vec<Pubkey> {
+        known([
+            self.pool,
+            self.global_config,
+            self.fee_config,
+            self.token_vault,
+            self.sol_vault,
+            self.token_mint,
+            self.base_mint,
+        ])
+    }
+
+    /// Takes the fee rates from the fee program's `FeeConfig`, falling back to the AMM's
+    /// `GlobalConfig` while no `FeeConfig` exists, and both vault balances
+    fn update(&mut self, accounts: &AccountMap) -> Result<()> {
+        let data = &pool_account(accounts, &self.pool, self.kind())?.data;
+        let amm_info = PumpAmmInfo::load_checked(data)?;
+        ensure_pair(
+            &self.token_mint,
+            &amm_info.base_mint,
+            &amm_info.quote_mint,
+            self.kind(),
+            &self.pool,
+        )?;
+
+        self.token_is_pool_base = self.token_mint == amm_info.base_mint;
+        (
+            self.token_vault,
+            self.sol_vault,
+            self.token_mint,
+            self.base_mint,
+        ) = if self.token_is_pool_base {
+            (
+                amm_info.pool_base_token_account,
+                amm_info.pool_quote_token_account,
+                amm_info.base_mint,
+                amm_info.quote_mint,
+            )
+        } else {
+            (
+                amm_info.pool_quote_token_account,
+                amm_info.pool_base_token_account,
+                amm_info.quote_mint,
+                amm_info.base_mint,
+            )
+        };
+        self.token_program = mint_program(accounts, &self.token_mint)?;
+        self.base_token_program = mint_program(accounts, &self.base_mint)?;
+        // Creator and protocol fees are paid in the pool's quote mint, which may be Token-2022
+        let quote_token_program = if self.token_is_pool_base {
+            self.base_token_program
+        } else {
+            self.token_program
+        };
+        self.coin_creator_vault_authority = amm_info.coin_creator_vault_authority;
+        self.coin_creator_vault_ata = get_associated_token_address_with_program_id(
+            &amm_info.coin_creator_vault_authority,
+            &amm_info.quote_mint,
+            &quote_token_program,
+        );
+        self.global_config = global_config_address();
+
+        let config = account(accounts, &self.global_config, "GlobalConfig")?;
+        let config = PumpGlobalConfig::load_checked(&config.data)?;
+        if !config.swap_enabled() {
+            return Err(anyhow!(
+                "Swaps are disabled (flags {:#07b})",
+                config.disable_flags
+            ));
+        }
+        self.protocol_fee_recipient = config
+            .protocol_fee_recipient()
+            .ok_or_else(|| anyhow!("GlobalConfig has no protocol fee recipient"))?;
+        self.fee_token_wallet = get_associated_token_address_with_program_id(
+            &self.protocol_fee_recipient,
+            &amm_info.quote_mint,
+            &quote_token_program,
+        );
+
+        self.token_reserve = vault_amount(accounts, &self.token_vault)?;
+        self.sol_reserve = vault_amount(accounts, &self.sol_vault)?;
+
+        self.fee_config = fee_config_address();
+        let fees = match accounts.get(&self.fee_config) {
+            Some(fee_config) => {
+                let fee_config = PumpFeeConfig::load_checked(&fee_config.data)?;
+                let (base_reserve, quote_reserve) = pool_order(
+                    self.token_is_pool_base,
+                    self.token_reserve,
+                    self.sol_reserve,
+                );
+                let base_mint = account(accounts, &amm_info.base_mint, "base mint")?;
+                let market_cap =
+                    pool_market_cap(mint_supply(&base_mint.data)?, base_reserve, quote_reserve)?;
+                fee_config.fees(amm_info.is_canonical_pump_pool(), market_cap)
+            }
+            None => PumpFees {
+                lp_fee_basis_points: config.lp_fee_basis_points,
+                protocol_fee_basis_points: config.protocol_fee_basis_points,
+                coin_creator_fee_basis_points: config.coin_creator_fee_basis_points,
+            },
+        };
+        self.lp_fee_basis_points = fees.lp_fee_basis_points;
+        self.protocol_fee_basis_points = fees.protocol_fee_basis_points;
+        self.coin_creator_fee_basis_points = if amm_info.has_coin_creator() {
+            fees.coin_creator_fee_basis_points
+        } else {
+            0
+        };
+        Ok(())
+    }
+
+    fn quote(&self, amount_in: u64, direction: SwapDirection) -> Result<SwapQuote> {
+        self.quote_exact_in(amount_in, direction)
+    }
+
+    fn swap_account_metas(&self, wallet: &Pubkey) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(pump_program_id(), false),
+            AccountMeta::new_readonly(self.base_mint, false),
+            AccountMeta::new_readonly(self.global_config, false),
+            AccountMeta::new_readonly(pump_event_authority(), false),
+            AccountMeta::new(self.protocol_fee_recipient, false),
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new(self.token_vault, false),
+            AccountMeta::new(self.sol_vault, false),
+            AccountMeta::new(self.fee_token_wallet, false),
+            AccountMeta::new(self.coin_creator_vault_ata, false),
+            AccountMeta::new_readonly(self.coin_creator_vault_authority, false),
+            AccountMeta::new(global_volume_accumulator_address(), false),
+            AccountMeta::new(user_volume_accumulator_address(wallet), false),
+        ]
+    }
+
+    fn token_programs(&self) -> (Pubkey, Pubkey) {
+        (self.token_program, self.base_token_program)
+    }
+
+    /// `sell` when selling the pool's base mint, otherwise `buy`. Buys are exact out, so they
+    /// receive exactly `minimum_amount_out` for at most `amount_in`.
+    fn swap_instruction(
+        &self,
+        wallet: &Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        direction: SwapDirection,
+    ) -> Result<Instruction> {
+        let (token_account, base_account) = wallet_token_accounts(self, wallet)?;
+        let pool_side =
+            |token_side, base_side| pool_order(self.token_is_pool_base, token_side, base_side);
+        let (base_mint, quote_mint) = pool_side(self.token_mint, self.base_mint);
+        let (user_base_token_account, user_quote_token_account) =
+            pool_side(token_account, base_account);
+        let (pool_base_token_account, pool_quote_token_account) =
+            pool_side(self.token_vault, self.sol_vault);
+        let (base_token_program, quote_token_program) =
+            pool_side(self.token_program, self.base_token_program);
+
+        let accounts = PumpSwapAccounts {
+            pool: self.pool,
+            user: *wallet,
+            global_config: self.global_config,
+            base_mint,
+            quote_mint,
+            user_base_token_account,
+            user_quote_token_account,
+            pool_base_token_account,
+            pool_quote_token_account,
+            protocol_fee_recipient: self.protocol_fee_recipient,
+            protocol_fee_recipient_token_account: self.fee_token_wallet,
+            base_token_program,
+            quote_token_program,
+            coin_creator_vault_ata: self.coin_creator_vault_ata,
+            coin_creator_vault_authority: self.coin_creator_vault_authority,
+        };
+        Ok(if sells_first(direction, self.token_is_pool_base) {
+            pump_instruction::sell(&accounts, amount_in, minimum_amount_out)
+        } else {
+            pump_instruction::buy(&accounts, minimum_amount_out, amount_in)
+        })
+    }
+}
+
+impl DexPool for DlmmPool {
+    fn kind(&self) -> PoolKind {
+        PoolKind::MeteoraDlmm
+    }
+
+    fn address(&self) -> Pubkey {
+        self.pair
+    }
+
+    fn token_mint(&self) -> Pubkey {
+        self.token_mint
+    }
+
+    fn base_mint(&self) -> Pubkey {
+        self.base_mint
+    }
+
+    fn accounts_to_fetch(&self) -> Vec<Pubkey> {
+        let mut keys = known([
+            self.pair,
+            sysvar::clock::ID,
+            self.token_mint,
+            self.base_mint,
+        ]);
+        keys.extend(&self.bin_arrays);
+        keys
+    }
+
+    /// Loads the bin arrays around the active bin. Arrays that do not exist are left out, as
+    /// the swap treats them as empty.
+    fn update(&mut self, accounts: &AccountMap) -> Result<()> {
+        let data = &pool_account(accounts, &self.pair, self.kind())?.data;
+        let dlmm_info = DlmmInfo::load_checked(data)?;
+        ensure_pair(
+            &self.token_mint,
+            &dlmm_info.token_x_mint,
+            &dlmm_info.token_y_mint,
+            self.kind(),
+            &self.pair,
+        )?;
+
+        self.token_is_x = self.token_mint == dlmm_info.token_x_mint;
+        (self.token_mint, self.base_mint, self.token_vault, self.sol_vault) = if self.token_is_x
+        {
+            (
+                dlmm_info.token_x_mint,
+                dlmm_info.token_y_mint,
+                dlmm_info.token_x_vault,
+                dlmm_info.token_y_vault,
+            )
+        } else {
+            (
+                dlmm_info.token_y_mint,
+                dlmm_info.token_x_mint,
+                dlmm_info.token_y_vault,
+                dlmm_info.token_x_vault,
+            )
+        };
+        self.token_program = mint_program(accounts, &self.token_mint)?;
+        self.base_token_program = mint_program(accounts, &self.base_mint)?;
+        self.oracle = dlmm_info.oracle;
+        self.bin_arrays = dlmm_info.calculate_bin_arrays(&self.pair)?;
+        self.lb_pair = Some(dlmm_info.lb_pair);
+
+        self.bin_array_state = self
+            .bin_arrays
+            .iter()
+            .filter_map(|pubkey| accounts.get(pubkey))
+            .map(|account| BinArray::load_checked(&account.data))
+            .collect::<Result<_>>()?;
+        self.current_timestamp = clock(accounts)?.unix_timestamp;
+        Ok(())
+    }
+
+    fn quote(&self, amount_in: u64, direction: SwapDirection) -> Result<SwapQuote> {
+        self.quote_exact_in(amount_in, direction)
+    }
+
+    fn swap_account_metas(&self, _wallet: &Pubkey) -> Vec<AccountMeta> {
+        let mut metas = vec![
+            AccountMeta::new_readonly(dlmm_program_id(), false),
+            AccountMeta::new_readonly(self.base_mint, false),
+            AccountMeta::new_readonly(dlmm_event_authority(), false),
+        ];
+        if let Some(memo_program) = self.memo_program {
+            metas.push(AccountMeta::new_readonly(memo_program, false));
+        }
+        metas.extend([
+            AccountMeta::new(self.pair, false),
+            AccountMeta::new(self.token_vault, false),
+            AccountMeta::new(self.sol_vault, false),
+            AccountMeta::new(self.oracle, false),
+        ]);
+        metas.extend(
+            self.bin_arrays
+                .iter()
+                .map(|bin_array| AccountMeta::new(*bin_array, false)),
+        );
+        metas
+    }
+
+    fn token_programs(&self) -> (Pubkey, Pubkey) {
+        (self.token_program, self.base_token_program)
+    }
+
+    /// `swap` through the loaded bin arrays from the active one in the swap's direction
+    fn swap_instruction(
+        &self,
+        wallet: &Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        direction: SwapDirection,
+    ) -> Result<Instruction> {
+        let lb_pair = self
+            .lb_pair
+            .as_ref()
+            .ok_or_else(|| anyhow!("DLMM pair {} has not been loaded", self.pair))?;
+        let (token_account, base_account) = wallet_token_accounts(self, wallet)?;
+        let (user_token_in, user_token_out) = in_out(direction, token_account, base_account);
+        let (reserve_x, reserve_y) = pool_order(self.token_is_x, self.token_vault, self.sol_vault);
+        let (token_x_mint, token_y_mint) =
+            pool_order(self.token_is_x, self.token_mint, self.base_mint);
+        let (token_x_program, token_y_program) =
+            pool_order(self.token_is_x, self.token_program, self.base_token_program);
+
+        // Selling X moves the active bin down
+        let swap_for_y = sells_first(direction, self.token_is_x);
+        let active_index = bin_id_to_bin_array_index(lb_pair.active_id) as i64;
+        let mut indices: Vec<i64> = self
+            .bin_array_state
+            .iter()
+            .map(|bin_array| bin_array.index)
+            .filter(|&index| {
+                if swap_for_y {
+                    index <= active_index
+                } else {
+                    index >= active_index
+                }
+            })
+            .collect();
+        indices.sort_unstable();
+        if swap_for_y {
+            indices.reverse();
+        }
+
+        Ok(meteora_instruction::dlmm_swap(
+            &DlmmSwapAccounts {
+                lb_pair: self.pair,
+                reserve_x,
+                reserve_y,
+                user_token_in,
+                user_token_out,
+                token_x_mint,
+                token_y_mint,
+                oracle: self.oracle,
+                user: *wallet,
+                token_x_program,
+                token_y_program,
+                bin_arrays: indices
+                    .into_iter()
+                    .map(|index| bin_array_address(&self.pair, index))
+                    .collect(),
+            },
+            amount_in,
+            minimum_amount_out,
+        ))
+    }
+}
+
+/// The run of consecutive loaded tick arrays that holds `tick_current`
+fn tick_array_run(
+    mut arrays: Vec<TickArray>,
+    tick_current: i32,
+    tick_spacing: u16,
+) -> Result<TickArraySequence> {
+    let ticks_in_array = TICK_ARRAY_SIZE as i32 * tick_spacing as i32;
+    arrays.sort_by_key(|array| array.start_tick_index);
+    arrays.dedup_by_key(|array| array.start_tick_index);
+
+    let current = arrays
+        .iter()
+        .position(|array| {
+            tick_current >= array.start_tick_index
+                && tick_current < array.start_tick_index + ticks_in_array
+        })
+        .ok_or_else(|| anyhow!("No loaded tick array holds tick {}", tick_current))?;
+    let consecutive =
+        |pair: &[TickArray]| pair[1].start_tick_index == pair[0].start_tick_index + ticks_in_array;
+    let first = current
+        - arrays[..=current]
+            .windows(2)
+            .rev()
+            .take_while(|pair| consecutive(pair))
+            .count();
+    let last = current
+        + arrays[current..]
+            .windows(2)
+            .take_while(|pair| consecutive(pair))
+            .count();
+
+    TickArraySequence::new(arrays.drain(first..=last).collect(), tick_spacing)
+}
+
+impl DexPool for WhirlpoolPool {
+    fn kind(&self) -> PoolKind {
+        PoolKind::Whirlpool
+    }
+
+    fn address(&self) -> Pubkey {
+        self.pool
+    }
+
+    fn token_mint(&self) -> Pubkey {
+        self.token_mint
+    }
+
+    fn base_mint(&self) -> Pubkey {
+        self.base_mint
+    }
+
+    fn accounts_to_fetch(&self) -> Vec<Pubkey> {
+        let mut keys = known([self.pool, self.token_mint, self.base_mint]);
+        keys.extend(&self.tick_arrays);
+        keys
+    }
+
+    /// Loads the tick arrays passed to the swap and keeps the consecutive run around the
+    /// current tick for quoting
+    fn update(&mut self, accounts: &AccountMap) -> Result<()> {
+        let data = &pool_account(accounts, &self.pool, self.kind())?.data;
+        let whirlpool = WhirlpoolState::try_deserialize(data)?;
+        ensure_pair(
+            &self.token_mint,
+            &whirlpool.token_mint_a,
+            &whirlpool.token_mint_b,
+            self.kind(),
+            &self.pool,
+        )?;
+
+        self.token_is_mint_a = self.token_mint == whirlpool.token_mint_a;
+        (self.x_vault, self.y_vault) = if self.token_is_mint_a {
+            (whirlpool.token_vault_a, whirlpool.token_vault_b)
+        } else {
+            (whirlpool.token_vault_b, whirlpool.token_vault_a)
+        };
+        (self.token_mint, self.base_mint) = if self.token_is_mint_a {
+            (whirlpool.token_mint_a, whirlpool.token_mint_b)
+        } else {
+            (whirlpool.token_mint_b, whirlpool.token_mint_a)
+        };
+        self.token_program = mint_program(accounts, &self.token_mint)?;
+        self.base_token_program = mint_program(accounts, &self.base_mint)?;
+        self.oracle =
+            Pubkey::find_program_address(&[b"oracle", self.pool.as_ref()], &whirlpool_program_id())
+                .0;
+        self.tick_arrays =
+            update_tick_array_accounts_for_onchain(&whirlpool, &self.pool, &whirlpool_program_id())
+                .into_iter()
+                .map(|meta| meta.pubkey)
+                .collect();
+        self.whirlpool = Some(whirlpool);
+
+        let arrays = self
+            .tick_arrays
+            .iter()
+            .filter_map(|pubkey| accounts.get(pubkey))
+            .map(|account| Ok(TickArray::try_deserialize(&account.data)?))
+            .collect::<Result<Vec<_>>>()?;
+        self.tick_array_state = Some(tick_array_run(
+            arrays,
+            whirlpool.tick_current_index,
+            whirlpool.tick_spacing,
+        )?);
+        Ok(())
+    }
+
+    fn quote(&self, amount_in: u64, direction: SwapDirection) -> Result<SwapQuote> {
+        self.quote_exact_in(amount_in, direction)
+    }
+
+    fn swap_account_metas(&self, _wallet: &Pubkey) -> Vec<AccountMeta> {
+        let mut metas = vec![
+            AccountMeta::new_readonly(whirlpool_program_id(), false),
+            AccountMeta::new_readonly(self.base_mint, false),
+            // The executor always expects the memo program for Whirlpool
+            AccountMeta::new_readonly(memo_program_id(), false),
+            AccountMeta::new(self.pool, false),
+            // The oracle must be writable for Whirlpool
+            AccountMeta::new(self.oracle, false),
+            AccountMeta::new(self.x_vault, false),
+            AccountMeta::new(self.y_vault, false),
+        ];
+        metas.extend(
+            self.tick_arrays
+                .iter()
+                .map(|tick_array| AccountMeta::new(*tick_array, false)),
+        );
+        metas
+    }
+
+    fn token_programs(&self) -> (Pubkey, Pubkey) {
+        (self.token_program, self.base_token_program)
+    }
+
+    /// `swap_v2` through the tick arrays around the current tick
+    fn swap_instruction(
+        &self,
+        wallet: &Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        direction: SwapDirection,
+    ) -> Result<Instruction> {
+        let tick_arrays: [Pubkey; 3] = self
+            .tick_arrays
+            .clone()
+            .try_into()
+            .map_err(|_| anyhow!("Tick arrays of Whirlpool {} are not loaded", self.pool))?;
+        let (token_account, base_account) = wallet_token_accounts(self, wallet)?;
+        let pool_side =
+            |token_side, base_side| pool_order(self.token_is_mint_a, token_side, base_side);
+        let (token_program_a, token_program_b) =
+            pool_side(self.token_program, self.base_token_program);
+        let (token_mint_a, token_mint_b) = pool_side(self.token_mint, self.base_mint);
+        let (token_owner_account_a, token_owner_account_b) = pool_side(token_account, base_account);
+        let (token_vault_a, token_vault_b) = pool_side(self.x_vault, self.y_vault);
+
+        Ok(whirlpool_instruction::swap_v2(
+            &SwapV2Accounts {
+                token_program_a,
+                token_program_b,
+                token_authority: *wallet,
+                whirlpool: self.pool,
+                token_mint_a,
+                token_mint_b,
+                token_owner_account_a,
+                token_vault_a,
+                token_owner_account_b,
+                token_vault_b,
+                tick_arrays,
+                oracle: self.oracle,
+            },
+            amount_in,
+            minimum_amount_out,
+            sells_first(direction, self.token_is_mint_a),
+        ))
+    }
+}
+
+/// Address of the tick array bitmap extension of a Raydium CLMM pool
+fn raydium_clmm_bitmap_extension(pool: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[POOL_TICK_ARRAY_BITMAP_SEED.as_bytes(), pool.as_ref()],
+        &raydium_clmm_program_id(),
+    )
+    .0
+}
+
+/// The tick array around the current price plus one on each side, taking the nearest
+/// initialized neighbours and falling back to the adjacent arrays, as the executor expects
+fn raydium_clmm_tick_arrays(
+    pool: &Pubkey,
+    pool_state: &PoolState,
+    extension: Option<&TickArrayBitmapExtension>,
+) -> Vec<Pubkey> {
+    let ticks_in_array = tick_count(pool_state.tick_spacing);
+    let current = compute_tick_array_start_index(pool_state.tick_current, pool_state.tick_spacing);
+    let below = pool_state
+        .initialized_tick_array_start_indices(extension, true, 2)
+        .into_iter()
+        .find(|&start_index| start_index < current)
+        .unwrap_or(current - ticks_in_array);
+    let above = pool_state
+        .initialized_tick_array_start_indices(extension, false, 2)
+        .into_iter()
+        .find(|&start_index| start_index > current)
+        .unwrap_or(current + ticks_in_array);
+
+    [below, current, above]
+        .into_iter()
+        .map(|start_index| get_tick_array_pubkey(pool, start_index, &raydium_clmm_program_id()))
+        .collect()
+}
+
+impl DexPool for RaydiumClmmPool {
+    fn kind(&self) -> PoolKind {
+        PoolKind::RaydiumClmm
+    }
+
+    fn address(&self) -> Pubkey {
+        self.pool
+    }
+
+    fn token_mint(&self) -> Pubkey {
+        self.token_mint
+    }
+
+    fn base_mint(&self) -> Pubkey {
+        self.base_mint
+    }
+
+    fn accounts_to_fetch(&self) -> Vec<Pubkey> {
+        let mut keys = known([
+            self.pool,
+            self.amm_config,
+            self.bitmap_extension,
+            self.token_mint,
+            self.base_mint,
+        ]);
+        keys.extend(&self.tick_arrays);
+        keys
+    }
+
+    /// Locates the tick arrays through the pool bitmap and, when the account exists, the
+    /// bitmap extension, then loads the initialized ones
+    fn update(&mut self, accounts: &AccountMap) -> Result<()> {
+        let data = &pool_account(accounts, &self.pool, self.kind())?.data;
+        let pool_state = PoolState::load_checked(data)?;
+        ensure_pair(
+            &self.token_mint,
+            &pool_state.token_mint_0,
+            &pool_state.token_mint_1,
+            self.kind(),
+            &self.pool,
+        )?;
+        if !pool_state.swap_enabled() {
+            return Err(anyhow!(
+                "Swaps are disabled (status {:#07b})",
+                pool_state.status
+            ));
+        }
+
+        self.token_is_mint_0 = self.token_mint == pool_state.token_mint_0;
+        (self.x_vault, self.y_vault) = if self.token_is_mint_0 {
+            (pool_state.token_vault_0, pool_state.token_vault_1)
+        } else {
+            (pool_state.token_vault_1, pool_state.token_vault_0)
+        };
+        (self.token_mint, self.base_mint) = if self.token_is_mint_0 {
+            (pool_state.token_mint_0, pool_state.token_mint_1)
+        } else {
+            (pool_state.token_mint_1, pool_state.token_mint_0)
+        };
+        self.token_program = mint_program(accounts, &self.token_mint)?;
+        self.base_token_program = mint_program(accounts, &self.base_mint)?;
+        self.amm_config = pool_state.amm_config;
+        self.observation_state = pool_state.observation_key;
+        self.bitmap_extension = raydium_clmm_bitmap_extension(&self.pool);
+        self.extension = accounts
+            .get(&self.bitmap_extension)
+            .map(|account| TickArrayBitmapExtension::load_checked(&account.data))
+            .transpose()?;
+        self.tick_arrays =
+            raydium_clmm_tick_arrays(&self.pool, &pool_state, self.extension.as_ref());
+        self.pool_state = Some(pool_state);
+
+        let amm_config = account(accounts, &self.amm_config, "AmmConfig")?;
+        self.trade_fee_rate = AmmConfig::load_checked(&amm_config.data)?.trade_fee_rate;
+        self.tick_array_state = self
+            .tick_arrays
+            .iter()
+            .filter_map(|pubkey| accounts.get(pubkey))
+            .map(|account| TickArrayState::load_checked(&account.data))
+            .collect::<Result<_>>()?;
+        Ok(())
+    }
+
+    fn quote(&self, amount_in: u64, direction: SwapDirection) -> Result<SwapQuote> {
+        self.quote_exact_in(amount_in, direction)
+    }
+
+    fn swap_account_metas(&self, _wallet: &Pubkey) -> Vec<AccountMeta> {
+        let mut metas = vec![
+            AccountMeta::new_readonly(raydium_clmm_program_id(), false),
+            AccountMeta::new_readonly(self.base_mint, false),
+        ];
+        if let Some(memo_program) = self.memo_program {
+            metas.push(AccountMeta::new_readonly(memo_program, false));
+        }
+        metas.extend([
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new_readonly(self.amm_config, false),
+            AccountMeta::new(self.observation_state, false),
+            AccountMeta::new(self.bitmap_extension, false),
+            AccountMeta::new(self.x_vault, false),
+            AccountMeta::new(self.y_vault, false),
+        ]);
+        metas.extend(
+            self.tick_arrays
+                .iter()
+                .map(|tick_array| AccountMeta::new(*tick_array, false)),
+        );
+        metas
+    }
+
+    fn token_programs(&self) -> (Pubkey, Pubkey) {
+        (self.token_program, self.base_token_program)
+    }
+
+    /// `swap_v2` through the initialized tick arrays from the current one in the swap's
+    /// direction
+    fn swap_instruction(
+        &self,
+        wallet: &Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        direction: SwapDirection,
+    ) -> Result<Instruction> {
+        let pool_state = self
+            .pool_state
+            .as_ref()
+            .ok_or_else(|| anyhow!("Raydium CLMM pool {} has not been loaded", self.pool))?;
+        let (token_account, base_account) = wallet_token_accounts(self, wallet)?;
+        let (input_token_account, output_token_account) =
+            in_out(direction, token_account, base_account);
+        let (input_vault, output_vault) = in_out(direction, self.x_vault, self.y_vault);
+        let (input_vault_mint, output_vault_mint) =
+            in_out(direction, self.token_mint, self.base_mint);
+
+        // Selling mint 0 moves the price down
+        let zero_for_one = sells_first(direction, self.token_is_mint_0);
+        let current =
+            compute_tick_array_start_index(pool_state.tick_current, pool_state.tick_spacing);
+        let mut start_indices: Vec<i32> = self
+            .tick_array_state
+            .iter()
+            .map(|tick_array| tick_array.start_tick_index)
+            .filter(|&start_index| {
+                if zero_for_one {
+                    start_index <= current
+                } else {
+                    start_index >= current
+                }
+            })
+            .collect();
+        start_indices.sort_unstable();
+        if zero_for_one {
+            start_indices.reverse();
+        }
+
+        Ok(raydium_instruction::clmm_swap_v2(
+            &ClmmSwapV2Accounts {
+                payer: *wallet,
+                amm_config: self.amm_config,
+                pool_state: self.pool,
+                input_token_account,
+                output_token_account,
+                input_vault,
+                output_vault,
+                observation_state: self.observation_state,
+                input_vault_mint,
+                output_vault_mint,
+                bitmap_extension: self.extension.as_ref().map(|_| self.bitmap_extension),
+                tick_arrays: start_indices
+                    .into_iter()
+                    .map(|start_index| {
+                        get_tick_array_pubkey(&self.pool, start_index, &raydium_clmm_program_id())
+                    })
+                    .collect(),
+            },
+            amount_in,
+            minimum_amount_out,
+        ))
+    }
+}
+
+/// One side of a DAMM pool with its vault, the pool's vault LP and the vault's token account
+fn damm_vault_side(
+    accounts: &AccountMap,
+    vault: &DammVaultInfo,
+    pool_lp: &Pubkey,
+) -> Result<DammVaultSide> {
+    Ok(DammVaultSide {
+        vault: vault.vault.clone(),
+        pool_lp_amount: token_account_amount(&account(accounts, pool_lp, "Vault LP")?.data)?,
+        vault_lp_supply: mint_supply(&account(accounts, &vault.lp_mint, "Vault LP mint")?.data)?,
+        token_vault_amount: vault_amount(accounts, &vault.token_vault)?,
+    })
+}
+
+impl DexPool for MeteoraDAmmPool {
+    fn kind(&self) -> PoolKind {
+        PoolKind::MeteoraDamm
+    }
+
+    fn address(&self) -> Pubkey {
+        self.pool
+    }
+
+    fn token_mint(&self) -> Pubkey {
+        self.token_mint
+    }
+
+    fn base_mint(&self) -> Pubkey {
+        self.base_mint
+    }
+
+    fn accounts_to_fetch(&self) -> Vec<Pubkey> {
+        known([
+            self.pool,
+            self.token_x_vault,
+            self.token_sol_vault,
+            self.token_x_pool_lp,
+            self.token_sol_pool_lp,
+            self.token_x_lp_mint,
+            self.token_sol_lp_mint,
+            self.token_x_token_vault,
+            self.token_sol_token_vault,
+            sysvar::clock::ID,
+        ])
+    }
+
+    /// Values the pool's reserves through its share of each vault, excluding vault profit
+    /// that is still locked
+    fn update(&mut self, accounts: &AccountMap) -> Result<()> {
+        let data = &pool_account(accounts, &self.pool, self.kind())?.data;
+        let damm_info = DammInfo::load_checked(data)?;
+        ensure_pair(
+            &self.token_mint,
+            &damm_info.token_a_mint,
+            &damm_info.token_b_mint,
+            self.kind(),
+            &self.pool,
+        )?;
+        if !damm_info.pool.enabled {
+            return Err(anyhow!("Pool is disabled"));
+        }
+
+        self.token_is_a = self.token_mint == damm_info.token_a_mint;
+        let a = (
+            damm_info.a_vault,
+            damm_info.a_vault_lp,
+            damm_info.admin_token_a_fee,
+            damm_info.token_a_mint,
+        );
+        let b = (
+            damm_info.b_vault,
+            damm_info.b_vault_lp,
+            damm_info.admin_token_b_fee,
+            damm_info.token_b_mint,
+        );
+        let (x, sol) = if self.token_is_a { (a, b) } else { (b, a) };
+        (
+            self.token_x_vault,
+            self.token_x_pool_lp,
+            self.admin_token_fee_x,
+            self.token_mint,
+        ) = x;
+        (
+            self.token_sol_vault,
+            self.token_sol_pool_lp,
+            self.admin_token_fee_sol,
+            self.base_mint,
+        ) = sol;
+        self.token_x_lp_mint = vault_lp_mint(&self.token_x_vault);
+        self.token_sol_lp_mint = vault_lp_mint(&self.token_sol_vault);
+
+        let fees = &damm_info.pool.fees;
+        self.trade_fee_numerator = fees.trade_fee_numerator;
+        self.trade_fee_denominator = fees.trade_fee_denominator;
+        self.owner_trade_fee_numerator = fees.owner_trade_fee_numerator;
+        self.owner_trade_fee_denominator = fees.owner_trade_fee_denominator;
+        self.damm_pool = Some(damm_info.pool);
+
+        let x_vault =
+            DammVaultInfo::load_checked(&account(accounts, &self.token_x_vault, "Vault")?.data)?;
+        let sol_vault =
+            DammVaultInfo::load_checked(&account(accounts, &self.token_sol_vault, "Vault")?.data)?;
+        self.token_x_token_vault = x_vault.token_vault;
+        self.token_sol_token_vault = sol_vault.token_vault;
+        self.token_x_lp_mint = x_vault.lp_mint;
+        self.token_sol_lp_mint = sol_vault.lp_mint;
+
+        self.current_time = u64::try_from(clock(accounts)?.unix_timestamp)?;
+        let x_side = damm_vault_side(accounts, &x_vault, &self.token_x_pool_lp)?;
+        let sol_side = damm_vault_side(accounts, &sol_vault, &self.token_sol_pool_lp)?;
+        self.token_reserve = x_vault.underlying_amount(
+            self.current_time,
+            x_side.pool_lp_amount,
+            x_side.vault_lp_supply,
+        )?;
+        self.sol_reserve = sol_vault.underlying_amount(
+            self.current_time,
+            sol_side.pool_lp_amount,
+            sol_side.vault_lp_supply,
+        )?;
+        self.token_x_side = Some(x_side);
+        self.token_sol_side = Some(sol_side);
+        Ok(())
+    }
+
+    fn quote(&self, amount_in: u64, direction: SwapDirection) -> Result<SwapQuote> {
+        self.quote_exact_in(amount_in, direction)
+    }
+
+    fn swap_account_metas(&self, _wallet: &Pubkey) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(damm_program_id(), false),
+            AccountMeta::new_readonly(self.base_mint, false),
+            AccountMeta::new_readonly(vault_program_id(), false),
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new(self.token_x_vault, false),
+            AccountMeta::new(self.token_sol_vault, false),
+            AccountMeta::new(self.token_x_token_vault, false),
+            AccountMeta::new(self.token_sol_token_vault, false),
+            AccountMeta::new(self.token_x_lp_mint, false),
+            AccountMeta::new(self.token_sol_lp_mint, false),
+            AccountMeta::new(self.token_x_pool_lp, false),
+            AccountMeta::new(self.token_sol_pool_lp, false),
+            AccountMeta::new(self.admin_token_fee_x, false),
+            AccountMeta::new(self.admin_token_fee_sol, false),
+        ]
+    }
+
+    fn token_programs(&self) -> (Pubkey, Pubkey) {
+        (spl_token::ID, spl_token::ID)
+    }
+
+    /// `swap` through both vaults, paying the admin fee in the input mint
+    fn swap_instruction(
+        &self,
+        wallet: &Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        direction: SwapDirection,
+    ) -> Result<Instruction> {
+        let (token_account, base_account) = wallet_token_accounts(self, wallet)?;
+        let (user_source_token, user_destination_token) =
+            in_out(direction, token_account, base_account);
+        let pool_side = |token_side, base_side| pool_order(self.token_is_a, token_side, base_side);
+        let (a_vault, b_vault) = pool_side(self.token_x_vault, self.token_sol_vault);
+        let (a_token_vault, b_token_vault) =
+            pool_side(self.token_x_token_vault, self.token_sol_token_vault);
+        let (a_vault_lp_mint, b_vault_lp_mint) =
+            pool_side(self.token_x_lp_mint, self.token_sol_lp_mint);
+        let (a_vault_lp, b_vault_lp) = pool_side(self.token_x_pool_lp, self.token_sol_pool_lp);
+
+        Ok(meteora_instruction::damm_swap(
+            &DammSwapAccounts {
+                pool: self.pool,
+                user_source_token,
+                user_destination_token,
+                a_vault,
+                b_vault,
+                a_token_vault,
+                b_token_vault,
+                a_vault_lp_mint,
+                b_vault_lp_mint,
+                a_vault_lp,
+                b_vault_lp,
+                protocol_token_fee: in_out(
+                    direction,
+                    self.admin_token_fee_x,
+                    self.admin_token_fee_sol,
+                )
+                .0,
+                user: *wallet,
+            },
+            amount_in,
+            minimum_amount_out,
+        ))
+    }
+}
+
+impl DexPool for SolfiPool {
+    fn kind(&self) -> PoolKind {
+        PoolKind::Solfi
+    }
+
+    fn address(&self) -> Pubkey {
+        self.pool
+    }
+
+    fn token_mint(&self) -> Pubkey {
+        self.token_mint
+    }
+
+    fn base_mint(&self) -> Pubkey {
+        self.base_mint
+    }
+
+    fn accounts_to_fetch(&self) -> Vec<Pubkey> {
+        known([self.pool, self.token_x_vault, self.token_sol_vault])
+    }
+
+    /// Takes the reserves from the vault balances
+    fn update(&mut self, accounts: &AccountMap) -> Result<()> {
+        let data = &pool_account(accounts, &self.pool, self.kind())?.data;
+        let solfi_info = SolfiInfo::load_checked(data)?;
+        ensure_pair(
+            &self.token_mint,
+            &solfi_info.base_mint,
+            &solfi_info.quote_mint,
+            self.kind(),
+            &self.pool,
+        )?;
+
+        self.token_is_base = self.token_mint == solfi_info.base_mint;
+        (self.token_x_vault, self.token_sol_vault) = if self.token_is_base {
+            (solfi_info.base_vault, solfi_info.quote_vault)
+        } else {
+            (solfi_info.quote_vault, solfi_info.base_vault)
+        };
+        (self.token_mint, self.base_mint) = if self.token_is_base {
+            (solfi_info.base_mint, solfi_info.quote_mint)
+        } else {
+            (solfi_info.quote_mint, solfi_info.base_mint)
+        };
+
+        self.token_reserve = vault_amount(accounts, &self.token_x_vault)?;
+        self.sol_reserve = vault_amount(accounts, &self.token_sol_vault)?;
+        Ok(())
+    }
+
+    /// Solfi prices from state that is not decoded, so its pools cannot be quoted
+    fn quote(&self, _amount_in: u64, _direction: SwapDirection) -> Result<SwapQuote> {
+        Err(anyhow!(
+            "Solfi pool {} cannot be quoted: its pricing state is not decoded",
+            self.pool
+        ))
+    }
+
+    fn swap_account_metas(&self, _wallet: &Pubkey) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(solfi_program_id(), false),
+            AccountMeta::new_readonly(self.base_mint, false),
+            AccountMeta::new_readonly(sysvar::instructions::ID, false),
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new(self.token_x_vault, false),
+            AccountMeta::new(self.token_sol_vault, false),
+        ]
+    }
+
+    fn token_programs(&self) -> (Pubkey, Pubkey) {
+        (spl_token::ID, spl_token::ID)
+    }
+
+    fn swap_instruction(
+        &self,
+        wallet: &Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        direction: SwapDirection,
+    ) -> Result<Instruction> {
+        let (token_account, base_account) = wallet_token_accounts(self, wallet)?;
+        let pool_side =
+            |token_side, base_side| pool_order(self.token_is_base, token_side, base_side);
+        let (pool_base_vault, pool_quote_vault) =
+            pool_side(self.token_x_vault, self.token_sol_vault);
+        let (user_base_token_account, user_quote_token_account) =
+            pool_side(token_account, base_account);
+
+        Ok(solfi_instruction::swap(
+            &SolfiSwapAccounts {
+                user: *wallet,
+                pool: self.pool,
+                pool_base_vault,
+                pool_quote_vault,
+                user_base_token_account,
+                user_quote_token_account,
+            },
+            amount_in,
+            minimum_amount_out,
+            sells_first(direction, self.token_is_base),
+        ))
+    }
+}
+
+impl DexPool for MeteoraDAmmV2Pool {
+    fn kind(&self) -> PoolKind {
+        PoolKind::MeteoraDammV2
+    }
+
+    fn address(&self) -> Pubkey {
+        self.pool
+    }
+
+    fn token_mint(&self) -> Pubkey {
+        self.token_mint
+    }
+
+    fn base_mint(&self) -> Pubkey {
+        self.base_mint
+    }
+
+    fn accounts_to_fetch(&self) -> Vec<Pubkey> {
+        known([self.pool, sysvar::clock::ID])
+    }
+
+    /// Takes the slot and time the fee schedule runs on from the clock sysvar
+    fn update(&mut self, accounts: &AccountMap) -> Result<()> {
+        let data = &pool_account(accounts, &self.pool, self.kind())?.data;
+        let damm_v2 = DammV2Pool::load_checked(data)?;
+        ensure_pair(
+            &self.token_mint,
+            &damm_v2.token_a_mint,
+            &damm_v2.token_b_mint,
+            self.kind(),
+            &self.pool,
+        )?;
+        if !damm_v2.is_enabled() {
+            return Err(anyhow!("Pool is disabled (status {})", damm_v2.pool_status));
+        }
+
+        self.token_is_a = self.token_mint == damm_v2.token_a_mint;
+        (
+            self.token_mint,
+            self.base_mint,
+            self.token_x_vault,
+            self.token_sol_vault,
+        ) = if self.token_is_a {
+            (
+                damm_v2.token_a_mint,
+                damm_v2.token_b_mint,
+                damm_v2.token_a_vault,
+                damm_v2.token_b_vault,
+            )
+        } else {
+            (
+                damm_v2.token_b_mint,
+                damm_v2.token_a_mint,
+                damm_v2.token_b_vault,
+                damm_v2.token_a_vault,
+            )
+        };
+        (self.token_program, self.base_token_program) = pool_order(
+            self.token_is_a,
+            damm_v2.token_a_program(),
+            damm_v2.token_b_program(),
+        );
+        self.damm_v2 = Some(damm_v2);
+
+        let clock = clock(accounts)?;
+        self.current_slot = clock.slot;
+        self.current_timestamp = u64::try_from(clock.unix_timestamp)?;
+        Ok(())
+    }
+
+    fn quote(&self, amount_in: u64, direction: SwapDirection) -> Result<SwapQuote> {
+        self.quote_exact_in(amount_in, direction)
+    }
+
+    fn swap_account_metas(&self, _wallet: &Pubkey) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(damm_v2_program_id(), false),
+            AccountMeta::new_readonly(self.base_mint, false),
+            AccountMeta::new_readonly(damm_v2_event_authority(), false),
+            AccountMeta::new_readonly(damm_v2_pool_authority(), false),
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new(self.token_x_vault, false),
+            AccountMeta::new(self.token_sol_vault, false),
+        ]
+    }
+
+    fn token_programs(&self) -> (Pubkey, Pubkey) {
+        (self.token_program, self.base_token_program)
+    }
+
+    fn swap_instruction(
+        &self,
+        wallet: &Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        direction: SwapDirection,
+    ) -> Result<Instruction> {
+        let (token_account, base_account) = wallet_token_accounts(self, wallet)?;
+        let (input_token_account, output_token_account) =
+            in_out(direction, token_account, base_account);
+        let pool_side = |token_side, base_side| pool_order(self.token_is_a, token_side, base_side);
+        let (token_a_vault, token_b_vault) = pool_side(self.token_x_vault, self.token_sol_vault);
+        let (token_a_mint, token_b_mint) = pool_side(self.token_mint, self.base_mint);
+        let (token_a_program, token_b_program) =
+            pool_side(self.token_program, self.base_token_program);
+
+        Ok(meteora_instruction::damm_v2_swap(
+            &DammV2SwapAccounts {
+                pool: self.pool,
+                input_token_account,
+                output_token_account,
+                token_a_vault,
+                token_b_vault,
+                token_a_mint,
+                token_b_mint,
+                payer: *wallet,
+                token_a_program,
+                token_b_program,
+            },
+            amount_in,
+            minimum_amount_out,
+        ))
+    }
+}
+
+impl DexPool for VertigoPool {
+    fn kind(&self) -> PoolKind {
+        PoolKind::Vertigo
+    }
+
+    fn address(&self) -> Pubkey {
+        self.pool
+    }
+
+    fn token_mint(&self) -> Pubkey {
+        self.token_mint
+    }
+
+    fn base_mint(&self) -> Pubkey {
+        self.base_mint
+    }
+
+    fn accounts_to_fetch(&self) -> Vec<Pubkey> {
+        known([
+            self.pool,
+            sysvar::clock::ID,
+            self.token_mint,
+            self.base_mint,
+        ])
+    }
+
+    /// Takes the reserves the pool tracks itself and the current slot for its fee schedule
+    fn update(&mut self, accounts: &AccountMap) -> Result<()> {
+        let data = &pool_account(accounts, &self.pool, self.kind())?.data;
+        let state = VertigoState::load_checked(data)?;
+        ensure_pair(
+            &self.token_mint,
+            &state.mint_a,
+            &state.mint_b,
+            self.kind(),
+            &self.pool,
+        )?;
+        if !state.enabled {
+            return Err(anyhow!("Pool is disabled"));
+        }
+
+        self.token_is_mint_a = self.token_mint == state.mint_a;
+        let (token_mint, base_mint, token_reserve, sol_reserve) = if self.token_is_mint_a {
+            (
+                state.mint_a,
+                state.mint_b,
+                state.token_a_reserves,
+                state.token_b_reserves,
+            )
+        } else {
+            (
+                state.mint_b,
+                state.mint_a,
+                state.token_b_reserves,
+                state.token_a_reserves,
+            )
+        };
+        self.pool_owner = state.owner;
+        self.token_mint = token_mint;
+        self.base_mint = base_mint;
+        self.token_program = mint_program(accounts, &token_mint)?;
+        self.base_token_program = mint_program(accounts, &base_mint)?;
+        self.token_x_vault = derive_vault_address(&self.pool, &token_mint).0;
+        self.token_sol_vault = derive_vault_address(&self.pool, &base_mint).0;
+        self.token_reserve = u64::try_from(token_reserve)?;
+        self.sol_reserve = u64::try_from(sol_reserve)?;
+        self.shift = state.shift;
+        self.fee_params = state.fee_params;
+
+        self.current_slot = clock(accounts)?.slot;
+        Ok(())
+    }
+
+    fn quote(&self, amount_in: u64, direction: SwapDirection) -> Result<SwapQuote> {
+        self.quote_exact_in(amount_in, direction)
+    }
+
+    fn swap_account_metas(&self, _wallet: &Pubkey) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(vertigo_program_id(), false),
+            AccountMeta::new_readonly(self.base_mint, false),
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new_readonly(self.pool_owner, false),
+            AccountMeta::new(self.token_x_vault, false),
+            AccountMeta::new(self.token_sol_vault, false),
+        ]
+    }
+
+    fn token_programs(&self) -> (Pubkey, Pubkey) {
+        (self.token_program, self.base_token_program)
+    }
+
+    /// `buy` when selling mint A, `sell` when selling mint B
+    fn swap_instruction(
+        &self,
+        wallet: &Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        direction: SwapDirection,
+    ) -> Result<Instruction> {
+        let (token_account, base_account) = wallet_token_accounts(self, wallet)?;
+        let pool_side =
+            |token_side, base_side| pool_order(self.token_is_mint_a, token_side, base_side);
+        let (mint_a, mint_b) = pool_side(self.token_mint, self.base_mint);
+        let (user_ta_a, user_ta_b) = pool_side(token_account, base_account);
+        let (vault_a, vault_b) = pool_side(self.token_x_vault, self.token_sol_vault);
+        let (token_program_a, token_program_b) =
+            pool_side(self.token_program, self.base_token_program);
+
+        let accounts = VertigoSwapAccounts {
+            pool: self.pool,
+            user: *wallet,
+            owner: self.pool_owner,
+            mint_a,
+            mint_b,
+            user_ta_a,
+            user_ta_b,
+            vault_a,
+            vault_b,
+            token_program_a,
+            token_program_b,
+        };
+        Ok(if sells_first(direction, self.token_is_mint_a) {
+            vertigo_instruction::buy(&accounts, amount_in, minimum_amount_out)
+        } else {
+            vertigo_instruction::sell(&accounts, amount_in, minimum_amount_out)
+        })
+    }
+}
+
+/// A constant product pool held in memory, for testing code that only needs quotes
+#[cfg(test)]
+pub(crate) mod test_pool {
+    use super::*;
+    use crate::dex::quote::{constant_product_amount_out, to_u64};
+
+    #[derive(Debug, Clone)]
+    pub struct TestPool {
+        pub address: Pubkey,
+        pub token_mint: Pubkey,
+        pub base_mint: Pubkey,
+        pub base_reserve: u64,
+        pub token_reserve: u64,
+        /// Charged on the input
+        pub fee_basis_points: u64,
+    }
+
+    impl TestPool {
+        pub fn new(
+            token_mint: Pubkey,
+            base_mint: Pubkey,
+            base_reserve: u64,
+            token_reserve: u64,
+            fee_basis_points: u64,
+        ) -> Self {
+            TestPool {
+                address: Pubkey::new_unique(),
+                token_mint,
+                base_mint,
+                base_reserve,
+                token_reserve,
+                fee_basis_points,
+            }
+        }
+    }
+
+    impl DexPool for TestPool {
+        fn kind(&self) -> PoolKind {
+            PoolKind::Raydium
+        }
+
+        fn address(&self) -> Pubkey {
+            self.address
+        }
+
+        fn token_mint(&self) -> Pubkey {
+            self.token_mint
+        }
+
+        fn base_mint(&self) -> Pubkey {
+            self.base_mint
+        }
+
+        fn accounts_to_fetch(&self) -> Vec<Pubkey> {
+            Vec::new()
+        }
+
+        fn update(&mut self, _accounts: &AccountMap) -> Result<()> {
+            Ok(())
+        }
+
+        fn quote(&self, amount_in: u64, direction: SwapDirection) -> Result<SwapQuote> {
+            let (reserve_in, reserve_out) = match direction {
+                SwapDirection::SolToToken => (self.base_reserve, self.token_reserve),
+                SwapDirection::TokenToSol => (self.token_reserve, self.base_reserve),
+            };
+            let amount_in_after_fee =
+                amount_in as u128 * (10_000 - self.fee_basis_points) as u128 / 10_000;
+            let amount_out = constant_product_amount_out(
+                amount_in_after_fee,
+                reserve_in as u128,
+                reserve_out as u128,
+            )?;
+            Ok(SwapQuote {
+                amount_in,
+                amount_out: to_u64(amount_out)?,
+                fee_amount: to_u64(amount_in as u128 - amount_in_after_fee)?,
+            })
+        }
+
+        fn swap_account_metas(&self, _wallet: &Pubkey) -> Vec<AccountMeta> {
+            Vec::new()
+        }
+
+        fn token_programs(&self) -> (Pubkey, Pubkey) {
+            (spl_token::id(), spl_token::id())
+        }
+
+        fn swap_instruction(
+            &self,
+            _wallet: &Pubkey,
+            _amount_in: u64,
+            _minimum_amount_out: u64,
+            _direction: SwapDirection,
+        ) -> Result<Instruction> {
+            Err(anyhow!("Test pools cannot be swapped"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        chain::constants::sol_mint,
+        dex::{
+            layout::Layout,
+            meteora::{dammv2_info::DAMM_V2_POOL_SIZE, dlmm_info::LbPair},
+            raydium::{amm_info::AMM_INFO_SIZE, clmm_info, cp_amm_info, market_info},
+            solfi::info::MIN_POOL_ACCOUNT_SIZE,
+            vertigo::info::{POOL_ACCOUNT_SIZE as VERTIGO_POOL_SIZE, POOL_DISCRIMINATOR},
+            whirlpool::{get_tick_array_address, state::Tick},
+        },
+    };
+    use solana_sdk::account::create_account_for_test;
+
+    const DIRECTIONS: [SwapDirection; 2] = [SwapDirection::SolToToken, SwapDirection::TokenToSol];
+    /// Anchor's `global:buy` and `global:sell`, shared by Pump and Vertigo
+    const BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+    const SELL_DISCRIMINATOR: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
+
+    /// Stubbed accounts for a pool trading a new token against SOL, with the token on the
+    /// side of the pool given by `token_is_first`
+    struct Fixture {
+        accounts: AccountMap,
+        wallet: Pubkey,
+        token: Pubkey,
+        token_program: Pubkey,
+        token_vault: Pubkey,
+        sol_vault: Pubkey,
+        token_is_first: bool,
+    }
+
+    impl Fixture {
+        /// Both mints, a funded vault for each and the clock
+        fn new(token_program: Pubkey, token_is_first: bool) -> Self {
+            let mut fixture = Self {
+                accounts: AccountMap::new(),
+                wallet: Pubkey::new_unique(),
+                token: Pubkey::new_unique(),
+                token_program,
+                token_vault: Pubkey::new_unique(),
+                sol_vault: Pubkey::new_unique(),
+                token_is_first,
+            };
+            fixture.mint(fixture.token, token_program, 1_000_000_000_000_000);
+            fixture.mint(sol_mint(), spl_token::ID, 0);
+            fixture.token_account(fixture.token_vault, fixture.token, 5_000_000_000_000);
+            fixture.token_account(fixture.sol_vault, sol_mint(), 50_000_000_000);
+            fixture.accounts.insert(
+                sysvar::clock::ID,
+                create_account_for_test(&Clock {
+                    slot: 300_000_000,
+                    unix_timestamp: 1_750_000_000,
+                    ..Default::default()
+                }),
+            );
+            fixture
+        }
+
+        fn insert(&mut self, pubkey: Pubkey, owner: Pubkey, data: Vec<u8>) {
+            let account = Account {
+                lamports: 1_000_000_000,
+                data,
+                owner,
+                executable: false,
+                rent_epoch: 0,
+            };
+            self.accounts.insert(pubkey, account);
+        }
+
+        /// SPL token account holding `amount` of `mint`
+        fn token_account(&mut self, pubkey: Pubkey, mint: Pubkey, amount: u64) {
+            let mut layout = Layout::new();
+            layout
+                .pubkey(&mint)
+                .pubkey(&Pubkey::new_unique()) // owner
+                .u64(amount)
+                .zeros(93);
+            self.insert(pubkey, spl_token::ID, layout.finish());
+        }
+
+        /// Mint account of `program` with `supply`
+        fn mint(&mut self, pubkey: Pubkey, program: Pubkey, supply: u64) {
+            let mut layout = Layout::new();
+            layout
+                .u32(0) // mint_authority
+                .zeros(32)
+                .u64(supply)
+                .u8(6) // decimals
+                .bool(true) // is_initialized
+                .u32(0) // freeze_authority
+                .zeros(32);
+            self.insert(pubkey, program, layout.finish());
+        }
+
+        fn pool_order<T>(&self, token_side: T, base_side: T) -> (T, T) {
+            pool_order(self.token_is_first, token_side, base_side)
+        }
+
+        fn mints(&self) -> (Pubkey, Pubkey) {
+            self.pool_order(self.token, sol_mint())
+        }
+
+        fn vaults(&self) -> (Pubkey, Pubkey) {
+            self.pool_order(self.token_vault, self.sol_vault)
+        }
+
+        fn programs(&self) -> (Pubkey, Pubkey) {
+            self.pool_order(self.token_program, spl_token::ID)
+        }
+
+        /// The wallet's token and SOL accounts
+        fn user_accounts(&self) -> (Pubkey, Pubkey) {
+            (
+                get_associated_token_address_with_program_id(
+                    &self.wallet,
+                    &self.token,
+                    &self.token_program,
+                ),
+                get_associated_token_address_with_program_id(
+                    &self.wallet,
+                    &sol_mint(),
+                    &spl_token::ID,
+                ),
+            )
+        }
+
+        fn user_in_out(&self, direction: SwapDirection) -> (Pubkey, Pubkey) {
+            let (token_account, sol_account) = self.user_accounts();
+            in_out(direction, token_account, sol_account)
+        }
+
+        /// A pool of `kind` at `pool` after one update, which must resolve the pair
+        fn update(&self, kind: PoolKind, pool: Pubkey) -> Box<dyn DexPool> {
+            let mut dex_pool = kind.new_pool(pool, self.token);
+            dex_pool.update(&self.accounts).unwrap();
+            assert_eq!(dex_pool.token_mint(), self.token);
+            assert_eq!(dex_pool.base_mint(), sol_mint());
+            assert_eq!(
+                dex_pool.token_programs(),
+                (self.token_program, spl_token::ID)
+            );
+            dex_pool
+        }
+
+        fn swap(&self, pool: &dyn DexPool, direction: SwapDirection) -> (Vec<Pubkey>, Vec<u8>) {
+            let instruction = pool
+                .swap_instruction(&self.wallet, 1_000_000, 1, direction)
+                .unwrap();
+            assert_eq!(instruction.program_id, pool.kind().program_id());
+            let keys = instruction.accounts.iter().map(|meta| meta.pubkey);
+            (keys.collect(), instruction.data)
+        }
+
+        fn metas(&self, pool: &dyn DexPool) -> Vec<Pubkey> {
+            let metas = pool.swap_account_metas(&self.wallet);
+            let keys: Vec<Pubkey> = metas.iter().map(|meta| meta.pubkey).collect();
+            assert_eq!(keys[..2], [pool.kind().program_id(), sol_mint()]);
+            keys
+        }
+    }
+
+    #[test]
+    fn raydium_orders_coin_and_pc_by_direction() {
+        for token_is_first in [true, false] {
+            let mut fixture = Fixture::new(spl_token::ID, token_is_first);
+            let (pool, market, market_program) = (
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+            );
+            let (coin_mint, pc_mint) = fixture.mints();
+            let (coin_vault, pc_vault) = fixture.vaults();
+            let mut layout = Layout::new();
+            layout
+                .u64(6) // status
+                .zeros(136)
+                .u64(25) // trade_fee_numerator
+                .u64(10_000) // trade_fee_denominator
+                .zeros(16) // pnl_numerator, pnl_denominator
+                .u64(25) // swap_fee_numerator
+                .u64(10_000) // swap_fee_denominator
+                .zeros(144) // state_data
+                .pubkey(&coin_vault)
+                .pubkey(&pc_vault)
+                .pubkey(&coin_mint)
+                .pubkey(&pc_mint)
+                .pubkey(&Pubkey::new_unique()) // lp_mint
+                .pubkey(&Pubkey::new_unique()) // open_orders
+                .pubkey(&market)
+                .pubkey(&market_program);
+            layout.zeros(AMM_INFO_SIZE - layout.len());
+            fixture.insert(pool, raydium_program_id(), layout.finish());
+
+            let nonce = (0u64..)
+                .find(|nonce| {
+                    Pubkey::create_program_address(
+                        &[market.as_ref(), &nonce.to_le_bytes()],
+                        &market_program,
+                    )
+                    .is_ok()
+                })
+                .unwrap();
+            let mut layout = Layout::new();
+            layout
+                .bytes(b"serum")
+                .u64(3) // account_flags
+                .pubkey(&market)
+                .u64(nonce)
+                .pubkey(&coin_mint)
+                .pubkey(&pc_mint)
+                .pubkey(&Pubkey::new_unique()) // coin_vault
+                .zeros(16)
+                .pubkey(&Pubkey::new_unique()) // pc_vault
+                .zeros(24)
+                .pubkey(&Pubkey::new_unique()) // req_q
+                .pubkey(&Pubkey::new_unique()) // event_q
+                .pubkey(&Pubkey::new_unique()) // bids
+                .pubkey(&Pubkey::new_unique()); // asks
+            layout.zeros(market_info::MARKET_STATE_SIZE - layout.len());
+            fixture.insert(market, market_program, layout.finish());
+
+            let dex_pool = fixture.update(PoolKind::Raydium, pool);
+            let metas = fixture.metas(dex_pool.as_ref());
+            assert_eq!(metas[4..6], [fixture.token_vault, fixture.sol_vault]);
+            for direction in DIRECTIONS {
+                let (keys, _) = fixture.swap(dex_pool.as_ref(), direction);
+                let (user_in, user_out) = fixture.user_in_out(direction);
+                assert_eq!(keys[4..6], [coin_vault, pc_vault]);
+                assert_eq!(keys[14..16], [user_in, user_out]);
+            }
+        }
+    }
+
+    #[test]
+    fn raydium_cp_orders_vaults_mints_and_programs_by_direction() {
+        for token_is_first in [true, false] {
+            let mut fixture = Fixture::new(TOKEN_2022_PROGRAM_ID, token_is_first);
+            let (pool, amm_config) = (Pubkey::new_unique(), Pubkey::new_unique());
+            let (mint_0, mint_1) = fixture.mints();
+            let (vault_0, vault_1) = fixture.vaults();
+            let (program_0, program_1) = fixture.programs();
+            let mut layout = Layout::anchor([247, 237, 227, 245, 215, 195, 222, 70]);
+            layout
+                .pubkey(&amm_config)
+                .pubkey(&Pubkey::new_unique()) // pool_creator
+                .pubkey(&vault_0)
+                .pubkey(&vault_1)
+                .pubkey(&Pubkey::new_unique()) // lp_mint
+                .pubkey(&mint_0)
+                .pubkey(&mint_1)
+                .pubkey(&program_0)
+                .pubkey(&program_1)
+                .pubkey(&Pubkey::new_unique()); // observation_key
+            layout.zeros(cp_amm_info::POOL_STATE_SIZE - layout.len());
+            fixture.insert(pool, raydium_cp_program_id(), layout.finish());
+            let mut layout = Layout::anchor([218, 244, 33, 104, 203, 203, 43, 111]);
+            layout
+                .u8(255) // bump
+                .bool(false) // disable_create_pool
+                .u16(0) // index
+                .u64(2_500); // trade_fee_rate
+            layout.zeros(cp_amm_info::AMM_CONFIG_SIZE - layout.len());
+            fixture.insert(amm_config, raydium_cp_program_id(), layout.finish());
+
+            let dex_pool = fixture.update(PoolKind::RaydiumCp, pool);
+            let metas = fixture.metas(dex_pool.as_ref());
+            assert_eq!(metas[5..7], [fixture.token_vault, fixture.sol_vault]);
+            for direction in DIRECTIONS {
+                let (keys, _) = fixture.swap(dex_pool.as_ref(), direction);
+                let (user_in, user_out) = fixture.user_in_out(direction);
+                let (vault_in, vault_out) =
+                    in_out(direction, fixture.token_vault, fixture.sol_vault);
+                let (program_in, program_out) =
+                    in_out(direction, TOKEN_2022_PROGRAM_ID, spl_token::ID);
+                let (mint_in, mint_out) = in_out(direction, fixture.token, sol_mint());
+                assert_eq!(keys[4..8], [user_in, user_out, vault_in, vault_out]);
+                assert_eq!(keys[8..12], [program_in, program_out, mint_in, mint_out]);
+            }
+        }
+    }
+
+    #[test]
+    fn pump_sells_the_pool_base_and_buys_it_back() {
+        for token_is_first in [true, false] {
+            let mut fixture = Fixture::new(TOKEN_2022_PROGRAM_ID, token_is_first);
+            let pool = Pubkey::new_unique();
+            let (base_mint, quote_mint) = fixture.mints();
+            let (base_vault, quote_vault) = fixture.vaults();
+            let mut layout = Layout::anchor([241, 154, 109, 4, 17, 177, 109, 188]);
+            layout
+                .u8(255) // pool_bump
+                .u16(0) // index
+                .pubkey(&Pubkey::new_unique()) // creator
+                .pubkey(&base_mint)
+                .pubkey(&quote_mint)
+                .pubkey(&Pubkey::new_unique()) // lp_mint
+                .pubkey(&base_vault)
+                .pubkey(&quote_vault)
+                .u64(1_000_000) // lp_supply
+                .pubkey(&Pubkey::new_unique()); // coin_creator
+            fixture.insert(pool, pump_program_id(), layout.finish());
+            let mut layout = Layout::anchor([149, 8, 156, 202, 160, 252, 176, 217]);
+            layout
+                .pubkey(&Pubkey::new_unique()) // admin
+                .u64(20) // lp_fee_basis_points
+                .u64(5) // protocol_fee_basis_points
+                .u8(0) // disable_flags
+                .pubkey(&Pubkey::new_unique()) // protocol_fee_recipients
+                .zeros(7 * 32)
+                .u64(5); // coin_creator_fee_basis_points
+            fixture.insert(global_config_address(), pump_program_id(), layout.finish());
+
+            let dex_pool = fixture.update(PoolKind::Pump, pool);
+            let metas = fixture.metas(dex_pool.as_ref());
+            assert_eq!(metas[6..8], [fixture.token_vault, fixture.sol_vault]);
+            let (token_account, sol_account) = fixture.user_accounts();
+            for direction in DIRECTIONS {
+                let (keys, data) = fixture.swap(dex_pool.as_ref(), direction);
+                assert_eq!(keys[3..5], [base_mint, quote_mint]);
+                let (user_base, user_quote) = fixture.pool_order(token_account, sol_account);
+                assert_eq!(keys[5..9], [user_base, user_quote, base_vault, quote_vault]);
+                let (base_program, quote_program) = fixture.programs();
+                assert_eq!(keys[11..13], [base_program, quote_program]);
+                let sells_base = sells_first(direction, token_is_first);
+                let discriminator = if sells_base {
+                    SELL_DISCRIMINATOR
+                } else {
+                    BUY_DISCRIMINATOR
+                };
+                assert_eq!(data[..8], discriminator);
+            }
+        }
+    }
+
+    #[test]
+    fn dlmm_orders_reserves_mints_and_programs_by_x_and_y() {
+        for token_is_first in [true, false] {
+            let mut fixture = Fixture::new(TOKEN_2022_PROGRAM_ID, token_is_first);
+            let (pair, oracle) = (Pubkey::new_unique(), Pubkey::new_unique());
+            let (mint_x, mint_y) = fixture.mints();
+            let (reserve_x, reserve_y) = fixture.vaults();
+            let mut layout = Layout::anchor([33, 11, 49, 98, 181, 101, 177, 13]);
+            layout
+                .zeros(64) // parameters, v_parameters
+                .zeros(4) // bump_seed, bin_step_seed, pair_type
+                .i32(0) // active_id
+                .u16(10) // bin_step
+                .u8(0) // status
+                .zeros(5)
+                .pubkey(&mint_x)
+                .pubkey(&mint_y)
+                .pubkey(&reserve_x)
+                .pubkey(&reserve_y)
+                .zeros(16 + 32 + 2 * 144) // protocol_fee, padding, reward_infos
+                .pubkey(&oracle);
+            layout.zeros(8 + std::mem::size_of::<LbPair>() - layout.len());
+            fixture.insert(pair, dlmm_program_id(), layout.finish());
+
+            let dex_pool = fixture.update(PoolKind::MeteoraDlmm, pair);
+            let metas = fixture.metas(dex_pool.as_ref());
+            assert_eq!(
+                metas[4..7],
+                [fixture.token_vault, fixture.sol_vault, oracle]
+            );
+            for direction in DIRECTIONS {
+                let (keys, _) = fixture.swap(dex_pool.as_ref(), direction);
+                let (user_in, user_out) = fixture.user_in_out(direction);
+                assert_eq!(keys[2..6], [reserve_x, reserve_y, user_in, user_out]);
+                assert_eq!(keys[6..9], [mint_x, mint_y, oracle]);
+                let (program_x, program_y) = fixture.programs();
+                assert_eq!(keys[11..13], [program_x, program_y]);
+            }
+        }
+    }
+
+    #[test]
+    fn whirlpool_orders_a_and_b_and_sets_a_to_b_by_direction() {
+        for token_is_first in [true, false] {
+            let mut fixture = Fixture::new(TOKEN_2022_PROGRAM_ID, token_is_first);
+            let pool = Pubkey::new_unique();
+            let (mint_a, mint_b) = fixture.mints();
+            let (vault_a, vault_b) = fixture.vaults();
+            let mut layout = Layout::anchor([63, 149, 209, 12, 225, 128, 99, 9]);
+            layout
+                .pubkey(&Pubkey::new_unique()) // whirlpools_config
+                .u8(255) // whirlpool_bump
+                .u16(64) // tick_spacing
+                .u16(64) // tick_spacing_seed
+                .u16(3_000) // fee_rate
+                .u16(300) // protocol_fee_rate
+                .u128(1_000_000_000_000) // liquidity
+                .u128(1 << 64) // sqrt_price
+                .i32(100) // tick_current_index
+                .zeros(16) // protocol_fee_owed_a, protocol_fee_owed_b
+                .pubkey(&mint_a)
+                .pubkey(&vault_a)
+                .zeros(16) // fee_growth_global_a
+                .pubkey(&mint_b)
+                .pubkey(&vault_b)
+                .zeros(16 + 8 + 384); // fee_growth_global_b, reward_last_updated_timestamp, reward_infos
+            assert_eq!(layout.len(), WhirlpoolState::LEN);
+            fixture.insert(pool, whirlpool_program_id(), layout.finish());
+            let mut layout = Layout::anchor(TickArray::DISCRIMINATOR);
+            layout
+                .i32(0) // start_tick_index
+                .zeros(Tick::LEN * TICK_ARRAY_SIZE)
+                .pubkey(&pool);
+            let tick_array = get_tick_array_address(&pool, 0, &whirlpool_program_id());
+            fixture.insert(tick_array, whirlpool_program_id(), layout.finish());
+
+            let dex_pool = fixture.update(PoolKind::Whirlpool, pool);
+            let metas = fixture.metas(dex_pool.as_ref());
+            assert_eq!(metas[5..7], [fixture.token_vault, fixture.sol_vault]);
+            assert!(metas[7..].contains(&tick_array));
+            let (token_account, sol_account) = fixture.user_accounts();
+            let (owner_a, owner_b) = fixture.pool_order(token_account, sol_account);
+            for direction in DIRECTIONS {
+                let (keys, data) = fixture.swap(dex_pool.as_ref(), direction);
+                let (program_a, program_b) = fixture.programs();
+                assert_eq!(keys[..2], [program_a, program_b]);
+                assert_eq!(keys[5..7], [mint_a, mint_b]);
+                assert_eq!(keys[7..11], [owner_a, vault_a, owner_b, vault_b]);
+                let a_to_b = sells_first(direction, token_is_first);
+                assert_eq!(data[41], a_to_b as u8);
+            }
+        }
+    }
+
+    #[test]
+    fn raydium_clmm_orders_vaults_and_mints_by_direction() {
+        for token_is_first in [true, false] {
+            let mut fixture = Fixture::new(TOKEN_2022_PROGRAM_ID, token_is_first);
+            let (pool, amm_config, observation) = (
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+            );
+            let (mint_0, mint_1) = fixture.mints();
+            let (vault_0, vault_1) = fixture.vaults();
+            let mut layout = Layout::anchor([247, 237, 227, 245, 215, 195, 222, 70]);
+            layout
+                .u8(255) // bump
+                .pubkey(&amm_config)
+                .pubkey(&Pubkey::new_unique()) // owner
+                .pubkey(&mint_0)
+                .pubkey(&mint_1)
+                .pubkey(&vault_0)
+                .pubkey(&vault_1)
+                .pubkey(&observation)
+                .u8(6) // mint_decimals_0
+                .u8(9) // mint_decimals_1
+                .u16(60) // tick_spacing
+                .u128(1_000_000_000_000) // liquidity
+                .u128(1 << 64) // sqrt_price_x64
+                .i32(100); // tick_current
+            layout.zeros(clmm_info::POOL_STATE_SIZE - layout.len());
+            fixture.insert(pool, raydium_clmm_program_id(), layout.finish());
+            let mut layout = Layout::anchor([218, 244, 33, 104, 203, 203, 43, 111]);
+            layout
+                .u8(255) // bump
+                .u16(0) // index
+                .pubkey(&Pubkey::new_unique()) // owner
+                .u32(120_000) // protocol_fee_rate
+                .u32(2_500) // trade_fee_rate
+                .u16(60); // tick_spacing
+            layout.zeros(clmm_info::AMM_CONFIG_SIZE - layout.len());
+            fixture.insert(amm_config, raydium_clmm_program_id(), layout.finish());
+
+            let dex_pool = fixture.update(PoolKind::RaydiumClmm, pool);
+            let metas = fixture.metas(dex_pool.as_ref());
+            assert_eq!(metas[2..4], [pool, amm_config]);
+            assert_eq!(metas[6..8], [fixture.token_vault, fixture.sol_vault]);
+            for direction in DIRECTIONS {
+                let (keys, _) = fixture.swap(dex_pool.as_ref(), direction);
+                let (user_in, user_out) = fixture.user_in_out(direction);
+                let (vault_in, vault_out) =
+                    in_out(direction, fixture.token_vault, fixture.sol_vault);
+                let (mint_in, mint_out) = in_out(direction, fixture.token, sol_mint());
+                assert_eq!(
+                    keys[3..8],
+                    [user_in, user_out, vault_in, vault_out, observation]
+                );
+                assert_eq!(keys[11..13], [mint_in, mint_out]);
+            }
+        }
+    }
+
+    #[test]
+    fn damm_orders_vault_accounts_by_a_and_b() {
+        for token_is_first in [true, false] {
+            let mut fixture = Fixture::new(spl_token::ID, token_is_first);
+            let pool = Pubkey::new_unique();
+            let token_side = [(); 4].map(|_| Pubkey::new_unique());
+            let sol_side = [(); 4].map(|_| Pubkey::new_unique());
+            // Meteora vault, the pool's LP of that vault, the vault's LP mint and the admin fee
+            // account, for the token then SOL
+            let [token_meteora_vault, token_pool_lp, token_lp_mint, token_admin_fee] = token_side;
+            let [sol_meteora_vault, sol_pool_lp, sol_lp_mint, sol_admin_fee] = sol_side;
+            for (meteora_vault, token_vault, mint, lp_mint, pool_lp) in [
+                (
+                    token_meteora_vault,
+                    fixture.token_vault,
+                    fixture.token,
+                    token_lp_mint,
+                    token_pool_lp,
+                ),
+                (
+                    sol_meteora_vault,
+                    fixture.sol_vault,
+                    sol_mint(),
+                    sol_lp_mint,
+                    sol_pool_lp,
+                ),
+            ] {
+                let mut layout = Layout::anchor([211, 8, 232, 43, 2, 152, 117, 119]);
+                layout
+                    .u8(1) // enabled
+                    .zeros(2) // bumps
+                    .u64(1_000_000_000) // total_amount
+                    .pubkey(&token_vault)
+                    .pubkey(&Pubkey::new_unique()) // fee_vault
+                    .pubkey(&mint)
+                    .pubkey(&lp_mint)
+                    .zeros(30 * 32) // strategies
+                    .zeros(3 * 32) // base, admin, operator
+                    .zeros(3 * 8); // locked_profit_tracker
+                fixture.insert(meteora_vault, vault_program_id(), layout.finish());
+                fixture.mint(lp_mint, spl_token::ID, 1_000_000);
+                fixture.token_account(pool_lp, lp_mint, 500_000);
+            }
+            let (mint_a, mint_b) = fixture.mints();
+            let (vault_a, vault_b) = fixture.pool_order(token_meteora_vault, sol_meteora_vault);
+            let (pool_lp_a, pool_lp_b) = fixture.pool_order(token_pool_lp, sol_pool_lp);
+            let (admin_fee_a, admin_fee_b) = fixture.pool_order(token_admin_fee, sol_admin_fee);
+            let mut layout = Layout::anchor([241, 154, 109, 4, 17, 177, 109, 188]);
+            layout
+                .pubkey(&Pubkey::new_unique()) // lp_mint
+                .pubkey(&mint_a)
+                .pubkey(&mint_b)
+                .pubkey(&vault_a)
+                .pubkey(&vault_b)
+                .pubkey(&pool_lp_a)
+                .pubkey(&pool_lp_b)
+                .u8(254) // a_vault_lp_bump
+                .bool(true) // enabled
+                .pubkey(&admin_fee_a)
+                .pubkey(&admin_fee_b)
+                .pubkey(&Pubkey::new_unique()) // admin
+                .u64(25) // trade_fee_numerator
+                .u64(10_000) // trade_fee_denominator
+                .u64(5) // owner_trade_fee_numerator
+                .u64(10_000) // owner_trade_fee_denominator
+                .u8(1) // pool_type
+                .pubkey(&Pubkey::default()) // stake
+                .zeros(15 + 29 * 16) // padding
+                .u8(0); // curve_type
+            fixture.insert(pool, damm_program_id(), layout.finish());
+
+            let dex_pool = fixture.update(PoolKind::MeteoraDamm, pool);
+            let metas = fixture.metas(dex_pool.as_ref());
+            assert_eq!(
+                metas[4..8],
+                [
+                    token_meteora_vault,
+                    sol_meteora_vault,
+                    fixture.token_vault,
+                    fixture.sol_vault
+                ]
+            );
+            assert_eq!(metas[8..10], [token_lp_mint, sol_lp_mint]);
+            assert_eq!(
+                metas[10..14],
+                [token_pool_lp, sol_pool_lp, token_admin_fee, sol_admin_fee]
+            );
+            let (token_vault_a, token_vault_b) = fixture.vaults();
+            let (lp_mint_a, lp_mint_b) = fixture.pool_order(token_lp_mint, sol_lp_mint);
+            for direction in DIRECTIONS {
+                let (keys, _) = fixture.swap(dex_pool.as_ref(), direction);
+                let (user_in, user_out) = fixture.user_in_out(direction);
+                assert_eq!(keys[1..5], [user_in, user_out, vault_a, vault_b]);
+                assert_eq!(
+                    keys[5..9],
+                    [token_vault_a, token_vault_b, lp_mint_a, lp_mint_b]
+                );
+                assert_eq!(keys[9..11], [pool_lp_a, pool_lp_b]);
+                assert_eq!(
+                    keys[11],
+                    in_out(direction, token_admin_fee, sol_admin_fee).0
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn damm_v2_orders_vaults_mints_and_programs_by_a_and_b() {
+        for token_is_first in [true, false] {
+            let mut fixture = Fixture::new(TOKEN_2022_PROGRAM_ID, token_is_first);
+            let pool = Pubkey::new_unique();
+            let (mint_a, mint_b) = fixture.mints();
+            let (vault_a, vault_b) = fixture.vaults();
+            let (flag_a, flag_b) = fixture.pool_order(1, 0);
+            let mut layout = Layout::anchor([241, 154, 109, 4, 17, 177, 109, 188]);
+            layout
+                .zeros(160) // pool_fees
+                .pubkey(&mint_a)
+                .pubkey(&mint_b)
+                .pubkey(&vault_a)
+                .pubkey(&vault_b)
+                .zeros(185) // whitelisted_vault through activation_type
+                .u8(0) // pool_status
+                .u8(flag_a) // token_a_flag
+                .u8(flag_b); // token_b_flag
+            layout.zeros(DAMM_V2_POOL_SIZE - layout.len());
+            fixture.insert(pool, damm_v2_program_id(), layout.finish());
+
+            let dex_pool = fixture.update(PoolKind::MeteoraDammV2, pool);
+            let metas = fixture.metas(dex_pool.as_ref());
+            assert_eq!(metas[5..7], [fixture.token_vault, fixture.sol_vault]);
+            for direction in DIRECTIONS {
+                let (keys, _) = fixture.swap(dex_pool.as_ref(), direction);
+                let (user_in, user_out) = fixture.user_in_out(direction);
+                assert_eq!(keys[2..6], [user_in, user_out, vault_a, vault_b]);
+                assert_eq!(keys[6..8], [mint_a, mint_b]);
+                let (program_a, program_b) = fixture.programs();
+                assert_eq!(keys[9..11], [program_a, program_b]);
+            }
+        }
+    }
+
+    #[test]
+    fn vertigo_buys_when_selling_mint_a() {
+        for token_is_first in [true, false] {
+            let mut fixture = Fixture::new(TOKEN_2022_PROGRAM_ID, token_is_first);
+            let (pool, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+            let (mint_a, mint_b) = fixture.mints();
+            let (reserves_a, reserves_b) = fixture.pool_order(5_000_000_000_000, 50_000_000_000);
+            let mut layout = Layout::anchor(POOL_DISCRIMINATOR);
+            layout
+                .bool(true) // enabled
+                .pubkey(&owner)
+                .pubkey(&mint_a)
+                .pubkey(&mint_b)
+                .u128(reserves_a)
+                .u128(reserves_b)
+                .u128(30_000_000_000) // shift
+                .u64(0) // royalties
+                .u64(0) // vertigo_fees
+                .u8(254) // bump
+                .u64(1_000) // normalization_period
+                .f64(10.0) // decay
+                .u64(0) // reference
+                .u16(100) // royalties_bps
+                .option_pubkey(None); // privileged_swapper
+            layout.zeros(VERTIGO_POOL_SIZE - layout.len());
+            fixture.insert(pool, vertigo_program_id(), layout.finish());
+
+            let dex_pool = fixture.update(PoolKind::Vertigo, pool);
+            let token_vault = derive_vault_address(&pool, &fixture.token).0;
+            let sol_vault = derive_vault_address(&pool, &sol_mint()).0;
+            let metas = fixture.metas(dex_pool.as_ref());
+            assert_eq!(metas[2..6], [pool, owner, token_vault, sol_vault]);
+            let (token_account, sol_account) = fixture.user_accounts();
+            let (user_a, user_b) = fixture.pool_order(token_account, sol_account);
+            let (vault_a, vault_b) = fixture.pool_order(token_vault, sol_vault);
+            for direction in DIRECTIONS {
+                let (keys, data) = fixture.swap(dex_pool.as_ref(), direction);
+                assert_eq!(keys[3..7], [mint_a, mint_b, user_a, user_b]);
+                assert_eq!(keys[7..9], [vault_a, vault_b]);
+                let (program_a, program_b) = fixture.programs();
+                assert_eq!(keys[9..11], [program_a, program_b]);
+                let sells_a = sells_first(direction, token_is_first);
+                let discriminator = if sells_a {
+                    BUY_DISCRIMINATOR
+                } else {
+                    SELL_DISCRIMINATOR
+                };
+                assert_eq!(data[..8], discriminator);
+            }
+        }
+    }
+
+    #[test]
+    fn solfi_orders_base_and_quote_by_direction() {
+        for token_is_first in [true, false] {
+            let mut fixture = Fixture::new(spl_token::ID, token_is_first);
+            let pool = Pubkey::new_unique();
+            let (base_mint, quote_mint) = fixture.mints();
+            let (base_vault, quote_vault) = fixture.vaults();
+            let mut layout = Layout::new();
+            layout
+                .zeros(2_664) // undocumented pricing state
+                .pubkey(&base_mint)
+                .pubkey(&quote_mint)
+                .zeros(8)
+                .pubkey(&base_vault)
+                .pubkey(&quote_vault);
+            assert_eq!(layout.len(), MIN_POOL_ACCOUNT_SIZE);
+            fixture.insert(pool, solfi_program_id(), layout.finish());
+
+            let dex_pool = fixture.update(PoolKind::Solfi, pool);
+            let metas = fixture.metas(dex_pool.as_ref());
+            assert_eq!(metas[4..6], [fixture.token_vault, fixture.sol_vault]);
+            let (token_account, sol_account) = fixture.user_accounts();
+            let (user_base, user_quote) = fixture.pool_order(token_account, sol_account);
+            for direction in DIRECTIONS {
+                let (keys, data) = fixture.swap(dex_pool.as_ref(), direction);
+                assert_eq!(keys[2..6], [base_vault, quote_vault, user_base, user_quote]);
+                let base_to_quote = sells_first(direction, token_is_first);
+                assert_eq!(data.last(), Some(&(!base_to_quote as u8)));
+            }
+        }
+    }
+}
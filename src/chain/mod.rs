@@ -1,6 +1,12 @@
+pub mod arbitrage;
+pub mod compute_units;
 pub mod constants;
+pub mod dex_pool;
 pub mod pools;
+pub mod quote;
 pub mod refresh;
+pub mod route;
+pub mod simulation;
 pub mod transaction;
 pub mod token_fetch;
 pub mod token_price;
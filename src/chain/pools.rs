@@ -1,20 +1,172 @@
 use crate::{
-    chain::constants::SOL_MINT,
-    dex::raydium::{clmm_info::POOL_TICK_ARRAY_BITMAP_SEED, raydium_clmm_program_id},
+    chain::{constants::SOL_MINT, dex_pool::DexPool},
+    dex::{
+        meteora::{
+            constants::{damm_program_id, damm_v2_program_id, dlmm_program_id},
+            damm_quote::DammVaultSide,
+            dammv2_info::DammV2Pool,
+            dlmm_info::{BinArray, LbPair},
+        },
+        pump::{
+            amm_info::{fee_config_address, global_config_address},
+            constants::pump_program_id,
+        },
+        raydium::{
+            clmm_info::{
+                PoolState, TickArrayBitmapExtension, TickArrayState, POOL_TICK_ARRAY_BITMAP_SEED,
+            },
+            market_info::MarketAccounts,
+            raydium_clmm_program_id, raydium_cp_program_id, raydium_program_id,
+        },
+        solfi::constants::solfi_program_id,
+        vertigo::{constants::vertigo_program_id, info::FeeParams},
+        whirlpool::{
+            constants::whirlpool_program_id, state::Whirlpool, tick_sequence::TickArraySequence,
+        },
+    },
 };
+use meteora_damm_cpi::Pool as DammPoolState;
 use solana_sdk::pubkey::Pubkey;
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 
-#[derive(Debug, Clone)]
+// Fee charged by every Raydium AMM v4 pool, used until the pool state has been fetched
+const RAYDIUM_DEFAULT_SWAP_FEE_NUMERATOR: u64 = 25;
+const RAYDIUM_DEFAULT_SWAP_FEE_DENOMINATOR: u64 = 10000;
+
+/// The DEX program family a configured pool belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PoolKind {
+    Raydium,
+    RaydiumCp,
+    RaydiumClmm,
+    Pump,
+    MeteoraDlmm,
+    MeteoraDamm,
+    MeteoraDammV2,
+    Whirlpool,
+    Solfi,
+    Vertigo,
+}
+
+impl PoolKind {
+    /// Program that owns pool accounts of this kind
+    pub fn program_id(&self) -> Pubkey {
+        match self {
+            PoolKind::Raydium => raydium_program_id(),
+            PoolKind::RaydiumCp => raydium_cp_program_id(),
+            PoolKind::RaydiumClmm => raydium_clmm_program_id(),
+            PoolKind::Pump => pump_program_id(),
+            PoolKind::MeteoraDlmm => dlmm_program_id(),
+            PoolKind::MeteoraDamm => damm_program_id(),
+            PoolKind::MeteoraDammV2 => damm_v2_program_id(),
+            PoolKind::Whirlpool => whirlpool_program_id(),
+            PoolKind::Solfi => solfi_program_id(),
+            PoolKind::Vertigo => vertigo_program_id(),
+        }
+    }
+
+    /// Whether pools of this kind can be quoted. Solfi prices from state that is not decoded,
+    /// so its pools are skipped when loading rather than failing every scan.
+    pub fn is_quotable(&self) -> bool {
+        !matches!(self, PoolKind::Solfi)
+    }
+
+    /// A pool of this kind at `pool` that knows nothing else yet. Its first update resolves
+    /// it for `mint` and fails unless the pool trades `mint` against SOL.
+    pub fn new_pool(&self, pool: Pubkey, mint: Pubkey) -> Box<dyn DexPool> {
+        match self {
+            PoolKind::Raydium => Box::new(RaydiumPool {
+                pool,
+                token_mint: mint,
+                swap_fee_numerator: RAYDIUM_DEFAULT_SWAP_FEE_NUMERATOR,
+                swap_fee_denominator: RAYDIUM_DEFAULT_SWAP_FEE_DENOMINATOR,
+                ..Default::default()
+            }),
+            PoolKind::RaydiumCp => Box::new(RaydiumCpPool {
+                pool,
+                token_mint: mint,
+                ..Default::default()
+            }),
+            PoolKind::RaydiumClmm => Box::new(RaydiumClmmPool {
+                pool,
+                token_mint: mint,
+                ..Default::default()
+            }),
+            PoolKind::Pump => Box::new(PumpPool {
+                pool,
+                token_mint: mint,
+                ..Default::default()
+            }),
+            PoolKind::MeteoraDlmm => Box::new(DlmmPool {
+                pair: pool,
+                token_mint: mint,
+                ..Default::default()
+            }),
+            PoolKind::MeteoraDamm => Box::new(MeteoraDAmmPool {
+                pool,
+                token_mint: mint,
+                ..Default::default()
+            }),
+            PoolKind::MeteoraDammV2 => Box::new(MeteoraDAmmV2Pool {
+                pool,
+                token_mint: mint,
+                ..Default::default()
+            }),
+            PoolKind::Whirlpool => Box::new(WhirlpoolPool {
+                pool,
+                token_mint: mint,
+                ..Default::default()
+            }),
+            PoolKind::Solfi => Box::new(SolfiPool {
+                pool,
+                token_mint: mint,
+                ..Default::default()
+            }),
+            PoolKind::Vertigo => Box::new(VertigoPool {
+                pool,
+                token_mint: mint,
+                ..Default::default()
+            }),
+        }
+    }
+}
+
+impl fmt::Display for PoolKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            PoolKind::Raydium => "raydium",
+            PoolKind::RaydiumCp => "raydium_cp",
+            PoolKind::RaydiumClmm => "raydium_clmm",
+            PoolKind::Pump => "pump",
+            PoolKind::MeteoraDlmm => "meteora_dlmm",
+            PoolKind::MeteoraDamm => "meteora_damm",
+            PoolKind::MeteoraDammV2 => "meteora_damm_v2",
+            PoolKind::Whirlpool => "whirlpool",
+            PoolKind::Solfi => "solfi",
+            PoolKind::Vertigo => "vertigo",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct RaydiumPool {
     pub pool: Pubkey,
     pub token_vault: Pubkey,
     pub sol_vault: Pubkey,
     pub token_mint: Pubkey,
     pub base_mint: Pubkey,
+    pub token_is_coin: bool,
+    pub open_orders: Pubkey,
+    pub market: Pubkey,
+    pub market_accounts: Option<MarketAccounts>, // None until the market has been loaded
+    pub swap_fee_numerator: u64,
+    pub swap_fee_denominator: u64,
+    pub token_reserve: u64, // vault balance minus pending PnL
+    pub sol_reserve: u64,   // vault balance minus pending PnL
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct RaydiumCpPool {
     pub pool: Pubkey,
     pub token_vault: Pubkey,
@@ -23,9 +175,18 @@ pub struct RaydiumCpPool {
     pub observation: Pubkey,
     pub token_mint: Pubkey,
     pub base_mint: Pubkey,
+    pub token_program: Pubkey, // owner of token_mint
+    pub base_token_program: Pubkey,
+    pub trade_fee_rate: u64, // out of FEE_RATE_DENOMINATOR
+    pub protocol_fee_rate: u64,
+    pub fund_fee_rate: u64,
+    pub creator_fee_rate: u64, // zero unless the pool enables creator fees
+    pub creator_fee_on: u8,    // 0 = input token, 1 = token 0 only, 2 = token 1 only
+    pub token_reserve: u64,    // vault balance minus accrued fees
+    pub sol_reserve: u64,      // vault balance minus accrued fees
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct PumpPool {
     pub pool: Pubkey,
     pub token_vault: Pubkey,
@@ -35,9 +196,20 @@ pub struct PumpPool {
     pub coin_creator_vault_authority: Pubkey,
     pub token_mint: Pubkey,
     pub base_mint: Pubkey,
+    pub token_program: Pubkey, // owner of token_mint
+    pub base_token_program: Pubkey,
+    pub global_config: Pubkey,
+    pub fee_config: Pubkey, // fee program account that overrides GlobalConfig's rates
+    pub protocol_fee_recipient: Pubkey,
+    pub token_is_pool_base: bool, // whether token_mint is the pool's base mint
+    pub lp_fee_basis_points: u64,
+    pub protocol_fee_basis_points: u64,
+    pub coin_creator_fee_basis_points: u64, // zero unless the pool has a coin creator
+    pub token_reserve: u64,
+    pub sol_reserve: u64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct DlmmPool {
     pub pair: Pubkey,
     pub token_vault: Pubkey,
@@ -47,9 +219,15 @@ pub struct DlmmPool {
     pub memo_program: Option<Pubkey>, // For Token 2022 support
     pub token_mint: Pubkey,
     pub base_mint: Pubkey,
+    pub token_program: Pubkey, // owner of token_mint
+    pub base_token_program: Pubkey,
+    pub token_is_x: bool, // whether token_mint is the pair's token X
+    pub lb_pair: Option<LbPair>,
+    pub bin_array_state: Vec<BinArray>, // the loaded subset of bin_arrays
+    pub current_timestamp: i64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct WhirlpoolPool {
     pub pool: Pubkey,
     pub oracle: Pubkey,
@@ -59,9 +237,14 @@ pub struct WhirlpoolPool {
     pub memo_program: Option<Pubkey>, // For Token 2022 support
     pub token_mint: Pubkey,
     pub base_mint: Pubkey,
+    pub token_program: Pubkey, // owner of token_mint
+    pub base_token_program: Pubkey,
+    pub token_is_mint_a: bool,
+    pub whirlpool: Option<Whirlpool>,
+    pub tick_array_state: Option<TickArraySequence>, // loaded arrays around the current tick
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct RaydiumClmmPool {
     pub pool: Pubkey,
     pub amm_config: Pubkey,
@@ -73,9 +256,16 @@ pub struct RaydiumClmmPool {
     pub memo_program: Option<Pubkey>, // For Token 2022 support
     pub token_mint: Pubkey,
     pub base_mint: Pubkey,
+    pub token_program: Pubkey, // owner of token_mint
+    pub base_token_program: Pubkey,
+    pub token_is_mint_0: bool,
+    pub pool_state: Option<PoolState>,
+    pub trade_fee_rate: u32, // from the AMM config, out of FEE_RATE_DENOMINATOR
+    pub extension: Option<TickArrayBitmapExtension>,
+    pub tick_array_state: Vec<TickArrayState>, // the initialized subset of tick_arrays
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct MeteoraDAmmPool {
     pub pool: Pubkey,
     pub token_x_vault: Pubkey,
@@ -90,27 +280,47 @@ pub struct MeteoraDAmmPool {
     pub admin_token_fee_sol: Pubkey,
     pub token_mint: Pubkey,
     pub base_mint: Pubkey,
+    pub token_reserve: u64, // underlying tokens behind the pool's vault LP
+    pub sol_reserve: u64,   // underlying tokens behind the pool's vault LP
+    pub trade_fee_numerator: u64,
+    pub trade_fee_denominator: u64,
+    pub owner_trade_fee_numerator: u64,
+    pub owner_trade_fee_denominator: u64,
+    pub token_is_a: bool,
+    pub damm_pool: Option<DammPoolState>,
+    pub token_x_side: Option<DammVaultSide>,
+    pub token_sol_side: Option<DammVaultSide>,
+    pub current_time: u64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct SolfiPool {
     pub pool: Pubkey,
     pub token_x_vault: Pubkey,
     pub token_sol_vault: Pubkey,
     pub token_mint: Pubkey,
     pub base_mint: Pubkey,
+    pub token_is_base: bool, // whether token_mint is the pool's base mint
+    pub token_reserve: u64,  // vault balance
+    pub sol_reserve: u64,    // vault balance
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct MeteoraDAmmV2Pool {
     pub pool: Pubkey,
     pub token_x_vault: Pubkey,
     pub token_sol_vault: Pubkey,
     pub token_mint: Pubkey,
     pub base_mint: Pubkey,
+    pub token_program: Pubkey, // owner of token_mint
+    pub base_token_program: Pubkey,
+    pub token_is_a: bool,
+    pub damm_v2: Option<DammV2Pool>,
+    pub current_slot: u64,
+    pub current_timestamp: u64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct VertigoPool {
     pub pool: Pubkey,
     pub pool_owner: Pubkey,
@@ -118,28 +328,33 @@ pub struct VertigoPool {
     pub token_sol_vault: Pubkey,
     pub token_mint: Pubkey,
     pub base_mint: Pubkey,
+    pub token_program: Pubkey, // owner of token_mint
+    pub base_token_program: Pubkey,
+    pub token_is_mint_a: bool,
+    pub token_reserve: u64, // reserves tracked by the pool, without the virtual shift
+    pub sol_reserve: u64,
+    pub shift: u128,        // virtual liquidity on the mint A side
+    pub fee_params: FeeParams,
+    pub current_slot: u64,
 }
 
 #[derive(Debug, Clone)]
 pub struct MintPoolData {
     pub mint: Pubkey,
     pub token_program: Pubkey, // Support for both Token and Token 2022
+    pub decimals: u8,          // read from the mint account when the pools are loaded
     pub wallet_account: Pubkey,
     pub wallet_wsol_account: Pubkey,
-    pub raydium_pools: Vec<RaydiumPool>,
-    pub raydium_cp_pools: Vec<RaydiumCpPool>,
-    pub pump_pools: Vec<PumpPool>,
-    pub dlmm_pairs: Vec<DlmmPool>,
-    pub whirlpool_pools: Vec<WhirlpoolPool>,
-    pub raydium_clmm_pools: Vec<RaydiumClmmPool>,
-    pub meteora_damm_pools: Vec<MeteoraDAmmPool>,
-    pub solfi_pools: Vec<SolfiPool>,
-    pub meteora_damm_v2_pools: Vec<MeteoraDAmmV2Pool>,
-    pub vertigo_pools: Vec<VertigoPool>,
+    pub pools: Vec<Box<dyn DexPool>>,
 }
 
 impl MintPoolData {
-    pub fn new(mint: &str, wallet_account: &str, token_program: Pubkey) -> anyhow::Result<Self> {
+    pub fn new(
+        mint: &str,
+        wallet_account: &str,
+        token_program: Pubkey,
+        decimals: u8,
+    ) -> anyhow::Result<Self> {
         let sol_mint = Pubkey::from_str(SOL_MINT)?;
         let wallet_pk = Pubkey::from_str(wallet_account)?;
         let wallet_wsol_pk =
@@ -147,21 +362,26 @@ impl MintPoolData {
         Ok(Self {
             mint: Pubkey::from_str(mint)?,
             token_program,
+            decimals,
             wallet_account: wallet_pk,
             wallet_wsol_account: wallet_wsol_pk,
-            raydium_pools: Vec::new(),
-            raydium_cp_pools: Vec::new(),
-            pump_pools: Vec::new(),
-            dlmm_pairs: Vec::new(),
-            whirlpool_pools: Vec::new(),
-            raydium_clmm_pools: Vec::new(),
-            meteora_damm_pools: Vec::new(),
-            solfi_pools: Vec::new(),
-            meteora_damm_v2_pools: Vec::new(),
-            vertigo_pools: Vec::new(),
+            pools: Vec::new(),
         })
     }
 
+    pub fn add_pool(&mut self, pool: impl DexPool + 'static) {
+        self.pools.push(Box::new(pool));
+    }
+
+    pub fn pools(&self) -> impl Iterator<Item = &dyn DexPool> {
+        self.pools.iter().map(|pool| pool.as_ref())
+    }
+
+    /// Number of pools of `kind`
+    pub fn pool_count(&self, kind: PoolKind) -> usize {
+        self.pools().filter(|pool| pool.kind() == kind).count()
+    }
+
     pub fn add_raydium_pool(
         &mut self,
         pool: &str,
@@ -170,12 +390,17 @@ impl MintPoolData {
         token_mint: &str,
         base_mint: &str,
     ) -> anyhow::Result<()> {
-        self.raydium_pools.push(RaydiumPool {
+        self.add_pool(RaydiumPool {
             pool: Pubkey::from_str(pool)?,
             token_vault: Pubkey::from_str(token_vault)?,
             sol_vault: Pubkey::from_str(sol_vault)?,
             token_mint: Pubkey::from_str(token_mint)?,
             base_mint: Pubkey::from_str(base_mint)?,
+            swap_fee_numerator: RAYDIUM_DEFAULT_SWAP_FEE_NUMERATOR,
+            swap_fee_denominator: RAYDIUM_DEFAULT_SWAP_FEE_DENOMINATOR,
+            token_reserve: 0,
+            sol_reserve: 0,
+            ..Default::default()
         });
         Ok(())
     }
@@ -190,7 +415,7 @@ impl MintPoolData {
         token_mint: &str,
         base_mint: &str,
     ) -> anyhow::Result<()> {
-        self.raydium_cp_pools.push(RaydiumCpPool {
+        self.add_pool(RaydiumCpPool {
             pool: Pubkey::from_str(pool)?,
             token_vault: Pubkey::from_str(token_vault)?,
             sol_vault: Pubkey::from_str(sol_vault)?,
@@ -198,6 +423,14 @@ impl MintPoolData {
             observation: Pubkey::from_str(observation)?,
             token_mint: Pubkey::from_str(token_mint)?,
             base_mint: Pubkey::from_str(base_mint)?,
+            trade_fee_rate: 0,
+            protocol_fee_rate: 0,
+            fund_fee_rate: 0,
+            creator_fee_rate: 0,
+            creator_fee_on: 0,
+            token_reserve: 0,
+            sol_reserve: 0,
+            ..Default::default()
         });
         Ok(())
     }
@@ -208,12 +441,13 @@ impl MintPoolData {
         token_vault: &str,
         sol_vault: &str,
         fee_token_wallet: &str,
+        protocol_fee_recipient: &str,
         coin_creator_vault_ata: &str,
         coin_creator_authority: &str,
         token_mint: &str,
         base_mint: &str,
     ) -> anyhow::Result<()> {
-        self.pump_pools.push(PumpPool {
+        self.add_pool(PumpPool {
             pool: Pubkey::from_str(pool)?,
             token_vault: Pubkey::from_str(token_vault)?,
             sol_vault: Pubkey::from_str(sol_vault)?,
@@ -222,6 +456,16 @@ impl MintPoolData {
             coin_creator_vault_authority: Pubkey::from_str(coin_creator_authority)?,
             token_mint: Pubkey::from_str(token_mint)?,
             base_mint: Pubkey::from_str(base_mint)?,
+            global_config: global_config_address(),
+            fee_config: fee_config_address(),
+            protocol_fee_recipient: Pubkey::from_str(protocol_fee_recipient)?,
+            token_is_pool_base: true,
+            lp_fee_basis_points: 0,
+            protocol_fee_basis_points: 0,
+            coin_creator_fee_basis_points: 0,
+            token_reserve: 0,
+            sol_reserve: 0,
+            ..Default::default()
         });
         Ok(())
     }
@@ -248,7 +492,7 @@ impl MintPoolData {
             None
         };
 
-        self.dlmm_pairs.push(DlmmPool {
+        self.add_pool(DlmmPool {
             pair: Pubkey::from_str(pair)?,
             token_vault: Pubkey::from_str(token_vault)?,
             sol_vault: Pubkey::from_str(sol_vault)?,
@@ -257,6 +501,7 @@ impl MintPoolData {
             memo_program: memo_program_pubkey,
             token_mint: Pubkey::from_str(token_mint)?,
             base_mint: Pubkey::from_str(base_mint)?,
+            ..Default::default()
         });
         Ok(())
     }
@@ -283,7 +528,7 @@ impl MintPoolData {
             None
         };
 
-        self.whirlpool_pools.push(WhirlpoolPool {
+        self.add_pool(WhirlpoolPool {
             pool: Pubkey::from_str(pool)?,
             oracle: Pubkey::from_str(oracle)?,
             x_vault: Pubkey::from_str(x_vault)?,
@@ -292,6 +537,7 @@ impl MintPoolData {
             memo_program: memo_program_pubkey,
             token_mint: Pubkey::from_str(token_mint)?,
             base_mint: Pubkey::from_str(base_mint)?,
+            ..Default::default()
         });
         Ok(())
     }
@@ -328,7 +574,7 @@ impl MintPoolData {
             None
         };
 
-        self.raydium_clmm_pools.push(RaydiumClmmPool {
+        self.add_pool(RaydiumClmmPool {
             pool: pool_pubkey,
             amm_config: Pubkey::from_str(amm_config)?,
             observation_state: Pubkey::from_str(observation_state)?,
@@ -339,6 +585,7 @@ impl MintPoolData {
             memo_program: memo_program_pubkey,
             token_mint: Pubkey::from_str(token_mint)?,
             base_mint: Pubkey::from_str(base_mint)?,
+            ..Default::default()
         });
         Ok(())
     }
@@ -359,7 +606,7 @@ impl MintPoolData {
         token_mint: &str,
         base_mint: &str,
     ) -> anyhow::Result<()> {
-        self.meteora_damm_pools.push(MeteoraDAmmPool {
+        self.add_pool(MeteoraDAmmPool {
             pool: Pubkey::from_str(pool)?,
             token_x_vault: Pubkey::from_str(token_x_vault)?,
             token_sol_vault: Pubkey::from_str(token_sol_vault)?,
@@ -373,6 +620,13 @@ impl MintPoolData {
             admin_token_fee_sol: Pubkey::from_str(admin_token_fee_sol)?,
             token_mint: Pubkey::from_str(token_mint)?,
             base_mint: Pubkey::from_str(base_mint)?,
+            token_reserve: 0,
+            sol_reserve: 0,
+            trade_fee_numerator: 0,
+            trade_fee_denominator: 0,
+            owner_trade_fee_numerator: 0,
+            owner_trade_fee_denominator: 0,
+            ..Default::default()
         });
         Ok(())
     }
@@ -385,12 +639,15 @@ impl MintPoolData {
         token_mint: &str,
         base_mint: &str,
     ) -> anyhow::Result<()> {
-        self.solfi_pools.push(SolfiPool {
+        self.add_pool(SolfiPool {
             pool: Pubkey::from_str(pool)?,
             token_x_vault: Pubkey::from_str(token_x_vault)?,
             token_sol_vault: Pubkey::from_str(token_sol_vault)?,
             token_mint: Pubkey::from_str(token_mint)?,
             base_mint: Pubkey::from_str(base_mint)?,
+            token_reserve: 0,
+            sol_reserve: 0,
+            ..Default::default()
         });
         Ok(())
     }
@@ -403,12 +660,13 @@ impl MintPoolData {
         token_mint: &str,
         base_mint: &str,
     ) -> anyhow::Result<()> {
-        self.meteora_damm_v2_pools.push(MeteoraDAmmV2Pool {
+        self.add_pool(MeteoraDAmmV2Pool {
             pool: Pubkey::from_str(pool)?,
             token_x_vault: Pubkey::from_str(token_x_vault)?,
             token_sol_vault: Pubkey::from_str(token_sol_vault)?,
             token_mint: Pubkey::from_str(token_mint)?,
             base_mint: Pubkey::from_str(base_mint)?,
+            ..Default::default()
         });
         Ok(())
     }
@@ -421,14 +679,22 @@ impl MintPoolData {
         token_sol_vault: &str,
         token_mint: &str,
         base_mint: &str,
+        token_is_mint_a: bool,
+        fee_params: FeeParams,
     ) -> anyhow::Result<()> {
-        self.vertigo_pools.push(VertigoPool {
+        self.add_pool(VertigoPool {
             pool: Pubkey::from_str(pool)?,
             pool_owner: Pubkey::from_str(pool_owner)?,
             token_x_vault: Pubkey::from_str(token_x_vault)?,
             token_sol_vault: Pubkey::from_str(token_sol_vault)?,
             token_mint: Pubkey::from_str(token_mint)?,
             base_mint: Pubkey::from_str(base_mint)?,
+            token_is_mint_a,
+            token_reserve: 0,
+            sol_reserve: 0,
+            shift: 0,
+            fee_params,
+            ..Default::default()
         });
         Ok(())
     }
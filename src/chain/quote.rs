@@ -0,0 +1,393 @@
+use crate::{
+    chain::pools::{
+        DlmmPool, MeteoraDAmmPool, MeteoraDAmmV2Pool, PumpPool, RaydiumClmmPool, RaydiumCpPool,
+        RaydiumPool, VertigoPool, WhirlpoolPool,
+    },
+    dex::{
+        clmm::SwapResult,
+        meteora::{damm_quote, dammv2_quote, dlmm_quote},
+        pump::quote::{self as pump_quote, PumpFees},
+        quote::{ceil_div, constant_product_amount_out, to_u64, SwapDirection, SwapQuote},
+        raydium::{
+            clmm_swap,
+            quote::{
+                amm_quote_exact_in, amm_quote_exact_out, cp_quote_exact_in, cp_quote_exact_out,
+                CpSwapFees,
+            },
+        },
+        whirlpool::swap as whirlpool_swap,
+    },
+};
+use anyhow::{anyhow, Result};
+
+// Creator fee modes of a CP-Swap pool
+const CREATOR_FEE_ON_BOTH_TOKENS: u8 = 0;
+const CREATOR_FEE_ON_TOKEN_0: u8 = 1;
+
+const BASIS_POINTS: u128 = 10_000;
+
+/// Whether a swap in `direction` sells the first token of a pair whose first token is the
+/// configured mint exactly when `token_is_first` holds
+fn sells_first(token_is_first: bool, direction: SwapDirection) -> bool {
+    (direction == SwapDirection::TokenToSol) == token_is_first
+}
+
+/// An exact-in CLMM swap as a quote, refused when the loaded tick arrays cannot fill it
+fn filled_exact_in(result: SwapResult) -> Result<SwapQuote> {
+    if result.amount_remaining > 0 {
+        return Err(anyhow!(
+            "Loaded tick arrays can only fill {} of the input",
+            result.amount_in
+        ));
+    }
+    Ok(SwapQuote {
+        amount_in: result.amount_in,
+        amount_out: result.amount_out,
+        fee_amount: result.fee_amount,
+    })
+}
+
+/// `(reserve_in, reserve_out)` for a swap in `direction`
+fn directional_reserves(
+    token_reserve: u64,
+    sol_reserve: u64,
+    direction: SwapDirection,
+) -> (u64, u64) {
+    match direction {
+        SwapDirection::SolToToken => (sol_reserve, token_reserve),
+        SwapDirection::TokenToSol => (token_reserve, sol_reserve),
+    }
+}
+
+impl RaydiumPool {
+    pub fn quote_exact_in(&self, amount_in: u64, direction: SwapDirection) -> Result<SwapQuote> {
+        let (reserve_in, reserve_out) =
+            directional_reserves(self.token_reserve, self.sol_reserve, direction);
+        amm_quote_exact_in(
+            amount_in,
+            reserve_in,
+            reserve_out,
+            self.swap_fee_numerator,
+            self.swap_fee_denominator,
+        )
+    }
+
+    pub fn quote_exact_out(&self, amount_out: u64, direction: SwapDirection) -> Result<SwapQuote> {
+        let (reserve_in, reserve_out) =
+            directional_reserves(self.token_reserve, self.sol_reserve, direction);
+        amm_quote_exact_out(
+            amount_out,
+            reserve_in,
+            reserve_out,
+            self.swap_fee_numerator,
+            self.swap_fee_denominator,
+        )
+    }
+}
+
+impl RaydiumCpPool {
+    fn fees(&self, direction: SwapDirection) -> CpSwapFees {
+        // CP-Swap pools always order token 0 below token 1
+        let (input_mint, output_mint) = match direction {
+            SwapDirection::SolToToken => (self.base_mint, self.token_mint),
+            SwapDirection::TokenToSol => (self.token_mint, self.base_mint),
+        };
+        let input_is_token_0 = input_mint < output_mint;
+        let creator_fee_on_input = match self.creator_fee_on {
+            CREATOR_FEE_ON_BOTH_TOKENS => true,
+            CREATOR_FEE_ON_TOKEN_0 => input_is_token_0,
+            _ => !input_is_token_0,
+        };
+
+        CpSwapFees {
+            trade_fee_rate: self.trade_fee_rate,
+            creator_fee_rate: self.creator_fee_rate,
+            creator_fee_on_input,
+        }
+    }
+
+    pub fn quote_exact_in(&self, amount_in: u64, direction: SwapDirection) -> Result<SwapQuote> {
+        let (reserve_in, reserve_out) =
+            directional_reserves(self.token_reserve, self.sol_reserve, direction);
+        cp_quote_exact_in(amount_in, reserve_in, reserve_out, self.fees(direction))
+    }
+
+    pub fn quote_exact_out(&self, amount_out: u64, direction: SwapDirection) -> Result<SwapQuote> {
+        let (reserve_in, reserve_out) =
+            directional_reserves(self.token_reserve, self.sol_reserve, direction);
+        cp_quote_exact_out(amount_out, reserve_in, reserve_out, self.fees(direction))
+    }
+}
+
+impl PumpPool {
+    fn fees(&self) -> PumpFees {
+        PumpFees {
+            lp_fee_basis_points: self.lp_fee_basis_points,
+            protocol_fee_basis_points: self.protocol_fee_basis_points,
+            coin_creator_fee_basis_points: self.coin_creator_fee_basis_points,
+        }
+    }
+
+    /// `(base_reserve, quote_reserve, buys_base)` for a swap in `direction`
+    fn pool_side(&self, direction: SwapDirection) -> (u64, u64, bool) {
+        if self.token_is_pool_base {
+            (
+                self.token_reserve,
+                self.sol_reserve,
+                direction == SwapDirection::SolToToken,
+            )
+        } else {
+            (
+                self.sol_reserve,
+                self.token_reserve,
+                direction == SwapDirection::TokenToSol,
+            )
+        }
+    }
+
+    pub fn quote_exact_in(&self, amount_in: u64, direction: SwapDirection) -> Result<SwapQuote> {
+        let (base_reserve, quote_reserve, buys_base) = self.pool_side(direction);
+        if buys_base {
+            pump_quote::buy_exact_in(amount_in, base_reserve, quote_reserve, self.fees())
+        } else {
+            pump_quote::sell_exact_in(amount_in, base_reserve, quote_reserve, self.fees())
+        }
+    }
+
+    pub fn quote_exact_out(&self, amount_out: u64, direction: SwapDirection) -> Result<SwapQuote> {
+        let (base_reserve, quote_reserve, buys_base) = self.pool_side(direction);
+        if buys_base {
+            pump_quote::buy_exact_out(amount_out, base_reserve, quote_reserve, self.fees())
+        } else {
+            pump_quote::sell_exact_out(amount_out, base_reserve, quote_reserve, self.fees())
+        }
+    }
+}
+
+impl DlmmPool {
+    pub fn quote_exact_in(&self, amount_in: u64, direction: SwapDirection) -> Result<SwapQuote> {
+        let lb_pair = self
+            .lb_pair
+            .as_ref()
+            .ok_or_else(|| anyhow!("DLMM pair {} has not been loaded", self.pair))?;
+        let quote = dlmm_quote::quote_exact_in(
+            lb_pair,
+            &self.bin_array_state,
+            amount_in,
+            sells_first(self.token_is_x, direction),
+            self.current_timestamp,
+        )?;
+        Ok(quote.quote)
+    }
+}
+
+impl WhirlpoolPool {
+    pub fn quote_exact_in(&self, amount_in: u64, direction: SwapDirection) -> Result<SwapQuote> {
+        let (Some(whirlpool), Some(tick_arrays)) = (&self.whirlpool, &self.tick_array_state) else {
+            return Err(anyhow!("Whirlpool {} has not been loaded", self.pool));
+        };
+        filled_exact_in(whirlpool_swap::simulate_swap(
+            whirlpool,
+            tick_arrays,
+            amount_in,
+            true,
+            sells_first(self.token_is_mint_a, direction),
+            None,
+        )?)
+    }
+}
+
+impl RaydiumClmmPool {
+    pub fn quote_exact_in(&self, amount_in: u64, direction: SwapDirection) -> Result<SwapQuote> {
+        let pool_state = self
+            .pool_state
+            .as_ref()
+            .ok_or_else(|| anyhow!("Raydium CLMM pool {} has not been loaded", self.pool))?;
+        filled_exact_in(clmm_swap::simulate_swap(
+            pool_state,
+            self.trade_fee_rate,
+            self.extension.as_ref(),
+            &self.tick_array_state,
+            amount_in,
+            true,
+            sells_first(self.token_is_mint_0, direction),
+            None,
+        )?)
+    }
+}
+
+impl MeteoraDAmmPool {
+    pub fn quote_exact_in(&self, amount_in: u64, direction: SwapDirection) -> Result<SwapQuote> {
+        let (Some(pool), Some(token_side), Some(sol_side)) =
+            (&self.damm_pool, &self.token_x_side, &self.token_sol_side)
+        else {
+            return Err(anyhow!(
+                "Meteora DAMM pool {} has not been loaded",
+                self.pool
+            ));
+        };
+        let (a, b) = if self.token_is_a {
+            (token_side, sol_side)
+        } else {
+            (sol_side, token_side)
+        };
+        damm_quote::quote_exact_in(
+            pool,
+            a,
+            b,
+            amount_in,
+            sells_first(self.token_is_a, direction),
+            self.current_time,
+        )
+    }
+}
+
+impl MeteoraDAmmV2Pool {
+    pub fn quote_exact_in(&self, amount_in: u64, direction: SwapDirection) -> Result<SwapQuote> {
+        let pool = self
+            .damm_v2
+            .as_ref()
+            .ok_or_else(|| anyhow!("Meteora DAMM V2 pool {} has not been loaded", self.pool))?;
+        dammv2_quote::quote_exact_in(
+            pool,
+            amount_in,
+            sells_first(self.token_is_a, direction),
+            pool.current_point(self.current_slot, self.current_timestamp),
+        )
+    }
+}
+
+impl VertigoPool {
+    /// Constant product over the shifted reserves, with `royalties_bps` charged on the mint A
+    /// side of the swap. The launch fee that decays over `normalization_period` slots is not
+    /// modelled, so quotes are refused until it has run out.
+    pub fn quote_exact_in(&self, amount_in: u64, direction: SwapDirection) -> Result<SwapQuote> {
+        let launch_end = self
+            .fee_params
+            .reference
+            .saturating_add(self.fee_params.normalization_period);
+        if self.current_slot < launch_end {
+            return Err(anyhow!(
+                "Pool charges its launch fee until slot {}",
+                launch_end
+            ));
+        }
+
+        let (reserve_a, reserve_b) = if self.token_is_mint_a {
+            (self.token_reserve, self.sol_reserve)
+        } else {
+            (self.sol_reserve, self.token_reserve)
+        };
+        let shifted_a = (reserve_a as u128)
+            .checked_add(self.shift)
+            .ok_or_else(|| anyhow!("Shifted reserve overflowed"))?;
+        let fee_bps = self.fee_params.royalties_bps as u128;
+        if fee_bps > BASIS_POINTS {
+            return Err(anyhow!("Royalties of {} bps exceed 100%", fee_bps));
+        }
+        let fee_on = |amount: u128| {
+            ceil_div(amount * fee_bps, BASIS_POINTS).ok_or_else(|| anyhow!("Fee overflowed"))
+        };
+
+        let (amount_out, fee_amount) = if sells_first(self.token_is_mint_a, direction) {
+            let fee = fee_on(amount_in as u128)?;
+            let out =
+                constant_product_amount_out(amount_in as u128 - fee, shifted_a, reserve_b as u128)?;
+            (out, fee)
+        } else {
+            let gross =
+                constant_product_amount_out(amount_in as u128, reserve_b as u128, shifted_a)?;
+            // The virtual shift prices the curve but cannot be withdrawn
+            if gross > reserve_a as u128 {
+                return Err(anyhow!(
+                    "Output {} exceeds the reserve of {}",
+                    gross,
+                    reserve_a
+                ));
+            }
+            let fee = fee_on(gross)?;
+            (gross - fee, fee)
+        };
+
+        Ok(SwapQuote {
+            amount_in,
+            amount_out: to_u64(amount_out)?,
+            fee_amount: to_u64(fee_amount)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::vertigo::info::FeeParams;
+
+    /// 10 SOL (mint A) shifted by 30 SOL of virtual liquidity against 1M tokens, past its
+    /// launch fee and charging 1% royalties
+    fn vertigo_pool(royalties_bps: u16) -> VertigoPool {
+        VertigoPool {
+            token_is_mint_a: false,
+            token_reserve: 1_000_000_000_000,
+            sol_reserve: 10_000_000_000,
+            shift: 30_000_000_000,
+            fee_params: FeeParams {
+                normalization_period: 1_000,
+                decay: 10.0,
+                reference: 100,
+                royalties_bps,
+                privileged_swapper: None,
+            },
+            current_slot: 2_000,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn vertigo_buy_charges_royalties_on_the_sol_input() {
+        let quote = vertigo_pool(100)
+            .quote_exact_in(1_000_000_000, SwapDirection::SolToToken)
+            .unwrap();
+
+        assert_eq!(quote.fee_amount, 10_000_000);
+        // 1e12 * 0.99e9 / (40e9 + 0.99e9)
+        assert_eq!(quote.amount_out, 24_152_232_251);
+    }
+
+    #[test]
+    fn vertigo_sell_charges_royalties_on_the_sol_output() {
+        let quote = vertigo_pool(100)
+            .quote_exact_in(10_000_000_000, SwapDirection::TokenToSol)
+            .unwrap();
+
+        // 40e9 * 1e10 / (1e12 + 1e10) = 396_039_603 before the fee
+        assert_eq!(quote.fee_amount, 3_960_397);
+        assert_eq!(quote.amount_out, 392_079_206);
+    }
+
+    #[test]
+    fn vertigo_refuses_output_from_the_virtual_shift() {
+        let quote = vertigo_pool(100).quote_exact_in(3_000_000_000_000, SwapDirection::TokenToSol);
+        assert!(quote.is_err());
+    }
+
+    #[test]
+    fn vertigo_refuses_quotes_during_the_launch_fee() {
+        let mut pool = vertigo_pool(100);
+        pool.current_slot = 1_099;
+        let quote = pool.quote_exact_in(1_000_000_000, SwapDirection::SolToToken);
+        assert!(quote.is_err());
+    }
+
+    #[test]
+    fn vertigo_rejects_royalties_above_100_percent() {
+        let pool = vertigo_pool(10_001);
+        for direction in [SwapDirection::SolToToken, SwapDirection::TokenToSol] {
+            assert!(pool.quote_exact_in(1_000_000_000, direction).is_err());
+        }
+
+        let quote = vertigo_pool(10_000)
+            .quote_exact_in(1_000_000_000, SwapDirection::SolToToken)
+            .unwrap();
+        assert_eq!(quote.amount_out, 0);
+    }
+}
@@ -0,0 +1,588 @@
+use crate::{
+    chain::{
+        arbitrage::{maximize_profit, ExecutionCosts, MIN_TRADE_LAMPORTS},
+        compute_units::{ComputeUnitEstimator, RouteShape},
+        constants::sol_mint,
+        dex_pool::DexPool,
+        pools::{MintPoolData, PoolKind},
+    },
+    config::MinProfit,
+    dex::quote::{SwapDirection, SwapQuote},
+};
+use solana_sdk::pubkey::Pubkey;
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+};
+use tracing::warn;
+
+/// Longest cycle the search looks for
+pub const MAX_ROUTE_HOPS: usize = 4;
+
+/// Cycles are discovered with a hundredth of the largest trade: small enough that rates stay
+/// close to the marginal price, large enough that quotes do not round away
+const PROBE_DIVISOR: u64 = 100;
+
+/// Paths to each mint every layer of the search keeps
+const PATHS_PER_MINT: usize = 3;
+
+/// One direction through one pool
+#[derive(Debug, Clone, Copy)]
+pub struct Edge {
+    /// Index into the graph's pools
+    pub pool: usize,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub direction: SwapDirection,
+}
+
+/// Every loaded pool across all mints as a pair of directed edges between the mints it trades
+pub struct TokenGraph<'a> {
+    pools: Vec<&'a dyn DexPool>,
+    edges: Vec<Edge>,
+    outgoing: HashMap<Pubkey, Vec<usize>>,
+}
+
+impl<'a> TokenGraph<'a> {
+    /// A pool configured under more than one mint is added once
+    pub fn new(pool_data: &'a [MintPoolData]) -> Self {
+        let mut seen = HashSet::new();
+        let pools: Vec<&dyn DexPool> = pool_data
+            .iter()
+            .flat_map(|data| data.pools())
+            .filter(|pool| seen.insert(pool.address()))
+            .collect();
+
+        let mut edges = Vec::with_capacity(pools.len() * 2);
+        let mut outgoing: HashMap<Pubkey, Vec<usize>> = HashMap::new();
+        for (index, pool) in pools.iter().enumerate() {
+            for (input_mint, output_mint, direction) in [
+                (
+                    pool.base_mint(),
+                    pool.token_mint(),
+                    SwapDirection::SolToToken,
+                ),
+                (
+                    pool.token_mint(),
+                    pool.base_mint(),
+                    SwapDirection::TokenToSol,
+                ),
+            ] {
+                outgoing.entry(input_mint).or_default().push(edges.len());
+                edges.push(Edge {
+                    pool: index,
+                    input_mint,
+                    output_mint,
+                    direction,
+                });
+            }
+        }
+
+        Self {
+            pools,
+            edges,
+            outgoing,
+        }
+    }
+
+    pub fn pool(&self, edge: &Edge) -> &'a dyn DexPool {
+        self.pools[edge.pool]
+    }
+
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    fn outgoing(&self, mint: &Pubkey) -> &[usize] {
+        self.outgoing.get(mint).map_or(&[], Vec::as_slice)
+    }
+
+    fn quote(&self, edge: usize, amount_in: u64) -> Option<SwapQuote> {
+        let edge = &self.edges[edge];
+        self.pool(edge)
+            .quote(amount_in, edge.direction)
+            .ok()
+            .filter(|quote| quote.amount_out > 0)
+    }
+
+    /// `lamports` in `base_mint` units, quoted through the direct SOL pool that pays the most.
+    /// `None` when no loaded pool pairs the base mint with SOL.
+    fn lamports_in(&self, base_mint: &Pubkey, lamports: u64) -> Option<u64> {
+        let sol_mint = sol_mint();
+        if *base_mint == sol_mint {
+            return Some(lamports);
+        }
+        self.outgoing(&sol_mint)
+            .iter()
+            .filter(|&&edge| self.edges[edge].output_mint == *base_mint)
+            .filter_map(|&edge| self.quote(edge, lamports))
+            .map(|quote| quote.amount_out)
+            .max()
+    }
+
+    /// Quotes for passing `amount_in` through `edges` in order
+    fn quote_path(&self, edges: &[usize], amount_in: u64) -> Option<Vec<SwapQuote>> {
+        let mut amount = amount_in;
+        edges
+            .iter()
+            .map(|&edge| {
+                let quote = self.quote(edge, amount)?;
+                amount = quote.amount_out;
+                Some(quote)
+            })
+            .collect()
+    }
+}
+
+/// One swap of a route
+#[derive(Debug, Clone)]
+pub struct RouteLeg {
+    pub pool: Pubkey,
+    pub kind: PoolKind,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub direction: SwapDirection,
+    pub quote: SwapQuote,
+}
+
+/// A cycle that starts and ends in the base mint, sized for the largest profit
+#[derive(Debug, Clone)]
+pub struct RoutePlan {
+    pub base_mint: Pubkey,
+    /// Base mint put into the first leg
+    pub amount_in: u64,
+    pub legs: Vec<RouteLeg>,
+    /// Base mint returned above `amount_in`, after every pool's fees
+    pub gross_profit: u64,
+    /// Execution costs converted to base mint units at planning time
+    pub execution_cost: u64,
+    /// Compute unit limit `execution_cost` was priced at
+    pub compute_unit_limit: u32,
+    pub expected_profit: u64,
+}
+
+impl RoutePlan {
+    /// Base mint the transaction must gain on chain: the execution cost plus `min_profit`'s
+    /// floor on the expected profit
+    pub fn minimum_profit(&self, min_profit: MinProfit) -> u64 {
+        self.execution_cost
+            .saturating_add(min_profit.floor(self.expected_profit))
+    }
+
+    /// Whether the plan is expected to clear `min_profit`'s floor. A fixed floor can sit above
+    /// a plan's expected profit, and such a plan would only revert.
+    pub fn clears_floor(&self, min_profit: MinProfit) -> bool {
+        min_profit.floor(self.expected_profit) <= self.expected_profit
+    }
+}
+
+/// A path from the base mint, kept while the search extends it
+#[derive(Debug, Clone)]
+struct PathState {
+    /// Sum of the log rates along the path
+    log_gain: f64,
+    amount: u64,
+    edges: Vec<usize>,
+}
+
+impl PathState {
+    fn uses_pool(&self, graph: &TokenGraph, pool: usize) -> bool {
+        self.edges
+            .iter()
+            .any(|&edge| graph.edges[edge].pool == pool)
+    }
+
+    fn visits(&self, graph: &TokenGraph, mint: &Pubkey) -> bool {
+        self.edges
+            .iter()
+            .any(|&edge| graph.edges[edge].output_mint == *mint)
+    }
+}
+
+/// Find profitable cycles of 2 to `MAX_ROUTE_HOPS` hops from `base_mint`, most profitable first.
+///
+/// The search runs over log rates layered by hop count, where an edge's rate comes from an
+/// exact quote of the amount the path carries when it gets there. Unlike Bellman-Ford, which
+/// keeps one best path per mint, each layer keeps the `PATHS_PER_MINT` best paths to every
+/// mint, since the exact sizing later can rank cycles differently from the probe. Paths that
+/// close back at the base mint with a positive log gain are then sized with `maximize_profit`
+/// against exact quotes, and kept when the best size clears `costs`. No path, open or closing,
+/// passes through a pool twice or revisits a mint before closing.
+///
+/// Each cycle's costs are priced at the compute unit limit `compute_units` holds for it sent as
+/// native swap instructions. `max_amount_in` and `costs` are in lamports. For any other base
+/// mint they are converted through its SOL pool, and no cycles are searched without one.
+pub fn find_cycles(
+    graph: &TokenGraph,
+    base_mint: &Pubkey,
+    max_amount_in: u64,
+    costs: &ExecutionCosts,
+    compute_units: &ComputeUnitEstimator,
+) -> Vec<RoutePlan> {
+    let (Some(min_amount_in), Some(max_amount_in)) = (
+        graph.lamports_in(base_mint, MIN_TRADE_LAMPORTS),
+        graph.lamports_in(base_mint, max_amount_in),
+    ) else {
+        warn!(
+            "No SOL pool prices base mint {}, skipping the route search",
+            base_mint
+        );
+        return Vec::new();
+    };
+
+    let mut layer = HashMap::from([(
+        *base_mint,
+        vec![PathState {
+            log_gain: 0.0,
+            amount: (max_amount_in / PROBE_DIVISOR).max(1),
+            edges: Vec::new(),
+        }],
+    )]);
+    let mut cycles = HashSet::new();
+
+    for hop in 1..=MAX_ROUTE_HOPS {
+        let mut next: HashMap<Pubkey, Vec<PathState>> = HashMap::new();
+        for (mint, states) in &layer {
+            for state in states {
+                for &edge_index in graph.outgoing(mint) {
+                    let edge = &graph.edges[edge_index];
+                    let closes = edge.output_mint == *base_mint;
+                    if state.uses_pool(graph, edge.pool)
+                        || (closes && hop < 2)
+                        || (!closes && state.visits(graph, &edge.output_mint))
+                    {
+                        continue;
+                    }
+                    let Some(quote) = graph.quote(edge_index, state.amount) else {
+                        continue;
+                    };
+
+                    let log_gain =
+                        state.log_gain + (quote.amount_out as f64 / state.amount as f64).ln();
+                    let mut edges = state.edges.clone();
+                    edges.push(edge_index);
+                    let path = PathState {
+                        log_gain,
+                        amount: quote.amount_out,
+                        edges,
+                    };
+
+                    if closes {
+                        if log_gain > 0.0 {
+                            cycles.insert(path.edges);
+                        }
+                    } else {
+                        let paths = next.entry(edge.output_mint).or_default();
+                        paths.push(path);
+                        paths.sort_by(|a, b| b.log_gain.total_cmp(&a.log_gain));
+                        paths.truncate(PATHS_PER_MINT);
+                    }
+                }
+            }
+        }
+        layer = next;
+    }
+
+    let mut plans: Vec<RoutePlan> = cycles
+        .iter()
+        .filter_map(|edges| {
+            let shape = RouteShape::native_legs(
+                edges
+                    .iter()
+                    .map(|&edge| graph.pool(&graph.edges[edge]).kind()),
+            );
+            size_cycle(
+                graph,
+                base_mint,
+                edges,
+                min_amount_in.min(max_amount_in),
+                max_amount_in,
+                &costs.with_compute_unit_limit(compute_units.limit_or_fallback(&shape)),
+            )
+        })
+        .collect();
+    plans.sort_by_key(|plan| Reverse(plan.expected_profit));
+    plans
+}
+
+/// Size a cycle between `min_amount_in` and `max_amount_in` base units
+fn size_cycle(
+    graph: &TokenGraph,
+    base_mint: &Pubkey,
+    edges: &[usize],
+    min_amount_in: u64,
+    max_amount_in: u64,
+    costs: &ExecutionCosts,
+) -> Option<RoutePlan> {
+    let execution_cost = graph.lamports_in(base_mint, costs.total_lamports())?;
+    let amount_in = maximize_profit(min_amount_in, max_amount_in, |amount_in| {
+        graph
+            .quote_path(edges, amount_in)
+            .and_then(|quotes| quotes.last().copied())
+            .map_or(i128::MIN, |last| {
+                last.amount_out as i128 - amount_in as i128
+            })
+    });
+
+    let quotes = graph.quote_path(edges, amount_in)?;
+    let gross_profit = quotes.last()?.amount_out.checked_sub(amount_in)?;
+    let expected_profit = gross_profit.checked_sub(execution_cost)?;
+    if expected_profit == 0 {
+        return None;
+    }
+
+    let legs = edges
+        .iter()
+        .zip(quotes)
+        .map(|(&edge_index, quote)| {
+            let edge = &graph.edges[edge_index];
+            let pool = graph.pool(edge);
+            RouteLeg {
+                pool: pool.address(),
+                kind: pool.kind(),
+                input_mint: edge.input_mint,
+                output_mint: edge.output_mint,
+                direction: edge.direction,
+                quote,
+            }
+        })
+        .collect();
+
+    Some(RoutePlan {
+        base_mint: *base_mint,
+        amount_in,
+        legs,
+        gross_profit,
+        execution_cost,
+        compute_unit_limit: costs.compute_unit_limit,
+        expected_profit,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::{arbitrage::BASE_FEE_LAMPORTS, dex_pool::test_pool::TestPool};
+
+    const RESERVE: u64 = 1_000_000_000_000;
+    const FEE_BASIS_POINTS: u64 = 30;
+    const MAX_AMOUNT_IN: u64 = 10_000_000_000;
+    const FALLBACK_LIMIT: u32 = 200_000;
+
+    fn pool(
+        token_mint: Pubkey,
+        base_mint: Pubkey,
+        base_reserve: u64,
+        token_reserve: u64,
+    ) -> TestPool {
+        TestPool::new(
+            token_mint,
+            base_mint,
+            base_reserve,
+            token_reserve,
+            FEE_BASIS_POINTS,
+        )
+    }
+
+    fn estimator() -> ComputeUnitEstimator {
+        ComputeUnitEstimator::new(FALLBACK_LIMIT, 0)
+    }
+
+    fn pool_data(pools: Vec<TestPool>) -> Vec<MintPoolData> {
+        let mut data = MintPoolData::new(
+            &Pubkey::new_unique().to_string(),
+            &Pubkey::new_unique().to_string(),
+            spl_token::id(),
+            9,
+        )
+        .unwrap();
+        for pool in pools {
+            data.add_pool(pool);
+        }
+        vec![data]
+    }
+
+    fn leg_pools(plan: &RoutePlan) -> Vec<Pubkey> {
+        plan.legs.iter().map(|leg| leg.pool).collect()
+    }
+
+    #[test]
+    fn finds_a_triangular_cycle() {
+        let (token_a, token_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        // A and B trade at par with each other, but B is worth 1.2 SOL and A only 1
+        let sol_a = pool(token_a, sol_mint(), RESERVE, RESERVE);
+        let a_b = pool(token_b, token_a, RESERVE, RESERVE);
+        let sol_b = pool(token_b, sol_mint(), RESERVE * 6 / 5, RESERVE);
+        let expected = vec![sol_a.address, a_b.address, sol_b.address];
+        let pool_data = pool_data(vec![sol_a, a_b, sol_b]);
+        let graph = TokenGraph::new(&pool_data);
+
+        let plans = find_cycles(
+            &graph,
+            &sol_mint(),
+            MAX_AMOUNT_IN,
+            &ExecutionCosts::default(),
+            &estimator(),
+        );
+        assert_eq!(plans.len(), 1);
+        let plan = &plans[0];
+        assert_eq!(leg_pools(plan), expected);
+        assert_eq!(plan.legs[1].input_mint, token_a);
+        assert_eq!(plan.legs[1].direction, SwapDirection::SolToToken);
+        assert_eq!(plan.execution_cost, BASE_FEE_LAMPORTS);
+        assert_eq!(
+            plan.expected_profit,
+            plan.gross_profit - plan.execution_cost
+        );
+        assert!(plan.amount_in <= MAX_AMOUNT_IN);
+    }
+
+    #[test]
+    fn finds_nothing_in_a_consistent_market() {
+        let (token_a, token_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let pool_data = pool_data(vec![
+            pool(token_a, sol_mint(), RESERVE, RESERVE),
+            pool(token_b, token_a, RESERVE, RESERVE),
+            pool(token_b, sol_mint(), RESERVE, RESERVE),
+        ]);
+        let graph = TokenGraph::new(&pool_data);
+        assert!(find_cycles(
+            &graph,
+            &sol_mint(),
+            MAX_AMOUNT_IN,
+            &ExecutionCosts::default(),
+            &estimator()
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn keeps_more_than_the_best_path_to_each_mint() {
+        let (token_a, token_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        // Two SOL/A pools at slightly different prices both lead into the same A -> B -> SOL
+        // leg, so both triangles must be sized
+        let cheap_a = pool(token_a, sol_mint(), RESERVE, RESERVE * 101 / 100);
+        let dear_a = pool(token_a, sol_mint(), RESERVE, RESERVE);
+        let entries = HashSet::from([cheap_a.address, dear_a.address]);
+        let pool_data = pool_data(vec![
+            cheap_a,
+            dear_a,
+            pool(token_b, token_a, RESERVE, RESERVE),
+            pool(token_b, sol_mint(), RESERVE * 6 / 5, RESERVE),
+        ]);
+        let graph = TokenGraph::new(&pool_data);
+
+        let plans = find_cycles(
+            &graph,
+            &sol_mint(),
+            MAX_AMOUNT_IN,
+            &ExecutionCosts::default(),
+            &estimator(),
+        );
+        let triangle_entries: HashSet<Pubkey> = plans
+            .iter()
+            .filter(|plan| plan.legs.len() == 3)
+            .map(|plan| plan.legs[0].pool)
+            .collect();
+        assert_eq!(triangle_entries, entries);
+        // No cycle goes in and out through the same pool
+        for plan in &plans {
+            let pools: HashSet<Pubkey> = leg_pools(plan).into_iter().collect();
+            assert_eq!(pools.len(), plan.legs.len());
+        }
+    }
+
+    #[test]
+    fn converts_lamport_amounts_for_other_base_mints() {
+        let (usdc, token_a) = (Pubkey::new_unique(), Pubkey::new_unique());
+        // 1 SOL buys 100 USDC units
+        let sol_usdc = pool(usdc, sol_mint(), RESERVE, RESERVE * 100);
+        let usdc_a = pool(token_a, usdc, RESERVE, RESERVE);
+        let a_sol = pool(token_a, sol_mint(), RESERVE * 2 / 100, RESERVE);
+        let pool_data = pool_data(vec![sol_usdc, usdc_a, a_sol]);
+        let graph = TokenGraph::new(&pool_data);
+
+        let max_amount_in = graph.lamports_in(&usdc, MAX_AMOUNT_IN).unwrap();
+        assert!(max_amount_in > MAX_AMOUNT_IN * 98 && max_amount_in < MAX_AMOUNT_IN * 100);
+        let plans = find_cycles(
+            &graph,
+            &usdc,
+            MAX_AMOUNT_IN,
+            &ExecutionCosts::default(),
+            &estimator(),
+        );
+        assert!(!plans.is_empty());
+        for plan in &plans {
+            assert_eq!(plan.base_mint, usdc);
+            assert!(plan.amount_in <= max_amount_in);
+            assert!(plan.execution_cost > BASE_FEE_LAMPORTS * 98);
+        }
+
+        // Without a SOL pool for the base mint there is nothing to size trades with
+        let orphan = Pubkey::new_unique();
+        assert!(find_cycles(
+            &graph,
+            &orphan,
+            MAX_AMOUNT_IN,
+            &ExecutionCosts::default(),
+            &estimator()
+        )
+        .is_empty());
+    }
+
+    fn route_plan(execution_cost: u64, expected_profit: u64) -> RoutePlan {
+        RoutePlan {
+            base_mint: sol_mint(),
+            amount_in: 1_000_000_000,
+            legs: Vec::new(),
+            gross_profit: execution_cost.saturating_add(expected_profit),
+            execution_cost,
+            compute_unit_limit: FALLBACK_LIMIT,
+            expected_profit,
+        }
+    }
+
+    #[test]
+    fn applies_profit_floors() {
+        let plan = route_plan(10_000, 100_000);
+        let fraction = MinProfit::Fraction(0.25);
+        assert_eq!(plan.minimum_profit(fraction), 35_000);
+        assert!(plan.clears_floor(fraction));
+
+        assert_eq!(plan.minimum_profit(MinProfit::Lamports(100_000)), 110_000);
+        assert!(plan.clears_floor(MinProfit::Lamports(100_000)));
+        assert!(!plan.clears_floor(MinProfit::Lamports(100_001)));
+
+        let plan = route_plan(u64::MAX, 1);
+        assert_eq!(plan.minimum_profit(MinProfit::Lamports(1)), u64::MAX);
+    }
+
+    #[test]
+    fn prices_cycles_at_their_learned_limit() {
+        let (token_a, token_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let pool_data = pool_data(vec![
+            pool(token_a, sol_mint(), RESERVE, RESERVE),
+            pool(token_b, token_a, RESERVE, RESERVE),
+            pool(token_b, sol_mint(), RESERVE * 6 / 5, RESERVE),
+        ]);
+        let graph = TokenGraph::new(&pool_data);
+        // One lamport per compute unit
+        let costs = ExecutionCosts {
+            compute_unit_price: 1_000_000,
+            ..ExecutionCosts::default()
+        };
+
+        let compute_units = estimator();
+        let plans = find_cycles(&graph, &sol_mint(), MAX_AMOUNT_IN, &costs, &compute_units);
+        assert_eq!(plans[0].compute_unit_limit, FALLBACK_LIMIT);
+        assert_eq!(
+            plans[0].execution_cost,
+            BASE_FEE_LAMPORTS + FALLBACK_LIMIT as u64
+        );
+
+        compute_units.record(&RouteShape::native(&plans[0]), 90_000);
+        let plans = find_cycles(&graph, &sol_mint(), MAX_AMOUNT_IN, &costs, &compute_units);
+        assert_eq!(plans[0].compute_unit_limit, 90_000);
+        assert_eq!(plans[0].execution_cost, BASE_FEE_LAMPORTS + 90_000);
+    }
+}
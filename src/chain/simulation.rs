@@ -0,0 +1,465 @@
+use crate::{
+    chain::{constants::TOKEN_2022_PROGRAM_ID, token_fetch::token_account_amount},
+    dex::{
+        meteora::constants::dlmm_program_id,
+        pump::pump_program_id,
+        raydium::{raydium_clmm_program_id, raydium_cp_program_id, raydium_program_id},
+        whirlpool::constants::whirlpool_program_id,
+    },
+};
+use anyhow::{anyhow, Result};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::{
+        RpcAccountInfoConfig, RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig,
+    },
+};
+use solana_sdk::{
+    account::Account,
+    instruction::InstructionError,
+    pubkey::Pubkey,
+    system_program,
+    transaction::{TransactionError, VersionedTransaction},
+};
+use std::{fmt, str::FromStr};
+use tracing::debug;
+
+/// A program, by its id function, and one of its custom error codes
+type ProgramError = (fn() -> Pubkey, u32);
+
+/// Custom error codes, by program, that mark each kind of failure
+const INSUFFICIENT_FUNDS_ERRORS: &[ProgramError] = &[
+    (system_program::id, 1),    // ResultWithNegativeLamports
+    (spl_token::id, 1),         // InsufficientFunds
+    (token_2022_program_id, 1), // InsufficientFunds
+];
+const STALE_TICK_ARRAY_ERRORS: &[ProgramError] = &[
+    (raydium_clmm_program_id, 6027), // NotEnoughTickArrayAccount
+    (raydium_clmm_program_id, 6028), // InvalidFirstTickArrayAccount
+    (whirlpool_program_id, 6023),    // InvalidTickArraySequence
+    (whirlpool_program_id, 6038),    // TickArraySequenceInvalidIndex
+];
+const SLIPPAGE_ERRORS: &[ProgramError] = &[
+    (raydium_program_id, 30),        // ExceededSlippage
+    (raydium_cp_program_id, 6005),   // ExceededSlippage
+    (raydium_clmm_program_id, 6022), // TooLittleOutputReceived
+    (whirlpool_program_id, 6036),    // AmountOutBelowMinimum
+    (dlmm_program_id, 6003),         // ExceededAmountSlippageTolerance
+    (pump_program_id, 6004),         // ExceededSlippage
+];
+
+/// Anchor error names that mark each kind of failure, for codes the lists above leave out
+const STALE_TICK_ARRAY_ERROR_NAMES: &[&str] = &[
+    "BinArrayNotFound",
+    "InvalidFirstTickArrayAccount",
+    "InvalidTickArraySequence",
+    "NotEnoughTickArrayAccount",
+    "TickArraySequenceInvalidIndex",
+];
+const SLIPPAGE_ERROR_NAMES: &[&str] = &[
+    "AmountOutBelowMinimum",
+    "ExceededAmountSlippageTolerance",
+    "ExceededSlippage",
+    "TooLittleOutputReceived",
+];
+
+fn token_2022_program_id() -> Pubkey {
+    TOKEN_2022_PROGRAM_ID
+}
+
+/// Why a simulated transaction failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SimulationFailure {
+    /// A pool returned less than its minimum output, or the executor less than its floor
+    Slippage,
+    /// A tick or bin array passed to a pool no longer covers the swap's price range
+    StaleTickArray,
+    /// The wallet cannot pay for an input, for rent or for the fee
+    InsufficientFunds,
+    /// The transaction ran out of its compute unit limit
+    ComputeBudgetExceeded,
+    Other(String),
+}
+
+impl fmt::Display for SimulationFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimulationFailure::Slippage => write!(f, "slippage"),
+            SimulationFailure::StaleTickArray => write!(f, "stale tick array"),
+            SimulationFailure::InsufficientFunds => write!(f, "insufficient funds"),
+            SimulationFailure::ComputeBudgetExceeded => write!(f, "compute budget exceeded"),
+            SimulationFailure::Other(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+/// What a transaction would do if it landed now
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    pub failure: Option<SimulationFailure>,
+    /// Change of each watched token account, in the order they were given
+    pub balance_changes: Vec<i128>,
+    pub units_consumed: Option<u64>,
+    pub logs: Vec<String>,
+}
+
+impl Simulation {
+    /// Change of the first watched account, the wallet's base mint account
+    pub fn realized_profit(&self) -> i128 {
+        self.balance_changes.first().copied().unwrap_or(0)
+    }
+}
+
+/// Simulate `tx` against the latest state and compare the token accounts in `token_accounts`
+/// before and after. Accounts that do not exist yet count as empty.
+///
+/// The balances before are read in a separate request, and the simulation runs at that slot or
+/// a later one. A transfer landing between the two slots shows up as part of the change.
+pub fn simulate_transaction(
+    client: &RpcClient,
+    tx: &VersionedTransaction,
+    token_accounts: &[Pubkey],
+) -> Result<Simulation> {
+    let (pre_balances, min_context_slot) = if token_accounts.is_empty() {
+        (Vec::new(), None)
+    } else {
+        let response = client.get_multiple_accounts_with_config(
+            token_accounts,
+            RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                commitment: Some(client.commitment()),
+                ..Default::default()
+            },
+        )?;
+        let pre_balances = response
+            .value
+            .iter()
+            .map(|account| balance(account.as_ref()))
+            .collect::<Result<Vec<_>>>()?;
+        (pre_balances, Some(response.context.slot))
+    };
+
+    let result = client
+        .simulate_transaction_with_config(
+            tx,
+            RpcSimulateTransactionConfig {
+                sig_verify: false,
+                replace_recent_blockhash: true,
+                commitment: Some(client.commitment()),
+                accounts: Some(RpcSimulateTransactionAccountsConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    addresses: token_accounts.iter().map(|key| key.to_string()).collect(),
+                }),
+                min_context_slot,
+                inner_instructions: true,
+                ..Default::default()
+            },
+        )?
+        .value;
+
+    let logs = result.logs.unwrap_or_default();
+    debug!(
+        "Simulation ran {} inner instruction sets in {:?} compute units",
+        result.inner_instructions.as_ref().map_or(0, Vec::len),
+        result.units_consumed
+    );
+
+    let post_accounts = result.accounts.unwrap_or_default();
+    let balance_changes = if result.err.is_some() {
+        vec![0; token_accounts.len()]
+    } else {
+        if post_accounts.len() != token_accounts.len() {
+            return Err(anyhow!(
+                "Simulation returned {} accounts for {} requested",
+                post_accounts.len(),
+                token_accounts.len()
+            ));
+        }
+        post_accounts
+            .iter()
+            .zip(&pre_balances)
+            .map(|(account, pre)| {
+                let account = account
+                    .as_ref()
+                    .and_then(|account| account.decode::<Account>());
+                Ok(balance(account.as_ref())? as i128 - *pre as i128)
+            })
+            .collect::<Result<Vec<_>>>()?
+    };
+
+    Ok(Simulation {
+        failure: result
+            .err
+            .map(|err| classify(&err, failing_program(tx, &err).as_ref(), &logs)),
+        balance_changes,
+        units_consumed: result.units_consumed,
+        logs,
+    })
+}
+
+fn balance(account: Option<&Account>) -> Result<u64> {
+    account.map_or(Ok(0), |account| token_account_amount(&account.data))
+}
+
+/// The program of the top-level instruction `err` names
+fn failing_program(tx: &VersionedTransaction, err: &TransactionError) -> Option<Pubkey> {
+    let TransactionError::InstructionError(index, _) = err else {
+        return None;
+    };
+    let instruction = tx.message.instructions().get(*index as usize)?;
+    tx.message
+        .static_account_keys()
+        .get(instruction.program_id_index as usize)
+        .copied()
+}
+
+/// The innermost program the logs show failing, and the lines it logged itself
+fn failed_invocation(logs: &[String]) -> Option<(Pubkey, Vec<&str>)> {
+    let mut stack: Vec<(Pubkey, Vec<&str>)> = Vec::new();
+    for line in logs {
+        let Some(rest) = line.strip_prefix("Program ") else {
+            continue;
+        };
+        let (program, outcome) = rest.split_once(' ').unwrap_or((rest, ""));
+        match Pubkey::from_str(program) {
+            Ok(program) if outcome.starts_with("invoke [") => stack.push((program, Vec::new())),
+            Ok(_) if outcome == "success" => {
+                stack.pop();
+            }
+            Ok(program) if outcome.starts_with("failed") => {
+                let lines = stack.pop().map(|(_, lines)| lines).unwrap_or_default();
+                return Some((program, lines));
+            }
+            Ok(_) => {}
+            // `Program log:`, `Program data:` and `Program return:` lines
+            Err(_) => {
+                if let Some((_, lines)) = stack.last_mut() {
+                    lines.push(line);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// The name in an Anchor error log line, as in `AnchorError occurred. Error Code: Name.`
+fn anchor_error_name(line: &str) -> Option<&str> {
+    let (_, rest) = line.split_once("Error Code: ")?;
+    rest.split(['.', ' ']).next()
+}
+
+/// Classify a failed simulation from its error: the custom error code of the program that
+/// failed, which is the innermost one the logs show failing and otherwise `program`, the
+/// program of the failing top-level instruction. Codes the supported programs are not known
+/// to use are looked up by the Anchor error name that program logged.
+pub fn classify(
+    err: &TransactionError,
+    program: Option<&Pubkey>,
+    logs: &[String],
+) -> SimulationFailure {
+    let other = || SimulationFailure::Other(err.to_string());
+    let code = match err {
+        TransactionError::InsufficientFundsForFee
+        | TransactionError::InsufficientFundsForRent { .. } => {
+            return SimulationFailure::InsufficientFunds
+        }
+        TransactionError::InstructionError(_, InstructionError::ComputationalBudgetExceeded) => {
+            return SimulationFailure::ComputeBudgetExceeded
+        }
+        TransactionError::InstructionError(_, InstructionError::InsufficientFunds) => {
+            return SimulationFailure::InsufficientFunds
+        }
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => *code,
+        _ => return other(),
+    };
+
+    let failed = failed_invocation(logs);
+    let Some(program) = failed.as_ref().map(|(program, _)| program).or(program) else {
+        return other();
+    };
+    let listed = |errors: &[ProgramError]| {
+        errors
+            .iter()
+            .any(|(id, error)| *error == code && id() == *program)
+    };
+    if listed(INSUFFICIENT_FUNDS_ERRORS) {
+        return SimulationFailure::InsufficientFunds;
+    }
+    if listed(STALE_TICK_ARRAY_ERRORS) {
+        return SimulationFailure::StaleTickArray;
+    }
+    if listed(SLIPPAGE_ERRORS) {
+        return SimulationFailure::Slippage;
+    }
+
+    let name = failed
+        .iter()
+        .flat_map(|(_, lines)| lines)
+        .find_map(|line| anchor_error_name(line));
+    match name {
+        Some(name) if SLIPPAGE_ERROR_NAMES.contains(&name) => SimulationFailure::Slippage,
+        Some(name) if STALE_TICK_ARRAY_ERROR_NAMES.contains(&name) => {
+            SimulationFailure::StaleTickArray
+        }
+        _ => other(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::meteora::constants::damm_program_id;
+
+    fn custom_error(code: u32) -> TransactionError {
+        TransactionError::InstructionError(2, InstructionError::Custom(code))
+    }
+
+    fn logs(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    /// Logs of `program` failing with `code` after logging `lines`, invoked by `caller` if any
+    fn failure_logs(
+        caller: Option<Pubkey>,
+        program: Pubkey,
+        lines: &[&str],
+        code: u32,
+    ) -> Vec<String> {
+        let mut logs = Vec::new();
+        if let Some(caller) = caller {
+            logs.push(format!("Program {} invoke [1]", caller));
+        }
+        logs.push(format!("Program {} invoke [{}]", program, logs.len() + 1));
+        logs.extend(lines.iter().map(|line| line.to_string()));
+        logs.push(format!(
+            "Program {} consumed 5000 of 200000 compute units",
+            program
+        ));
+        logs.push(format!(
+            "Program {} failed: custom program error: {:#x}",
+            program, code
+        ));
+        if let Some(caller) = caller {
+            logs.push(format!(
+                "Program {} failed: custom program error: {:#x}",
+                caller, code
+            ));
+        }
+        logs
+    }
+
+    #[test]
+    fn classifies_from_program_error_codes() {
+        let cases = [
+            (raydium_clmm_program_id(), 6022, SimulationFailure::Slippage),
+            (
+                raydium_clmm_program_id(),
+                6027,
+                SimulationFailure::StaleTickArray,
+            ),
+            (raydium_program_id(), 30, SimulationFailure::Slippage),
+            (whirlpool_program_id(), 6036, SimulationFailure::Slippage),
+            (
+                whirlpool_program_id(),
+                6023,
+                SimulationFailure::StaleTickArray,
+            ),
+            (dlmm_program_id(), 6003, SimulationFailure::Slippage),
+            (pump_program_id(), 6004, SimulationFailure::Slippage),
+            (spl_token::id(), 1, SimulationFailure::InsufficientFunds),
+        ];
+        for (program, code, expected) in cases {
+            assert_eq!(
+                classify(&custom_error(code), Some(&program), &[]),
+                expected,
+                "{} {}",
+                program,
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn reads_codes_against_the_program_that_failed() {
+        // 6022 is Raydium CLMM's TooLittleOutputReceived, but Whirlpool's InvalidTimestamp
+        let err = custom_error(6022);
+        assert_eq!(
+            classify(&err, Some(&whirlpool_program_id()), &[]),
+            SimulationFailure::Other(err.to_string())
+        );
+
+        // Through the executor, the pool the executor invoked is the one that failed
+        let executor = Pubkey::new_unique();
+        let logs = failure_logs(Some(executor), raydium_clmm_program_id(), &[], 6022);
+        assert_eq!(
+            classify(&err, Some(&executor), &logs),
+            SimulationFailure::Slippage
+        );
+    }
+
+    #[test]
+    fn classifies_from_the_failing_programs_anchor_error() {
+        let cases = [
+            (
+                "Program log: AnchorError occurred. Error Code: ExceededSlippage. Error Number: 6003.",
+                SimulationFailure::Slippage,
+            ),
+            (
+                "Program log: AnchorError thrown in src/lib.rs:10. Error Code: InvalidTickArraySequence. Error Number: 6023.",
+                SimulationFailure::StaleTickArray,
+            ),
+        ];
+        for (line, expected) in cases {
+            let logs = failure_logs(None, damm_program_id(), &[line], 6100);
+            assert_eq!(
+                classify(&custom_error(6100), Some(&damm_program_id()), &logs),
+                expected,
+                "{}",
+                line
+            );
+        }
+
+        // Errors another program logged before the failure do not count
+        let err = custom_error(6100);
+        let mut logs =
+            logs(&["Program log: AnchorError occurred. Error Code: AmountOutBelowMinimum."]);
+        logs.extend(failure_logs(
+            None,
+            damm_program_id(),
+            &["Program log: AnchorError occurred. Error Code: MathOverflow."],
+            6100,
+        ));
+        assert_eq!(
+            classify(&err, Some(&damm_program_id()), &logs),
+            SimulationFailure::Other(err.to_string())
+        );
+    }
+
+    #[test]
+    fn classifies_fee_errors_without_logs() {
+        assert_eq!(
+            classify(&TransactionError::InsufficientFundsForFee, None, &[]),
+            SimulationFailure::InsufficientFunds
+        );
+    }
+
+    #[test]
+    fn classifies_compute_budget_errors() {
+        let err =
+            TransactionError::InstructionError(3, InstructionError::ComputationalBudgetExceeded);
+        assert_eq!(
+            classify(&err, None, &logs(&["Program log: Instruction: SwapV2"])),
+            SimulationFailure::ComputeBudgetExceeded
+        );
+    }
+
+    #[test]
+    fn keeps_unknown_errors() {
+        let err = custom_error(6022);
+        assert_eq!(
+            classify(&err, None, &logs(&["Program log: Instruction: Swap"])),
+            SimulationFailure::Other(err.to_string())
+        );
+    }
+}
@@ -1,51 +1,36 @@
-use crate::{
-    chain::{
-        pools::{
-            DlmmPool, MeteoraDAmmPool, MeteoraDAmmV2Pool, MintPoolData, PumpPool, RaydiumClmmPool,
-            RaydiumCpPool, RaydiumPool, SolfiPool, VertigoPool, WhirlpoolPool,
-        },
-        constants::sol_mint,
-    },
-        dex::{
-        meteora::{
-            constants::{
-                damm_program_id,
-            },
-        },
-        pump::{
-            amm_info::PumpAmmInfo,
-            constants::{pump_fee_wallet, pump_program_id},
-        },
-        raydium::{
-            amm_info::RaydiumAmmInfo,
-            constants::*,
-        },
-    },
+use crate::chain::{
+    constants::TOKEN_2022_PROGRAM_ID,
+    dex_pool::DexPool,
+    pools::{MintPoolData, PoolKind},
 };
 use anyhow::{anyhow, Result};
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::{account::Account, pubkey::Pubkey};
-use spl_associated_token_account;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     str::FromStr,
     sync::Arc,
     time::{Duration, Instant},
 };
 use tokio::time::sleep;
-use tracing::{error, info, warn};
+use tracing::{debug, info, warn};
 
-const TOKEN_2022_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
-    6, 221, 246, 225, 215, 101, 161, 147, 217, 203, 225, 70, 206, 235, 121, 172, 28, 180, 134, 244,
-    64, 118, 252, 1, 16, 241, 37, 236, 114, 157, 18, 16,
-]);
+/// Upper bound the RPC accepts for a single `getMultipleAccounts` call
+pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// Accounts resolved by the fetcher, keyed by address
+pub type AccountMap = HashMap<Pubkey, Account>;
+
+/// Rounds `refresh_pools` runs before giving up on pools that keep asking for new accounts.
+/// The deepest chain today is a Meteora DAMM pool: pool, then vaults, then their token accounts.
+const MAX_REFRESH_ROUNDS: usize = 4;
 
 /// Configuration for token fetching
 #[derive(Debug, Clone)]
 pub struct TokenFetchConfig {
     pub max_retries: u32,
     pub retry_delay_ms: u64,
-    pub batch_size: usize,
+    pub batch_size: usize, // keys per getMultipleAccounts call, capped at MAX_MULTIPLE_ACCOUNTS
     pub timeout_seconds: u64,
     pub enable_caching: bool,
     pub cache_ttl_seconds: u64,
@@ -56,7 +41,7 @@ impl Default for TokenFetchConfig {
         Self {
             max_retries: 3,
             retry_delay_ms: 1000,
-            batch_size: 10,
+            batch_size: MAX_MULTIPLE_ACCOUNTS,
             timeout_seconds: 30,
             enable_caching: true,
             cache_ttl_seconds: 300, // 5 minutes
@@ -64,10 +49,25 @@ impl Default for TokenFetchConfig {
     }
 }
 
+/// A configured pool that could not be resolved
+#[derive(Debug, Clone)]
+pub struct PoolFetchFailure {
+    pub kind: PoolKind,
+    pub pool: String,
+    pub error: String,
+}
+
+/// Pool data for a mint together with every pool that failed to resolve
+#[derive(Debug, Clone)]
+pub struct PoolFetchResult {
+    pub pool_data: MintPoolData,
+    pub failures: Vec<PoolFetchFailure>,
+}
+
 /// Cache entry for token data
 #[derive(Debug, Clone)]
 struct CacheEntry {
-    data: MintPoolData,
+    data: PoolFetchResult,
     timestamp: Instant,
 }
 
@@ -87,7 +87,9 @@ impl TokenFetcher {
         }
     }
 
-    /// Initialize pool data with enhanced error handling and caching
+    /// Initialize pool data with enhanced error handling and caching.
+    ///
+    /// Accounts are resolved through `refresh_pools`, together with the mint.
     pub async fn initialize_pool_data(
         &mut self,
         mint: &str,
@@ -102,9 +104,9 @@ impl TokenFetcher {
         solfi_pools: Option<&Vec<String>>,
         meteora_damm_v2_pools: Option<&Vec<String>>,
         vertigo_pools: Option<&Vec<String>>,
-    ) -> Result<MintPoolData> {
+    ) -> Result<PoolFetchResult> {
         let cache_key = format!("{}_{}", mint, wallet_account);
-        
+
         // Check cache first
         if self.config.enable_caching {
             if let Some(entry) = self.cache.get(&cache_key) {
@@ -117,166 +119,93 @@ impl TokenFetcher {
 
         info!("Initializing pool data for mint: {}", mint);
         let start_time = Instant::now();
-
-        // Fetch mint account with retry logic
         let mint_pubkey = Pubkey::from_str(mint)?;
-        let mint_account = self.fetch_account_with_retry(&mint_pubkey).await?;
 
-        // Determine token program based on mint account owner
-        let token_program = self.determine_token_program(&mint_account, mint)?;
-        info!("Detected token program: {}", token_program);
-
-        let mut pool_data = MintPoolData::new(mint, wallet_account, token_program)?;
-        info!("Pool data initialized for mint: {}", mint);
-
-        // Helper function to return empty future
-        async fn empty_result<T>() -> Result<Vec<T>> {
-            Ok(Vec::new())
-        }
-
-        // Fetch pools concurrently using join! macro for better performance
-        let (pump_result, raydium_result, raydium_cp_result, dlmm_result, whirlpool_result, 
-             raydium_clmm_result, meteora_damm_result, solfi_result, meteora_damm_v2_result, vertigo_result) = 
-            futures::join!(
-                async {
-                    if let Some(pools) = pump_pools { 
-                        self.fetch_pump_pools(pools, &mint_pubkey).await 
-                    } else { 
-                        empty_result().await 
-                    }
-                },
-                async {
-                    if let Some(pools) = raydium_pools { 
-                        self.fetch_raydium_pools(pools, &mint_pubkey).await 
-                    } else { 
-                        empty_result().await 
-                    }
-                },
-                async {
-                    if let Some(pools) = raydium_cp_pools { 
-                        self.fetch_raydium_cp_pools(pools, &mint_pubkey).await 
-                    } else { 
-                        empty_result().await 
-                    }
-                },
-                async {
-                    if let Some(pools) = dlmm_pools { 
-                        self.fetch_dlmm_pools(pools, &mint_pubkey).await 
-                    } else { 
-                        empty_result().await 
-                    }
-                },
-                async {
-                    if let Some(pools) = whirlpool_pools { 
-                        self.fetch_whirlpool_pools(pools, &mint_pubkey).await 
-                    } else { 
-                        empty_result().await 
-                    }
-                },
-                async {
-                    if let Some(pools) = raydium_clmm_pools { 
-                        self.fetch_raydium_clmm_pools(pools, &mint_pubkey).await 
-                    } else { 
-                        empty_result().await 
-                    }
-                },
-                async {
-                    if let Some(pools) = meteora_damm_pools { 
-                        self.fetch_meteora_damm_pools(pools, &mint_pubkey).await 
-                    } else { 
-                        empty_result().await 
-                    }
-                },
-                async {
-                    if let Some(pools) = solfi_pools { 
-                        self.fetch_solfi_pools(pools, &mint_pubkey).await 
-                    } else { 
-                        empty_result().await 
-                    }
-                },
-                async {
-                    if let Some(pools) = meteora_damm_v2_pools { 
-                        self.fetch_meteora_damm_v2_pools(pools, &mint_pubkey).await 
-                    } else { 
-                        empty_result().await 
-                    }
-                },
-                async {
-                    if let Some(pools) = vertigo_pools { 
-                        self.fetch_vertigo_pools(pools, &mint_pubkey).await 
-                    } else { 
-                        empty_result().await 
-                    }
+        let pool_lists = [
+            (PoolKind::Pump, pump_pools),
+            (PoolKind::Raydium, raydium_pools),
+            (PoolKind::RaydiumCp, raydium_cp_pools),
+            (PoolKind::MeteoraDlmm, dlmm_pools),
+            (PoolKind::Whirlpool, whirlpool_pools),
+            (PoolKind::RaydiumClmm, raydium_clmm_pools),
+            (PoolKind::MeteoraDamm, meteora_damm_pools),
+            (PoolKind::Solfi, solfi_pools),
+            (PoolKind::MeteoraDammV2, meteora_damm_v2_pools),
+            (PoolKind::Vertigo, vertigo_pools),
+        ];
+
+        let mut failures = Vec::new();
+        let mut pools = Vec::new();
+        for (kind, addresses) in pool_lists {
+            for address in addresses.into_iter().flatten() {
+                if !kind.is_quotable() {
+                    failures.push(PoolFetchFailure {
+                        kind,
+                        pool: address.clone(),
+                        error: "Pricing state is not decoded, so the pool cannot be quoted".to_string(),
+                    });
+                    continue;
                 }
-            );
-
-        // Merge results into pool_data
-        if let Ok(pools) = pump_result {
-            pool_data.pump_pools.extend(pools);
-        } else if let Err(e) = pump_result {
-            warn!("Failed to fetch pump pools: {}", e);
-        }
-
-        if let Ok(pools) = raydium_result {
-            pool_data.raydium_pools.extend(pools);
-        } else if let Err(e) = raydium_result {
-            warn!("Failed to fetch raydium pools: {}", e);
-        }
-
-        if let Ok(pools) = raydium_cp_result {
-            pool_data.raydium_cp_pools.extend(pools);
-        } else if let Err(e) = raydium_cp_result {
-            warn!("Failed to fetch raydium cp pools: {}", e);
-        }
-
-        if let Ok(pools) = dlmm_result {
-            pool_data.dlmm_pairs.extend(pools);
-        } else if let Err(e) = dlmm_result {
-            warn!("Failed to fetch dlmm pools: {}", e);
+                match Pubkey::from_str(address) {
+                    Ok(pubkey) => pools.push(kind.new_pool(pubkey, mint_pubkey)),
+                    Err(e) => failures.push(PoolFetchFailure {
+                        kind,
+                        pool: address.clone(),
+                        error: format!("Invalid pool address: {}", e),
+                    }),
+                }
+            }
         }
 
-        if let Ok(pools) = whirlpool_result {
-            pool_data.whirlpool_pools.extend(pools);
-        } else if let Err(e) = whirlpool_result {
-            warn!("Failed to fetch whirlpool pools: {}", e);
-        }
+        let (accounts, updates) = self.refresh_pools(&mut pools, &[mint_pubkey]).await?;
 
-        if let Ok(pools) = raydium_clmm_result {
-            pool_data.raydium_clmm_pools.extend(pools);
-        } else if let Err(e) = raydium_clmm_result {
-            warn!("Failed to fetch raydium clmm pools: {}", e);
-        }
+        // Determine token program based on mint account owner
+        let mint_account = accounts
+            .get(&mint_pubkey)
+            .ok_or_else(|| anyhow!("Mint account not found: {}", mint))?;
+        let token_program = self.determine_token_program(mint_account, mint)?;
+        info!("Detected token program: {}", token_program);
+        let decimals = mint_decimals(&mint_account.data)?;
 
-        if let Ok(pools) = meteora_damm_result {
-            pool_data.meteora_damm_pools.extend(pools);
-        } else if let Err(e) = meteora_damm_result {
-            warn!("Failed to fetch meteora damm pools: {}", e);
-        }
+        let mut pool_data = MintPoolData::new(mint, wallet_account, token_program, decimals)?;
+        info!("Pool data initialized for mint: {}", mint);
 
-        if let Ok(pools) = solfi_result {
-            pool_data.solfi_pools.extend(pools);
-        } else if let Err(e) = solfi_result {
-            warn!("Failed to fetch solfi pools: {}", e);
+        for (pool, update) in pools.into_iter().zip(updates) {
+            match update {
+                Ok(()) => {
+                    info!("{} pool added: {}", pool.kind(), pool.address());
+                    pool_data.pools.push(pool);
+                }
+                Err(e) => {
+                    warn!("Failed to resolve {} pool {}: {}", pool.kind(), pool.address(), e);
+                    failures.push(PoolFetchFailure {
+                        kind: pool.kind(),
+                        pool: pool.address().to_string(),
+                        error: e.to_string(),
+                    });
+                }
+            }
         }
 
-        if let Ok(pools) = meteora_damm_v2_result {
-            pool_data.meteora_damm_v2_pools.extend(pools);
-        } else if let Err(e) = meteora_damm_v2_result {
-            warn!("Failed to fetch meteora damm v2 pools: {}", e);
+        if !failures.is_empty() {
+            warn!(
+                "{} pool(s) could not be resolved for mint: {}",
+                failures.len(),
+                mint
+            );
         }
 
-        if let Ok(pools) = vertigo_result {
-            pool_data.vertigo_pools.extend(pools);
-        } else if let Err(e) = vertigo_result {
-            warn!("Failed to fetch vertigo pools: {}", e);
-        }
+        let result = PoolFetchResult {
+            pool_data,
+            failures,
+        };
 
         // Cache the result
         if self.config.enable_caching {
             self.cache.insert(
                 cache_key,
                 CacheEntry {
-                    data: pool_data.clone(),
+                    data: result.clone(),
                     timestamp: Instant::now(),
                 },
             );
@@ -288,22 +217,112 @@ impl TokenFetcher {
             mint, elapsed
         );
 
-        Ok(pool_data)
+        Ok(result)
+    }
+
+    /// Fetch the accounts `pools` need and update each of them, returning every fetched account
+    /// and one update result per pool.
+    ///
+    /// A pool only names the accounts it can locate so far, so fetching runs in rounds: each
+    /// round fetches the keys no earlier round requested, together with `extra_keys` in the
+    /// first, then updates every pool. It stops once no pool asks for anything new.
+    pub async fn refresh_pools(
+        &self,
+        pools: &mut [Box<dyn DexPool>],
+        extra_keys: &[Pubkey],
+    ) -> Result<(AccountMap, Vec<Result<()>>)> {
+        let mut accounts = AccountMap::new();
+        let mut requested: HashSet<Pubkey> = HashSet::new();
+        let mut updates = Vec::new();
+        let mut keys = extra_keys.to_vec();
+
+        for round in 0..MAX_REFRESH_ROUNDS {
+            keys.extend(pools.iter().flat_map(|pool| pool.accounts_to_fetch()));
+            keys.retain(|pubkey| requested.insert(*pubkey));
+            if keys.is_empty() && round > 0 {
+                break;
+            }
+
+            accounts.extend(self.fetch_multiple_accounts_with_retry(&keys).await?);
+            keys.clear();
+            updates = pools
+                .iter_mut()
+                .map(|pool| pool.update(&accounts))
+                .collect();
+        }
+
+        Ok((accounts, updates))
     }
 
-    /// Fetch account with retry logic
-    async fn fetch_account_with_retry(&self, pubkey: &Pubkey) -> Result<Account> {
+    /// Bring every pool in `pool_data` up to date, dropping the ones that no longer resolve
+    pub async fn refresh_pool_data(&self, pool_data: &mut MintPoolData) -> Result<Vec<PoolFetchFailure>> {
+        let (_, updates) = self.refresh_pools(&mut pool_data.pools, &[]).await?;
+
+        let mut failures = Vec::new();
+        let mut updates = updates.into_iter();
+        pool_data.pools.retain(|pool| match updates.next() {
+            Some(Err(e)) => {
+                warn!("Failed to refresh {} pool {}: {}", pool.kind(), pool.address(), e);
+                failures.push(PoolFetchFailure {
+                    kind: pool.kind(),
+                    pool: pool.address().to_string(),
+                    error: e.to_string(),
+                });
+                false
+            }
+            _ => true,
+        });
+
+        Ok(failures)
+    }
+
+    /// Fetch accounts in chunked `getMultipleAccounts` calls with retry logic.
+    ///
+    /// Duplicate keys are requested once and accounts that do not exist are left out of the
+    /// returned map.
+    pub async fn fetch_multiple_accounts_with_retry(&self, pubkeys: &[Pubkey]) -> Result<AccountMap> {
+        let mut seen = HashSet::new();
+        let unique: Vec<Pubkey> = pubkeys
+            .iter()
+            .filter(|pubkey| seen.insert(**pubkey))
+            .copied()
+            .collect();
+
+        let chunk_size = self.config.batch_size.clamp(1, MAX_MULTIPLE_ACCOUNTS);
+        let mut accounts = AccountMap::with_capacity(unique.len());
+
+        for chunk in unique.chunks(chunk_size) {
+            let fetched = self.fetch_chunk_with_retry(chunk).await?;
+            for (pubkey, account) in chunk.iter().zip(fetched) {
+                if let Some(account) = account {
+                    accounts.insert(*pubkey, account);
+                }
+            }
+        }
+
+        debug!(
+            "Fetched {} of {} accounts in {} batch(es)",
+            accounts.len(),
+            unique.len(),
+            unique.len().div_ceil(chunk_size)
+        );
+
+        Ok(accounts)
+    }
+
+    /// Fetch a single `getMultipleAccounts` chunk with retry logic
+    async fn fetch_chunk_with_retry(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>> {
         let mut last_error = None;
-        
+
         for attempt in 0..self.config.max_retries {
-            match self.rpc_client.get_account(pubkey) {
-                Ok(account) => return Ok(account),
+            match self.rpc_client.get_multiple_accounts(pubkeys) {
+                Ok(accounts) => return Ok(accounts),
                 Err(e) => {
                     last_error = Some(e);
                     if attempt < self.config.max_retries - 1 {
                         warn!(
-                            "Failed to fetch account {} (attempt {}/{}), retrying in {}ms",
-                            pubkey, attempt + 1, self.config.max_retries, self.config.retry_delay_ms
+                            "Failed to fetch {} accounts (attempt {}/{}), retrying in {}ms",
+                            pubkeys.len(), attempt + 1, self.config.max_retries, self.config.retry_delay_ms
                         );
                         sleep(Duration::from_millis(self.config.retry_delay_ms)).await;
                     }
@@ -312,8 +331,8 @@ impl TokenFetcher {
         }
 
         Err(anyhow!(
-            "Failed to fetch account {} after {} attempts: {:?}",
-            pubkey,
+            "Failed to fetch {} accounts after {} attempts: {:?}",
+            pubkeys.len(),
             self.config.max_retries,
             last_error
         ))
@@ -330,240 +349,6 @@ impl TokenFetcher {
         }
     }
 
-    /// Fetch pump pools with enhanced error handling
-    async fn fetch_pump_pools(
-        &self,
-        pools: &[String],
-        mint_pubkey: &Pubkey,
-    ) -> Result<Vec<PumpPool>> {
-        let mut pump_pools = Vec::new();
-        
-        for pool_address in pools {
-            match self.fetch_single_pump_pool(pool_address, mint_pubkey).await {
-                Ok(pool) => {
-                    pump_pools.push(pool);
-                    info!("Pump pool added: {}", pool_address);
-                }
-                Err(e) => {
-                    error!("Failed to fetch pump pool {}: {}", pool_address, e);
-                    // Continue with other pools
-                }
-            }
-        }
-        
-        Ok(pump_pools)
-    }
-
-    /// Fetch a single pump pool
-    async fn fetch_single_pump_pool(
-        &self,
-        pool_address: &str,
-        mint_pubkey: &Pubkey,
-    ) -> Result<PumpPool> {
-        let pump_pool_pubkey = Pubkey::from_str(pool_address)?;
-        let account = self.fetch_account_with_retry(&pump_pool_pubkey).await?;
-
-        if account.owner != pump_program_id() {
-            return Err(anyhow!(
-                "Pump pool account is not owned by the Pump program: {}",
-                pool_address
-            ));
-        }
-
-        let amm_info = PumpAmmInfo::load_checked(&account.data)?;
-        
-        let (sol_vault, token_vault) = if sol_mint() == amm_info.base_mint {
-            (
-                amm_info.pool_base_token_account,
-                amm_info.pool_quote_token_account,
-            )
-        } else if sol_mint() == amm_info.quote_mint {
-            (
-                amm_info.pool_quote_token_account,
-                amm_info.pool_base_token_account,
-            )
-        } else {
-            (
-                amm_info.pool_quote_token_account,
-                amm_info.pool_base_token_account,
-            )
-        };
-
-        let fee_token_wallet = spl_associated_token_account::get_associated_token_address(
-            &pump_fee_wallet(),
-            &amm_info.quote_mint,
-        );
-
-        let coin_creator_vault_ata = spl_associated_token_account::get_associated_token_address(
-            &amm_info.coin_creator_vault_authority,
-            &amm_info.quote_mint,
-        );
-
-        let (token_mint, base_mint) = if *mint_pubkey == amm_info.base_mint {
-            (amm_info.base_mint, amm_info.quote_mint)
-        } else {
-            (amm_info.quote_mint, amm_info.base_mint)
-        };
-
-        Ok(PumpPool {
-            pool: pump_pool_pubkey,
-            token_vault,
-            sol_vault,
-            fee_token_wallet,
-            coin_creator_vault_ata,
-            coin_creator_vault_authority: amm_info.coin_creator_vault_authority,
-            token_mint,
-            base_mint,
-        })
-    }
-
-    /// Fetch Raydium pools
-    async fn fetch_raydium_pools(
-        &self,
-        pools: &[String],
-        mint_pubkey: &Pubkey,
-    ) -> Result<Vec<RaydiumPool>> {
-        let mut raydium_pools = Vec::new();
-        
-        for pool_address in pools {
-            match self.fetch_single_raydium_pool(pool_address, mint_pubkey).await {
-                Ok(pool) => {
-                    raydium_pools.push(pool);
-                    info!("Raydium pool added: {}", pool_address);
-                }
-                Err(e) => {
-                    error!("Failed to fetch Raydium pool {}: {}", pool_address, e);
-                }
-            }
-        }
-        
-        Ok(raydium_pools)
-    }
-
-    /// Fetch a single Raydium pool
-    async fn fetch_single_raydium_pool(
-        &self,
-        pool_address: &str,
-        mint_pubkey: &Pubkey,
-    ) -> Result<RaydiumPool> {
-        let raydium_pool_pubkey = Pubkey::from_str(pool_address)?;
-        let account = self.fetch_account_with_retry(&raydium_pool_pubkey).await?;
-
-        if account.owner != raydium_program_id() {
-            return Err(anyhow!(
-                "Raydium pool account is not owned by the Raydium program: {}",
-                pool_address
-            ));
-        }
-
-        let amm_info = RaydiumAmmInfo::load_checked(&account.data)?;
-        
-        let (sol_vault, token_vault) = if sol_mint() == amm_info.coin_mint {
-            (
-                amm_info.coin_vault,
-                amm_info.pc_vault,
-            )
-        } else if sol_mint() == amm_info.pc_mint {
-            (
-                amm_info.pc_vault,
-                amm_info.coin_vault,
-            )
-        } else {
-            (
-                amm_info.pc_vault,
-                amm_info.coin_vault,
-            )
-        };
-
-        let (token_mint, base_mint) = if *mint_pubkey == amm_info.coin_mint {
-            (amm_info.coin_mint, amm_info.pc_mint)
-        } else {
-            (amm_info.pc_mint, amm_info.coin_mint)
-        };
-
-        Ok(RaydiumPool {
-            pool: raydium_pool_pubkey,
-            token_vault,
-            sol_vault,
-            token_mint,
-            base_mint,
-        })
-    }
-
-    // Placeholder methods for other pool types - implement similar to above
-    async fn fetch_raydium_cp_pools(
-        &self,
-        _pools: &[String],
-        _mint_pubkey: &Pubkey,
-    ) -> Result<Vec<RaydiumCpPool>> {
-        // TODO: Implement Raydium CP pool fetching
-        Ok(Vec::new())
-    }
-
-    async fn fetch_dlmm_pools(
-        &self,
-        _pools: &[String],
-        _mint_pubkey: &Pubkey,
-    ) -> Result<Vec<DlmmPool>> {
-        // TODO: Implement DLMM pool fetching
-        Ok(Vec::new())
-    }
-
-    async fn fetch_whirlpool_pools(
-        &self,
-        _pools: &[String],
-        _mint_pubkey: &Pubkey,
-    ) -> Result<Vec<WhirlpoolPool>> {
-        // TODO: Implement Whirlpool fetching
-        Ok(Vec::new())
-    }
-
-    async fn fetch_raydium_clmm_pools(
-        &self,
-        _pools: &[String],
-        _mint_pubkey: &Pubkey,
-    ) -> Result<Vec<RaydiumClmmPool>> {
-        // TODO: Implement Raydium CLMM pool fetching
-        Ok(Vec::new())
-    }
-
-    async fn fetch_meteora_damm_pools(
-        &self,
-        _pools: &[String],
-        _mint_pubkey: &Pubkey,
-    ) -> Result<Vec<MeteoraDAmmPool>> {
-        // TODO: Implement Meteora DAmm pool fetching
-        Ok(Vec::new())
-    }
-
-    async fn fetch_solfi_pools(
-        &self,
-        _pools: &[String],
-        _mint_pubkey: &Pubkey,
-    ) -> Result<Vec<SolfiPool>> {
-        // TODO: Implement Solfi pool fetching
-        Ok(Vec::new())
-    }
-
-    async fn fetch_meteora_damm_v2_pools(
-        &self,
-        _pools: &[String],
-        _mint_pubkey: &Pubkey,
-    ) -> Result<Vec<MeteoraDAmmV2Pool>> {
-        // TODO: Implement Meteora DAmm V2 pool fetching
-        Ok(Vec::new())
-    }
-
-    async fn fetch_vertigo_pools(
-        &self,
-        _pools: &[String],
-        _mint_pubkey: &Pubkey,
-    ) -> Result<Vec<VertigoPool>> {
-        // TODO: Implement Vertigo pool fetching
-        Ok(Vec::new())
-    }
-
-
     /// Clear expired cache entries
     pub fn clear_expired_cache(&mut self) {
         let now = Instant::now();
@@ -586,4 +371,32 @@ impl TokenFetcher {
     }
 }
 
+/// Read the `amount` field of an SPL Token or Token-2022 token account
+pub fn token_account_amount(data: &[u8]) -> Result<u64> {
+    const AMOUNT_OFFSET: usize = 64;
+    if data.len() < AMOUNT_OFFSET + 8 {
+        return Err(anyhow!("Invalid data length for token account"));
+    }
+    Ok(u64::from_le_bytes(
+        data[AMOUNT_OFFSET..AMOUNT_OFFSET + 8].try_into().unwrap(),
+    ))
+}
 
+/// Read the `supply` field of an SPL Token or Token-2022 mint account
+pub fn mint_supply(data: &[u8]) -> Result<u64> {
+    const SUPPLY_OFFSET: usize = 36;
+    if data.len() < SUPPLY_OFFSET + 8 {
+        return Err(anyhow!("Invalid data length for mint account"));
+    }
+    Ok(u64::from_le_bytes(
+        data[SUPPLY_OFFSET..SUPPLY_OFFSET + 8].try_into().unwrap(),
+    ))
+}
+
+/// Read the `decimals` field of an SPL Token or Token-2022 mint account
+pub fn mint_decimals(data: &[u8]) -> Result<u8> {
+    const DECIMALS_OFFSET: usize = 44;
+    data.get(DECIMALS_OFFSET)
+        .copied()
+        .ok_or_else(|| anyhow!("Invalid data length for mint account"))
+}
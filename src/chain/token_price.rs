@@ -1,11 +1,12 @@
-use crate::chain::pools::{MintPoolData, PumpPool, RaydiumPool};
 use crate::chain::constants::SOL_MINT;
+use crate::chain::dex_pool::DexPool;
+use crate::chain::pools::{MintPoolData, PoolKind};
+use crate::dex::quote::{SwapDirection, SwapQuote};
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
-use solana_client::rpc_client::RpcClient;
 use solana_sdk::pubkey::Pubkey;
-use std::{collections::HashMap, str::FromStr, sync::Arc, time::Instant};
-use tracing::{info, warn};
+use std::{collections::{HashMap, HashSet}, str::FromStr, sync::Arc, time::Instant};
+use tracing::{debug, info, warn};
 
 /// Token price information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,31 +20,62 @@ pub struct TokenPrice {
     pub source: String,
 }
 
-/// Price comparison for arbitrage opportunities
+/// Which side of which pool a quote is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PoolQuoteKey {
+    pub pool: Pubkey,
+    pub direction: SwapDirection,
+}
+
+/// A probe quote on one side of one pool
+#[derive(Debug, Clone)]
+pub struct PoolQuote {
+    pub pool: Pubkey,
+    pub kind: PoolKind,
+    pub direction: SwapDirection,
+    pub quote: SwapQuote,
+    /// SOL per token implied by the quote, fees included
+    pub price: f64,
+    /// Fraction of the output lost to the curve compared with a much smaller trade
+    pub price_impact: f64,
+}
+
+/// Price comparison for arbitrage opportunities: buy on one pool, sell on another
 #[derive(Debug, Clone)]
 pub struct PriceComparison {
     pub token_mint: String,
-    pub dex_prices: HashMap<String, f64>, // DEX name -> price in SOL
-    pub best_buy_price: f64,
-    pub best_sell_price: f64,
-    pub best_buy_dex: String,
-    pub best_sell_dex: String,
+    /// Every valid pool quote the comparison was drawn from, shared by the comparisons of
+    /// one scan
+    pub pool_quotes: Arc<HashMap<PoolQuoteKey, PoolQuote>>,
+    pub buy: PoolQuote,
+    pub sell: PoolQuote,
     pub price_spread: f64,
     pub potential_profit_percent: f64,
     pub timestamp: Instant,
 }
 
+/// SOL sold into each pool to price it: large enough that rounding does not distort the
+/// price, small enough that price impact stays negligible in a liquid pool
+const PRICE_PROBE_LAMPORTS: u64 = 10_000_000;
+
+/// Price impact is measured against a trade this many times smaller than the probe
+const PRICE_IMPACT_DIVISOR: u64 = 100;
+
 /// Market data fetcher
 pub struct MarketDataFetcher {
-    rpc_client: Arc<RpcClient>,
     price_cache: HashMap<String, TokenPrice>,
     cache_ttl_seconds: u64,
 }
 
+impl Default for MarketDataFetcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MarketDataFetcher {
-    pub fn new(rpc_client: Arc<RpcClient>) -> Self {
+    pub fn new() -> Self {
         Self {
-            rpc_client,
             price_cache: HashMap::new(),
             cache_ttl_seconds: 30, // 30 seconds cache
         }
@@ -298,7 +330,11 @@ impl MarketDataFetcher {
         prices.into_iter().next().unwrap()
     }
 
-    /// Calculate arbitrage opportunities from pool data
+    /// Calculate arbitrage opportunities from pool data.
+    ///
+    /// Every SOL pool is quoted on both sides, and each ordered pair of distinct pools whose
+    /// sell price beats the other's buy price by the threshold becomes an opportunity, most
+    /// profitable first.
     pub async fn calculate_arbitrage_opportunities(
         &self,
         pool_data: &MintPoolData,
@@ -306,210 +342,170 @@ impl MarketDataFetcher {
         let mut opportunities = Vec::new();
         let token_mint = pool_data.mint.to_string();
 
-        // Calculate prices from different DEX pools
-        let mut dex_prices = HashMap::new();
+        let token_decimals = pool_data.decimals;
 
-        // Calculate Raydium prices
-        for pool in &pool_data.raydium_pools {
-            if let Ok(price) = self.calculate_raydium_price(pool).await {
-                dex_prices.insert("raydium".to_string(), price);
+        // Prices are in SOL, so pools pairing the token with another mint are left out
+        let sol_mint_pubkey = Pubkey::from_str(SOL_MINT)?;
+        let mut pool_quotes = HashMap::new();
+        for pool in pool_data
+            .pools()
+            .filter(|pool| pool.base_mint() == sol_mint_pubkey)
+        {
+            match self.quote_pool_sides(pool, token_decimals) {
+                Ok(sides) => {
+                    for side in sides {
+                        pool_quotes.insert(
+                            PoolQuoteKey {
+                                pool: side.pool,
+                                direction: side.direction,
+                            },
+                            side,
+                        );
+                    }
+                }
+                Err(e) => warn!("Failed to price {} pool {}: {}", pool.kind(), pool.address(), e),
             }
         }
 
-        // Calculate Pump prices
-        for pool in &pool_data.pump_pools {
-            if let Ok(price) = self.calculate_pump_price(pool).await {
-                dex_prices.insert("pump".to_string(), price);
-            }
+        for side in pool_quotes.values() {
+            debug!(
+                "{} pool {} {:?}: {:.9} SOL ({:.4}% impact)",
+                side.kind,
+                side.pool,
+                side.direction,
+                side.price,
+                side.price_impact * 100.0
+            );
         }
 
-        println!("dex_prices: {:?}", dex_prices);
-        println!("-------\n");
-        
         // Filter out invalid prices before comparison
-        let sol_mint_pubkey = Pubkey::from_str(SOL_MINT)?;
         let is_wsol_token = pool_data.mint == sol_mint_pubkey;
-        
-        let valid_prices: HashMap<String, f64> = dex_prices
-            .iter()
-            .filter(|(_, &price)| {
-                // For WSOL, prices should be close to 1.0 (between 0.1 and 10.0)
-                // For other tokens, just check they're reasonable
-                if is_wsol_token {
-                    price >= 0.1 && price <= 10.0
-                } else {
-                    price > 0.0 && price < 1e12
-                }
-            })
-            .map(|(k, v)| (k.clone(), *v))
+
+        let valid_quotes: Arc<HashMap<PoolQuoteKey, PoolQuote>> = Arc::new(
+            pool_quotes
+                .iter()
+                .filter(|(_, side)| {
+                    // For WSOL, prices should be close to 1.0 (between 0.1 and 10.0)
+                    // For other tokens, just check they're reasonable
+                    if is_wsol_token {
+                        side.price >= 0.1 && side.price <= 10.0
+                    } else {
+                        side.price > 0.0 && side.price < 1e12
+                    }
+                })
+                .map(|(key, side)| (*key, side.clone()))
+                .collect(),
+        );
+
+        let buys: Vec<&PoolQuote> = valid_quotes
+            .values()
+            .filter(|side| side.direction == SwapDirection::SolToToken)
             .collect();
-        
-        if valid_prices.len() < 2 {
-            warn!("Not enough valid prices for arbitrage (got {} valid out of {} total)", 
-                  valid_prices.len(), dex_prices.len());
+        let sells: Vec<&PoolQuote> = valid_quotes
+            .values()
+            .filter(|side| side.direction == SwapDirection::TokenToSol)
+            .collect();
+
+        let priced_pools: HashSet<Pubkey> = valid_quotes.keys().map(|key| key.pool).collect();
+        if priced_pools.len() < 2 {
+            warn!("Not enough valid prices for arbitrage (got {} valid out of {} total)",
+                  valid_quotes.len(), pool_quotes.len());
             return Ok(opportunities);
         }
-        
-        // Find best buy and sell prices from valid prices only
-        let (best_buy_dex, best_buy_price) = valid_prices
-            .iter()
-            .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
-            .unwrap();
-
-        let (best_sell_dex, best_sell_price) = valid_prices
-            .iter()
-            .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
-            .unwrap();  
-
-        let price_spread = best_sell_price - best_buy_price;
-        let potential_profit_percent = (price_spread / best_buy_price) * 100.0;
-        println!("potential_profit_percent: {:?}", potential_profit_percent);
 
         // Additional validation: for WSOL, spreads > 50% are likely calculation errors
         // For other tokens, we'll use the 0.5% threshold
         let min_profit_threshold = if is_wsol_token { 5.0 } else { 0.5 };
-        
-        // Only consider opportunities with significant spread
-        if potential_profit_percent > min_profit_threshold {
-            // For WSOL, if the spread is still very large after validation, it's likely an error
-            if is_wsol_token && potential_profit_percent > 50.0 {
-                warn!("WSOL arbitrage opportunity with {}% profit seems too good to be true. Skipping.", 
-                      potential_profit_percent);
-                return Ok(opportunities);
+
+        for buy in &buys {
+            for sell in sells.iter().filter(|sell| sell.pool != buy.pool) {
+                let price_spread = sell.price - buy.price;
+                let potential_profit_percent = (price_spread / buy.price) * 100.0;
+
+                // Only consider opportunities with significant spread
+                if potential_profit_percent <= min_profit_threshold {
+                    continue;
+                }
+                // For WSOL, if the spread is still very large after validation, it's likely an error
+                if is_wsol_token && potential_profit_percent > 50.0 {
+                    warn!("WSOL arbitrage opportunity with {}% profit between {} and {} seems too good to be true. Skipping.",
+                          potential_profit_percent, buy.pool, sell.pool);
+                    continue;
+                }
+
+                opportunities.push(PriceComparison {
+                    token_mint: token_mint.clone(),
+                    pool_quotes: Arc::clone(&valid_quotes),
+                    buy: (*buy).clone(),
+                    sell: (*sell).clone(),
+                    price_spread,
+                    potential_profit_percent,
+                    timestamp: Instant::now(),
+                });
             }
-            
-            opportunities.push(PriceComparison {
-                token_mint: token_mint.clone(),
-                dex_prices: valid_prices.clone(),
-                best_buy_price: *best_buy_price,
-                best_sell_price: *best_sell_price,
-                best_buy_dex: best_buy_dex.clone(),
-                best_sell_dex: best_sell_dex.clone(),
-                price_spread,
-                potential_profit_percent,
-                timestamp: Instant::now(),
-            });
         }
 
+        opportunities.sort_by(|a, b| {
+            b.potential_profit_percent
+                .total_cmp(&a.potential_profit_percent)
+        });
         Ok(opportunities)
     }
 
-    /// Calculate price from Raydium pool
-    async fn calculate_raydium_price(&self, pool: &RaydiumPool) -> Result<f64> {
-        let sol_mint_pubkey = Pubkey::from_str(SOL_MINT)?;
-        let is_wsol_pool = pool.token_mint == sol_mint_pubkey || pool.base_mint == sol_mint_pubkey;
-
-        // Fetch token account balances using RPC client (handles parsing automatically)
-        let token_balance = self.rpc_client
-            .get_token_account_balance(&pool.token_vault)
-            .map_err(|e| anyhow!("Failed to fetch token vault balance: {}", e))?;
-        
-        let sol_balance = self.rpc_client
-            .get_token_account_balance(&pool.sol_vault)
-            .map_err(|e| anyhow!("Failed to fetch SOL vault balance: {}", e))?;
-
-        // Parse amounts from UI strings (e.g., "1000.5" -> 1000.5)
-        let token_amount: f64 = token_balance.amount.parse::<u64>()
-            .map_err(|e| anyhow!("Failed to parse token balance: {}", e))?
-            as f64 / 10_f64.powi(token_balance.decimals as i32);
-        
-        let sol_amount: f64 = sol_balance.amount.parse::<u64>()
-            .map_err(|e| anyhow!("Failed to parse SOL balance: {}", e))?
-            as f64 / 10_f64.powi(sol_balance.decimals as i32);
-
-        if token_amount == 0.0 {
-            return Err(anyhow!("Token reserve is zero, cannot calculate price"));
-        }
+    /// Quote both sides of a pool against its last update, so fees and the pool's own curve
+    /// are included: a `PRICE_PROBE_LAMPORTS` buy, then a sell of the tokens that buy returns
+    fn quote_pool_sides(&self, pool: &dyn DexPool, token_decimals: u8) -> Result<[PoolQuote; 2]> {
+        let buy = Self::quote_pool(pool, PRICE_PROBE_LAMPORTS, SwapDirection::SolToToken, token_decimals)?;
+        let sell = Self::quote_pool(pool, buy.quote.amount_out, SwapDirection::TokenToSol, token_decimals)?;
+        Ok([buy, sell])
+    }
 
-        if sol_amount == 0.0 {
-            return Err(anyhow!("SOL reserve is zero, cannot calculate price"));
+    fn quote_pool(
+        pool: &dyn DexPool,
+        amount_in: u64,
+        direction: SwapDirection,
+        token_decimals: u8,
+    ) -> Result<PoolQuote> {
+        let quote = pool.quote(amount_in, direction)?;
+        if quote.amount_out == 0 {
+            return Err(anyhow!("Quote returned nothing for {:?}, cannot calculate price", direction));
         }
 
-        // Calculate price: price = sol_amount / token_amount
-        // This gives us the price of 1 token in SOL
-        let mut price = sol_amount / token_amount;
-
-        // For WSOL pools, validate and cap the price (WSOL should be ~1:1 with SOL)
-        if is_wsol_pool {
-            // If price is way off (more than 10x difference), the vaults might be swapped
-            // or there's a calculation error - try the inverse
-            if price > 10.0 || price < 0.1 {
-                warn!("Raydium WSOL pool price seems inverted: {}. Trying inverse calculation.", price);
-                price = token_amount / sol_amount;
-            }
-            
-            // Cap WSOL price to reasonable range (0.5 to 2.0 SOL per WSOL)
-            if price > 2.0 || price < 0.5 {
-                warn!("Raydium WSOL pool price out of reasonable range: {}. Capping to 1.0", price);
-                price = 1.0;
-            }
-        }
+        let (lamports, tokens) = match direction {
+            SwapDirection::SolToToken => (quote.amount_in, quote.amount_out),
+            SwapDirection::TokenToSol => (quote.amount_out, quote.amount_in),
+        };
+        let sol_amount = lamports as f64 / 1e9;
+        let token_amount = tokens as f64 / 10_f64.powi(token_decimals as i32);
+        let price = sol_amount / token_amount;
 
         // Validate price is reasonable for any token (not negative or zero, and not astronomical)
         if price <= 0.0 || price > 1e12 {
             return Err(anyhow!("Calculated price is invalid: {} SOL per token", price));
         }
 
-        Ok(price)
+        Ok(PoolQuote {
+            pool: pool.address(),
+            kind: pool.kind(),
+            direction,
+            quote,
+            price,
+            price_impact: Self::price_impact(pool, direction, &quote),
+        })
     }
 
-    /// Calculate price from Pump pool
-    async fn calculate_pump_price(&self, pool: &PumpPool) -> Result<f64> {
-        let sol_mint_pubkey = Pubkey::from_str(SOL_MINT)?;
-        let is_wsol_pool = pool.token_mint == sol_mint_pubkey || pool.base_mint == sol_mint_pubkey;
-
-        // Fetch token account balances using RPC client (handles parsing automatically)
-        let token_balance = self.rpc_client
-            .get_token_account_balance(&pool.token_vault)
-            .map_err(|e| anyhow!("Failed to fetch token vault balance: {}", e))?;
-        
-        let sol_balance = self.rpc_client
-            .get_token_account_balance(&pool.sol_vault)
-            .map_err(|e| anyhow!("Failed to fetch SOL vault balance: {}", e))?;
-
-        // Parse amounts from UI strings (e.g., "1000.5" -> 1000.5)
-        let token_amount: f64 = token_balance.amount.parse::<u64>()
-            .map_err(|e| anyhow!("Failed to parse token balance: {}", e))?
-            as f64 / 10_f64.powi(token_balance.decimals as i32);
-        
-        let sol_amount: f64 = sol_balance.amount.parse::<u64>()
-            .map_err(|e| anyhow!("Failed to parse SOL balance: {}", e))?
-            as f64 / 10_f64.powi(sol_balance.decimals as i32);
-
-        if token_amount == 0.0 {
-            return Err(anyhow!("Token reserve is zero, cannot calculate price"));
-        }
-
-        if sol_amount == 0.0 {
-            return Err(anyhow!("SOL reserve is zero, cannot calculate price"));
-        }
-
-        // Calculate price: price = sol_amount / token_amount
-        // This gives us the price of 1 token in SOL
-        let mut price = sol_amount / token_amount;
-
-        // For WSOL pools, validate and cap the price (WSOL should be ~1:1 with SOL)
-        if is_wsol_pool {
-            // If price is way off (more than 10x difference), the vaults might be swapped
-            // or there's a calculation error - try the inverse
-            if price > 10.0 || price < 0.1 {
-                warn!("Pump WSOL pool price seems inverted: {}. Trying inverse calculation.", price);
-                price = token_amount / sol_amount;
+    /// Fraction of the output lost against a trade `PRICE_IMPACT_DIVISOR` times smaller, or 0
+    /// when the smaller trade rounds to nothing
+    fn price_impact(pool: &dyn DexPool, direction: SwapDirection, quote: &SwapQuote) -> f64 {
+        let reference_in = quote.amount_in / PRICE_IMPACT_DIVISOR;
+        match pool.quote(reference_in, direction) {
+            Ok(reference) if reference_in > 0 && reference.amount_out > 0 => {
+                let rate = quote.amount_out as f64 / quote.amount_in as f64;
+                let reference_rate = reference.amount_out as f64 / reference_in as f64;
+                (1.0 - rate / reference_rate).max(0.0)
             }
-            
-            // Cap WSOL price to reasonable range (0.5 to 2.0 SOL per WSOL)
-            if price > 2.0 || price < 0.5 {
-                warn!("Pump WSOL pool price out of reasonable range: {}. Capping to 1.0", price);
-                price = 1.0;
-            }
-        }
-
-        // Validate price is reasonable for any token (not negative or zero, and not astronomical)
-        if price <= 0.0 || price > 1e12 {
-            return Err(anyhow!("Calculated price is invalid: {} SOL per token", price));
+            _ => 0.0,
         }
-
-        Ok(price)
     }
 
     /// Get market statistics
@@ -540,13 +536,9 @@ pub struct PriceMonitor {
 }
 
 impl PriceMonitor {
-    pub fn new(
-        rpc_client: Arc<RpcClient>,
-        monitoring_interval_ms: u64,
-        price_threshold: f64,
-    ) -> Self {
+    pub fn new(monitoring_interval_ms: u64, price_threshold: f64) -> Self {
         Self {
-            market_fetcher: MarketDataFetcher::new(rpc_client),
+            market_fetcher: MarketDataFetcher::new(),
             monitoring_interval_ms,
             price_threshold,
         }
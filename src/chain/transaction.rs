@@ -1,12 +1,20 @@
 use crate::{
-    config::Config,
+    chain::{
+        compute_units::{ComputeUnitEstimator, RouteShape},
+        constants::{memo_program_id, TOKEN_2022_PROGRAM_ID},
+        dex_pool::DexPool,
+        pools::MintPoolData,
+        route::{RouteLeg, RoutePlan},
+        simulation::{simulate_transaction, SimulationFailure},
+        token_fetch::token_account_amount,
+    },
+    config::{BotConfig, Config, ExecutorConfig},
     dex::{
-        raydium::{raydium_authority, raydium_cp_authority},
-        solfi::constants::solfi_program_id,
-        vertigo::constants::vertigo_program_id,
+        quote::SwapDirection,
+        raydium::{raydium_authority, raydium_program_id},
     },
-    chain::pools::MintPoolData,
 };
+use anyhow::anyhow;
 use solana_client::rpc_client::RpcClient;
 use solana_program::instruction::Instruction;
 use solana_sdk::address_lookup_table::AddressLookupTableAccount;
@@ -18,71 +26,307 @@ use solana_sdk::message::v0::Message;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::{Keypair, Signature};
 use solana_sdk::signer::Signer;
+use solana_sdk::system_instruction;
 use solana_sdk::system_program;
 use solana_sdk::transaction::VersionedTransaction;
 use std::sync::Arc;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 use super::constants::sol_mint;
-use crate::dex::{
-    meteora::constants::{
-        damm_program_id, damm_v2_event_authority, damm_v2_pool_authority, damm_v2_program_id,
-        dlmm_event_authority, dlmm_program_id, vault_program_id,
-    },
-    pump::constants::{pump_fee_wallet, pump_program_id},
-    raydium::constants::{raydium_clmm_program_id, raydium_cp_program_id, raydium_program_id},
-    whirlpool::constants::whirlpool_program_id,
-};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
 use spl_associated_token_account::ID as associated_token_program_id;
 use spl_token::ID as token_program_id;
 use std::str::FromStr;
 
+/// Tag of the `Transfer` instruction in SPL Token and Token-2022
+const TRANSFER_INSTRUCTION_TAG: u8 = 3;
+
+/// Size of an SPL Token account, and of a Token-2022 associated token account, which carries
+/// the immutable owner extension
+const TOKEN_ACCOUNT_LEN: usize = 165;
+const TOKEN_2022_ACCOUNT_LEN: usize = 170;
+
+/// Send the executor instruction over `mint_pool_data`, reverting unless it gains at least
+/// `plan`'s minimum profit
 pub async fn build_and_send_transaction(
     wallet_kp: &Keypair,
     config: &Config,
     mint_pool_data: &MintPoolData,
+    plan: &RoutePlan,
+    compute_units: &ComputeUnitEstimator,
     rpc_clients: &[Arc<RpcClient>],
     blockhash: Hash,
     address_lookup_table_accounts: &[AddressLookupTableAccount],
 ) -> anyhow::Result<Vec<Signature>> {
+    if !clears_floor(config, plan) {
+        return Ok(Vec::new());
+    }
     let enable_flashloan = config.flashloan.as_ref().map_or(false, |k| k.enabled);
-    let compute_unit_limit = config.bot.compute_unit_limit;
-    let mut instructions = vec![];
-    // Add a random number here to make each transaction unique
-    let compute_budget_ix = ComputeBudgetInstruction::set_compute_unit_limit(
-        compute_unit_limit + rand::random::<u32>() % 1000,
-    );
-    instructions.push(compute_budget_ix);
+    let minimum_profit = plan.minimum_profit(config.bot.min_profit);
+    let build = |compute_unit_limit: u32| {
+        let mut instructions = compute_budget_instructions(config, compute_unit_limit);
+
+        let swap_ix = create_swap_instruction(
+            wallet_kp,
+            &config.executor,
+            mint_pool_data,
+            minimum_profit,
+            compute_unit_limit,
+            enable_flashloan,
+        )?;
+
+        debug!("Adding swap instruction");
+        instructions.push(swap_ix);
+        instructions.extend(tip_instruction(&config.bot, &wallet_kp.pubkey()));
+        instructions.push(uniqueness_instruction());
+
+        sign_transaction(
+            wallet_kp,
+            &instructions,
+            blockhash,
+            address_lookup_table_accounts,
+        )
+    };
+
+    let token_accounts = route_token_accounts(
+        &wallet_kp.pubkey(),
+        std::slice::from_ref(mint_pool_data),
+        plan,
+    )?;
+    let Some((tx, compute_unit_limit)) = prepare_transaction(
+        config,
+        compute_units,
+        &RouteShape::executor(mint_pool_data),
+        rpc_clients,
+        &token_accounts,
+        minimum_profit,
+        build,
+    )?
+    else {
+        return Ok(Vec::new());
+    };
+    if !priced_within_limit(plan, compute_unit_limit) {
+        return Ok(Vec::new());
+    }
+
+    send_signed_transaction(config, &tx, rpc_clients).await
+}
+
+/// Send `plan` as each pool's own swap instruction in sequence, without the executor program.
+/// The wallet must already hold the plan's input in its base mint associated token account.
+/// A trailing balance check reverts the transaction unless that account gains the plan's
+/// minimum profit.
+pub async fn build_and_send_native_transaction(
+    wallet_kp: &Keypair,
+    config: &Config,
+    pool_data: &[MintPoolData],
+    plan: &RoutePlan,
+    compute_units: &ComputeUnitEstimator,
+    rpc_clients: &[Arc<RpcClient>],
+    blockhash: Hash,
+    address_lookup_table_accounts: &[AddressLookupTableAccount],
+) -> anyhow::Result<Vec<Signature>> {
+    if !clears_floor(config, plan) {
+        return Ok(Vec::new());
+    }
+    let client = rpc_clients
+        .first()
+        .ok_or_else(|| anyhow!("No RPC client to read the wallet's balances with"))?;
+    let wallet = wallet_kp.pubkey();
+    let minimum_profit = plan.minimum_profit(config.bot.min_profit);
+    let swap_instructions =
+        create_native_swap_instructions(&wallet, pool_data, plan, minimum_profit)?;
+    let balance_check =
+        create_balance_check_instruction(client, &wallet, pool_data, plan, minimum_profit)?;
+    let build = |compute_unit_limit: u32| {
+        let mut instructions = compute_budget_instructions(config, compute_unit_limit);
+        instructions.extend(swap_instructions.iter().cloned());
+        instructions.push(balance_check.clone());
+        instructions.extend(tip_instruction(&config.bot, &wallet));
+        instructions.push(uniqueness_instruction());
+
+        sign_transaction(
+            wallet_kp,
+            &instructions,
+            blockhash,
+            address_lookup_table_accounts,
+        )
+    };
+
+    let token_accounts = route_token_accounts(&wallet, pool_data, plan)?;
+    let Some((tx, compute_unit_limit)) = prepare_transaction(
+        config,
+        compute_units,
+        &RouteShape::native(plan),
+        rpc_clients,
+        &token_accounts,
+        minimum_profit,
+        build,
+    )?
+    else {
+        return Ok(Vec::new());
+    };
+    if !priced_within_limit(plan, compute_unit_limit) {
+        return Ok(Vec::new());
+    }
+
+    send_signed_transaction(config, &tx, rpc_clients).await
+}
+
+/// Whether `plan` is expected to clear the configured profit floor, logging the plans that are
+/// not
+fn clears_floor(config: &Config, plan: &RoutePlan) -> bool {
+    let clears = plan.clears_floor(config.bot.min_profit);
+    if !clears {
+        info!(
+            "Dropping plan expected to gain {}, below the floor of {}",
+            plan.expected_profit,
+            config.bot.min_profit.floor(plan.expected_profit)
+        );
+    }
+    clears
+}
+
+fn compute_budget_instructions(config: &Config, compute_unit_limit: u32) -> Vec<Instruction> {
+    let compute_budget_ix = ComputeBudgetInstruction::set_compute_unit_limit(compute_unit_limit);
 
     let compute_unit_price = config.spam.as_ref().map_or(1000, |s| s.compute_unit_price);
     let compute_budget_price_ix =
         ComputeBudgetInstruction::set_compute_unit_price(compute_unit_price);
-    instructions.push(compute_budget_price_ix);
 
-    let swap_ix = create_swap_instruction(
-        wallet_kp,
-        mint_pool_data,
-        compute_unit_limit,
-        enable_flashloan,
-    )?;
+    vec![compute_budget_ix, compute_budget_price_ix]
+}
 
-    let mut all_instructions = instructions.clone();
+/// A transfer of the configured tip from the wallet to one of the tip accounts, picked at
+/// random, or `None` when no tip is configured
+fn tip_instruction(bot: &BotConfig, wallet: &Pubkey) -> Option<Instruction> {
+    if bot.tip_lamports == 0 || bot.tip_accounts.is_empty() {
+        return None;
+    }
+    let tip_account = bot.tip_accounts[rand::random::<usize>() % bot.tip_accounts.len()];
+    Some(system_instruction::transfer(
+        wallet,
+        &tip_account,
+        bot.tip_lamports,
+    ))
+}
 
-    debug!("Adding swap instruction");
-    all_instructions.push(swap_ix);
+/// A memo of a random nonce, so that resending the same route makes a new signature
+fn uniqueness_instruction() -> Instruction {
+    Instruction {
+        program_id: memo_program_id(),
+        accounts: vec![],
+        data: format!("{:016x}", rand::random::<u64>()).into_bytes(),
+    }
+}
 
+fn sign_transaction(
+    wallet_kp: &Keypair,
+    instructions: &[Instruction],
+    blockhash: Hash,
+    address_lookup_table_accounts: &[AddressLookupTableAccount],
+) -> anyhow::Result<VersionedTransaction> {
     let message = Message::try_compile(
         &wallet_kp.pubkey(),
-        &all_instructions,
+        instructions,
         address_lookup_table_accounts,
         blockhash,
     )?;
 
-    let tx = VersionedTransaction::try_new(
+    Ok(VersionedTransaction::try_new(
         solana_sdk::message::VersionedMessage::V0(message),
         &[wallet_kp],
-    )?;
+    )?)
+}
+
+/// Build the transaction at `shape`'s compute unit limit with `build`, returning it with that
+/// limit. A shape without a learned limit is built at the fallback limit and simulated once to
+/// learn it, then rebuilt at the learned limit.
+///
+/// When the simulation gate is enabled the transaction is simulated through the first RPC
+/// client, and the same simulation teaches the limit. Transactions that fail, or that gain less
+/// than `minimum_profit` in the first of `token_accounts`, are dropped by returning `None`.
+fn prepare_transaction(
+    config: &Config,
+    compute_units: &ComputeUnitEstimator,
+    shape: &RouteShape,
+    rpc_clients: &[Arc<RpcClient>],
+    token_accounts: &[Pubkey],
+    minimum_profit: u64,
+    build: impl Fn(u32) -> anyhow::Result<VersionedTransaction>,
+) -> anyhow::Result<Option<(VersionedTransaction, u32)>> {
+    let gate = config.simulation.as_ref().is_some_and(|s| s.enabled);
+    let learned_limit = compute_units.limit(shape);
+    let compute_unit_limit = compute_units.limit_or_fallback(shape);
+    let tx = build(compute_unit_limit)?;
+    let Some(client) = rpc_clients
+        .first()
+        .filter(|_| gate || learned_limit.is_none())
+    else {
+        return Ok(Some((tx, compute_unit_limit)));
+    };
+
+    let watched = if gate { token_accounts } else { &[] };
+    let simulation = simulate_transaction(client, &tx, watched)?;
+    match (&simulation.failure, simulation.units_consumed) {
+        (Some(SimulationFailure::ComputeBudgetExceeded), _) => compute_units.forget(shape),
+        (None, Some(units_consumed)) => {
+            let limit = compute_units.record(shape, units_consumed);
+            debug!(
+                "Learned a limit of {} compute units for {:?} from {} consumed",
+                limit, shape, units_consumed
+            );
+        }
+        (failure, _) => {
+            debug!(
+                "Could not measure compute units for {:?}: {:?}",
+                shape, failure
+            );
+        }
+    }
+
+    if gate {
+        if let Some(failure) = &simulation.failure {
+            warn!("Dropping transaction that fails in simulation: {}", failure);
+            debug!("Simulation logs: {:#?}", simulation.logs);
+            return Ok(None);
+        }
+        if simulation.realized_profit() < minimum_profit as i128 {
+            info!(
+                "Dropping transaction that gains {} in simulation, below the floor of {}",
+                simulation.realized_profit(),
+                minimum_profit
+            );
+            return Ok(None);
+        }
+    }
+
+    match compute_units.limit(shape) {
+        Some(limit) if learned_limit.is_none() => Ok(Some((build(limit)?, limit))),
+        _ => Ok(Some((tx, compute_unit_limit))),
+    }
+}
+
+/// Whether `plan`'s costs were priced at `compute_unit_limit` or above. A plan priced at a lower
+/// limit underestimates its priority fee, and so its floor, and is left for the next search,
+/// which prices it at the learned limit.
+fn priced_within_limit(plan: &RoutePlan, compute_unit_limit: u32) -> bool {
+    let priced = compute_unit_limit <= plan.compute_unit_limit;
+    if !priced {
+        info!(
+            "Dropping plan priced at {} compute units, below the limit of {}",
+            plan.compute_unit_limit, compute_unit_limit
+        );
+    }
+    priced
+}
 
+async fn send_signed_transaction(
+    config: &Config,
+    tx: &VersionedTransaction,
+    rpc_clients: &[Arc<RpcClient>],
+) -> anyhow::Result<Vec<Signature>> {
     let max_retries = config
         .spam
         .as_ref()
@@ -94,7 +338,7 @@ pub async fn build_and_send_transaction(
     for (i, client) in rpc_clients.iter().enumerate() {
         debug!("Sending transaction through RPC client {}", i);
 
-        let signature = match send_transaction_with_retries(client, &tx, max_retries).await {
+        let signature = match send_transaction_with_retries(client, tx, max_retries).await {
             Ok(sig) => sig,
             Err(e) => {
                 error!("Failed to send transaction through RPC client {}: {}", i, e);
@@ -112,6 +356,147 @@ pub async fn build_and_send_transaction(
     Ok(signatures)
 }
 
+/// The legs of `plan` as plain instructions: each leg creates its output token account if
+/// needed and then swaps exactly its quoted input. Every leg but the last requires its quoted
+/// output, which is exactly what the next leg sells. The last one has to return the plan's
+/// `amount_in` plus `minimum_profit`, so its minimum output is the route's balance check.
+pub fn create_native_swap_instructions(
+    wallet: &Pubkey,
+    pool_data: &[MintPoolData],
+    plan: &RoutePlan,
+    minimum_profit: u64,
+) -> anyhow::Result<Vec<Instruction>> {
+    let mut instructions = Vec::new();
+
+    for (i, leg) in plan.legs.iter().enumerate() {
+        let pool = leg_pool(pool_data, leg)?;
+
+        if leg.output_mint != plan.base_mint {
+            instructions.push(create_associated_token_account_idempotent(
+                wallet,
+                wallet,
+                &leg.output_mint,
+                &output_token_program(pool, leg.direction),
+            ));
+        }
+
+        let minimum_amount_out = if i + 1 == plan.legs.len() {
+            plan.amount_in.saturating_add(minimum_profit)
+        } else {
+            leg.quote.amount_out
+        };
+        instructions.push(pool.swap_instruction(
+            wallet,
+            leg.quote.amount_in,
+            minimum_amount_out,
+            leg.direction,
+        )?);
+    }
+
+    Ok(instructions)
+}
+
+/// An instruction that fails unless the wallet's base mint account holds its current balance
+/// plus `minimum_profit`, and, for a SOL base, plus the rent of the token accounts the route
+/// creates. It transfers that amount from the account to itself, which the token program
+/// refuses when the balance falls short.
+fn create_balance_check_instruction(
+    client: &RpcClient,
+    wallet: &Pubkey,
+    pool_data: &[MintPoolData],
+    plan: &RoutePlan,
+    minimum_profit: u64,
+) -> anyhow::Result<Instruction> {
+    let accounts = route_token_account_programs(wallet, pool_data, plan)?;
+    let keys: Vec<Pubkey> = accounts.iter().map(|(account, _)| *account).collect();
+    let existing = client.get_multiple_accounts(&keys)?;
+
+    let (base_account, base_program) = accounts[0];
+    let base_balance = existing[0]
+        .as_ref()
+        .map_or(Ok(0), |account| token_account_amount(&account.data))?;
+    let mut required_gain = minimum_profit;
+    if plan.base_mint == sol_mint() {
+        for ((_, program), account) in accounts.iter().zip(&existing).skip(1) {
+            if account.is_none() {
+                let len = if *program == TOKEN_2022_PROGRAM_ID {
+                    TOKEN_2022_ACCOUNT_LEN
+                } else {
+                    TOKEN_ACCOUNT_LEN
+                };
+                required_gain = required_gain
+                    .saturating_add(client.get_minimum_balance_for_rent_exemption(len)?);
+            }
+        }
+    }
+
+    let mut data = vec![TRANSFER_INSTRUCTION_TAG];
+    data.extend_from_slice(&base_balance.saturating_add(required_gain).to_le_bytes());
+    Ok(Instruction {
+        program_id: base_program,
+        accounts: vec![
+            AccountMeta::new(base_account, false),
+            AccountMeta::new(base_account, false),
+            AccountMeta::new_readonly(*wallet, true),
+        ],
+        data,
+    })
+}
+
+/// The wallet's token account for the base mint of `plan`, then for every mint it passes
+/// through
+fn route_token_accounts(
+    wallet: &Pubkey,
+    pool_data: &[MintPoolData],
+    plan: &RoutePlan,
+) -> anyhow::Result<Vec<Pubkey>> {
+    Ok(route_token_account_programs(wallet, pool_data, plan)?
+        .into_iter()
+        .map(|(account, _)| account)
+        .collect())
+}
+
+/// `route_token_accounts` alongside each account's token program
+fn route_token_account_programs(
+    wallet: &Pubkey,
+    pool_data: &[MintPoolData],
+    plan: &RoutePlan,
+) -> anyhow::Result<Vec<(Pubkey, Pubkey)>> {
+    let mut base_account = None;
+    let mut accounts = Vec::new();
+    for leg in &plan.legs {
+        let token_program = output_token_program(leg_pool(pool_data, leg)?, leg.direction);
+        let account =
+            get_associated_token_address_with_program_id(wallet, &leg.output_mint, &token_program);
+        if leg.output_mint == plan.base_mint {
+            base_account = Some((account, token_program));
+        } else {
+            accounts.push((account, token_program));
+        }
+    }
+
+    let base_account =
+        base_account.ok_or_else(|| anyhow!("Route does not return to {}", plan.base_mint))?;
+    accounts.insert(0, base_account);
+    Ok(accounts)
+}
+
+fn leg_pool<'a>(pool_data: &'a [MintPoolData], leg: &RouteLeg) -> anyhow::Result<&'a dyn DexPool> {
+    pool_data
+        .iter()
+        .flat_map(|data| data.pools())
+        .find(|pool| pool.address() == leg.pool)
+        .ok_or_else(|| anyhow!("{} pool {} is not loaded", leg.kind, leg.pool))
+}
+
+fn output_token_program(pool: &dyn DexPool, direction: SwapDirection) -> Pubkey {
+    let (token_program, base_program) = pool.token_programs();
+    match direction {
+        SwapDirection::SolToToken => token_program,
+        SwapDirection::TokenToSol => base_program,
+    }
+}
+
 async fn send_transaction_with_retries(
     client: &RpcClient,
     tx: &VersionedTransaction,
@@ -135,37 +520,28 @@ pub fn derive_vault_token_account(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey
 
 fn create_swap_instruction(
     wallet_kp: &Keypair,
+    executor: &ExecutorConfig,
     mint_pool_data: &MintPoolData,
+    minimum_profit: u64,
     compute_unit_limit: u32,
     use_flashloan: bool,
 ) -> anyhow::Result<Instruction> {
     debug!("Creating swap instruction for all DEX types");
 
-    let executor_program_id =
-        Pubkey::from_str("MEViEnscUm6tsQRoGd9h6nLQaQspKj7DB2M5FwM3Xvz").unwrap();
-
     let fee_collector = if use_flashloan {
-        Pubkey::from_str("6AGB9kqgSp2mQXwYpdrV4QVV8urvCaDS35U1wsLssy6H").unwrap()
+        executor.flashloan_fee_collector
     } else {
-        let fee_accounts = [
-            Pubkey::from_str("GPpkDpzCDmYJY5qNhYmM14c7rct1zmkjWc2CjR5g7RZ1").unwrap(),
-            Pubkey::from_str("J6c7noBHvWju4mMA3wXt3igbBSp2m9ATbA6cjMtAUged").unwrap(),
-            Pubkey::from_str("BjsfwxDu7GX7RRW6oSRTpMkASdXAgCcHnXEcatqSfuuY").unwrap(),
-        ];
+        let fee_accounts = &executor.fee_collectors;
         fee_accounts[rand::random::<usize>() % fee_accounts.len()]
     };
 
-    let pump_global_config =
-        Pubkey::from_str("ADyA8hdefvWN2dbGGWFotbzWxrAvLW83WG6QCVXvJKqw").unwrap();
-    let pump_authority = Pubkey::from_str("GS4CU59F31iL7aR2Q8zVS8DRrcRnXX1yjQ66TqNVQnaR").unwrap();
     let sysvar_instructions =
         Pubkey::from_str("Sysvar1nstructions1111111111111111111111111").unwrap();
-    let memo_program = Pubkey::from_str("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr").unwrap();
 
     let wallet = wallet_kp.pubkey();
     let sol_mint_pubkey = sol_mint();
     let wallet_sol_account = mint_pool_data.wallet_wsol_account;
-    let usdc_mint = Pubkey::from_str("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap();
+    let usdc_mint = executor.usdc_mint;
 
     let mut accounts = vec![
         AccountMeta::new(wallet, true), // 0. Wallet (signer)
@@ -180,32 +556,15 @@ fn create_swap_instruction(
     // Determine the base mint for flashloan if needed
     let flashloan_base_mint = if use_flashloan {
         // For flashloan, we need a common base mint across all pools
-        // Check if all pools use SOL as base mint
-        let mut all_sol_base = true;
-        let mut all_usdc_base = true;
-
-        // Check all pool types to see their base mints
-        for pool in &mint_pool_data.raydium_pools {
-            if pool.base_mint != sol_mint_pubkey {
-                all_sol_base = false;
-            }
-            if pool.base_mint != usdc_mint {
-                all_usdc_base = false;
-            }
-        }
-        for pool in &mint_pool_data.raydium_cp_pools {
-            if pool.base_mint != sol_mint_pubkey {
-                all_sol_base = false;
-            }
-            if pool.base_mint != usdc_mint {
-                all_usdc_base = false;
-            }
-        }
-        // Add other pool type checks as needed...
-
-        if all_sol_base {
+        if mint_pool_data
+            .pools()
+            .all(|pool| pool.base_mint() == sol_mint_pubkey)
+        {
             sol_mint_pubkey
-        } else if all_usdc_base {
+        } else if mint_pool_data
+            .pools()
+            .all(|pool| pool.base_mint() == usdc_mint)
+        {
             usdc_mint
         } else {
             // Mixed base mints - default to SOL for now
@@ -216,56 +575,31 @@ fn create_swap_instruction(
     };
 
     if use_flashloan {
-        accounts.push(AccountMeta::new_readonly(
-            Pubkey::from_str("5LFpzqgsxrSfhKwbaFiAEJ2kbc9QyimjKueswsyU4T3o").unwrap(),
-            false,
-        ));
-        let token_pda = derive_vault_token_account(
-            &Pubkey::from_str("MEViEnscUm6tsQRoGd9h6nLQaQspKj7DB2M5FwM3Xvz").unwrap(),
-            &flashloan_base_mint,
-        );
+        accounts.push(AccountMeta::new_readonly(executor.flashloan_account, false));
+        let token_pda = derive_vault_token_account(&executor.program_id, &flashloan_base_mint);
         accounts.push(AccountMeta::new(token_pda.0, false));
     }
 
     // Check for mixed mode (USDC base)
-    let mut has_usdc_base = false;
-
-    // Check all pools to see if any have USDC as base mint
-    for pool in &mint_pool_data.raydium_pools {
-        if pool.base_mint == usdc_mint {
-            has_usdc_base = true;
-            break;
-        }
-    }
-    if !has_usdc_base {
-        for pool in &mint_pool_data.raydium_cp_pools {
-            if pool.base_mint == usdc_mint {
-                has_usdc_base = true;
-                break;
-            }
-        }
-    }
-    // Check other pool types as needed...
+    let has_usdc_base = mint_pool_data
+        .pools()
+        .any(|pool| pool.base_mint() == usdc_mint);
 
     // If mixed mode is detected, add the required accounts
     if has_usdc_base {
         let wallet_usdc_account =
             spl_associated_token_account::get_associated_token_address(&wallet, &usdc_mint);
-        let raydium_sol_usdc_pool =
-            Pubkey::from_str("58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2").unwrap();
-        let raydium_usdc_vault =
-            Pubkey::from_str("HLmqeL62xR1QoZ1HKKbXRrdN1p3phKpxRMb2VVopvBBz").unwrap();
-        let raydium_sol_vault =
-            Pubkey::from_str("DQyrAcCrDXQ7NeoqGgDCZwBvWDcYmFCjSb9JtteuvPpz").unwrap();
-
         accounts.push(AccountMeta::new_readonly(usdc_mint, false));
         accounts.push(AccountMeta::new(wallet_usdc_account, false));
         accounts.push(AccountMeta::new_readonly(raydium_program_id(), false));
         accounts.push(AccountMeta::new_readonly(raydium_authority(), false));
         accounts.push(AccountMeta::new_readonly(sysvar_instructions, false));
-        accounts.push(AccountMeta::new(raydium_sol_usdc_pool, false));
-        accounts.push(AccountMeta::new(raydium_usdc_vault, false));
-        accounts.push(AccountMeta::new(raydium_sol_vault, false));
+        accounts.push(AccountMeta::new(executor.raydium_sol_usdc_pool, false));
+        accounts.push(AccountMeta::new(
+            executor.raydium_sol_usdc_usdc_vault,
+            false,
+        ));
+        accounts.push(AccountMeta::new(executor.raydium_sol_usdc_sol_vault, false));
     }
 
     // Add token mint and pools
@@ -282,158 +616,13 @@ fn create_swap_instruction(
         );
     accounts.push(AccountMeta::new(wallet_x_account, false));
 
-    // Add Raydium pools
-    for pool in &mint_pool_data.raydium_pools {
-        accounts.push(AccountMeta::new_readonly(raydium_program_id(), false));
-        accounts.push(AccountMeta::new_readonly(pool.base_mint, false)); // V9: Add base mint
-        accounts.push(AccountMeta::new_readonly(raydium_authority(), false));
-        accounts.push(AccountMeta::new(pool.pool, false));
-        accounts.push(AccountMeta::new(pool.token_vault, false));
-        accounts.push(AccountMeta::new(pool.sol_vault, false));
-    }
-
-    // Add Raydium CP pools
-    for pool in &mint_pool_data.raydium_cp_pools {
-        accounts.push(AccountMeta::new_readonly(raydium_cp_program_id(), false));
-        accounts.push(AccountMeta::new_readonly(pool.base_mint, false)); // V9: Add base mint
-        accounts.push(AccountMeta::new_readonly(raydium_cp_authority(), false));
-        accounts.push(AccountMeta::new(pool.pool, false));
-        accounts.push(AccountMeta::new_readonly(pool.amm_config, false));
-        accounts.push(AccountMeta::new(pool.token_vault, false));
-        accounts.push(AccountMeta::new(pool.sol_vault, false));
-        accounts.push(AccountMeta::new(pool.observation, false));
-    }
-
-    // Add Pump pools
-    for pool in &mint_pool_data.pump_pools {
-        accounts.push(AccountMeta::new_readonly(pump_program_id(), false));
-        accounts.push(AccountMeta::new_readonly(pool.base_mint, false)); // V9: Add base mint
-        accounts.push(AccountMeta::new_readonly(pump_global_config, false));
-        accounts.push(AccountMeta::new_readonly(pump_authority, false));
-        accounts.push(AccountMeta::new(pump_fee_wallet(), false));
-        accounts.push(AccountMeta::new(pool.pool, false));
-        accounts.push(AccountMeta::new(pool.token_vault, false));
-        accounts.push(AccountMeta::new(pool.sol_vault, false));
-        accounts.push(AccountMeta::new(pool.fee_token_wallet, false));
-        accounts.push(AccountMeta::new(pool.coin_creator_vault_ata, false));
-        accounts.push(AccountMeta::new_readonly(
-            pool.coin_creator_vault_authority,
-            false,
-        ));
-        let pump_program_id =
-            Pubkey::from_str("pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA").unwrap();
-        let (global_volume_accumulator, _) =
-            Pubkey::find_program_address(&[b"global_volume_accumulator"], &pump_program_id);
-        let (user_volume_accumulator, _) = Pubkey::find_program_address(
-            &[b"user_volume_accumulator", wallet.as_ref()],
-            &pump_program_id,
-        );
-        accounts.push(AccountMeta::new(global_volume_accumulator, false));
-        accounts.push(AccountMeta::new(user_volume_accumulator, false));
-    }
-
-    // Add DLMM pairs
-    for pair in &mint_pool_data.dlmm_pairs {
-        accounts.push(AccountMeta::new_readonly(dlmm_program_id(), false));
-        accounts.push(AccountMeta::new_readonly(pair.base_mint, false)); // V9: Add base mint
-        accounts.push(AccountMeta::new_readonly(dlmm_event_authority(), false));
-        if let Some(memo_program) = pair.memo_program {
-            accounts.push(AccountMeta::new_readonly(memo_program, false));
-        }
-        accounts.push(AccountMeta::new(pair.pair, false));
-        accounts.push(AccountMeta::new(pair.token_vault, false));
-        accounts.push(AccountMeta::new(pair.sol_vault, false));
-        accounts.push(AccountMeta::new(pair.oracle, false));
-        for bin_array in &pair.bin_arrays {
-            accounts.push(AccountMeta::new(*bin_array, false));
-        }
-    }
-
-    // Add Whirlpool pools
-    for pool in &mint_pool_data.whirlpool_pools {
-        accounts.push(AccountMeta::new_readonly(whirlpool_program_id(), false));
-        accounts.push(AccountMeta::new_readonly(pool.base_mint, false)); // V9: Add base mint
-        accounts.push(AccountMeta::new_readonly(memo_program, false)); // Always add memo program for Whirlpool
-        accounts.push(AccountMeta::new(pool.pool, false));
-        accounts.push(AccountMeta::new(pool.oracle, false)); // Oracle NEEDS to be writable for Whirlpool
-        accounts.push(AccountMeta::new(pool.x_vault, false));
-        accounts.push(AccountMeta::new(pool.y_vault, false));
-        for tick_array in &pool.tick_arrays {
-            accounts.push(AccountMeta::new(*tick_array, false));
-        }
-    }
-
-    // Add Raydium CLMM pools
-    for pool in &mint_pool_data.raydium_clmm_pools {
-        accounts.push(AccountMeta::new_readonly(raydium_clmm_program_id(), false));
-        accounts.push(AccountMeta::new_readonly(pool.base_mint, false)); // V9: Add base mint
-        if let Some(memo_program) = pool.memo_program {
-            accounts.push(AccountMeta::new_readonly(memo_program, false));
-        }
-        accounts.push(AccountMeta::new(pool.pool, false));
-        accounts.push(AccountMeta::new_readonly(pool.amm_config, false));
-        accounts.push(AccountMeta::new(pool.observation_state, false));
-        accounts.push(AccountMeta::new(pool.bitmap_extension, false));
-        accounts.push(AccountMeta::new(pool.x_vault, false));
-        accounts.push(AccountMeta::new(pool.y_vault, false));
-        for tick_array in &pool.tick_arrays {
-            accounts.push(AccountMeta::new(*tick_array, false));
-        }
-    }
-
-    // Add Meteora DAMM pools
-    for pool in &mint_pool_data.meteora_damm_pools {
-        accounts.push(AccountMeta::new_readonly(damm_program_id(), false));
-        accounts.push(AccountMeta::new_readonly(pool.base_mint, false)); // V9: Add base mint
-        accounts.push(AccountMeta::new_readonly(vault_program_id(), false));
-        accounts.push(AccountMeta::new(pool.pool, false));
-        accounts.push(AccountMeta::new(pool.token_x_vault, false));
-        accounts.push(AccountMeta::new(pool.token_sol_vault, false));
-        accounts.push(AccountMeta::new(pool.token_x_token_vault, false));
-        accounts.push(AccountMeta::new(pool.token_sol_token_vault, false));
-        accounts.push(AccountMeta::new(pool.token_x_lp_mint, false));
-        accounts.push(AccountMeta::new(pool.token_sol_lp_mint, false));
-        accounts.push(AccountMeta::new(pool.token_x_pool_lp, false));
-        accounts.push(AccountMeta::new(pool.token_sol_pool_lp, false));
-        accounts.push(AccountMeta::new(pool.admin_token_fee_x, false));
-        accounts.push(AccountMeta::new(pool.admin_token_fee_sol, false));
-    }
-
-    // Add Meteora DAMM V2 pools
-    for pool in &mint_pool_data.meteora_damm_v2_pools {
-        accounts.push(AccountMeta::new_readonly(damm_v2_program_id(), false));
-        accounts.push(AccountMeta::new_readonly(pool.base_mint, false)); // V9: Add base mint
-        accounts.push(AccountMeta::new_readonly(damm_v2_event_authority(), false));
-        accounts.push(AccountMeta::new_readonly(damm_v2_pool_authority(), false));
-        accounts.push(AccountMeta::new(pool.pool, false));
-        accounts.push(AccountMeta::new(pool.token_x_vault, false));
-        accounts.push(AccountMeta::new(pool.token_sol_vault, false));
-    }
-
-    // Add Solfi pools
-    for pool in &mint_pool_data.solfi_pools {
-        accounts.push(AccountMeta::new_readonly(solfi_program_id(), false));
-        accounts.push(AccountMeta::new_readonly(pool.base_mint, false)); // V9: Add base mint
-        accounts.push(AccountMeta::new_readonly(sysvar_instructions, false));
-        accounts.push(AccountMeta::new(pool.pool, false));
-        accounts.push(AccountMeta::new(pool.token_x_vault, false));
-        accounts.push(AccountMeta::new(pool.token_sol_vault, false));
-    }
-
-    // Add Vertigo pools
-    for pool in &mint_pool_data.vertigo_pools {
-        accounts.push(AccountMeta::new_readonly(vertigo_program_id(), false));
-        accounts.push(AccountMeta::new_readonly(pool.base_mint, false)); // V9: Add base mint
-        accounts.push(AccountMeta::new(pool.pool, false));
-        accounts.push(AccountMeta::new_readonly(pool.pool_owner, false));
-        accounts.push(AccountMeta::new(pool.token_x_vault, false));
-        accounts.push(AccountMeta::new(pool.token_sol_vault, false));
+    for pool in mint_pool_data.pools() {
+        accounts.extend(pool.swap_account_metas(&wallet));
     }
 
     // Create instruction data
     let mut data = vec![28u8];
 
-    let minimum_profit: u64 = 0;
     // When true, the bot will not fail the transaction even when it can't find a profitable arbitrage. It will just do nothing and succeed.
     let no_failure_mode = false;
 
@@ -444,8 +633,42 @@ fn create_swap_instruction(
     data.extend_from_slice(if use_flashloan { &[1] } else { &[0] });
 
     Ok(Instruction {
-        program_id: executor_program_id,
+        program_id: executor.program_id,
         accounts,
         data,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MinProfit;
+
+    fn bot(tip_lamports: u64, tip_accounts: Vec<Pubkey>) -> BotConfig {
+        BotConfig {
+            compute_unit_limit: 600_000,
+            compute_unit_margin_percent: 15,
+            max_trade_lamports: 1_000_000_000,
+            tip_lamports,
+            tip_accounts,
+            min_profit: MinProfit::Lamports(0),
+        }
+    }
+
+    #[test]
+    fn transfers_the_tip_to_a_tip_account() {
+        let wallet = Pubkey::new_unique();
+        let tip_accounts = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let ix = tip_instruction(&bot(10_000, tip_accounts.clone()), &wallet).unwrap();
+
+        assert_eq!(ix.program_id, system_program::ID);
+        assert_eq!(ix.accounts[0], AccountMeta::new(wallet, true));
+        assert!(tip_accounts.contains(&ix.accounts[1].pubkey));
+        assert!(ix.accounts[1].is_writable);
+        // System program `Transfer`: a u32 tag of 2, then the lamports
+        assert_eq!(ix.data[..4], 2u32.to_le_bytes());
+        assert_eq!(ix.data[4..], 10_000u64.to_le_bytes());
+
+        assert!(tip_instruction(&bot(0, tip_accounts), &wallet).is_none());
+    }
+}
@@ -1,5 +1,11 @@
+use std::collections::HashSet;
 use std::env;
+use std::str::FromStr;
+use anyhow::{anyhow, Context};
 use dotenv::dotenv;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use crate::{chain::constants::sol_mint, dex::raydium::amm_info::RaydiumAmmInfo};
 
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -9,16 +15,66 @@ pub struct Config {
     pub spam: Option<SpamConfig>,
     pub wallet: WalletConfig,
     pub flashloan: Option<FlashloanConfig>,
+    pub executor: ExecutorConfig,
+    pub simulation: Option<SimulationConfig>,
 }
 
 #[derive(Debug, Clone)]
 pub struct BotConfig {
-    pub compute_unit_limit: u32,
+    pub compute_unit_limit: u32, // limit for a route shape until simulation has measured it
+    pub compute_unit_margin_percent: u32, // headroom over the units a simulation consumed
+    pub max_trade_lamports: u64, // largest SOL input an arbitrage plan may use
+    pub tip_lamports: u64,       // tip paid per landed transaction
+    pub tip_accounts: Vec<Pubkey>, // one is picked at random per transaction
+    pub min_profit: MinProfit,
+}
+
+impl BotConfig {
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.tip_lamports > 0 && self.tip_accounts.is_empty() {
+            return Err(anyhow!(
+                "BOT_TIP_ACCOUNTS must list an account when BOT_TIP_LAMPORTS is set"
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Profit a landed arbitrage must clear after its tip and priority fee, or it reverts
+#[derive(Debug, Clone, Copy)]
+pub enum MinProfit {
+    Lamports(u64), // fixed floor
+    Fraction(f64), // share of the plan's expected profit
+}
+
+impl MinProfit {
+    /// A floor of `fraction` of the expected profit, which must lie in 0..=1
+    pub fn fraction(fraction: f64) -> anyhow::Result<Self> {
+        if !(0.0..=1.0).contains(&fraction) {
+            return Err(anyhow!(
+                "BOT_MIN_PROFIT_FRACTION must be between 0 and 1, got {}",
+                fraction
+            ));
+        }
+        Ok(MinProfit::Fraction(fraction))
+    }
+
+    /// The floor for a plan expected to net `expected_profit`. A fractional floor never rounds
+    /// above the expected profit.
+    pub fn floor(&self, expected_profit: u64) -> u64 {
+        match *self {
+            MinProfit::Lamports(lamports) => lamports,
+            MinProfit::Fraction(fraction) => {
+                ((expected_profit as f64 * fraction) as u64).min(expected_profit)
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct RoutingConfig {
     pub mint_config_list: Vec<MintConfig>,
+    pub base_mint: String, // asset multi-hop cycles start and end in
 }
 
 #[derive(Debug, Clone)]
@@ -68,6 +124,100 @@ pub struct FlashloanConfig {
     pub enabled: bool,
 }
 
+/// Simulate every transaction before sending it and drop the ones that fail or fall short of
+/// the profit floor
+#[derive(Debug, Clone)]
+pub struct SimulationConfig {
+    pub enabled: bool,
+}
+
+/// Accounts the executor program's swap instruction is built with
+#[derive(Debug, Clone)]
+pub struct ExecutorConfig {
+    pub program_id: Pubkey,
+    pub fee_collectors: Vec<Pubkey>, // one is picked at random per transaction
+    pub flashloan_fee_collector: Pubkey,
+    pub flashloan_account: Pubkey,
+    pub usdc_mint: Pubkey,
+    pub raydium_sol_usdc_pool: Pubkey, // converts between SOL and USDC for USDC-based pools
+    pub raydium_sol_usdc_usdc_vault: Pubkey,
+    pub raydium_sol_usdc_sol_vault: Pubkey,
+}
+
+impl ExecutorConfig {
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.fee_collectors.is_empty() {
+            return Err(anyhow!("EXECUTOR_FEE_COLLECTORS must list at least one account"));
+        }
+        let mut seen = HashSet::new();
+        if let Some(duplicate) = self.fee_collectors.iter().find(|key| !seen.insert(*key)) {
+            return Err(anyhow!("EXECUTOR_FEE_COLLECTORS lists {} twice", duplicate));
+        }
+        if self.raydium_sol_usdc_usdc_vault == self.raydium_sol_usdc_sol_vault {
+            return Err(anyhow!(
+                "EXECUTOR_RAYDIUM_SOL_USDC_USDC_VAULT and EXECUTOR_RAYDIUM_SOL_USDC_SOL_VAULT must differ"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Check the configured vaults against the SOL/USDC pool's own, each on its mint's side
+    pub fn validate_pool(&self, pool: &RaydiumAmmInfo) -> anyhow::Result<()> {
+        let mints = (pool.coin_mint, pool.pc_mint);
+        let (usdc_vault, sol_vault) = if mints == (self.usdc_mint, sol_mint()) {
+            (pool.coin_vault, pool.pc_vault)
+        } else if mints == (sol_mint(), self.usdc_mint) {
+            (pool.pc_vault, pool.coin_vault)
+        } else {
+            return Err(anyhow!(
+                "EXECUTOR_RAYDIUM_SOL_USDC_POOL {} pairs {} with {}, not SOL with {}",
+                self.raydium_sol_usdc_pool,
+                pool.coin_mint,
+                pool.pc_mint,
+                self.usdc_mint
+            ));
+        };
+        if self.raydium_sol_usdc_usdc_vault != usdc_vault {
+            return Err(anyhow!(
+                "EXECUTOR_RAYDIUM_SOL_USDC_USDC_VAULT is {}, but the pool's USDC vault is {}",
+                self.raydium_sol_usdc_usdc_vault,
+                usdc_vault
+            ));
+        }
+        if self.raydium_sol_usdc_sol_vault != sol_vault {
+            return Err(anyhow!(
+                "EXECUTOR_RAYDIUM_SOL_USDC_SOL_VAULT is {}, but the pool's SOL vault is {}",
+                self.raydium_sol_usdc_sol_vault,
+                sol_vault
+            ));
+        }
+        Ok(())
+    }
+
+    /// Fetch the SOL/USDC pool and run `validate_pool` against it
+    pub fn check_pool(&self, client: &RpcClient) -> anyhow::Result<()> {
+        let data = client
+            .get_account_data(&self.raydium_sol_usdc_pool)
+            .with_context(|| {
+                format!(
+                    "Failed to fetch EXECUTOR_RAYDIUM_SOL_USDC_POOL {}",
+                    self.raydium_sol_usdc_pool
+                )
+            })?;
+        self.validate_pool(&RaydiumAmmInfo::load_checked(&data)?)
+    }
+}
+
+/// Parse a comma-separated list of pubkeys, skipping blank entries and naming `key` on failure
+fn parse_pubkey_list(key: &str, value: &str) -> anyhow::Result<Vec<Pubkey>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| Pubkey::from_str(s).with_context(|| format!("Invalid {} entry: {}", key, s)))
+        .collect()
+}
+
 impl Config {
     pub fn load() -> anyhow::Result<Self> {
         // Load environment variables from .env file
@@ -101,11 +251,39 @@ impl Config {
                 .unwrap_or(default)
         }
 
+        // Helper function to get f64 environment variable
+        fn get_f64_env(key: &str, default: f64) -> f64 {
+            env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        }
+
         // Helper function to get optional u64 environment variable
         fn get_optional_u64_env(key: &str) -> Option<u64> {
             env::var(key).ok().and_then(|v| v.parse().ok())
         }
 
+        // Helper function to parse a pubkey environment variable, naming it on failure
+        fn get_pubkey_env(key: &str, default: &str) -> anyhow::Result<Pubkey> {
+            let value = get_env_or_default(key, default);
+            Pubkey::from_str(value.trim()).with_context(|| format!("Invalid {}: {}", key, value))
+        }
+
+        // Helper function to get an environment variable that has no default
+        fn get_required_env(key: &str) -> anyhow::Result<String> {
+            env::var(key)
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+                .ok_or_else(|| anyhow!("{} must be set", key))
+        }
+
+        // Helper function to parse a pubkey environment variable that has no default
+        fn get_required_pubkey_env(key: &str) -> anyhow::Result<Pubkey> {
+            let value = get_required_env(key)?;
+            Pubkey::from_str(value.trim()).with_context(|| format!("Invalid {}: {}", key, value))
+        }
+
         // Helper function to parse comma-separated string into Vec<String>
         fn parse_string_list(key: &str) -> Vec<String> {
             env::var(key)
@@ -120,9 +298,22 @@ impl Config {
         }
 
         // Build bot config
+        let min_profit = match get_optional_u64_env("BOT_MIN_PROFIT_LAMPORTS") {
+            Some(lamports) => MinProfit::Lamports(lamports),
+            None => MinProfit::fraction(get_f64_env("BOT_MIN_PROFIT_FRACTION", 0.5))?,
+        };
         let bot = BotConfig {
             compute_unit_limit: get_u32_env("BOT_COMPUTE_UNIT_LIMIT", 600000),
+            compute_unit_margin_percent: get_u32_env("BOT_COMPUTE_UNIT_MARGIN_PERCENT", 15),
+            max_trade_lamports: get_u64_env("BOT_MAX_TRADE_LAMPORTS", 1_000_000_000),
+            tip_lamports: get_u64_env("BOT_TIP_LAMPORTS", 0),
+            tip_accounts: parse_pubkey_list(
+                "BOT_TIP_ACCOUNTS",
+                &get_env_or_default("BOT_TIP_ACCOUNTS", ""),
+            )?,
+            min_profit,
         };
+        bot.validate()?;
 
         // Build RPC config
         let rpc = RpcConfig {
@@ -155,6 +346,42 @@ impl Config {
             None
         };
 
+        // Build simulation config (optional)
+        let simulation = if get_bool_env("SIMULATION_ENABLED", false) {
+            Some(SimulationConfig { enabled: true })
+        } else {
+            None
+        };
+
+        // Build executor config
+        let fee_collectors = parse_pubkey_list(
+            "EXECUTOR_FEE_COLLECTORS",
+            &get_required_env("EXECUTOR_FEE_COLLECTORS")?,
+        )?;
+        let executor = ExecutorConfig {
+            program_id: get_required_pubkey_env("EXECUTOR_PROGRAM_ID")?,
+            fee_collectors,
+            flashloan_fee_collector: get_required_pubkey_env("EXECUTOR_FLASHLOAN_FEE_COLLECTOR")?,
+            flashloan_account: get_required_pubkey_env("EXECUTOR_FLASHLOAN_ACCOUNT")?,
+            usdc_mint: get_pubkey_env(
+                "EXECUTOR_USDC_MINT",
+                "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+            )?,
+            raydium_sol_usdc_pool: get_pubkey_env(
+                "EXECUTOR_RAYDIUM_SOL_USDC_POOL",
+                "58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2",
+            )?,
+            raydium_sol_usdc_usdc_vault: get_pubkey_env(
+                "EXECUTOR_RAYDIUM_SOL_USDC_USDC_VAULT",
+                "HLmqeL62xR1QoZ1HKKbXRrdN1p3phKpxRMb2VVopvBBz",
+            )?,
+            raydium_sol_usdc_sol_vault: get_pubkey_env(
+                "EXECUTOR_RAYDIUM_SOL_USDC_SOL_VAULT",
+                "DQyrAcCrDXQ7NeoqGgDCZwBvWDcYmFCjSb9JtteuvPpz",
+            )?,
+        };
+        executor.validate()?;
+
         // Build routing config with mint configurations
         let mut mint_config_list = Vec::new();
         
@@ -183,6 +410,10 @@ impl Config {
 
         let routing = RoutingConfig {
             mint_config_list,
+            base_mint: get_env_or_default(
+                "ROUTING_BASE_MINT",
+                "So11111111111111111111111111111111111111112",
+            ),
         };
 
         Ok(Config {
@@ -192,6 +423,140 @@ impl Config {
             spam,
             wallet,
             flashloan,
+            executor,
+            simulation,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COIN_VAULT_OFFSET: usize = 336;
+    const PC_VAULT_OFFSET: usize = 368;
+    const COIN_MINT_OFFSET: usize = 400;
+    const PC_MINT_OFFSET: usize = 432;
+
+    fn executor() -> ExecutorConfig {
+        ExecutorConfig {
+            program_id: Pubkey::new_unique(),
+            fee_collectors: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            flashloan_fee_collector: Pubkey::new_unique(),
+            flashloan_account: Pubkey::new_unique(),
+            usdc_mint: Pubkey::new_unique(),
+            raydium_sol_usdc_pool: Pubkey::new_unique(),
+            raydium_sol_usdc_usdc_vault: Pubkey::new_unique(),
+            raydium_sol_usdc_sol_vault: Pubkey::new_unique(),
+        }
+    }
+
+    /// An AMM v4 pool pairing `coin` in `coin_vault` with `pc` in `pc_vault`
+    fn pool(coin: (Pubkey, Pubkey), pc: (Pubkey, Pubkey)) -> RaydiumAmmInfo {
+        let mut data = vec![0u8; 752];
+        // Both fee denominators must be non-zero to load
+        data[152..160].copy_from_slice(&10_000u64.to_le_bytes());
+        data[184..192].copy_from_slice(&10_000u64.to_le_bytes());
+        for (offset, key) in [
+            (COIN_MINT_OFFSET, coin.0),
+            (COIN_VAULT_OFFSET, coin.1),
+            (PC_MINT_OFFSET, pc.0),
+            (PC_VAULT_OFFSET, pc.1),
+        ] {
+            data[offset..offset + 32].copy_from_slice(key.as_ref());
+        }
+        RaydiumAmmInfo::load_checked(&data).unwrap()
+    }
+
+    #[test]
+    fn computes_profit_floors() {
+        assert_eq!(MinProfit::Lamports(5_000).floor(1_000), 5_000);
+        assert_eq!(MinProfit::Lamports(5_000).floor(0), 5_000);
+        assert_eq!(MinProfit::fraction(0.5).unwrap().floor(1_001), 500);
+        assert_eq!(MinProfit::fraction(0.0).unwrap().floor(1_000), 0);
+        assert_eq!(MinProfit::fraction(1.0).unwrap().floor(1_000), 1_000);
+        // 2^54 - 1 rounds up to 2^54 as an f64, and is clamped back down
+        let expected_profit = (1u64 << 54) - 1;
+        assert_eq!(
+            MinProfit::fraction(1.0).unwrap().floor(expected_profit),
+            expected_profit
+        );
+    }
+
+    #[test]
+    fn rejects_fractions_outside_zero_to_one() {
+        for fraction in [-0.01, 1.01, f64::NAN, f64::INFINITY] {
+            assert!(MinProfit::fraction(fraction).is_err(), "{}", fraction);
+        }
+    }
+
+    #[test]
+    fn parses_pubkey_lists() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert_eq!(
+            parse_pubkey_list("KEYS", &format!(" {} ,,{}, ", a, b)).unwrap(),
+            vec![a, b]
+        );
+        assert!(parse_pubkey_list("KEYS", "").unwrap().is_empty());
+        let err = parse_pubkey_list("KEYS", &format!("{},nope", a)).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid KEYS entry: nope");
+    }
+
+    #[test]
+    fn requires_tip_accounts_for_a_tip() {
+        let mut bot = BotConfig {
+            compute_unit_limit: 600_000,
+            compute_unit_margin_percent: 15,
+            max_trade_lamports: 1_000_000_000,
+            tip_lamports: 0,
+            tip_accounts: vec![],
+            min_profit: MinProfit::Lamports(0),
+        };
+        assert!(bot.validate().is_ok());
+
+        bot.tip_lamports = 10_000;
+        assert!(bot.validate().is_err());
+
+        bot.tip_accounts.push(Pubkey::new_unique());
+        assert!(bot.validate().is_ok());
+    }
+
+    #[test]
+    fn validates_fee_collectors_and_vaults() {
+        assert!(executor().validate().is_ok());
+
+        let mut config = executor();
+        config.fee_collectors.clear();
+        assert!(config.validate().is_err());
+
+        let mut config = executor();
+        config.fee_collectors.push(config.fee_collectors[0]);
+        assert!(config.validate().unwrap_err().to_string().contains("twice"));
+
+        let mut config = executor();
+        config.raydium_sol_usdc_sol_vault = config.raydium_sol_usdc_usdc_vault;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn validates_vaults_against_the_pool() {
+        let config = executor();
+        let sol = (sol_mint(), config.raydium_sol_usdc_sol_vault);
+        let usdc = (config.usdc_mint, config.raydium_sol_usdc_usdc_vault);
+        // Either mint may be the pool's coin side
+        assert!(config.validate_pool(&pool(sol, usdc)).is_ok());
+        assert!(config.validate_pool(&pool(usdc, sol)).is_ok());
+
+        // Vaults swapped between the sides
+        let swapped = pool((sol.0, usdc.1), (usdc.0, sol.1));
+        assert!(config.validate_pool(&swapped).is_err());
+        // A vault from another pool
+        assert!(config
+            .validate_pool(&pool((sol.0, Pubkey::new_unique()), usdc))
+            .is_err());
+        // A pool that is not SOL/USDC
+        assert!(config
+            .validate_pool(&pool((Pubkey::new_unique(), sol.1), usdc))
+            .is_err());
+    }
+}
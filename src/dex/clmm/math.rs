@@ -0,0 +1,362 @@
+use anyhow::{anyhow, Result};
+
+#[allow(clippy::assign_op_pattern, clippy::manual_div_ceil)]
+mod wide {
+    use uint::construct_uint;
+
+    construct_uint! {
+        pub struct U256(4);
+    }
+
+    construct_uint! {
+        pub struct U512(8);
+    }
+}
+
+pub use wide::{U256, U512};
+
+/// Fee rates of both programs are expressed in millionths
+pub const FEE_RATE_DENOMINATOR: u64 = 1_000_000;
+
+fn u512(value: u128) -> U512 {
+    U512::from(value)
+}
+
+fn div_round(numerator: U512, denominator: U512, round_up: bool) -> U512 {
+    let (quotient, remainder) = numerator.div_mod(denominator);
+    if round_up && !remainder.is_zero() {
+        quotient + U512::one()
+    } else {
+        quotient
+    }
+}
+
+fn to_amount(value: U512) -> Option<u64> {
+    (value <= U512::from(u64::MAX)).then(|| value.low_u64())
+}
+
+fn to_sqrt_price(value: U512) -> Result<u128> {
+    if value > U512::from(u128::MAX) {
+        return Err(anyhow!("Sqrt price overflowed"));
+    }
+    Ok(value.low_u128())
+}
+
+/// Token A (token 0) between two sqrt prices: `liquidity * (upper - lower) / (upper * lower)`
+/// in Q64.64, or `None` if it does not fit in u64
+pub fn get_amount_delta_a(
+    sqrt_price_0: u128,
+    sqrt_price_1: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<Option<u64>> {
+    let (lower, upper) = if sqrt_price_0 < sqrt_price_1 {
+        (sqrt_price_0, sqrt_price_1)
+    } else {
+        (sqrt_price_1, sqrt_price_0)
+    };
+    if lower == 0 {
+        return Err(anyhow!("Sqrt price must be positive"));
+    }
+
+    let numerator = (u512(liquidity) * u512(upper - lower)) << 64;
+    let denominator = u512(upper) * u512(lower);
+    Ok(to_amount(div_round(numerator, denominator, round_up)))
+}
+
+/// Token B (token 1) between two sqrt prices: `liquidity * (upper - lower)` in Q64.64, or
+/// `None` if it does not fit in u64
+pub fn get_amount_delta_b(
+    sqrt_price_0: u128,
+    sqrt_price_1: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<Option<u64>> {
+    let diff = sqrt_price_0.abs_diff(sqrt_price_1);
+    let numerator = u512(liquidity) * u512(diff);
+    Ok(to_amount(div_round(numerator, U512::one() << 64, round_up)))
+}
+
+/// Sqrt price after adding (`add`) or removing `amount` of token A, rounded up
+fn get_next_sqrt_price_from_a_round_up(
+    sqrt_price: u128,
+    liquidity: u128,
+    amount: u64,
+    add: bool,
+) -> Result<u128> {
+    if amount == 0 {
+        return Ok(sqrt_price);
+    }
+
+    let liquidity_x64 = u512(liquidity) << 64;
+    let product = u512(sqrt_price) * U512::from(amount);
+    let denominator = if add {
+        liquidity_x64 + product
+    } else {
+        if liquidity_x64 <= product {
+            return Err(anyhow!(
+                "Not enough liquidity to remove {} of token A",
+                amount
+            ));
+        }
+        liquidity_x64 - product
+    };
+    to_sqrt_price(div_round(
+        liquidity_x64 * u512(sqrt_price),
+        denominator,
+        true,
+    ))
+}
+
+/// Sqrt price after adding (`add`) or removing `amount` of token B, rounded down
+fn get_next_sqrt_price_from_b_round_down(
+    sqrt_price: u128,
+    liquidity: u128,
+    amount: u64,
+    add: bool,
+) -> Result<u128> {
+    if liquidity == 0 {
+        return Err(anyhow!("Cannot move the price without liquidity"));
+    }
+    let delta = to_sqrt_price(div_round(U512::from(amount) << 64, u512(liquidity), !add))?;
+    if add {
+        sqrt_price.checked_add(delta)
+    } else {
+        sqrt_price.checked_sub(delta)
+    }
+    .ok_or_else(|| anyhow!("Sqrt price moved out of range"))
+}
+
+/// Sqrt price reached by swapping `amount` of the specified side from `sqrt_price`
+pub fn get_next_sqrt_price(
+    sqrt_price: u128,
+    liquidity: u128,
+    amount: u64,
+    amount_specified_is_input: bool,
+    a_to_b: bool,
+) -> Result<u128> {
+    if amount_specified_is_input == a_to_b {
+        get_next_sqrt_price_from_a_round_up(
+            sqrt_price,
+            liquidity,
+            amount,
+            amount_specified_is_input,
+        )
+    } else {
+        get_next_sqrt_price_from_b_round_down(
+            sqrt_price,
+            liquidity,
+            amount,
+            amount_specified_is_input,
+        )
+    }
+}
+
+/// Amount of the specified side (input or output) moved between two sqrt prices
+fn get_amount_fixed_delta(
+    sqrt_price_current: u128,
+    sqrt_price_target: u128,
+    liquidity: u128,
+    amount_specified_is_input: bool,
+    a_to_b: bool,
+) -> Result<Option<u64>> {
+    if a_to_b == amount_specified_is_input {
+        get_amount_delta_a(
+            sqrt_price_current,
+            sqrt_price_target,
+            liquidity,
+            amount_specified_is_input,
+        )
+    } else {
+        get_amount_delta_b(
+            sqrt_price_current,
+            sqrt_price_target,
+            liquidity,
+            amount_specified_is_input,
+        )
+    }
+}
+
+/// Amount of the other side moved between two sqrt prices
+fn get_amount_unfixed_delta(
+    sqrt_price_current: u128,
+    sqrt_price_target: u128,
+    liquidity: u128,
+    amount_specified_is_input: bool,
+    a_to_b: bool,
+) -> Result<Option<u64>> {
+    if a_to_b == amount_specified_is_input {
+        get_amount_delta_b(
+            sqrt_price_current,
+            sqrt_price_target,
+            liquidity,
+            !amount_specified_is_input,
+        )
+    } else {
+        get_amount_delta_a(
+            sqrt_price_current,
+            sqrt_price_target,
+            liquidity,
+            !amount_specified_is_input,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapStep {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub next_sqrt_price: u128,
+    pub fee_amount: u64,
+}
+
+/// One swap step within a single liquidity range, moving from `sqrt_price_current` towards
+/// `sqrt_price_target` until `amount_remaining` runs out
+pub fn compute_swap_step(
+    amount_remaining: u64,
+    fee_rate: u32,
+    liquidity: u128,
+    sqrt_price_current: u128,
+    sqrt_price_target: u128,
+    amount_specified_is_input: bool,
+    a_to_b: bool,
+) -> Result<SwapStep> {
+    let fee_rate = fee_rate as u64;
+    if fee_rate >= FEE_RATE_DENOMINATOR {
+        return Err(anyhow!("Invalid fee rate {}", fee_rate));
+    }
+
+    // None when reaching the target would move more than u64::MAX
+    let initial_amount_fixed_delta = get_amount_fixed_delta(
+        sqrt_price_current,
+        sqrt_price_target,
+        liquidity,
+        amount_specified_is_input,
+        a_to_b,
+    )?;
+
+    let amount_calc = if amount_specified_is_input {
+        (amount_remaining as u128 * (FEE_RATE_DENOMINATOR - fee_rate) as u128
+            / FEE_RATE_DENOMINATOR as u128) as u64
+    } else {
+        amount_remaining
+    };
+
+    let next_sqrt_price = match initial_amount_fixed_delta {
+        Some(delta) if delta <= amount_calc => sqrt_price_target,
+        _ => get_next_sqrt_price(
+            sqrt_price_current,
+            liquidity,
+            amount_calc,
+            amount_specified_is_input,
+            a_to_b,
+        )?,
+    };
+    let is_max_swap = next_sqrt_price == sqrt_price_target;
+
+    let amount_unfixed_delta = get_amount_unfixed_delta(
+        sqrt_price_current,
+        next_sqrt_price,
+        liquidity,
+        amount_specified_is_input,
+        a_to_b,
+    )?
+    .ok_or_else(|| anyhow!("Swap step amount exceeds u64"))?;
+
+    let amount_fixed_delta = match initial_amount_fixed_delta {
+        Some(delta) if is_max_swap => delta,
+        _ => get_amount_fixed_delta(
+            sqrt_price_current,
+            next_sqrt_price,
+            liquidity,
+            amount_specified_is_input,
+            a_to_b,
+        )?
+        .ok_or_else(|| anyhow!("Swap step amount exceeds u64"))?,
+    };
+
+    let (amount_in, mut amount_out) = if amount_specified_is_input {
+        (amount_fixed_delta, amount_unfixed_delta)
+    } else {
+        (amount_unfixed_delta, amount_fixed_delta)
+    };
+
+    if !amount_specified_is_input && amount_out > amount_remaining {
+        amount_out = amount_remaining;
+    }
+
+    let fee_amount = if amount_specified_is_input && !is_max_swap {
+        // The price stopped short of the target, so the dust left over is kept as fee
+        amount_remaining - amount_in
+    } else {
+        let fee = div_round(
+            U512::from(amount_in) * U512::from(fee_rate),
+            U512::from(FEE_RATE_DENOMINATOR - fee_rate),
+            true,
+        );
+        to_amount(fee).ok_or_else(|| anyhow!("Swap fee exceeds u64"))?
+    };
+
+    Ok(SwapStep {
+        amount_in,
+        amount_out,
+        next_sqrt_price,
+        fee_amount,
+    })
+}
+
+/// Apply a signed liquidity change when crossing a tick
+pub fn add_liquidity_delta(liquidity: u128, delta: i128) -> Result<u128> {
+    if delta < 0 {
+        liquidity.checked_sub(delta.unsigned_abs())
+    } else {
+        liquidity.checked_add(delta as u128)
+    }
+    .ok_or_else(|| anyhow!("Liquidity {} cannot take a change of {}", liquidity, delta))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE_X64: u128 = 1 << 64;
+    const LIQUIDITY: u128 = 1_000;
+
+    #[test]
+    fn next_sqrt_price_from_token_a_rounds_up() {
+        // 1000 * 2^64 / 1003 = 18391569365612713475.3..
+        assert_eq!(
+            get_next_sqrt_price(ONE_X64, LIQUIDITY, 3, true, true).unwrap(),
+            18391569365612713476
+        );
+        // 1000 * 2^64 / 997 = 18502250826188115963.4..
+        assert_eq!(
+            get_next_sqrt_price(ONE_X64, LIQUIDITY, 3, false, false).unwrap(),
+            18502250826188115964
+        );
+    }
+
+    #[test]
+    fn next_sqrt_price_from_token_b_rounds_down() {
+        // 3 * 2^64 / 1000 = 55340232221128654.8, so adding input moves the price by the floor
+        // and removing output by the ceiling
+        assert_eq!(
+            get_next_sqrt_price(ONE_X64, LIQUIDITY, 3, true, false).unwrap(),
+            18502084305930680270
+        );
+        assert_eq!(
+            get_next_sqrt_price(ONE_X64, LIQUIDITY, 3, false, true).unwrap(),
+            18391403841488422961
+        );
+    }
+
+    #[test]
+    fn next_sqrt_price_rejects_draining_the_pool() {
+        assert!(get_next_sqrt_price(ONE_X64, LIQUIDITY, 1_000, false, false).is_err());
+        assert!(get_next_sqrt_price(ONE_X64, 0, 1, true, false).is_err());
+        assert_eq!(
+            get_next_sqrt_price(ONE_X64, LIQUIDITY, 0, true, true).unwrap(),
+            ONE_X64
+        );
+    }
+}
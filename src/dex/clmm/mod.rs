@@ -0,0 +1,4 @@
+pub mod math;
+pub mod swap;
+
+pub use swap::{swap, NextTick, SwapParams, SwapResult, TickTraversal};
@@ -0,0 +1,191 @@
+use crate::dex::clmm::math::{add_liquidity_delta, compute_swap_step};
+use anyhow::{anyhow, Result};
+
+/// Where the next swap step stops: an initialized tick, or the edge of the searchable range
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NextTick {
+    pub tick_index: i32,
+    /// `None` when the tick is not initialized and crossing it leaves liquidity unchanged
+    pub liquidity_net: Option<i128>,
+}
+
+/// Program-specific tick math and tick array traversal the swap loop runs on
+pub trait TickTraversal {
+    /// Next tick to step to from `tick_current`, loading further tick arrays as the program
+    /// would. Errors when the swap would need a tick array that is not loaded.
+    fn next_tick(&mut self, tick_current: i32, a_to_b: bool) -> Result<NextTick>;
+
+    fn sqrt_price_at_tick(&self, tick_index: i32) -> Result<u128>;
+
+    fn tick_at_sqrt_price(&self, sqrt_price: u128) -> Result<i32>;
+
+    /// Whether the program keeps stepping from `tick_current`
+    fn can_step_from(&self, _tick_current: i32) -> bool {
+        true
+    }
+
+    /// Start indices of the tick arrays the swap has read so far, in traversal order
+    fn tick_arrays_touched(&self) -> Vec<i32>;
+}
+
+/// Pool state a swap starts from
+#[derive(Debug, Clone, Copy)]
+pub struct SwapParams {
+    pub sqrt_price: u128,
+    pub tick_current: i32,
+    pub liquidity: u128,
+    /// In millionths
+    pub fee_rate: u32,
+    pub amount: u64,
+    pub amount_specified_is_input: bool,
+    pub a_to_b: bool,
+    pub sqrt_price_limit: u128,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwapResult {
+    /// Input including fees
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee_amount: u64,
+    /// Part of the specified amount left unfilled when the price limit was reached
+    pub amount_remaining: u64,
+    pub sqrt_price: u128,
+    pub tick_current: i32,
+    pub liquidity: u128,
+    pub tick_arrays: Vec<i32>,
+}
+
+/// Run a swap step by step across initialized ticks, as the CLMM programs do on chain
+pub fn swap<T: TickTraversal>(traversal: &mut T, params: SwapParams) -> Result<SwapResult> {
+    let SwapParams {
+        amount,
+        amount_specified_is_input,
+        a_to_b,
+        sqrt_price_limit,
+        fee_rate,
+        ..
+    } = params;
+    if (a_to_b && sqrt_price_limit > params.sqrt_price)
+        || (!a_to_b && sqrt_price_limit < params.sqrt_price)
+    {
+        return Err(anyhow!(
+            "Sqrt price limit {} is on the wrong side of the current price {}",
+            sqrt_price_limit,
+            params.sqrt_price
+        ));
+    }
+
+    let mut amount_remaining = amount;
+    let mut amount_calculated: u64 = 0;
+    let mut fee_amount: u64 = 0;
+    let mut sqrt_price = params.sqrt_price;
+    let mut tick_current = params.tick_current;
+    let mut liquidity = params.liquidity;
+
+    while amount_remaining > 0
+        && sqrt_price != sqrt_price_limit
+        && traversal.can_step_from(tick_current)
+    {
+        let next_tick = traversal.next_tick(tick_current, a_to_b)?;
+        let next_tick_sqrt_price = traversal.sqrt_price_at_tick(next_tick.tick_index)?;
+        let sqrt_price_target = if a_to_b {
+            next_tick_sqrt_price.max(sqrt_price_limit)
+        } else {
+            next_tick_sqrt_price.min(sqrt_price_limit)
+        };
+
+        let step = compute_swap_step(
+            amount_remaining,
+            fee_rate,
+            liquidity,
+            sqrt_price,
+            sqrt_price_target,
+            amount_specified_is_input,
+            a_to_b,
+        )?;
+
+        let overflow = || anyhow!("Swap amounts overflowed");
+        if amount_specified_is_input {
+            amount_remaining = amount_remaining
+                .checked_sub(step.amount_in)
+                .and_then(|amount| amount.checked_sub(step.fee_amount))
+                .ok_or_else(overflow)?;
+            amount_calculated = amount_calculated
+                .checked_add(step.amount_out)
+                .ok_or_else(overflow)?;
+        } else {
+            amount_remaining = amount_remaining
+                .checked_sub(step.amount_out)
+                .ok_or_else(overflow)?;
+            amount_calculated = amount_calculated
+                .checked_add(step.amount_in)
+                .and_then(|amount| amount.checked_add(step.fee_amount))
+                .ok_or_else(overflow)?;
+        }
+        fee_amount = fee_amount
+            .checked_add(step.fee_amount)
+            .ok_or_else(overflow)?;
+
+        if step.next_sqrt_price == next_tick_sqrt_price {
+            if let Some(liquidity_net) = next_tick.liquidity_net {
+                // Moving down crosses the tick from above, which reverses its sign
+                let delta = if a_to_b {
+                    -liquidity_net
+                } else {
+                    liquidity_net
+                };
+                liquidity = add_liquidity_delta(liquidity, delta)?;
+            }
+            tick_current = if a_to_b {
+                next_tick.tick_index - 1
+            } else {
+                next_tick.tick_index
+            };
+        } else if step.next_sqrt_price != sqrt_price {
+            tick_current = traversal.tick_at_sqrt_price(step.next_sqrt_price)?;
+        }
+        sqrt_price = step.next_sqrt_price;
+    }
+
+    let (amount_in, amount_out) = if amount_specified_is_input {
+        (amount - amount_remaining, amount_calculated)
+    } else {
+        (amount_calculated, amount - amount_remaining)
+    };
+
+    Ok(SwapResult {
+        amount_in,
+        amount_out,
+        fee_amount,
+        amount_remaining,
+        sqrt_price,
+        tick_current,
+        liquidity,
+        tick_arrays: traversal.tick_arrays_touched(),
+    })
+}
+
+/// Largest tick whose sqrt price does not exceed `sqrt_price`, given the program's own
+/// tick-to-price function. This is the tick the programs' log2 estimates resolve to.
+pub fn tick_at_sqrt_price(
+    sqrt_price: u128,
+    min_tick: i32,
+    max_tick: i32,
+    sqrt_price_at_tick: impl Fn(i32) -> Result<u128>,
+) -> Result<i32> {
+    if sqrt_price < sqrt_price_at_tick(min_tick)? || sqrt_price > sqrt_price_at_tick(max_tick)? {
+        return Err(anyhow!("Sqrt price {} is out of range", sqrt_price));
+    }
+
+    let (mut low, mut high) = (min_tick, max_tick);
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        if sqrt_price_at_tick(mid)? <= sqrt_price {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Ok(low)
+}
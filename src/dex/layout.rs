@@ -0,0 +1,92 @@
+//! Account fixtures written field by field, in the order the owning program declares them.
+//! Building fixtures this way checks the decoders' hand-computed offsets against the
+//! declarations instead of against themselves.
+
+use solana_program::pubkey::Pubkey;
+use std::str::FromStr;
+
+/// Little-endian account data, appended one field at a time
+#[derive(Debug, Default)]
+pub struct Layout {
+    data: Vec<u8>,
+}
+
+impl Layout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An Anchor account, starting with its 8 byte discriminator
+    pub fn anchor(discriminator: [u8; 8]) -> Self {
+        Self {
+            data: discriminator.to_vec(),
+        }
+    }
+
+    pub fn bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        self.data.extend_from_slice(bytes);
+        self
+    }
+
+    pub fn zeros(&mut self, len: usize) -> &mut Self {
+        self.data.resize(self.data.len() + len, 0);
+        self
+    }
+
+    pub fn u8(&mut self, value: u8) -> &mut Self {
+        self.bytes(&[value])
+    }
+
+    pub fn bool(&mut self, value: bool) -> &mut Self {
+        self.u8(value as u8)
+    }
+
+    pub fn u16(&mut self, value: u16) -> &mut Self {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    pub fn u32(&mut self, value: u32) -> &mut Self {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    pub fn i32(&mut self, value: i32) -> &mut Self {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    pub fn u64(&mut self, value: u64) -> &mut Self {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    pub fn u128(&mut self, value: u128) -> &mut Self {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    pub fn f64(&mut self, value: f64) -> &mut Self {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    /// Borsh `Option<Pubkey>`: a presence byte, then the key if present
+    pub fn option_pubkey(&mut self, key: Option<&Pubkey>) -> &mut Self {
+        match key {
+            Some(key) => self.u8(1).pubkey(key),
+            None => self.u8(0),
+        }
+    }
+
+    pub fn pubkey(&mut self, key: &Pubkey) -> &mut Self {
+        self.bytes(key.as_ref())
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn finish(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.data)
+    }
+}
+
+/// Parse a base58 address in a fixture
+pub fn key(address: &str) -> Pubkey {
+    Pubkey::from_str(address).unwrap()
+}
@@ -0,0 +1,90 @@
+use crate::dex::meteora::constants::vault_program_id;
+use anchor_lang::AccountDeserialize;
+use anyhow::Result;
+use meteora_damm_cpi::Pool;
+use meteora_vault_cpi::Vault;
+use solana_program::pubkey::Pubkey;
+
+const VAULT_LP_MINT_SEED: &[u8] = b"lp_mint";
+
+// The generated account types use anchor-lang's own Pubkey type
+fn to_pubkey(key: &anchor_lang::prelude::Pubkey) -> Pubkey {
+    Pubkey::new_from_array(key.to_bytes())
+}
+
+/// Meteora DAMM pool decoded through the generated `meteora_damm_cpi::Pool` type
+pub struct DammInfo {
+    pub pool: Pool,
+    pub lp_mint: Pubkey,
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub a_vault: Pubkey,
+    pub b_vault: Pubkey,
+    pub a_vault_lp: Pubkey,
+    pub b_vault_lp: Pubkey,
+    pub admin_token_a_fee: Pubkey,
+    pub admin_token_b_fee: Pubkey,
+}
+
+impl DammInfo {
+    pub fn load_checked(data: &[u8]) -> Result<Self> {
+        let pool = Pool::try_deserialize(&mut &data[..])
+            .map_err(|e| anyhow::anyhow!("Invalid DAMM pool account: {}", e))?;
+
+        Ok(Self {
+            lp_mint: to_pubkey(&pool.lp_mint),
+            token_a_mint: to_pubkey(&pool.token_a_mint),
+            token_b_mint: to_pubkey(&pool.token_b_mint),
+            a_vault: to_pubkey(&pool.a_vault),
+            b_vault: to_pubkey(&pool.b_vault),
+            a_vault_lp: to_pubkey(&pool.a_vault_lp),
+            b_vault_lp: to_pubkey(&pool.b_vault_lp),
+            admin_token_a_fee: to_pubkey(&pool.admin_token_a_fee),
+            admin_token_b_fee: to_pubkey(&pool.admin_token_b_fee),
+            pool,
+        })
+    }
+}
+
+/// Meteora vault decoded through the generated `meteora_vault_cpi::Vault` type
+pub struct DammVaultInfo {
+    pub vault: Vault,
+    pub token_vault: Pubkey,
+    pub token_mint: Pubkey,
+    pub lp_mint: Pubkey,
+}
+
+impl DammVaultInfo {
+    pub fn load_checked(data: &[u8]) -> Result<Self> {
+        let vault = Vault::try_deserialize(&mut &data[..])
+            .map_err(|e| anyhow::anyhow!("Invalid Meteora vault account: {}", e))?;
+
+        Ok(Self {
+            token_vault: to_pubkey(&vault.token_vault),
+            token_mint: to_pubkey(&vault.token_mint),
+            lp_mint: to_pubkey(&vault.lp_mint),
+            vault,
+        })
+    }
+
+    /// Underlying tokens behind `pool_lp_amount` of this vault's LP, excluding profit that is
+    /// still locked at `current_time`
+    pub fn underlying_amount(
+        &self,
+        current_time: u64,
+        pool_lp_amount: u64,
+        vault_lp_supply: u64,
+    ) -> Result<u64> {
+        if vault_lp_supply == 0 {
+            return Ok(0);
+        }
+        self.vault
+            .get_amount_by_share(current_time, pool_lp_amount, vault_lp_supply)
+            .ok_or_else(|| anyhow::anyhow!("Vault share calculation overflowed"))
+    }
+}
+
+/// LP mint the vault program derives for `vault`
+pub fn vault_lp_mint(vault: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[VAULT_LP_MINT_SEED, vault.as_ref()], &vault_program_id()).0
+}
@@ -0,0 +1,403 @@
+use crate::dex::{clmm::math::U256, quote::SwapQuote};
+use anyhow::{anyhow, Result};
+use meteora_damm_cpi::{CurveType, Depeg, DepegType, Pool, TokenMultiplier};
+use meteora_vault_cpi::Vault;
+
+/// Precision of `Depeg::base_virtual_price`
+const VIRTUAL_PRICE_PRECISION: u128 = 1_000_000;
+const N_COINS: u64 = 2;
+const MAX_ITERATIONS: usize = 256;
+
+/// One side of a DAMM pool: the vault it deposits into and the pool's share of that vault
+#[derive(Debug, Clone)]
+pub struct DammVaultSide {
+    pub vault: Vault,
+    /// Vault LP held by the pool
+    pub pool_lp_amount: u64,
+    pub vault_lp_supply: u64,
+    /// Balance of the vault's token account, which caps what a swap can withdraw
+    pub token_vault_amount: u64,
+}
+
+impl DammVaultSide {
+    fn amount_by_share(&self, current_time: u64, share: u64, total_supply: u64) -> Result<u64> {
+        self.vault
+            .get_amount_by_share(current_time, share, total_supply)
+            .ok_or_else(|| anyhow!("Vault share calculation overflowed"))
+    }
+
+    fn unmint_amount(&self, current_time: u64, out_token: u64, total_supply: u64) -> Result<u64> {
+        self.vault
+            .get_unmint_amount(current_time, out_token, total_supply)
+            .ok_or_else(|| anyhow!("Vault share calculation overflowed"))
+    }
+
+    /// Underlying tokens the pool owns through its vault LP
+    pub fn pool_amount(&self, current_time: u64) -> Result<u64> {
+        self.amount_by_share(current_time, self.pool_lp_amount, self.vault_lp_supply)
+    }
+}
+
+/// Quote a DAMM v1 swap of `amount_in`, following the program: the owner fee is skimmed before
+/// the deposit into the input vault, the trade fee is taken from what the deposit is worth,
+/// and the output is rounded down to what whole vault LP can withdraw.
+///
+/// Depeg pools use the virtual price cached in the pool; the program refreshes it from the
+/// stake pool when the cache is stale, so such quotes can drift until the next swap.
+pub fn quote_exact_in(
+    pool: &Pool,
+    a: &DammVaultSide,
+    b: &DammVaultSide,
+    amount_in: u64,
+    a_to_b: bool,
+    current_time: u64,
+) -> Result<SwapQuote> {
+    if !pool.enabled {
+        return Err(anyhow!("Pool is disabled"));
+    }
+    let token_a_amount = a.pool_amount(current_time)?;
+    let token_b_amount = b.pool_amount(current_time)?;
+    let (side_in, side_out, reserve_in, reserve_out) = if a_to_b {
+        (a, b, token_a_amount, token_b_amount)
+    } else {
+        (b, a, token_b_amount, token_a_amount)
+    };
+
+    let fee_overflow = || anyhow!("Trading fee overflowed");
+    let trade_fee = pool
+        .fees
+        .trading_fee(amount_in as u128)
+        .ok_or_else(fee_overflow)?;
+    let owner_fee = pool
+        .fees
+        .owner_trading_fee(amount_in as u128)
+        .ok_or_else(fee_overflow)?;
+    let amount_in_after_owner_fee = (amount_in as u128)
+        .checked_sub(owner_fee)
+        .ok_or_else(|| anyhow!("Owner fee exceeds the input"))?
+        as u64;
+
+    // Deposit into the input vault, then value the pool's enlarged share
+    let mut vault_in = side_in.clone();
+    let in_lp = vault_in.unmint_amount(
+        current_time,
+        amount_in_after_owner_fee,
+        vault_in.vault_lp_supply,
+    )?;
+    vault_in.vault.total_amount = vault_in
+        .vault
+        .total_amount
+        .checked_add(amount_in_after_owner_fee)
+        .ok_or_else(|| anyhow!("Vault amount overflowed"))?;
+    let after_in_amount = vault_in.amount_by_share(
+        current_time,
+        in_lp + vault_in.pool_lp_amount,
+        vault_in.vault_lp_supply + in_lp,
+    )?;
+    let actual_in_amount = after_in_amount
+        .checked_sub(reserve_in)
+        .ok_or_else(|| anyhow!("Deposit lost value in the vault"))?;
+    let actual_in_amount_after_fee = (actual_in_amount as u128)
+        .checked_sub(trade_fee)
+        .ok_or_else(|| anyhow!("Trading fee exceeds the deposit"))?
+        as u64;
+
+    let destination_amount = match pool.curve_type {
+        CurveType::ConstantProduct => {
+            constant_product_swap(actual_in_amount_after_fee, reserve_in, reserve_out)?
+        }
+        CurveType::Stable {
+            amp,
+            token_multiplier,
+            depeg,
+            ..
+        } => StableCurve {
+            amp,
+            token_multiplier,
+            depeg,
+        }
+        .swap(actual_in_amount_after_fee, reserve_in, reserve_out, a_to_b)?,
+    };
+
+    // Withdraw whole vault LP from the output vault
+    let out_lp =
+        side_out.unmint_amount(current_time, destination_amount, side_out.vault_lp_supply)?;
+    let amount_out = side_out.amount_by_share(current_time, out_lp, side_out.vault_lp_supply)?;
+    if amount_out >= side_out.token_vault_amount {
+        return Err(anyhow!(
+            "Output {} exceeds the vault reserve of {}",
+            amount_out,
+            side_out.token_vault_amount
+        ));
+    }
+
+    Ok(SwapQuote {
+        amount_in,
+        amount_out,
+        fee_amount: (trade_fee + owner_fee) as u64,
+    })
+}
+
+/// Constant product swap as in SPL token-swap, where the new destination balance is rounded up
+fn constant_product_swap(
+    source_amount: u64,
+    swap_source: u64,
+    swap_destination: u64,
+) -> Result<u64> {
+    let invariant = swap_source as u128 * swap_destination as u128;
+    let new_swap_source = swap_source as u128 + source_amount as u128;
+    let new_swap_destination = checked_ceil_div(invariant, new_swap_source)
+        .ok_or_else(|| anyhow!("Pool has an empty reserve"))?;
+    match (swap_destination as u128).checked_sub(new_swap_destination) {
+        Some(amount) if amount > 0 => Ok(amount as u64),
+        _ => Err(anyhow!("Swap output is zero")),
+    }
+}
+
+/// `ceil(dividend / divisor)` the way `spl_math::checked_ceil_div` rounds it, which snaps a
+/// zero quotient to one when the divisor is at most twice the dividend
+fn checked_ceil_div(dividend: u128, divisor: u128) -> Option<u128> {
+    let quotient = dividend.checked_div(divisor)?;
+    if quotient == 0 {
+        return Some(if dividend.checked_mul(2)? >= divisor {
+            1
+        } else {
+            0
+        });
+    }
+    if !dividend.is_multiple_of(divisor) {
+        Some(quotient + 1)
+    } else {
+        Some(quotient)
+    }
+}
+
+/// Stable swap invariant with the pool's decimal multipliers and, for depeg pools, token B
+/// valued at its virtual price
+struct StableCurve {
+    amp: u64,
+    token_multiplier: TokenMultiplier,
+    depeg: Depeg,
+}
+
+impl StableCurve {
+    fn has_depeg(&self) -> bool {
+        !matches!(self.depeg.depeg_type, DepegType::None)
+    }
+
+    fn upscale(&self, amount: u64, is_token_a: bool) -> Option<u128> {
+        if is_token_a {
+            return (amount as u128).checked_mul(self.token_multiplier.token_a_multiplier as u128);
+        }
+        let normalized =
+            (amount as u128).checked_mul(self.token_multiplier.token_b_multiplier as u128)?;
+        if self.has_depeg() {
+            normalized
+                .checked_mul(self.depeg.base_virtual_price as u128)?
+                .checked_div(VIRTUAL_PRICE_PRECISION)
+        } else {
+            Some(normalized)
+        }
+    }
+
+    fn downscale(&self, amount: u128, is_token_a: bool) -> Option<u128> {
+        if is_token_a {
+            return amount.checked_div(self.token_multiplier.token_a_multiplier as u128);
+        }
+        let denormalized = amount.checked_div(self.token_multiplier.token_b_multiplier as u128)?;
+        if self.has_depeg() {
+            denormalized
+                .checked_mul(VIRTUAL_PRICE_PRECISION)?
+                .checked_div(self.depeg.base_virtual_price as u128)
+        } else {
+            Some(denormalized)
+        }
+    }
+
+    fn swap(
+        &self,
+        source_amount: u64,
+        swap_source: u64,
+        swap_destination: u64,
+        a_to_b: bool,
+    ) -> Result<u64> {
+        let overflow = || anyhow!("Stable swap overflowed");
+        let to_u64 = |amount: u128| u64::try_from(amount).map_err(|_| overflow());
+
+        let source_amount = to_u64(self.upscale(source_amount, a_to_b).ok_or_else(overflow)?)?;
+        let swap_source = to_u64(self.upscale(swap_source, a_to_b).ok_or_else(overflow)?)?;
+        let swap_destination = to_u64(
+            self.upscale(swap_destination, !a_to_b)
+                .ok_or_else(overflow)?,
+        )?;
+
+        let d = compute_d(self.amp, swap_source, swap_destination).ok_or_else(overflow)?;
+        let new_swap_source = swap_source
+            .checked_add(source_amount)
+            .ok_or_else(overflow)?;
+        let new_swap_destination = compute_y(self.amp, new_swap_source, d).ok_or_else(overflow)?;
+        let amount_swapped = swap_destination
+            .checked_sub(new_swap_destination)
+            .ok_or_else(overflow)?;
+
+        let amount_out = self
+            .downscale(amount_swapped as u128, !a_to_b)
+            .ok_or_else(overflow)?;
+        match to_u64(amount_out)? {
+            0 => Err(anyhow!("Swap output is zero")),
+            amount => Ok(amount),
+        }
+    }
+}
+
+fn within_one(a: U256, b: U256) -> bool {
+    if a > b {
+        a - b <= U256::one()
+    } else {
+        b - a <= U256::one()
+    }
+}
+
+/// Stable swap invariant D by Newton's method, as in the saber stable-swap math
+fn compute_d(amp: u64, amount_a: u64, amount_b: u64) -> Option<U256> {
+    let sum_x = amount_a.checked_add(amount_b)?;
+    if sum_x == 0 {
+        return Some(U256::zero());
+    }
+    let amount_a_times_coins = U256::from(amount_a.checked_mul(N_COINS)?);
+    let amount_b_times_coins = U256::from(amount_b.checked_mul(N_COINS)?);
+    let ann = amp.checked_mul(N_COINS)?;
+    let leverage = U256::from(sum_x as u128 * ann as u128);
+
+    let mut d = U256::from(sum_x);
+    for _ in 0..MAX_ITERATIONS {
+        let mut d_prod = d;
+        d_prod = d_prod.checked_mul(d)?.checked_div(amount_a_times_coins)?;
+        d_prod = d_prod.checked_mul(d)?.checked_div(amount_b_times_coins)?;
+        let d_prev = d;
+
+        let numerator = d.checked_mul(d_prod.checked_mul(U256::from(N_COINS))? + leverage)?;
+        let denominator = d.checked_mul(U256::from(ann.checked_sub(1)?))?
+            + d_prod.checked_mul(U256::from(N_COINS + 1))?;
+        d = numerator.checked_div(denominator)?;
+
+        if within_one(d, d_prev) {
+            break;
+        }
+    }
+    Some(d)
+}
+
+/// Balance of the other token that keeps the invariant at `d` when one balance is `x`
+fn compute_y(amp: u64, x: u64, d: U256) -> Option<u64> {
+    let ann = amp.checked_mul(N_COINS)?;
+    let mut c = d
+        .checked_mul(d)?
+        .checked_div(U256::from(x.checked_mul(N_COINS)?))?;
+    c = c
+        .checked_mul(d)?
+        .checked_div(U256::from(ann.checked_mul(N_COINS)?))?;
+    let b = d.checked_div(U256::from(ann))? + U256::from(x);
+
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        let y_prev = y;
+        let numerator = y.checked_mul(y)?.checked_add(c)?;
+        let denominator = y
+            .checked_mul(U256::from(2))?
+            .checked_add(b)?
+            .checked_sub(d)?;
+        y = numerator.checked_div(denominator)?;
+        if within_one(y, y_prev) {
+            break;
+        }
+    }
+    (y <= U256::from(u64::MAX)).then(|| y.low_u64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESERVE: u64 = 1_000_000_000;
+
+    fn stable_curve(base_virtual_price: Option<u64>) -> StableCurve {
+        StableCurve {
+            amp: 100,
+            token_multiplier: TokenMultiplier {
+                token_a_multiplier: 1,
+                token_b_multiplier: 1,
+                precision_factor: 9,
+            },
+            depeg: Depeg {
+                base_virtual_price: base_virtual_price.unwrap_or(0),
+                base_cache_updated: 0,
+                depeg_type: if base_virtual_price.is_some() {
+                    DepegType::SplStake
+                } else {
+                    DepegType::None
+                },
+            },
+        }
+    }
+
+    #[test]
+    fn checked_ceil_div_snaps_small_quotients() {
+        assert_eq!(checked_ceil_div(1, 3), Some(0));
+        assert_eq!(checked_ceil_div(2, 3), Some(1));
+        assert_eq!(checked_ceil_div(7, 2), Some(4));
+        assert_eq!(checked_ceil_div(1, 0), None);
+    }
+
+    #[test]
+    fn constant_product_rounds_the_destination_up() {
+        assert_eq!(
+            constant_product_swap(10_000_000, RESERVE, RESERVE).unwrap(),
+            9_900_990
+        );
+        assert_eq!(
+            constant_product_swap(10_000_000, RESERVE, RESERVE / 10).unwrap(),
+            990_099
+        );
+        assert!(constant_product_swap(0, RESERVE, RESERVE).is_err());
+    }
+
+    #[test]
+    fn stable_curve_stays_near_par() {
+        let curve = stable_curve(None);
+        assert_eq!(
+            curve.swap(10_000_000, RESERVE, RESERVE, true).unwrap(),
+            9_999_010
+        );
+        // An imbalanced pool still pays far more than the constant product curve would
+        assert_eq!(
+            curve.swap(10_000_000, RESERVE, RESERVE / 10, true).unwrap(),
+            8_646_024
+        );
+    }
+
+    #[test]
+    fn depeg_curve_values_token_b_at_its_virtual_price() {
+        let curve = stable_curve(Some(1_100_000));
+        // 1.1 of A per B, so reserves of 1.1:1 are balanced
+        assert_eq!(
+            curve
+                .swap(11_000_000, 1_100_000_000, RESERVE, true)
+                .unwrap(),
+            9_999_010
+        );
+        assert_eq!(
+            curve
+                .swap(10_000_000, RESERVE, 1_100_000_000, false)
+                .unwrap(),
+            10_998_911
+        );
+        // Ignoring the virtual price pays out B as if it were worth A
+        assert_eq!(
+            stable_curve(None)
+                .swap(11_000_000, 1_100_000_000, RESERVE, true)
+                .unwrap(),
+            10_988_429
+        );
+    }
+}
@@ -1,14 +1,454 @@
+use crate::chain::constants::TOKEN_2022_PROGRAM_ID;
+use anyhow::Result;
 use solana_program::pubkey::Pubkey;
 
-pub fn get_dammv2_info(data: &[u8]) -> (Pubkey, Pubkey, Pubkey, Pubkey) {
-    if data.len() < 296 {
-        panic!("Invalid data length for DAMMv2 info");
+pub const DAMM_V2_POOL_SIZE: usize = 1112;
+const POOL_DISCRIMINATOR: [u8; 8] = [241, 154, 109, 4, 17, 177, 109, 188];
+
+// Pool account (after the 8 byte discriminator)
+// pool_fees.base_fee
+const CLIFF_FEE_NUMERATOR_OFFSET: usize = 8; // cliff_fee_numerator
+const FEE_SCHEDULER_MODE_OFFSET: usize = 16; // fee_scheduler_mode
+const NUMBER_OF_PERIOD_OFFSET: usize = 22; // number_of_period
+const PERIOD_FREQUENCY_OFFSET: usize = 24; // period_frequency
+const BASE_REDUCTION_FACTOR_OFFSET: usize = 32; // reduction_factor
+// pool_fees
+const PROTOCOL_FEE_PERCENT_OFFSET: usize = 48; // protocol_fee_percent
+const PARTNER_FEE_PERCENT_OFFSET: usize = 49; // partner_fee_percent
+const REFERRAL_FEE_PERCENT_OFFSET: usize = 50; // referral_fee_percent
+// pool_fees.dynamic_fee
+const DYNAMIC_FEE_OFFSET: usize = 56; // initialized
+const MAX_VOLATILITY_ACCUMULATOR_OFFSET: usize = 64; // max_volatility_accumulator
+const VARIABLE_FEE_CONTROL_OFFSET: usize = 68; // variable_fee_control
+const BIN_STEP_OFFSET: usize = 72; // bin_step
+const FILTER_PERIOD_OFFSET: usize = 74; // filter_period
+const DECAY_PERIOD_OFFSET: usize = 76; // decay_period
+const DYNAMIC_REDUCTION_FACTOR_OFFSET: usize = 78; // reduction_factor
+const LAST_UPDATE_TIMESTAMP_OFFSET: usize = 80; // last_update_timestamp
+const BIN_STEP_U128_OFFSET: usize = 88; // bin_step_u128
+const SQRT_PRICE_REFERENCE_OFFSET: usize = 104; // sqrt_price_reference
+const VOLATILITY_ACCUMULATOR_OFFSET: usize = 120; // volatility_accumulator
+const VOLATILITY_REFERENCE_OFFSET: usize = 136; // volatility_reference
+
+const TOKEN_A_MINT_OFFSET: usize = 168; // token_a_mint
+const TOKEN_B_MINT_OFFSET: usize = 200; // token_b_mint
+const TOKEN_A_VAULT_OFFSET: usize = 232; // token_a_vault
+const TOKEN_B_VAULT_OFFSET: usize = 264; // token_b_vault
+const WHITELISTED_VAULT_OFFSET: usize = 296; // whitelisted_vault
+const PARTNER_OFFSET: usize = 328; // partner
+const LIQUIDITY_OFFSET: usize = 360; // liquidity
+const PROTOCOL_A_FEE_OFFSET: usize = 392; // protocol_a_fee
+const PROTOCOL_B_FEE_OFFSET: usize = 400; // protocol_b_fee
+const PARTNER_A_FEE_OFFSET: usize = 408; // partner_a_fee
+const PARTNER_B_FEE_OFFSET: usize = 416; // partner_b_fee
+const SQRT_MIN_PRICE_OFFSET: usize = 424; // sqrt_min_price
+const SQRT_MAX_PRICE_OFFSET: usize = 440; // sqrt_max_price
+const SQRT_PRICE_OFFSET: usize = 456; // sqrt_price
+const ACTIVATION_POINT_OFFSET: usize = 472; // activation_point
+const ACTIVATION_TYPE_OFFSET: usize = 480; // activation_type
+const POOL_STATUS_OFFSET: usize = 481; // pool_status
+const TOKEN_A_FLAG_OFFSET: usize = 482; // token_a_flag
+const TOKEN_B_FLAG_OFFSET: usize = 483; // token_b_flag
+const COLLECT_FEE_MODE_OFFSET: usize = 484; // collect_fee_mode
+const POOL_TYPE_OFFSET: usize = 485; // pool_type
+const PERMANENT_LOCK_LIQUIDITY_OFFSET: usize = 552; // permanent_lock_liquidity
+
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes(data[offset..offset + 2].try_into().unwrap())
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_u128(data: &[u8], offset: usize) -> u128 {
+    u128::from_le_bytes(data[offset..offset + 16].try_into().unwrap())
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
+}
+
+/// How the base fee decays from `cliff_fee_numerator` after activation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeeSchedulerMode {
+    Linear,
+    Exponential,
+}
+
+impl TryFrom<u8> for FeeSchedulerMode {
+    type Error = anyhow::Error;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(Self::Linear),
+            1 => Ok(Self::Exponential),
+            _ => Err(anyhow::anyhow!("Unsupported DAMM v2 fee scheduler mode {}", value)),
+        }
+    }
+}
+
+/// Which tokens swap fees are taken in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollectFeeMode {
+    /// Fees are taken from the output token
+    BothToken,
+    /// Fees are always taken in token B, on input or output depending on the direction
+    OnlyB,
+}
+
+impl TryFrom<u8> for CollectFeeMode {
+    type Error = anyhow::Error;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(Self::BothToken),
+            1 => Ok(Self::OnlyB),
+            _ => Err(anyhow::anyhow!("Unknown DAMM v2 collect fee mode {}", value)),
+        }
+    }
+}
+
+/// Unit of `activation_point` and of the fee scheduler periods
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActivationType {
+    Slot,
+    Timestamp,
+}
+
+impl TryFrom<u8> for ActivationType {
+    type Error = anyhow::Error;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(Self::Slot),
+            1 => Ok(Self::Timestamp),
+            _ => Err(anyhow::anyhow!("Unknown DAMM v2 activation type {}", value)),
+        }
     }
+}
 
-    (
-        Pubkey::new_from_array(data[168..200].try_into().unwrap()),
-        Pubkey::new_from_array(data[200..232].try_into().unwrap()),
-        Pubkey::new_from_array(data[232..264].try_into().unwrap()),
-        Pubkey::new_from_array(data[264..296].try_into().unwrap()),
-    )
+#[derive(Debug, Clone, Copy)]
+pub struct BaseFee {
+    pub cliff_fee_numerator: u64,
+    pub fee_scheduler_mode: FeeSchedulerMode,
+    pub number_of_period: u16,
+    pub period_frequency: u64,
+    pub reduction_factor: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DynamicFee {
+    pub initialized: bool,
+    pub max_volatility_accumulator: u32,
+    pub variable_fee_control: u32,
+    pub bin_step: u16,
+    pub filter_period: u16,
+    pub decay_period: u16,
+    pub reduction_factor: u16,
+    pub last_update_timestamp: u64,
+    pub bin_step_u128: u128,
+    pub sqrt_price_reference: u128,
+    pub volatility_accumulator: u128,
+    pub volatility_reference: u128,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PoolFees {
+    pub base_fee: BaseFee,
+    pub protocol_fee_percent: u8,
+    pub partner_fee_percent: u8,
+    pub referral_fee_percent: u8,
+    pub dynamic_fee: DynamicFee,
+}
+
+/// Decoded Meteora DAMM v2 `Pool` account
+#[derive(Debug, Clone)]
+pub struct DammV2Pool {
+    pub pool_fees: PoolFees,
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub token_a_vault: Pubkey,
+    pub token_b_vault: Pubkey,
+    pub whitelisted_vault: Pubkey,
+    pub partner: Pubkey,
+    pub liquidity: u128,
+    pub protocol_a_fee: u64,
+    pub protocol_b_fee: u64,
+    pub partner_a_fee: u64,
+    pub partner_b_fee: u64,
+    pub sqrt_min_price: u128,
+    pub sqrt_max_price: u128,
+    pub sqrt_price: u128,
+    pub activation_point: u64,
+    pub activation_type: ActivationType,
+    pub pool_status: u8,
+    pub token_a_flag: u8,
+    pub token_b_flag: u8,
+    pub collect_fee_mode: CollectFeeMode,
+    pub pool_type: u8,
+    pub permanent_lock_liquidity: u128,
+}
+
+impl DammV2Pool {
+    pub fn load_checked(data: &[u8]) -> Result<Self> {
+        if data.len() < DAMM_V2_POOL_SIZE {
+            return Err(anyhow::anyhow!("Invalid data length for DammV2Pool"));
+        }
+        if data[..8] != POOL_DISCRIMINATOR {
+            return Err(anyhow::anyhow!("Invalid discriminator for DammV2Pool"));
+        }
+
+        let base_fee = BaseFee {
+            cliff_fee_numerator: read_u64(data, CLIFF_FEE_NUMERATOR_OFFSET),
+            fee_scheduler_mode: data[FEE_SCHEDULER_MODE_OFFSET].try_into()?,
+            number_of_period: read_u16(data, NUMBER_OF_PERIOD_OFFSET),
+            period_frequency: read_u64(data, PERIOD_FREQUENCY_OFFSET),
+            reduction_factor: read_u64(data, BASE_REDUCTION_FACTOR_OFFSET),
+        };
+
+        let dynamic_fee = DynamicFee {
+            initialized: data[DYNAMIC_FEE_OFFSET] != 0,
+            max_volatility_accumulator: read_u32(data, MAX_VOLATILITY_ACCUMULATOR_OFFSET),
+            variable_fee_control: read_u32(data, VARIABLE_FEE_CONTROL_OFFSET),
+            bin_step: read_u16(data, BIN_STEP_OFFSET),
+            filter_period: read_u16(data, FILTER_PERIOD_OFFSET),
+            decay_period: read_u16(data, DECAY_PERIOD_OFFSET),
+            reduction_factor: read_u16(data, DYNAMIC_REDUCTION_FACTOR_OFFSET),
+            last_update_timestamp: read_u64(data, LAST_UPDATE_TIMESTAMP_OFFSET),
+            bin_step_u128: read_u128(data, BIN_STEP_U128_OFFSET),
+            sqrt_price_reference: read_u128(data, SQRT_PRICE_REFERENCE_OFFSET),
+            volatility_accumulator: read_u128(data, VOLATILITY_ACCUMULATOR_OFFSET),
+            volatility_reference: read_u128(data, VOLATILITY_REFERENCE_OFFSET),
+        };
+
+        Ok(Self {
+            pool_fees: PoolFees {
+                base_fee,
+                protocol_fee_percent: data[PROTOCOL_FEE_PERCENT_OFFSET],
+                partner_fee_percent: data[PARTNER_FEE_PERCENT_OFFSET],
+                referral_fee_percent: data[REFERRAL_FEE_PERCENT_OFFSET],
+                dynamic_fee,
+            },
+            token_a_mint: read_pubkey(data, TOKEN_A_MINT_OFFSET),
+            token_b_mint: read_pubkey(data, TOKEN_B_MINT_OFFSET),
+            token_a_vault: read_pubkey(data, TOKEN_A_VAULT_OFFSET),
+            token_b_vault: read_pubkey(data, TOKEN_B_VAULT_OFFSET),
+            whitelisted_vault: read_pubkey(data, WHITELISTED_VAULT_OFFSET),
+            partner: read_pubkey(data, PARTNER_OFFSET),
+            liquidity: read_u128(data, LIQUIDITY_OFFSET),
+            protocol_a_fee: read_u64(data, PROTOCOL_A_FEE_OFFSET),
+            protocol_b_fee: read_u64(data, PROTOCOL_B_FEE_OFFSET),
+            partner_a_fee: read_u64(data, PARTNER_A_FEE_OFFSET),
+            partner_b_fee: read_u64(data, PARTNER_B_FEE_OFFSET),
+            sqrt_min_price: read_u128(data, SQRT_MIN_PRICE_OFFSET),
+            sqrt_max_price: read_u128(data, SQRT_MAX_PRICE_OFFSET),
+            sqrt_price: read_u128(data, SQRT_PRICE_OFFSET),
+            activation_point: read_u64(data, ACTIVATION_POINT_OFFSET),
+            activation_type: data[ACTIVATION_TYPE_OFFSET].try_into()?,
+            pool_status: data[POOL_STATUS_OFFSET],
+            token_a_flag: data[TOKEN_A_FLAG_OFFSET],
+            token_b_flag: data[TOKEN_B_FLAG_OFFSET],
+            collect_fee_mode: data[COLLECT_FEE_MODE_OFFSET].try_into()?,
+            pool_type: data[POOL_TYPE_OFFSET],
+            permanent_lock_liquidity: read_u128(data, PERMANENT_LOCK_LIQUIDITY_OFFSET),
+        })
+    }
+
+    /// Whether the pool accepts swaps (`pool_status` 0)
+    pub fn is_enabled(&self) -> bool {
+        self.pool_status == 0
+    }
+
+    pub fn token_a_program(&self) -> Pubkey {
+        token_program_from_flag(self.token_a_flag)
+    }
+
+    pub fn token_b_program(&self) -> Pubkey {
+        token_program_from_flag(self.token_b_flag)
+    }
+}
+
+fn token_program_from_flag(flag: u8) -> Pubkey {
+    if flag == 0 {
+        spl_token::ID
+    } else {
+        TOKEN_2022_PROGRAM_ID
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::layout::{key, Layout};
+
+    const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+    const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qA1xxoxHSgyfnkZTrNEwE7n5Xt";
+    /// Q64.64 square root of 0.15 USDC atoms per lamport, i.e. 150 USDC/SOL
+    const SQRT_PRICE: u128 = 7_144_393_258_922_745_856;
+    const MIN_SQRT_PRICE: u128 = 4_295_048_016;
+    const MAX_SQRT_PRICE: u128 = 79_226_673_521_066_979_257_578_248_091;
+
+    struct PoolFixture {
+        token_a_vault: Pubkey,
+        token_b_vault: Pubkey,
+        pool_status: u8,
+        token_b_flag: u8,
+        collect_fee_mode: u8,
+    }
+
+    impl PoolFixture {
+        fn new() -> Self {
+            Self {
+                token_a_vault: Pubkey::new_unique(),
+                token_b_vault: Pubkey::new_unique(),
+                pool_status: 0,
+                token_b_flag: 0,
+                collect_fee_mode: 1,
+            }
+        }
+    }
+
+    /// SOL/USDC `Pool`, written in the order the DAMM v2 program declares it
+    fn pool(fixture: &PoolFixture) -> Vec<u8> {
+        let mut layout = Layout::anchor(POOL_DISCRIMINATOR);
+        layout
+            // pool_fees.base_fee
+            .u64(2_500_000) // cliff_fee_numerator
+            .u8(1) // fee_scheduler_mode
+            .zeros(5)
+            .u16(120) // number_of_period
+            .u64(60) // period_frequency
+            .u64(500) // reduction_factor
+            .zeros(8)
+            .u8(20) // protocol_fee_percent
+            .u8(0) // partner_fee_percent
+            .u8(20) // referral_fee_percent
+            .zeros(5)
+            // pool_fees.dynamic_fee
+            .bool(true) // initialized
+            .zeros(7)
+            .u32(14_460_000) // max_volatility_accumulator
+            .u32(363_438) // variable_fee_control
+            .u16(1) // bin_step
+            .u16(10) // filter_period
+            .u16(120) // decay_period
+            .u16(5_000) // reduction_factor
+            .u64(1_750_000_000) // last_update_timestamp
+            .u128(1_844_674_407_370_955) // bin_step_u128
+            .u128(SQRT_PRICE) // sqrt_price_reference
+            .u128(0) // volatility_accumulator
+            .u128(0) // volatility_reference
+            .zeros(16);
+        assert_eq!(layout.len(), TOKEN_A_MINT_OFFSET);
+        layout
+            .pubkey(&key(SOL_MINT))
+            .pubkey(&key(USDC_MINT))
+            .pubkey(&fixture.token_a_vault)
+            .pubkey(&fixture.token_b_vault)
+            .pubkey(&Pubkey::default()) // whitelisted_vault
+            .pubkey(&Pubkey::default()) // partner
+            .u128(987_654_321_000_000_000_000) // liquidity
+            .zeros(16)
+            .u64(101) // protocol_a_fee
+            .u64(102) // protocol_b_fee
+            .u64(0) // partner_a_fee
+            .u64(0) // partner_b_fee
+            .u128(MIN_SQRT_PRICE)
+            .u128(MAX_SQRT_PRICE)
+            .u128(SQRT_PRICE)
+            .u64(1_749_000_000) // activation_point
+            .u8(1) // activation_type
+            .u8(fixture.pool_status)
+            .u8(0) // token_a_flag
+            .u8(fixture.token_b_flag)
+            .u8(fixture.collect_fee_mode)
+            .u8(0) // pool_type
+            .zeros(2)
+            .zeros(32) // fee_a_per_liquidity
+            .zeros(32) // fee_b_per_liquidity
+            .u128(1_000) // permanent_lock_liquidity
+            .zeros(80) // metrics
+            .pubkey(&Pubkey::new_unique()) // creator
+            .zeros(6 * 8)
+            .zeros(2 * 192); // reward_infos
+        assert_eq!(layout.len(), DAMM_V2_POOL_SIZE);
+        layout.finish()
+    }
+
+    #[test]
+    fn decodes_sol_usdc_pool() {
+        let fixture = PoolFixture::new();
+        let pool = DammV2Pool::load_checked(&pool(&fixture)).unwrap();
+
+        let base_fee = &pool.pool_fees.base_fee;
+        assert_eq!(base_fee.cliff_fee_numerator, 2_500_000);
+        assert_eq!(base_fee.fee_scheduler_mode, FeeSchedulerMode::Exponential);
+        assert_eq!(base_fee.number_of_period, 120);
+        assert_eq!(base_fee.period_frequency, 60);
+        assert_eq!(base_fee.reduction_factor, 500);
+        assert_eq!(pool.pool_fees.protocol_fee_percent, 20);
+        assert_eq!(pool.pool_fees.referral_fee_percent, 20);
+
+        let dynamic_fee = &pool.pool_fees.dynamic_fee;
+        assert!(dynamic_fee.initialized);
+        assert_eq!(dynamic_fee.max_volatility_accumulator, 14_460_000);
+        assert_eq!(dynamic_fee.variable_fee_control, 363_438);
+        assert_eq!(dynamic_fee.bin_step, 1);
+        assert_eq!(dynamic_fee.reduction_factor, 5_000);
+        assert_eq!(dynamic_fee.sqrt_price_reference, SQRT_PRICE);
+
+        assert_eq!(pool.token_a_mint, key(SOL_MINT));
+        assert_eq!(pool.token_b_mint, key(USDC_MINT));
+        assert_eq!(pool.token_a_vault, fixture.token_a_vault);
+        assert_eq!(pool.token_b_vault, fixture.token_b_vault);
+        assert_eq!(pool.liquidity, 987_654_321_000_000_000_000);
+        assert_eq!((pool.protocol_a_fee, pool.protocol_b_fee), (101, 102));
+        assert_eq!(pool.sqrt_min_price, MIN_SQRT_PRICE);
+        assert_eq!(pool.sqrt_max_price, MAX_SQRT_PRICE);
+        assert_eq!(pool.sqrt_price, SQRT_PRICE);
+        assert_eq!(pool.activation_point, 1_749_000_000);
+        assert_eq!(pool.activation_type, ActivationType::Timestamp);
+        assert_eq!(pool.collect_fee_mode, CollectFeeMode::OnlyB);
+        assert_eq!(pool.permanent_lock_liquidity, 1_000);
+        assert!(pool.is_enabled());
+        assert_eq!(pool.token_a_program(), spl_token::ID);
+        assert_eq!(pool.token_b_program(), spl_token::ID);
+    }
+
+    #[test]
+    fn token_flag_selects_token_2022() {
+        let fixture = PoolFixture {
+            token_b_flag: 1,
+            ..PoolFixture::new()
+        };
+        let pool = DammV2Pool::load_checked(&pool(&fixture)).unwrap();
+
+        assert_eq!(pool.token_a_program(), spl_token::ID);
+        assert_eq!(pool.token_b_program(), TOKEN_2022_PROGRAM_ID);
+    }
+
+    #[test]
+    fn rejects_disabled_and_malformed_pools() {
+        let fixture = PoolFixture {
+            pool_status: 1,
+            ..PoolFixture::new()
+        };
+        assert!(!DammV2Pool::load_checked(&pool(&fixture)).unwrap().is_enabled());
+
+        let fixture = PoolFixture {
+            collect_fee_mode: 2,
+            ..PoolFixture::new()
+        };
+        assert!(DammV2Pool::load_checked(&pool(&fixture)).is_err());
+
+        let mut data = pool(&PoolFixture::new());
+        data[0] ^= 1;
+        assert!(DammV2Pool::load_checked(&data).is_err());
+        assert!(DammV2Pool::load_checked(&pool(&PoolFixture::new())[..DAMM_V2_POOL_SIZE - 1]).is_err());
+    }
 }
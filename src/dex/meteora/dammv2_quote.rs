@@ -0,0 +1,284 @@
+use crate::dex::{
+    clmm::math::U256,
+    meteora::{
+        dammv2_info::{
+            ActivationType, BaseFee, CollectFeeMode, DammV2Pool, DynamicFee, FeeSchedulerMode,
+        },
+        math::{pow, BASIS_POINT_MAX, ONE, SCALE_OFFSET},
+    },
+    quote::SwapQuote,
+};
+use anyhow::{anyhow, Result};
+
+pub const FEE_DENOMINATOR: u64 = 1_000_000_000;
+pub const MAX_FEE_NUMERATOR: u64 = 500_000_000;
+
+impl BaseFee {
+    /// Scheduled base fee numerator at `current_point`. Before activation the pool charges the
+    /// fee of the last period.
+    pub fn current_fee_numerator(&self, current_point: u64, activation_point: u64) -> Result<u64> {
+        if self.period_frequency == 0 {
+            return Ok(self.cliff_fee_numerator);
+        }
+        let period = if current_point < activation_point {
+            self.number_of_period as u64
+        } else {
+            ((current_point - activation_point) / self.period_frequency)
+                .min(self.number_of_period as u64)
+        };
+
+        match self.fee_scheduler_mode {
+            FeeSchedulerMode::Linear => self
+                .cliff_fee_numerator
+                .checked_sub(period.saturating_mul(self.reduction_factor))
+                .ok_or_else(|| anyhow!("Base fee reduced below zero")),
+            FeeSchedulerMode::Exponential => {
+                if period == 0 {
+                    return Ok(self.cliff_fee_numerator);
+                }
+                let bps = ((self.reduction_factor as u128) << SCALE_OFFSET) / BASIS_POINT_MAX;
+                let base = ONE
+                    .checked_sub(bps)
+                    .ok_or_else(|| anyhow!("Invalid base fee reduction factor"))?;
+                let factor = pow(base, period as i32)
+                    .ok_or_else(|| anyhow!("Base fee schedule overflowed"))?;
+                let fee =
+                    (U256::from(factor) * U256::from(self.cliff_fee_numerator)) >> SCALE_OFFSET;
+                Ok(fee.low_u64())
+            }
+        }
+    }
+}
+
+impl DynamicFee {
+    /// Volatility-driven fee numerator, rounded up
+    pub fn variable_fee_numerator(&self) -> Result<u128> {
+        if !self.initialized {
+            return Ok(0);
+        }
+        self.volatility_accumulator
+            .checked_mul(self.bin_step as u128)
+            .and_then(|vfa_bin| vfa_bin.checked_pow(2))
+            .and_then(|square| square.checked_mul(self.variable_fee_control as u128))
+            .map(|v_fee| v_fee.div_ceil(100_000_000_000))
+            .ok_or_else(|| anyhow!("Dynamic fee overflowed"))
+    }
+}
+
+impl DammV2Pool {
+    /// Base plus dynamic fee numerator over `FEE_DENOMINATOR`, capped at `MAX_FEE_NUMERATOR`
+    pub fn total_fee_numerator(&self, current_point: u64) -> Result<u64> {
+        let base_fee = self
+            .pool_fees
+            .base_fee
+            .current_fee_numerator(current_point, self.activation_point)?;
+        let total = self.pool_fees.dynamic_fee.variable_fee_numerator()? + base_fee as u128;
+        Ok(total.min(MAX_FEE_NUMERATOR as u128) as u64)
+    }
+
+    /// Whether fees are taken from the input rather than the output of a swap
+    pub fn fees_on_input(&self, a_to_b: bool) -> bool {
+        self.collect_fee_mode == CollectFeeMode::OnlyB && !a_to_b
+    }
+
+    /// Slot or timestamp, matching the unit of `activation_point`
+    pub fn current_point(&self, current_slot: u64, current_timestamp: u64) -> u64 {
+        match self.activation_type {
+            ActivationType::Slot => current_slot,
+            ActivationType::Timestamp => current_timestamp,
+        }
+    }
+}
+
+/// Trading fee on `amount`, rounded up
+fn trading_fee(amount: u64, fee_numerator: u64) -> u64 {
+    (amount as u128 * fee_numerator as u128).div_ceil(FEE_DENOMINATOR as u128) as u64
+}
+
+fn to_u64(value: U256) -> Result<u64> {
+    if value > U256::from(u64::MAX) {
+        return Err(anyhow!("Swap amount exceeds u64"));
+    }
+    Ok(value.low_u64())
+}
+
+/// √P' = √P * L / (L + Δa * √P), rounded up
+fn next_sqrt_price_from_amount_a(sqrt_price: u128, liquidity: u128, amount: u64) -> Result<u128> {
+    if amount == 0 {
+        return Ok(sqrt_price);
+    }
+    let numerator = U256::from(liquidity) * U256::from(sqrt_price);
+    let denominator = U256::from(liquidity) + U256::from(amount) * U256::from(sqrt_price);
+    let (quotient, remainder) = numerator.div_mod(denominator);
+    let next = if remainder.is_zero() {
+        quotient
+    } else {
+        quotient + 1
+    };
+    if next > U256::from(u128::MAX) {
+        return Err(anyhow!("Sqrt price overflowed"));
+    }
+    Ok(next.low_u128())
+}
+
+/// √P' = √P + Δb / L, rounded down
+fn next_sqrt_price_from_amount_b(sqrt_price: u128, liquidity: u128, amount: u64) -> Result<u128> {
+    let next =
+        U256::from(sqrt_price) + (U256::from(amount) << (2 * SCALE_OFFSET)) / U256::from(liquidity);
+    if next > U256::from(u128::MAX) {
+        return Err(anyhow!("Sqrt price overflowed"));
+    }
+    Ok(next.low_u128())
+}
+
+/// Token A between two sqrt prices, rounded down. Liquidity carries its own Q64 scale.
+fn delta_amount_a(lower: u128, upper: u128, liquidity: u128) -> Result<u64> {
+    let denominator = U256::from(lower) * U256::from(upper);
+    to_u64(U256::from(liquidity) * U256::from(upper - lower) / denominator)
+}
+
+/// Token B between two sqrt prices, rounded down
+fn delta_amount_b(lower: u128, upper: u128, liquidity: u128) -> Result<u64> {
+    to_u64((U256::from(liquidity) * U256::from(upper - lower)) >> (2 * SCALE_OFFSET))
+}
+
+/// Quote a DAMM v2 swap of `amount_in`. `current_point` is the slot or timestamp the pool
+/// activates on (see `DammV2Pool::current_point`) and drives the base fee schedule; the
+/// dynamic fee uses the volatility the pool last recorded, as the program does.
+///
+/// `fee_amount` is in the input token when `DammV2Pool::fees_on_input` holds for the
+/// direction and in the output token otherwise.
+pub fn quote_exact_in(
+    pool: &DammV2Pool,
+    amount_in: u64,
+    a_to_b: bool,
+    current_point: u64,
+) -> Result<SwapQuote> {
+    if !pool.is_enabled() {
+        return Err(anyhow!("Pool is disabled"));
+    }
+    if current_point < pool.activation_point {
+        return Err(anyhow!("Pool activates at {}", pool.activation_point));
+    }
+    if pool.liquidity == 0 || pool.sqrt_price == 0 {
+        return Err(anyhow!("Pool has no liquidity"));
+    }
+
+    let fee_numerator = pool.total_fee_numerator(current_point)?;
+    let fees_on_input = pool.fees_on_input(a_to_b);
+
+    let (amount_after_fee, input_fee) = if fees_on_input {
+        let fee = trading_fee(amount_in, fee_numerator);
+        (amount_in - fee, fee)
+    } else {
+        (amount_in, 0)
+    };
+
+    let output_amount = if a_to_b {
+        let next_sqrt_price =
+            next_sqrt_price_from_amount_a(pool.sqrt_price, pool.liquidity, amount_after_fee)?;
+        if next_sqrt_price < pool.sqrt_min_price {
+            return Err(anyhow!("Swap moves the price below the pool range"));
+        }
+        delta_amount_b(next_sqrt_price, pool.sqrt_price, pool.liquidity)?
+    } else {
+        let next_sqrt_price =
+            next_sqrt_price_from_amount_b(pool.sqrt_price, pool.liquidity, amount_after_fee)?;
+        if next_sqrt_price > pool.sqrt_max_price {
+            return Err(anyhow!("Swap moves the price above the pool range"));
+        }
+        delta_amount_a(pool.sqrt_price, next_sqrt_price, pool.liquidity)?
+    };
+
+    let (amount_out, fee_amount) = if fees_on_input {
+        (output_amount, input_fee)
+    } else {
+        let fee = trading_fee(output_amount, fee_numerator);
+        (output_amount - fee, fee)
+    };
+
+    Ok(SwapQuote {
+        amount_in,
+        amount_out,
+        fee_amount,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLIFF_FEE_NUMERATOR: u64 = 500_000_000;
+    const ACTIVATION_POINT: u64 = 1_000;
+
+    fn base_fee(fee_scheduler_mode: FeeSchedulerMode, reduction_factor: u64) -> BaseFee {
+        BaseFee {
+            cliff_fee_numerator: CLIFF_FEE_NUMERATOR,
+            fee_scheduler_mode,
+            number_of_period: 10,
+            period_frequency: 60,
+            reduction_factor,
+        }
+    }
+
+    #[test]
+    fn linear_schedule_steps_down_each_period() {
+        let fee = base_fee(FeeSchedulerMode::Linear, 10_000_000);
+        let at = |point| fee.current_fee_numerator(point, ACTIVATION_POINT).unwrap();
+        assert_eq!(at(ACTIVATION_POINT), 500_000_000);
+        assert_eq!(at(ACTIVATION_POINT + 59), 500_000_000);
+        assert_eq!(at(ACTIVATION_POINT + 60), 490_000_000);
+        assert_eq!(at(ACTIVATION_POINT + 300), 450_000_000);
+        // The schedule stops after the last period, and applies it before activation
+        assert_eq!(at(ACTIVATION_POINT + 6_000), 400_000_000);
+        assert_eq!(at(0), 400_000_000);
+    }
+
+    #[test]
+    fn exponential_schedule_compounds_the_reduction() {
+        // 50 bps less each period
+        let fee = base_fee(FeeSchedulerMode::Exponential, 50);
+        let at = |point| fee.current_fee_numerator(point, ACTIVATION_POINT).unwrap();
+        assert_eq!(at(ACTIVATION_POINT), 500_000_000);
+        assert_eq!(at(ACTIVATION_POINT + 60), 497_500_000);
+        assert_eq!(at(ACTIVATION_POINT + 120), 495_012_499);
+        assert_eq!(at(ACTIVATION_POINT + 6_000), 475_555_065);
+    }
+
+    #[test]
+    fn schedule_without_periods_charges_the_cliff_fee() {
+        let mut fee = base_fee(FeeSchedulerMode::Linear, 10_000_000);
+        fee.period_frequency = 0;
+        assert_eq!(
+            fee.current_fee_numerator(ACTIVATION_POINT + 6_000, ACTIVATION_POINT)
+                .unwrap(),
+            CLIFF_FEE_NUMERATOR
+        );
+        // A reduction larger than the cliff fee is rejected rather than wrapping
+        let fee = base_fee(FeeSchedulerMode::Linear, 60_000_000);
+        assert!(fee
+            .current_fee_numerator(ACTIVATION_POINT + 6_000, ACTIVATION_POINT)
+            .is_err());
+    }
+
+    #[test]
+    fn dynamic_fee_grows_with_volatility() {
+        let mut dynamic_fee = DynamicFee {
+            initialized: true,
+            max_volatility_accumulator: 350_000,
+            variable_fee_control: 40_000,
+            bin_step: 10,
+            filter_period: 30,
+            decay_period: 600,
+            reduction_factor: 5_000,
+            last_update_timestamp: 0,
+            bin_step_u128: 0,
+            sqrt_price_reference: 0,
+            volatility_accumulator: 10_000,
+            volatility_reference: 0,
+        };
+        assert_eq!(dynamic_fee.variable_fee_numerator().unwrap(), 4_000);
+        dynamic_fee.initialized = false;
+        assert_eq!(dynamic_fee.variable_fee_numerator().unwrap(), 0);
+    }
+}
@@ -5,6 +5,19 @@ use anyhow::Result;
 use solana_sdk::pubkey::Pubkey;
 use std::mem::size_of;
 
+pub const MAX_BIN_PER_ARRAY: usize = 70;
+/// Bin arrays tracked on each side of zero by `LbPair::bin_array_bitmap`
+pub const BIN_ARRAY_BITMAP_SIZE: i32 = 512;
+
+const BIN_ARRAY_DISCRIMINATOR: [u8; 8] = [92, 142, 92, 220, 5, 148, 70, 181];
+const BIN_SIZE: usize = 144;
+// BinArray account (after the 8 byte discriminator)
+const BIN_ARRAY_INDEX_OFFSET: usize = 8; // index
+const BIN_ARRAY_VERSION_OFFSET: usize = 16; // version
+const BIN_ARRAY_LB_PAIR_OFFSET: usize = 24; // lb_pair
+const BIN_ARRAY_BINS_OFFSET: usize = 56; // bins
+pub const BIN_ARRAY_SIZE: usize = BIN_ARRAY_BINS_OFFSET + MAX_BIN_PER_ARRAY * BIN_SIZE;
+
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct ProtocolFee {
@@ -37,7 +50,8 @@ pub struct StaticParameters {
     pub min_bin_id: i32,
     pub max_bin_id: i32,
     pub protocol_share: u16,
-    pub _padding: [u8; 6],
+    pub base_fee_power_factor: u8,
+    pub _padding: [u8; 5],
 }
 
 #[repr(C)]
@@ -145,41 +159,103 @@ impl DlmmInfo {
         (token_vault, sol_vault)
     }
 
+    /// Bin arrays passed to a swap: the one holding the active bin plus the nearest
+    /// initialized array on each side, falling back to the adjacent array when a side has none
     pub fn calculate_bin_arrays(&self, pair_pubkey: &Pubkey) -> Result<Vec<Pubkey>> {
-        let bin_array_index = self.bin_id_to_bin_array_index(self.active_id)?;
+        let active_index = self.bin_id_to_bin_array_index(self.active_id)?;
+        let indices = self.bin_array_indices_around_active(1)?;
 
-        let mut bin_arrays = Vec::new();
-        let offsets = [-1, 0, 1];
+        let below = indices
+            .first()
+            .copied()
+            .filter(|&index| index < active_index)
+            .unwrap_or(active_index - 1);
+        let above = indices
+            .last()
+            .copied()
+            .filter(|&index| index > active_index)
+            .unwrap_or(active_index + 1);
 
-        for offset in offsets {
-            let array_idx = bin_array_index + offset;
-            let array_pda = self.derive_bin_array_pda(pair_pubkey, array_idx as i64)?;
-            bin_arrays.push(array_pda);
-        }
+        [below, active_index, above]
+            .into_iter()
+            .map(|index| self.derive_bin_array_pda(pair_pubkey, index as i64))
+            .collect()
+    }
 
-        Ok(bin_arrays)
+    /// Whether the pair's bitmap marks the bin array at `index` as initialized. Arrays beyond
+    /// the range of the bitmap live in the bitmap extension and are reported as uninitialized.
+    pub fn is_bin_array_initialized(&self, index: i32) -> bool {
+        self.lb_pair.is_bin_array_initialized(index)
     }
 
-    fn div_rem(&self, bin_id: i32, other: i32) -> (i32, i32) {
-        (bin_id / other, bin_id % other)
+    /// Indices of the bin array holding `active_id` and up to `arrays_per_side` initialized
+    /// arrays below and above it, skipping empty arrays, in ascending order
+    pub fn bin_array_indices_around_active(&self, arrays_per_side: usize) -> Result<Vec<i32>> {
+        let active_index = self.bin_id_to_bin_array_index(self.active_id)?;
+
+        let below = (-BIN_ARRAY_BITMAP_SIZE..active_index)
+            .rev()
+            .filter(|&index| self.is_bin_array_initialized(index))
+            .take(arrays_per_side);
+        let above = (active_index + 1..BIN_ARRAY_BITMAP_SIZE)
+            .filter(|&index| self.is_bin_array_initialized(index))
+            .take(arrays_per_side);
+
+        let mut indices: Vec<i32> = below.collect();
+        indices.reverse();
+        indices.push(active_index);
+        indices.extend(above);
+        Ok(indices)
     }
 
-    pub fn bin_id_to_bin_array_index(&self, bin_id: i32) -> Result<i32> {
-        let (idx, rem) = self.div_rem(bin_id, 70);
+    pub fn bin_array_pubkey(&self, pair_pubkey: &Pubkey, index: i32) -> Result<Pubkey> {
+        self.derive_bin_array_pda(pair_pubkey, index as i64)
+    }
 
-        if bin_id.is_negative() && rem != 0 {
-            Ok(idx - 1)
-        } else {
-            Ok(idx)
+    /// Liquidity of every non-empty bin within `bins_per_side` of `active_id`, ordered by bin
+    /// id. Bins whose array is missing from `bin_arrays` or empty in the bitmap are skipped.
+    pub fn bin_liquidity_around_active(
+        &self,
+        bin_arrays: &[BinArray],
+        bins_per_side: i32,
+    ) -> Result<Vec<BinLiquidity>> {
+        let lower = self.active_id.saturating_sub(bins_per_side);
+        let upper = self.active_id.saturating_add(bins_per_side);
+
+        let mut arrays: Vec<&BinArray> = bin_arrays
+            .iter()
+            .filter(|array| self.is_bin_array_initialized(array.index as i32))
+            .collect();
+        arrays.sort_by_key(|array| array.index);
+
+        let mut liquidity = Vec::new();
+        for array in arrays {
+            let (array_lower, array_upper) = array.bin_id_range();
+            for bin_id in array_lower.max(lower)..=array_upper.min(upper) {
+                let Some(bin) = array.get_bin(bin_id) else {
+                    continue;
+                };
+                if bin.amount_x == 0 && bin.amount_y == 0 {
+                    continue;
+                }
+                liquidity.push(BinLiquidity {
+                    bin_id,
+                    amount_x: bin.amount_x,
+                    amount_y: bin.amount_y,
+                    price: bin.price,
+                });
+            }
         }
-    }
 
-    fn derive_bin_array_pda(&self, lb_pair: &Pubkey, index: i64) -> Result<Pubkey> {
-        let seeds = [BIN_ARRAY, lb_pair.as_ref(), &index.to_le_bytes()[0..8]];
+        Ok(liquidity)
+    }
 
-        let (pda, _) = Pubkey::find_program_address(&seeds, &dlmm_program_id());
+    pub fn bin_id_to_bin_array_index(&self, bin_id: i32) -> Result<i32> {
+        Ok(bin_id_to_bin_array_index(bin_id))
+    }
 
-        Ok(pda)
+    fn derive_bin_array_pda(&self, lb_pair: &Pubkey, index: i64) -> Result<Pubkey> {
+        Ok(bin_array_address(lb_pair, index))
     }
 }
 
@@ -193,4 +269,129 @@ impl LbPair {
 
         Ok(lb_pair)
     }
+
+    /// Whether the bitmap marks the bin array at `index` as initialized. Arrays beyond the
+    /// range of the bitmap live in the bitmap extension and are reported as uninitialized.
+    pub fn is_bin_array_initialized(&self, index: i32) -> bool {
+        if !(-BIN_ARRAY_BITMAP_SIZE..BIN_ARRAY_BITMAP_SIZE).contains(&index) {
+            return false;
+        }
+        let bit = (index + BIN_ARRAY_BITMAP_SIZE) as usize;
+        self.bin_array_bitmap[bit / 64] & (1u64 << (bit % 64)) != 0
+    }
+}
+
+/// Address of the bin array at `index` of `lb_pair`
+pub fn bin_array_address(lb_pair: &Pubkey, index: i64) -> Pubkey {
+    let seeds = [BIN_ARRAY, lb_pair.as_ref(), &index.to_le_bytes()[0..8]];
+    Pubkey::find_program_address(&seeds, &dlmm_program_id()).0
+}
+
+/// Index of the bin array holding `bin_id`
+pub fn bin_id_to_bin_array_index(bin_id: i32) -> i32 {
+    let (idx, rem) = (bin_id / MAX_BIN_PER_ARRAY as i32, bin_id % MAX_BIN_PER_ARRAY as i32);
+    if bin_id.is_negative() && rem != 0 {
+        idx - 1
+    } else {
+        idx
+    }
+}
+
+/// A single price bin of a DLMM pair
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Bin {
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub price: u128, // Q64.64, token y per token x
+    pub liquidity_supply: u128,
+    pub reward_per_token_stored: [u128; 2],
+    pub fee_amount_x_per_token_stored: u128,
+    pub fee_amount_y_per_token_stored: u128,
+    pub amount_x_in: u128,
+    pub amount_y_in: u128,
+}
+
+impl Bin {
+    fn from_bytes(data: &[u8]) -> Self {
+        let read_u64 =
+            |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+        let read_u128 =
+            |offset: usize| u128::from_le_bytes(data[offset..offset + 16].try_into().unwrap());
+
+        Self {
+            amount_x: read_u64(0),
+            amount_y: read_u64(8),
+            price: read_u128(16),
+            liquidity_supply: read_u128(32),
+            reward_per_token_stored: [read_u128(48), read_u128(64)],
+            fee_amount_x_per_token_stored: read_u128(80),
+            fee_amount_y_per_token_stored: read_u128(96),
+            amount_x_in: read_u128(112),
+            amount_y_in: read_u128(128),
+        }
+    }
+}
+
+/// Decoded DLMM `BinArray` account holding 70 consecutive bins
+#[derive(Debug, Clone)]
+pub struct BinArray {
+    pub index: i64,
+    pub version: u8,
+    pub lb_pair: Pubkey,
+    pub bins: [Bin; MAX_BIN_PER_ARRAY],
+}
+
+impl BinArray {
+    pub fn load_checked(data: &[u8]) -> Result<Self> {
+        if data.len() < BIN_ARRAY_SIZE {
+            return Err(anyhow::anyhow!("Invalid data length for BinArray"));
+        }
+        if data[..8] != BIN_ARRAY_DISCRIMINATOR {
+            return Err(anyhow::anyhow!("Invalid discriminator for BinArray"));
+        }
+
+        let mut bins = [Bin::default(); MAX_BIN_PER_ARRAY];
+        for (i, bin) in bins.iter_mut().enumerate() {
+            let offset = BIN_ARRAY_BINS_OFFSET + i * BIN_SIZE;
+            *bin = Bin::from_bytes(&data[offset..offset + BIN_SIZE]);
+        }
+
+        Ok(Self {
+            index: i64::from_le_bytes(
+                data[BIN_ARRAY_INDEX_OFFSET..BIN_ARRAY_INDEX_OFFSET + 8]
+                    .try_into()
+                    .unwrap(),
+            ),
+            version: data[BIN_ARRAY_VERSION_OFFSET],
+            lb_pair: Pubkey::new_from_array(
+                data[BIN_ARRAY_LB_PAIR_OFFSET..BIN_ARRAY_LB_PAIR_OFFSET + 32]
+                    .try_into()
+                    .unwrap(),
+            ),
+            bins,
+        })
+    }
+
+    /// Lowest and highest bin id held by this array
+    pub fn bin_id_range(&self) -> (i32, i32) {
+        let lower = self.index as i32 * MAX_BIN_PER_ARRAY as i32;
+        (lower, lower + MAX_BIN_PER_ARRAY as i32 - 1)
+    }
+
+    pub fn get_bin(&self, bin_id: i32) -> Option<&Bin> {
+        let (lower, upper) = self.bin_id_range();
+        if bin_id < lower || bin_id > upper {
+            return None;
+        }
+        self.bins.get((bin_id - lower) as usize)
+    }
+}
+
+/// Reserves held in one bin, as returned by `DlmmInfo::bin_liquidity_around_active`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BinLiquidity {
+    pub bin_id: i32,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub price: u128,
 }
@@ -0,0 +1,468 @@
+use crate::dex::{
+    clmm::math::U256,
+    meteora::dlmm_info::{bin_id_to_bin_array_index, Bin, BinArray, LbPair, BIN_ARRAY_BITMAP_SIZE},
+    meteora::math::{pow, BASIS_POINT_MAX, ONE, SCALE_OFFSET},
+    quote::{to_u64, SwapQuote},
+};
+use anyhow::{anyhow, Result};
+
+pub const FEE_PRECISION: u128 = 1_000_000_000;
+pub const MAX_FEE_RATE: u128 = 100_000_000;
+pub const MIN_BIN_ID: i32 = -443636;
+pub const MAX_BIN_ID: i32 = 443636;
+
+/// Result of walking a DLMM pair bin by bin
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DlmmQuote {
+    /// `amount_in` includes the fee, which is charged in the input token
+    pub quote: SwapQuote,
+    /// Part of `quote.fee_amount` that goes to the protocol
+    pub protocol_fee: u64,
+    /// Active bin after the swap
+    pub active_id: i32,
+    pub volatility_accumulator: u32,
+    /// Indices of the bin arrays the swap reads, in the order it reads them
+    pub bin_arrays: Vec<i32>,
+}
+
+/// Q64.64 price of token X in token Y at `bin_id`: `(1 + bin_step / 10000)^bin_id`
+pub fn get_price_from_id(bin_id: i32, bin_step: u16) -> Result<u128> {
+    let bps = ((bin_step as u128) << SCALE_OFFSET) / BASIS_POINT_MAX;
+    pow(ONE + bps, bin_id).ok_or_else(|| anyhow!("Price of bin {} overflowed", bin_id))
+}
+
+fn mul_shr(x: u128, y: u128, round_up: bool) -> Result<u64> {
+    let product = U256::from(x) * U256::from(y);
+    let mut result = product >> SCALE_OFFSET;
+    if round_up && !(product & U256::from(u64::MAX)).is_zero() {
+        result = result + 1;
+    }
+    if result > U256::from(u64::MAX) {
+        return Err(anyhow!("Bin amount does not fit in u64"));
+    }
+    Ok(result.low_u64())
+}
+
+fn shl_div(x: u128, y: u128, round_up: bool) -> Result<u64> {
+    if y == 0 {
+        return Err(anyhow!("Bin price is zero"));
+    }
+    let (quotient, remainder) = (U256::from(x) << SCALE_OFFSET).div_mod(U256::from(y));
+    let result = if round_up && !remainder.is_zero() {
+        quotient + 1
+    } else {
+        quotient
+    };
+    if result > U256::from(u64::MAX) {
+        return Err(anyhow!("Bin amount does not fit in u64"));
+    }
+    Ok(result.low_u64())
+}
+
+impl LbPair {
+    /// Base fee rate in units of `FEE_PRECISION`
+    pub fn base_fee_rate(&self) -> u128 {
+        self.parameters.base_factor as u128
+            * self.bin_step as u128
+            * 10
+            * 10u128.pow(self.parameters.base_fee_power_factor as u32)
+    }
+
+    /// Volatility-driven fee rate in units of `FEE_PRECISION`, rounded up
+    pub fn variable_fee_rate(&self, volatility_accumulator: u32) -> u128 {
+        if self.parameters.variable_fee_control == 0 {
+            return 0;
+        }
+        let square_vfa_bin = (volatility_accumulator as u128 * self.bin_step as u128).pow(2);
+        let v_fee = self.parameters.variable_fee_control as u128 * square_vfa_bin;
+        v_fee.div_ceil(100_000_000_000)
+    }
+
+    pub fn total_fee_rate(&self) -> u128 {
+        (self.base_fee_rate() + self.variable_fee_rate(self.v_parameters.volatility_accumulator))
+            .min(MAX_FEE_RATE)
+    }
+
+    /// Fee to add on top of `amount`, rounded up
+    fn compute_fee(&self, amount: u64) -> Result<u64> {
+        let fee_rate = self.total_fee_rate();
+        let denominator = FEE_PRECISION - fee_rate;
+        to_u64((amount as u128 * fee_rate).div_ceil(denominator))
+    }
+
+    /// Fee contained in `amount_with_fees`, rounded up
+    fn compute_fee_from_amount(&self, amount_with_fees: u64) -> Result<u64> {
+        to_u64((amount_with_fees as u128 * self.total_fee_rate()).div_ceil(FEE_PRECISION))
+    }
+
+    fn compute_protocol_fee(&self, fee_amount: u64) -> u64 {
+        (fee_amount as u128 * self.parameters.protocol_share as u128 / BASIS_POINT_MAX) as u64
+    }
+
+    /// Decay the volatility reference once the filter period has passed since the last swap
+    fn update_references(&mut self, current_timestamp: i64) {
+        let elapsed = current_timestamp - self.v_parameters.last_update_timestamp;
+        if elapsed >= self.parameters.filter_period as i64 {
+            self.v_parameters.index_reference = self.active_id;
+            self.v_parameters.volatility_reference =
+                if elapsed < self.parameters.decay_period as i64 {
+                    (self.v_parameters.volatility_accumulator as u64
+                        * self.parameters.reduction_factor as u64
+                        / BASIS_POINT_MAX as u64) as u32
+                } else {
+                    0
+                };
+        }
+    }
+
+    fn update_volatility_accumulator(&mut self) {
+        let delta_id =
+            (self.v_parameters.index_reference as i64 - self.active_id as i64).unsigned_abs();
+        let volatility_accumulator =
+            self.v_parameters.volatility_reference as u64 + delta_id * BASIS_POINT_MAX as u64;
+        self.v_parameters.volatility_accumulator =
+            volatility_accumulator.min(self.parameters.max_volatility_accumulator as u64) as u32;
+    }
+
+    fn advance_active_bin(&mut self, swap_for_y: bool) -> Result<()> {
+        let next = if swap_for_y {
+            self.active_id - 1
+        } else {
+            self.active_id + 1
+        };
+        if !(MIN_BIN_ID..=MAX_BIN_ID).contains(&next) {
+            return Err(anyhow!("Pair is out of liquidity"));
+        }
+        self.active_id = next;
+        Ok(())
+    }
+}
+
+/// Bin price, computed from the bin step when the bin has not stored it yet
+fn bin_price(bin: &Bin, bin_id: i32, bin_step: u16) -> Result<u128> {
+    if bin.price == 0 {
+        get_price_from_id(bin_id, bin_step)
+    } else {
+        Ok(bin.price)
+    }
+}
+
+fn bin_max_amount_out(bin: &Bin, swap_for_y: bool) -> u64 {
+    if swap_for_y {
+        bin.amount_y
+    } else {
+        bin.amount_x
+    }
+}
+
+/// Input, before fees, that buys everything `amount_out` asks for at `price`, rounded up
+fn bin_amount_in(amount_out: u64, price: u128, swap_for_y: bool) -> Result<u64> {
+    if swap_for_y {
+        shl_div(amount_out as u128, price, true)
+    } else {
+        mul_shr(amount_out as u128, price, true)
+    }
+}
+
+fn bin_amount_out(amount_in: u64, price: u128, swap_for_y: bool) -> Result<u64> {
+    if swap_for_y {
+        mul_shr(price, amount_in as u128, false)
+    } else {
+        shl_div(amount_in as u128, price, false)
+    }
+}
+
+/// Bin array the swap continues in: the one holding the active bin if it is initialized,
+/// otherwise the next initialized array in the swap direction
+fn next_bin_array<'a>(
+    pair: &LbPair,
+    bin_arrays: &'a [BinArray],
+    swap_for_y: bool,
+) -> Result<&'a BinArray> {
+    let active_index = bin_id_to_bin_array_index(pair.active_id);
+    let mut index = active_index;
+    while !pair.is_bin_array_initialized(index) {
+        index += if swap_for_y { -1 } else { 1 };
+        if !(-BIN_ARRAY_BITMAP_SIZE..BIN_ARRAY_BITMAP_SIZE).contains(&index) {
+            return Err(anyhow!("Pair is out of liquidity"));
+        }
+    }
+    bin_arrays
+        .iter()
+        .find(|array| array.index == index as i64)
+        .ok_or_else(|| anyhow!("Swap needs bin array {} which is not loaded", index))
+}
+
+/// Walks bins from the active bin, moving the active id as the program does
+struct BinWalk<'a> {
+    pair: LbPair,
+    bin_arrays: &'a [BinArray],
+    swap_for_y: bool,
+    touched: Vec<i32>,
+}
+
+impl<'a> BinWalk<'a> {
+    fn new(
+        lb_pair: &LbPair,
+        bin_arrays: &'a [BinArray],
+        swap_for_y: bool,
+        current_timestamp: i64,
+    ) -> Result<Self> {
+        if lb_pair.status != 0 {
+            return Err(anyhow!("Pair is disabled"));
+        }
+        let mut pair = *lb_pair;
+        pair.update_references(current_timestamp);
+        Ok(Self {
+            pair,
+            bin_arrays,
+            swap_for_y,
+            touched: Vec::new(),
+        })
+    }
+
+    /// Enter the bin array the swap continues in, jumping the active bin over empty arrays
+    fn enter_bin_array(&mut self) -> Result<&'a BinArray> {
+        let array = next_bin_array(&self.pair, self.bin_arrays, self.swap_for_y)?;
+        let (lower, upper) = array.bin_id_range();
+        if bin_id_to_bin_array_index(self.pair.active_id) as i64 != array.index {
+            self.pair.active_id = if self.swap_for_y { upper } else { lower };
+        }
+        self.touched.push(array.index as i32);
+        Ok(array)
+    }
+
+    fn finish(self, quote: SwapQuote, protocol_fee: u64) -> DlmmQuote {
+        DlmmQuote {
+            quote,
+            protocol_fee,
+            active_id: self.pair.active_id,
+            volatility_accumulator: self.pair.v_parameters.volatility_accumulator,
+            bin_arrays: self.touched,
+        }
+    }
+}
+
+/// Quote a DLMM swap of exactly `amount_in`, including the fee. `swap_for_y` sells token X.
+pub fn quote_exact_in(
+    lb_pair: &LbPair,
+    bin_arrays: &[BinArray],
+    amount_in: u64,
+    swap_for_y: bool,
+    current_timestamp: i64,
+) -> Result<DlmmQuote> {
+    let mut walk = BinWalk::new(lb_pair, bin_arrays, swap_for_y, current_timestamp)?;
+    let mut amount_left = amount_in;
+    let mut amount_out: u64 = 0;
+    let mut fee_amount: u64 = 0;
+    let mut protocol_fee: u64 = 0;
+    let overflow = || anyhow!("Swap output overflowed");
+
+    while amount_left > 0 {
+        let array = walk.enter_bin_array()?;
+        while amount_left > 0 {
+            let Some(bin) = array.get_bin(walk.pair.active_id) else {
+                break;
+            };
+            walk.pair.update_volatility_accumulator();
+            let price = bin_price(bin, walk.pair.active_id, walk.pair.bin_step)?;
+
+            let max_amount_out = bin_max_amount_out(bin, swap_for_y);
+            if max_amount_out > 0 {
+                let max_amount_in = bin_amount_in(max_amount_out, price, swap_for_y)?;
+                let max_fee = walk.pair.compute_fee(max_amount_in)?;
+                let max_amount_in = max_amount_in
+                    .checked_add(max_fee)
+                    .ok_or_else(|| anyhow!("Bin input overflowed"))?;
+
+                let (bin_in, bin_out, bin_fee) = if amount_left >= max_amount_in {
+                    (max_amount_in, max_amount_out, max_fee)
+                } else {
+                    let fee = walk.pair.compute_fee_from_amount(amount_left)?;
+                    let out = bin_amount_out(amount_left - fee, price, swap_for_y)?;
+                    (amount_left, out.min(max_amount_out), fee)
+                };
+                amount_left -= bin_in;
+                amount_out = amount_out.checked_add(bin_out).ok_or_else(overflow)?;
+                fee_amount = fee_amount.checked_add(bin_fee).ok_or_else(overflow)?;
+                protocol_fee = protocol_fee
+                    .checked_add(walk.pair.compute_protocol_fee(bin_fee))
+                    .ok_or_else(overflow)?;
+            }
+
+            if amount_left > 0 {
+                walk.pair.advance_active_bin(swap_for_y)?;
+            }
+        }
+    }
+
+    let quote = SwapQuote {
+        amount_in,
+        amount_out,
+        fee_amount,
+    };
+    Ok(walk.finish(quote, protocol_fee))
+}
+
+/// Quote the input, fee included, a DLMM swap needs to deliver exactly `amount_out`
+pub fn quote_exact_out(
+    lb_pair: &LbPair,
+    bin_arrays: &[BinArray],
+    amount_out: u64,
+    swap_for_y: bool,
+    current_timestamp: i64,
+) -> Result<DlmmQuote> {
+    let mut walk = BinWalk::new(lb_pair, bin_arrays, swap_for_y, current_timestamp)?;
+    let mut amount_left = amount_out;
+    let mut amount_in: u64 = 0;
+    let mut fee_amount: u64 = 0;
+    let mut protocol_fee: u64 = 0;
+    let overflow = || anyhow!("Swap input overflowed");
+
+    while amount_left > 0 {
+        let array = walk.enter_bin_array()?;
+        while amount_left > 0 {
+            let Some(bin) = array.get_bin(walk.pair.active_id) else {
+                break;
+            };
+            walk.pair.update_volatility_accumulator();
+            let price = bin_price(bin, walk.pair.active_id, walk.pair.bin_step)?;
+
+            let max_amount_out = bin_max_amount_out(bin, swap_for_y);
+            if max_amount_out > 0 {
+                let bin_out = amount_left.min(max_amount_out);
+                let bin_in = bin_amount_in(bin_out, price, swap_for_y)?;
+                let bin_fee = walk.pair.compute_fee(bin_in)?;
+                amount_left -= bin_out;
+                amount_in = amount_in
+                    .checked_add(bin_in)
+                    .and_then(|amount| amount.checked_add(bin_fee))
+                    .ok_or_else(overflow)?;
+                fee_amount = fee_amount.checked_add(bin_fee).ok_or_else(overflow)?;
+                protocol_fee = protocol_fee
+                    .checked_add(walk.pair.compute_protocol_fee(bin_fee))
+                    .ok_or_else(overflow)?;
+            }
+
+            if amount_left > 0 {
+                walk.pair.advance_active_bin(swap_for_y)?;
+            }
+        }
+    }
+
+    let quote = SwapQuote {
+        amount_in,
+        amount_out,
+        fee_amount,
+    };
+    Ok(walk.finish(quote, protocol_fee))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::meteora::dlmm_info::MAX_BIN_PER_ARRAY;
+    use solana_sdk::pubkey::Pubkey;
+    use std::mem::size_of;
+
+    const BIN_AMOUNT: u64 = 1_000_000;
+
+    /// A 10 bps pair with a 0.1% base fee, no volatility yet and half the fee to the protocol
+    fn pair(initialized_arrays: &[i32]) -> LbPair {
+        let mut pair = LbPair::from_bytes(&[0u8; size_of::<LbPair>()]).unwrap();
+        pair.bin_step = 10;
+        pair.parameters.base_factor = 10_000;
+        pair.parameters.filter_period = 30;
+        pair.parameters.decay_period = 600;
+        pair.parameters.reduction_factor = 5_000;
+        pair.parameters.variable_fee_control = 40_000;
+        pair.parameters.max_volatility_accumulator = 350_000;
+        pair.parameters.protocol_share = 5_000;
+        for &index in initialized_arrays {
+            let bit = (index + BIN_ARRAY_BITMAP_SIZE) as usize;
+            pair.bin_array_bitmap[bit / 64] |= 1 << (bit % 64);
+        }
+        pair
+    }
+
+    /// Bin array whose `bin_ids` each hold `BIN_AMOUNT` of token Y at a price of one
+    fn bin_array(index: i64, bin_ids: &[i32]) -> BinArray {
+        let mut array = BinArray {
+            index,
+            version: 0,
+            lb_pair: Pubkey::default(),
+            bins: [Bin::default(); MAX_BIN_PER_ARRAY],
+        };
+        let (lower, _) = array.bin_id_range();
+        for &bin_id in bin_ids {
+            let bin = &mut array.bins[(bin_id - lower) as usize];
+            bin.amount_y = BIN_AMOUNT;
+            bin.price = ONE;
+        }
+        array
+    }
+
+    #[test]
+    fn consumes_a_bin_exactly() {
+        let pair = pair(&[0, -1]);
+        let arrays = [bin_array(0, &[0]), bin_array(-1, &[-1])];
+        // The whole bin plus its 1002 fee, with nothing left for the next bin
+        let quote = quote_exact_in(&pair, &arrays, 1_001_002, true, 0).unwrap();
+        assert_eq!(quote.quote.amount_out, BIN_AMOUNT);
+        assert_eq!(quote.quote.fee_amount, 1_002);
+        assert_eq!(quote.protocol_fee, 501);
+        assert_eq!(quote.active_id, 0);
+        assert_eq!(quote.bin_arrays, vec![0]);
+    }
+
+    #[test]
+    fn charges_base_and_variable_fees() {
+        let pair = pair(&[0, -1]);
+        let arrays = [bin_array(0, &[0]), bin_array(-1, &[-1, -2])];
+        let quote = quote_exact_in(&pair, &arrays, 2_500_000, true, 0).unwrap();
+        // Each bin crossed adds 10000 to the volatility accumulator, raising the fee from
+        // 0.1% to 0.1004% and then 0.1016%
+        assert_eq!(quote.quote.amount_out, 2_497_486);
+        assert_eq!(quote.quote.fee_amount, 2_514);
+        assert_eq!(quote.protocol_fee, 1_257);
+        assert_eq!(quote.active_id, -2);
+        assert_eq!(quote.volatility_accumulator, 20_000);
+        assert_eq!(quote.bin_arrays, vec![0, -1]);
+    }
+
+    #[test]
+    fn jumps_over_uninitialized_bin_arrays() {
+        let pair = pair(&[0, -3]);
+        let arrays = [bin_array(0, &[0]), bin_array(-3, &[-141])];
+        let quote = quote_exact_in(&pair, &arrays, 1_500_000, true, 0).unwrap();
+        // Landing in the top bin of array -3, 141 bins away, caps the volatility accumulator
+        assert_eq!(quote.active_id, -141);
+        assert_eq!(quote.bin_arrays, vec![0, -3]);
+        assert_eq!(quote.volatility_accumulator, 350_000);
+        assert_eq!(quote.quote.amount_out, 1_496_053);
+        assert_eq!(quote.quote.fee_amount, 3_947);
+    }
+
+    #[test]
+    fn decays_volatility_between_swaps() {
+        let mut pair = pair(&[0]);
+        pair.v_parameters.volatility_accumulator = 30_000;
+        pair.v_parameters.volatility_reference = 20_000;
+        let arrays = [bin_array(0, &[0])];
+        let quote_at =
+            |timestamp| quote_exact_in(&pair, &arrays, 100_000, true, timestamp).unwrap();
+
+        // Within the filter period the reference stays as it was
+        let quote = quote_at(10);
+        assert_eq!(quote.volatility_accumulator, 20_000);
+        assert_eq!(quote.quote.fee_amount, 102);
+        // Within the decay period it is reduced to half the last accumulator
+        let quote = quote_at(100);
+        assert_eq!(quote.volatility_accumulator, 15_000);
+        assert_eq!(quote.quote.fee_amount, 101);
+        // After the decay period it resets
+        let quote = quote_at(700);
+        assert_eq!(quote.volatility_accumulator, 0);
+        assert_eq!(quote.quote.fee_amount, 100);
+    }
+}
@@ -0,0 +1,166 @@
+use crate::dex::meteora::constants::{
+    damm_program_id, damm_v2_event_authority, damm_v2_pool_authority, damm_v2_program_id,
+    dlmm_event_authority, dlmm_program_id, vault_program_id,
+};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+/// DLMM, DAMM v1 and DAMM v2 all name their exact-in instruction `swap`
+const SWAP_DISCRIMINATOR: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+
+fn swap_data(amount_in: u64, minimum_amount_out: u64) -> Vec<u8> {
+    let mut data = SWAP_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&minimum_amount_out.to_le_bytes());
+    data
+}
+
+/// Accounts of a DLMM `swap` other than the program's own
+#[derive(Debug, Clone)]
+pub struct DlmmSwapAccounts {
+    pub lb_pair: Pubkey,
+    pub reserve_x: Pubkey,
+    pub reserve_y: Pubkey,
+    pub user_token_in: Pubkey,
+    pub user_token_out: Pubkey,
+    pub token_x_mint: Pubkey,
+    pub token_y_mint: Pubkey,
+    pub oracle: Pubkey,
+    pub user: Pubkey,
+    pub token_x_program: Pubkey,
+    pub token_y_program: Pubkey,
+    /// Bin arrays from the active one in the swap's direction
+    pub bin_arrays: Vec<Pubkey>,
+}
+
+/// DLMM `swap`: sell exactly `amount_in`, failing below `minimum_amount_out`. The optional
+/// bitmap extension and host fee accounts are left out by passing the program id.
+pub fn dlmm_swap(
+    accounts: &DlmmSwapAccounts,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Instruction {
+    let mut metas = vec![
+        AccountMeta::new(accounts.lb_pair, false),
+        AccountMeta::new_readonly(dlmm_program_id(), false), // bin_array_bitmap_extension
+        AccountMeta::new(accounts.reserve_x, false),
+        AccountMeta::new(accounts.reserve_y, false),
+        AccountMeta::new(accounts.user_token_in, false),
+        AccountMeta::new(accounts.user_token_out, false),
+        AccountMeta::new_readonly(accounts.token_x_mint, false),
+        AccountMeta::new_readonly(accounts.token_y_mint, false),
+        AccountMeta::new(accounts.oracle, false),
+        AccountMeta::new_readonly(dlmm_program_id(), false), // host_fee_in
+        AccountMeta::new_readonly(accounts.user, true),
+        AccountMeta::new_readonly(accounts.token_x_program, false),
+        AccountMeta::new_readonly(accounts.token_y_program, false),
+        AccountMeta::new_readonly(dlmm_event_authority(), false),
+        AccountMeta::new_readonly(dlmm_program_id(), false),
+    ];
+    metas.extend(
+        accounts
+            .bin_arrays
+            .iter()
+            .map(|bin_array| AccountMeta::new(*bin_array, false)),
+    );
+
+    Instruction {
+        program_id: dlmm_program_id(),
+        accounts: metas,
+        data: swap_data(amount_in, minimum_amount_out),
+    }
+}
+
+/// Accounts of a DAMM v1 `swap`, named from the pool's A/B sides
+#[derive(Debug, Clone)]
+pub struct DammSwapAccounts {
+    pub pool: Pubkey,
+    pub user_source_token: Pubkey,
+    pub user_destination_token: Pubkey,
+    pub a_vault: Pubkey,
+    pub b_vault: Pubkey,
+    pub a_token_vault: Pubkey,
+    pub b_token_vault: Pubkey,
+    pub a_vault_lp_mint: Pubkey,
+    pub b_vault_lp_mint: Pubkey,
+    pub a_vault_lp: Pubkey,
+    pub b_vault_lp: Pubkey,
+    /// The pool's admin fee account for the input mint
+    pub protocol_token_fee: Pubkey,
+    pub user: Pubkey,
+}
+
+/// DAMM v1 `swap`: sell exactly `amount_in`, failing below `minimum_amount_out`
+pub fn damm_swap(
+    accounts: &DammSwapAccounts,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Instruction {
+    Instruction {
+        program_id: damm_program_id(),
+        accounts: vec![
+            AccountMeta::new(accounts.pool, false),
+            AccountMeta::new(accounts.user_source_token, false),
+            AccountMeta::new(accounts.user_destination_token, false),
+            AccountMeta::new(accounts.a_vault, false),
+            AccountMeta::new(accounts.b_vault, false),
+            AccountMeta::new(accounts.a_token_vault, false),
+            AccountMeta::new(accounts.b_token_vault, false),
+            AccountMeta::new(accounts.a_vault_lp_mint, false),
+            AccountMeta::new(accounts.b_vault_lp_mint, false),
+            AccountMeta::new(accounts.a_vault_lp, false),
+            AccountMeta::new(accounts.b_vault_lp, false),
+            AccountMeta::new(accounts.protocol_token_fee, false),
+            AccountMeta::new_readonly(accounts.user, true),
+            AccountMeta::new_readonly(vault_program_id(), false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+        ],
+        data: swap_data(amount_in, minimum_amount_out),
+    }
+}
+
+/// Accounts of a DAMM v2 `swap` other than the program's own
+#[derive(Debug, Clone)]
+pub struct DammV2SwapAccounts {
+    pub pool: Pubkey,
+    pub input_token_account: Pubkey,
+    pub output_token_account: Pubkey,
+    pub token_a_vault: Pubkey,
+    pub token_b_vault: Pubkey,
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub payer: Pubkey,
+    pub token_a_program: Pubkey,
+    pub token_b_program: Pubkey,
+}
+
+/// DAMM v2 `swap`: sell exactly `amount_in`, failing below `minimum_amount_out`. The optional
+/// referral account is left out by passing the program id.
+pub fn damm_v2_swap(
+    accounts: &DammV2SwapAccounts,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Instruction {
+    Instruction {
+        program_id: damm_v2_program_id(),
+        accounts: vec![
+            AccountMeta::new_readonly(damm_v2_pool_authority(), false),
+            AccountMeta::new(accounts.pool, false),
+            AccountMeta::new(accounts.input_token_account, false),
+            AccountMeta::new(accounts.output_token_account, false),
+            AccountMeta::new(accounts.token_a_vault, false),
+            AccountMeta::new(accounts.token_b_vault, false),
+            AccountMeta::new_readonly(accounts.token_a_mint, false),
+            AccountMeta::new_readonly(accounts.token_b_mint, false),
+            AccountMeta::new_readonly(accounts.payer, true),
+            AccountMeta::new_readonly(accounts.token_a_program, false),
+            AccountMeta::new_readonly(accounts.token_b_program, false),
+            AccountMeta::new_readonly(damm_v2_program_id(), false), // referral_token_account
+            AccountMeta::new_readonly(damm_v2_event_authority(), false),
+            AccountMeta::new_readonly(damm_v2_program_id(), false),
+        ],
+        data: swap_data(amount_in, minimum_amount_out),
+    }
+}
@@ -0,0 +1,38 @@
+// Q64.64 fixed point shared by the Meteora programs
+pub const SCALE_OFFSET: u32 = 64;
+pub const ONE: u128 = 1 << SCALE_OFFSET;
+pub const BASIS_POINT_MAX: u128 = 10_000;
+const MAX_EXPONENTIAL: u32 = 0x80000;
+
+/// `base^exp` in Q64.64, following the program's square-and-multiply
+pub fn pow(base: u128, exp: i32) -> Option<u128> {
+    let mut invert = exp.is_negative();
+    if exp == 0 {
+        return Some(ONE);
+    }
+    let exp = exp.unsigned_abs();
+    if exp >= MAX_EXPONENTIAL {
+        return None;
+    }
+
+    let mut squared_base = base;
+    let mut result = ONE;
+    if squared_base >= result {
+        squared_base = u128::MAX.checked_div(squared_base)?;
+        invert = !invert;
+    }
+    for bit in 0..19 {
+        if exp & (1 << bit) != 0 {
+            result = result.checked_mul(squared_base)? >> SCALE_OFFSET;
+        }
+        squared_base = squared_base.checked_mul(squared_base)? >> SCALE_OFFSET;
+    }
+
+    if result == 0 {
+        return None;
+    }
+    if invert {
+        result = u128::MAX.checked_div(result)?;
+    }
+    Some(result)
+}
@@ -1,3 +1,9 @@
 pub mod constants;
+pub mod damm_info;
+pub mod damm_quote;
 pub mod dammv2_info;
+pub mod dammv2_quote;
 pub mod dlmm_info;
+pub mod dlmm_quote;
+pub mod instruction;
+pub mod math;
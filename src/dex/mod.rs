@@ -1,5 +1,9 @@
+pub mod clmm;
+#[cfg(test)]
+pub(crate) mod layout;
 pub mod meteora;
 pub mod pump;
+pub mod quote;
 pub mod raydium;
 pub mod solfi;
 pub mod vertigo;
@@ -1,61 +1,404 @@
-use std::str::FromStr;
-
+use crate::dex::pump::{
+    constants::{pump_bonding_curve_program_id, pump_fee_program_id, pump_program_id},
+    quote::PumpFees,
+};
 use anyhow::Result;
 use solana_program::pubkey::Pubkey;
 
-#[derive(Debug)]
+const POOL_DISCRIMINATOR: [u8; 8] = [241, 154, 109, 4, 17, 177, 109, 188];
+const GLOBAL_CONFIG_DISCRIMINATOR: [u8; 8] = [149, 8, 156, 202, 160, 252, 176, 217];
+const FEE_CONFIG_DISCRIMINATOR: [u8; 8] = [143, 52, 146, 187, 219, 123, 76, 155];
+
+// Pool account (after the 8 byte discriminator)
+const POOL_BUMP_OFFSET: usize = 8; // pool_bump
+const INDEX_OFFSET: usize = 9; // index
+const CREATOR_OFFSET: usize = 11; // creator
+const BASE_MINT_OFFSET: usize = 43; // base_mint
+const QUOTE_MINT_OFFSET: usize = 75; // quote_mint
+const LP_MINT_OFFSET: usize = 107; // lp_mint
+const POOL_BASE_TOKEN_ACCOUNT_OFFSET: usize = 139; // pool_base_token_account
+const POOL_QUOTE_TOKEN_ACCOUNT_OFFSET: usize = 171; // pool_quote_token_account
+const LP_SUPPLY_OFFSET: usize = 203; // lp_supply
+const COIN_CREATOR_OFFSET: usize = 211; // coin_creator, absent on pools created before creator fees
+
+// GlobalConfig account (after the 8 byte discriminator)
+const ADMIN_OFFSET: usize = 8; // admin
+const LP_FEE_BASIS_POINTS_OFFSET: usize = 40; // lp_fee_basis_points
+const PROTOCOL_FEE_BASIS_POINTS_OFFSET: usize = 48; // protocol_fee_basis_points
+const DISABLE_FLAGS_OFFSET: usize = 56; // disable_flags
+const PROTOCOL_FEE_RECIPIENTS_OFFSET: usize = 57; // protocol_fee_recipients
+const COIN_CREATOR_FEE_BASIS_POINTS_OFFSET: usize = 313; // coin_creator_fee_basis_points
+
+// FeeConfig account of the fee program (after the 8 byte discriminator)
+const FEE_CONFIG_ADMIN_OFFSET: usize = 9; // admin
+const FEE_CONFIG_FLAT_FEES_OFFSET: usize = 41; // flat_fees
+const FEE_CONFIG_FEE_TIERS_OFFSET: usize = 65; // fee_tiers, a u32 length then the tiers
+/// `market_cap_lamports_threshold` (u128) followed by the tier's three fee rates
+const FEE_TIER_LEN: usize = 40;
+
+const PROTOCOL_FEE_RECIPIENT_COUNT: usize = 8;
+/// Bits of `disable_flags` that pause buys and sells
+const DISABLE_SWAP_FLAGS: u8 = 0b1_1000;
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_u128(data: &[u8], offset: usize) -> u128 {
+    u128::from_le_bytes(data[offset..offset + 16].try_into().unwrap())
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
+}
+
+/// `Fees` struct of the fee program: LP, protocol and creator rates in basis points
+fn read_fees(data: &[u8], offset: usize) -> PumpFees {
+    PumpFees {
+        lp_fee_basis_points: read_u64(data, offset),
+        protocol_fee_basis_points: read_u64(data, offset + 8),
+        coin_creator_fee_basis_points: read_u64(data, offset + 16),
+    }
+}
+
+/// Decoded Pump AMM `Pool` account
+#[derive(Debug, Clone)]
 pub struct PumpAmmInfo {
+    pub pool_bump: u8,
+    pub index: u16,
+    pub creator: Pubkey,
     pub base_mint: Pubkey,
     pub quote_mint: Pubkey,
+    pub lp_mint: Pubkey,
     pub pool_base_token_account: Pubkey,
     pub pool_quote_token_account: Pubkey,
+    pub lp_supply: u64,
+    /// Default pubkey for pools created before creator fees existed
+    pub coin_creator: Pubkey,
     pub coin_creator_vault_authority: Pubkey,
 }
 
 impl PumpAmmInfo {
     pub fn load_checked(data: &[u8]) -> Result<Self> {
-        let data = &data[8 + 1 + 2 + 32..];
-
-        if data.len() < 4 * 32 + 8 {
-            // 4 Pubkeys (32 bytes each) + lp_supply (8 bytes)
+        if data.len() < COIN_CREATOR_OFFSET {
             return Err(anyhow::anyhow!("Invalid data length for PumpAmmInfo"));
         }
+        if data[..8] != POOL_DISCRIMINATOR {
+            return Err(anyhow::anyhow!("Invalid discriminator for PumpAmmInfo"));
+        }
 
-        let base_mint = Pubkey::from(<[u8; 32]>::try_from(&data[0..32]).unwrap());
-        let quote_mint = Pubkey::from(<[u8; 32]>::try_from(&data[32..64]).unwrap());
-        let pool_base_token_account = Pubkey::from(<[u8; 32]>::try_from(&data[96..128]).unwrap());
-        let pool_quote_token_account = Pubkey::from(<[u8; 32]>::try_from(&data[128..160]).unwrap());
-
-        let pump_program_id =
-            Pubkey::from_str("pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA").unwrap();
-        println!("data: {:?}", data.len());
-
-        let coin_creator = if data.len() < 257 {
-            Pubkey::default()
+        let coin_creator = if data.len() >= COIN_CREATOR_OFFSET + 32 {
+            read_pubkey(data, COIN_CREATOR_OFFSET)
         } else {
-            Pubkey::new_from_array(
-                data[168..200].try_into().unwrap(),
-            )
+            Pubkey::default()
         };
-        let key = Pubkey::find_program_address(
-            &[b"creator_vault", coin_creator.as_ref()],
-            &pump_program_id,
-        );
 
-        println!("coin_creator: {:?}", key.0);
+        Ok(Self {
+            pool_bump: data[POOL_BUMP_OFFSET],
+            index: u16::from_le_bytes(data[INDEX_OFFSET..INDEX_OFFSET + 2].try_into().unwrap()),
+            creator: read_pubkey(data, CREATOR_OFFSET),
+            base_mint: read_pubkey(data, BASE_MINT_OFFSET),
+            quote_mint: read_pubkey(data, QUOTE_MINT_OFFSET),
+            lp_mint: read_pubkey(data, LP_MINT_OFFSET),
+            pool_base_token_account: read_pubkey(data, POOL_BASE_TOKEN_ACCOUNT_OFFSET),
+            pool_quote_token_account: read_pubkey(data, POOL_QUOTE_TOKEN_ACCOUNT_OFFSET),
+            lp_supply: read_u64(data, LP_SUPPLY_OFFSET),
+            coin_creator,
+            coin_creator_vault_authority: coin_creator_vault_authority(&coin_creator),
+        })
+    }
+
+    /// Whether the pool pays creator fees on swaps
+    pub fn has_coin_creator(&self) -> bool {
+        self.coin_creator != Pubkey::default()
+    }
+
+    /// Whether the bonding curve created the pool when its coin graduated. Only these pools
+    /// are charged by market cap tier.
+    pub fn is_canonical_pump_pool(&self) -> bool {
+        self.creator == pool_authority_address(&self.base_mint)
+    }
+}
+
+/// Decoded Pump AMM `GlobalConfig` account
+#[derive(Debug, Clone)]
+pub struct PumpGlobalConfig {
+    pub admin: Pubkey,
+    pub lp_fee_basis_points: u64,
+    pub protocol_fee_basis_points: u64,
+    pub disable_flags: u8,
+    pub protocol_fee_recipients: [Pubkey; PROTOCOL_FEE_RECIPIENT_COUNT],
+    pub coin_creator_fee_basis_points: u64,
+}
+
+impl PumpGlobalConfig {
+    pub fn load_checked(data: &[u8]) -> Result<Self> {
+        if data.len() < COIN_CREATOR_FEE_BASIS_POINTS_OFFSET + 8 {
+            return Err(anyhow::anyhow!("Invalid data length for PumpGlobalConfig"));
+        }
+        if data[..8] != GLOBAL_CONFIG_DISCRIMINATOR {
+            return Err(anyhow::anyhow!("Invalid discriminator for PumpGlobalConfig"));
+        }
+
+        let mut protocol_fee_recipients = [Pubkey::default(); PROTOCOL_FEE_RECIPIENT_COUNT];
+        for (i, recipient) in protocol_fee_recipients.iter_mut().enumerate() {
+            *recipient = read_pubkey(data, PROTOCOL_FEE_RECIPIENTS_OFFSET + i * 32);
+        }
+
+        Ok(Self {
+            admin: read_pubkey(data, ADMIN_OFFSET),
+            lp_fee_basis_points: read_u64(data, LP_FEE_BASIS_POINTS_OFFSET),
+            protocol_fee_basis_points: read_u64(data, PROTOCOL_FEE_BASIS_POINTS_OFFSET),
+            disable_flags: data[DISABLE_FLAGS_OFFSET],
+            protocol_fee_recipients,
+            coin_creator_fee_basis_points: read_u64(data, COIN_CREATOR_FEE_BASIS_POINTS_OFFSET),
+        })
+    }
+
+    /// Whether buys and sells are currently allowed
+    pub fn swap_enabled(&self) -> bool {
+        self.disable_flags & DISABLE_SWAP_FLAGS == 0
+    }
+
+    /// First configured protocol fee recipient
+    pub fn protocol_fee_recipient(&self) -> Option<Pubkey> {
+        self.protocol_fee_recipients
+            .iter()
+            .find(|recipient| **recipient != Pubkey::default())
+            .copied()
+    }
+}
+
+/// Market cap tier of the fee program's `FeeConfig`
+#[derive(Debug, Clone, Copy)]
+pub struct PumpFeeTier {
+    pub market_cap_lamports_threshold: u128,
+    pub fees: PumpFees,
+}
+
+/// Decoded `FeeConfig` account of the Pump fee program. When the AMM is handed this account it
+/// takes its fees from here instead of from `GlobalConfig`.
+#[derive(Debug, Clone)]
+pub struct PumpFeeConfig {
+    pub admin: Pubkey,
+    pub flat_fees: PumpFees,
+    /// Sorted by ascending threshold
+    pub fee_tiers: Vec<PumpFeeTier>,
+}
+
+impl PumpFeeConfig {
+    pub fn load_checked(data: &[u8]) -> Result<Self> {
+        if data.len() < FEE_CONFIG_FEE_TIERS_OFFSET + 4 {
+            return Err(anyhow::anyhow!("Invalid data length for PumpFeeConfig"));
+        }
+        if data[..8] != FEE_CONFIG_DISCRIMINATOR {
+            return Err(anyhow::anyhow!("Invalid discriminator for PumpFeeConfig"));
+        }
+
+        let tier_count = u32::from_le_bytes(
+            data[FEE_CONFIG_FEE_TIERS_OFFSET..FEE_CONFIG_FEE_TIERS_OFFSET + 4]
+                .try_into()
+                .unwrap(),
+        ) as usize;
+        let tiers_offset = FEE_CONFIG_FEE_TIERS_OFFSET + 4;
+        if data.len() < tiers_offset + tier_count * FEE_TIER_LEN {
+            return Err(anyhow::anyhow!(
+                "PumpFeeConfig is too short for {} fee tiers",
+                tier_count
+            ));
+        }
+        let fee_tiers = (0..tier_count)
+            .map(|i| {
+                let offset = tiers_offset + i * FEE_TIER_LEN;
+                PumpFeeTier {
+                    market_cap_lamports_threshold: read_u128(data, offset),
+                    fees: read_fees(data, offset + 16),
+                }
+            })
+            .collect();
 
         Ok(Self {
-            base_mint,
-            quote_mint,
-            pool_base_token_account,
-            pool_quote_token_account,
-            coin_creator_vault_authority: key.0,
+            admin: read_pubkey(data, FEE_CONFIG_ADMIN_OFFSET),
+            flat_fees: read_fees(data, FEE_CONFIG_FLAT_FEES_OFFSET),
+            fee_tiers,
         })
     }
+
+    /// Fees the AMM charges a pool. Canonical pump pools pay the highest tier whose threshold
+    /// their market cap reaches (the first tier if it reaches none); other pools pay flat fees.
+    pub fn fees(&self, canonical_pump_pool: bool, market_cap_lamports: u128) -> PumpFees {
+        if !canonical_pump_pool {
+            return self.flat_fees;
+        }
+        let Some(first) = self.fee_tiers.first() else {
+            return self.flat_fees;
+        };
+        self.fee_tiers
+            .iter()
+            .rev()
+            .find(|tier| market_cap_lamports >= tier.market_cap_lamports_threshold)
+            .unwrap_or(first)
+            .fees
+    }
+}
+
+/// Market cap of the pool's base mint in quote atoms, priced at the pool's reserves
+pub fn pool_market_cap(
+    base_mint_supply: u64,
+    base_reserve: u64,
+    quote_reserve: u64,
+) -> Result<u128> {
+    if base_reserve == 0 {
+        return Err(anyhow::anyhow!("Pump pool has no base reserve"));
+    }
+    Ok(quote_reserve as u128 * base_mint_supply as u128 / base_reserve as u128)
 }
 
-pub fn get_pump_info(data: &[u8]) -> Result<Pubkey> {
-    Ok(Pubkey::new_from_array(
-        data[168..200].try_into().unwrap(),
-    ))
+/// Creator of the canonical pool the bonding curve opens for `base_mint`
+pub fn pool_authority_address(base_mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"pool-authority", base_mint.as_ref()],
+        &pump_bonding_curve_program_id(),
+    )
+    .0
+}
+
+/// Address of the program's `GlobalConfig` account
+pub fn global_config_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"global_config"], &pump_program_id()).0
+}
+
+pub fn coin_creator_vault_authority(coin_creator: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"creator_vault", coin_creator.as_ref()],
+        &pump_program_id(),
+    )
+    .0
+}
+
+/// Address of the program's `GlobalVolumeAccumulator` account
+pub fn global_volume_accumulator_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"global_volume_accumulator"], &pump_program_id()).0
+}
+
+/// Address of the fee program's `FeeConfig` account for the AMM
+pub fn fee_config_address() -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"fee_config", pump_program_id().as_ref()],
+        &pump_fee_program_id(),
+    )
+    .0
+}
+
+/// Address of the `UserVolumeAccumulator` account tracking `user`
+pub fn user_volume_accumulator_address(user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"user_volume_accumulator", user.as_ref()],
+        &pump_program_id(),
+    )
+    .0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::layout::Layout;
+
+    const LAMPORTS_PER_SOL: u128 = 1_000_000_000;
+
+    fn fees(lp: u64, protocol: u64, creator: u64) -> PumpFees {
+        PumpFees {
+            lp_fee_basis_points: lp,
+            protocol_fee_basis_points: protocol,
+            coin_creator_fee_basis_points: creator,
+        }
+    }
+
+    fn write_fees(layout: &mut Layout, fees: PumpFees) {
+        layout
+            .u64(fees.lp_fee_basis_points)
+            .u64(fees.protocol_fee_basis_points)
+            .u64(fees.coin_creator_fee_basis_points);
+    }
+
+    /// `FeeConfig` written in the order the fee program declares it
+    fn fee_config(admin: &Pubkey, tiers: &[(u128, PumpFees)]) -> Vec<u8> {
+        let mut layout = Layout::anchor(FEE_CONFIG_DISCRIMINATOR);
+        layout.u8(255).pubkey(admin);
+        write_fees(&mut layout, fees(25, 5, 0));
+        layout.u32(tiers.len() as u32);
+        for &(threshold, tier_fees) in tiers {
+            layout.u128(threshold);
+            write_fees(&mut layout, tier_fees);
+        }
+        layout.finish()
+    }
+
+    fn tiers() -> Vec<(u128, PumpFees)> {
+        vec![
+            (0, fees(2, 93, 30)),
+            (420 * LAMPORTS_PER_SOL, fees(20, 5, 95)),
+            (1_470 * LAMPORTS_PER_SOL, fees(20, 5, 90)),
+        ]
+    }
+
+    #[test]
+    fn decodes_fee_config() {
+        let admin = Pubkey::new_unique();
+        let config = PumpFeeConfig::load_checked(&fee_config(&admin, &tiers())).unwrap();
+
+        assert_eq!(config.admin, admin);
+        assert_eq!(config.flat_fees.lp_fee_basis_points, 25);
+        assert_eq!(config.flat_fees.protocol_fee_basis_points, 5);
+        assert_eq!(config.fee_tiers.len(), 3);
+        assert_eq!(
+            config.fee_tiers[1].market_cap_lamports_threshold,
+            420 * LAMPORTS_PER_SOL
+        );
+        assert_eq!(config.fee_tiers[1].fees.coin_creator_fee_basis_points, 95);
+    }
+
+    #[test]
+    fn rejects_truncated_fee_tiers() {
+        let data = fee_config(&Pubkey::new_unique(), &tiers());
+        assert!(PumpFeeConfig::load_checked(&data[..data.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn canonical_pools_pay_their_market_cap_tier() {
+        let config =
+            PumpFeeConfig::load_checked(&fee_config(&Pubkey::new_unique(), &tiers())).unwrap();
+        let creator_fee = |market_cap| config.fees(true, market_cap).coin_creator_fee_basis_points;
+
+        assert_eq!(creator_fee(0), 30);
+        assert_eq!(creator_fee(420 * LAMPORTS_PER_SOL - 1), 30);
+        assert_eq!(creator_fee(420 * LAMPORTS_PER_SOL), 95);
+        assert_eq!(creator_fee(u128::MAX), 90);
+        assert_eq!(
+            config
+                .fees(false, 500 * LAMPORTS_PER_SOL)
+                .lp_fee_basis_points,
+            25
+        );
+    }
+
+    #[test]
+    fn market_cap_below_the_first_threshold_pays_the_first_tier() {
+        let tiers = [(100, fees(1, 2, 3)), (200, fees(4, 5, 6))];
+        let config =
+            PumpFeeConfig::load_checked(&fee_config(&Pubkey::new_unique(), &tiers)).unwrap();
+
+        assert_eq!(config.fees(true, 50).lp_fee_basis_points, 1);
+        assert_eq!(config.fees(true, 150).lp_fee_basis_points, 1);
+        assert_eq!(config.fees(true, 250).lp_fee_basis_points, 4);
+    }
+
+    #[test]
+    fn market_cap_prices_the_supply_at_the_reserves() {
+        // 1B supply, 200M in the pool against 85 SOL
+        let market_cap =
+            pool_market_cap(1_000_000_000_000_000, 200_000_000_000_000, 85_000_000_000).unwrap();
+        assert_eq!(market_cap, 425 * LAMPORTS_PER_SOL);
+        assert!(pool_market_cap(1, 0, 1).is_err());
+    }
 }
@@ -2,12 +2,26 @@ use solana_program::pubkey::Pubkey;
 use std::str::FromStr;
 
 pub const PUMP_PROGRAM_ID: &str = "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA";
-pub const PUMP_FEE_WALLET: &str = "JCRGumoE9Qi5BBgULTgdgTLjSgkCMSbF62ZZfGs84JeU";
 
 pub fn pump_program_id() -> Pubkey {
     Pubkey::from_str(PUMP_PROGRAM_ID).unwrap()
 }
 
-pub fn pump_fee_wallet() -> Pubkey {
-    Pubkey::from_str(PUMP_FEE_WALLET).unwrap()
+pub const PUMP_EVENT_AUTHORITY: &str = "GS4CU59F31iL7aR2Q8zVS8DRrcRnXX1yjQ66TqNVQnaR";
+
+pub fn pump_event_authority() -> Pubkey {
+    Pubkey::from_str(PUMP_EVENT_AUTHORITY).unwrap()
+}
+
+pub const PUMP_FEE_PROGRAM_ID: &str = "pfeeUxB6jkeY1Hxd7CsFCAjcbHA9rWtchMGdZ6VojVZ";
+
+pub fn pump_fee_program_id() -> Pubkey {
+    Pubkey::from_str(PUMP_FEE_PROGRAM_ID).unwrap()
+}
+
+/// Bonding curve program that migrates graduated coins into canonical AMM pools
+pub const PUMP_BONDING_CURVE_PROGRAM_ID: &str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
+
+pub fn pump_bonding_curve_program_id() -> Pubkey {
+    Pubkey::from_str(PUMP_BONDING_CURVE_PROGRAM_ID).unwrap()
 }
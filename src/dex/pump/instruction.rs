@@ -0,0 +1,114 @@
+use crate::dex::pump::{
+    amm_info::{
+        fee_config_address, global_volume_accumulator_address, user_volume_accumulator_address,
+    },
+    constants::{pump_event_authority, pump_fee_program_id, pump_program_id},
+};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+
+const BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+const SELL_DISCRIMINATOR: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
+
+/// Accounts shared by the AMM's `buy` and `sell`, named from the pool's base/quote sides
+#[derive(Debug, Clone)]
+pub struct PumpSwapAccounts {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub global_config: Pubkey,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub user_base_token_account: Pubkey,
+    pub user_quote_token_account: Pubkey,
+    pub pool_base_token_account: Pubkey,
+    pub pool_quote_token_account: Pubkey,
+    pub protocol_fee_recipient: Pubkey,
+    pub protocol_fee_recipient_token_account: Pubkey,
+    pub base_token_program: Pubkey,
+    pub quote_token_program: Pubkey,
+    pub coin_creator_vault_ata: Pubkey,
+    pub coin_creator_vault_authority: Pubkey,
+}
+
+impl PumpSwapAccounts {
+    fn metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new(self.user, true),
+            AccountMeta::new_readonly(self.global_config, false),
+            AccountMeta::new_readonly(self.base_mint, false),
+            AccountMeta::new_readonly(self.quote_mint, false),
+            AccountMeta::new(self.user_base_token_account, false),
+            AccountMeta::new(self.user_quote_token_account, false),
+            AccountMeta::new(self.pool_base_token_account, false),
+            AccountMeta::new(self.pool_quote_token_account, false),
+            AccountMeta::new_readonly(self.protocol_fee_recipient, false),
+            AccountMeta::new(self.protocol_fee_recipient_token_account, false),
+            AccountMeta::new_readonly(self.base_token_program, false),
+            AccountMeta::new_readonly(self.quote_token_program, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+            AccountMeta::new_readonly(pump_event_authority(), false),
+            AccountMeta::new_readonly(pump_program_id(), false),
+            AccountMeta::new(self.coin_creator_vault_ata, false),
+            AccountMeta::new_readonly(self.coin_creator_vault_authority, false),
+        ]
+    }
+}
+
+fn fee_metas() -> [AccountMeta; 2] {
+    [
+        AccountMeta::new_readonly(fee_config_address(), false),
+        AccountMeta::new_readonly(pump_fee_program_id(), false),
+    ]
+}
+
+/// `buy`: receive exactly `base_amount_out` of the base mint, paying at most
+/// `max_quote_amount_in` of the quote mint
+pub fn buy(
+    accounts: &PumpSwapAccounts,
+    base_amount_out: u64,
+    max_quote_amount_in: u64,
+) -> Instruction {
+    let mut data = BUY_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&base_amount_out.to_le_bytes());
+    data.extend_from_slice(&max_quote_amount_in.to_le_bytes());
+    data.push(0); // track_volume
+
+    let mut metas = accounts.metas();
+    metas.extend([
+        AccountMeta::new(global_volume_accumulator_address(), false),
+        AccountMeta::new(user_volume_accumulator_address(&accounts.user), false),
+    ]);
+    metas.extend(fee_metas());
+
+    Instruction {
+        program_id: pump_program_id(),
+        accounts: metas,
+        data,
+    }
+}
+
+/// `sell`: sell exactly `base_amount_in` of the base mint, failing below
+/// `min_quote_amount_out` of the quote mint
+pub fn sell(
+    accounts: &PumpSwapAccounts,
+    base_amount_in: u64,
+    min_quote_amount_out: u64,
+) -> Instruction {
+    let mut data = SELL_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&base_amount_in.to_le_bytes());
+    data.extend_from_slice(&min_quote_amount_out.to_le_bytes());
+
+    let mut metas = accounts.metas();
+    metas.extend(fee_metas());
+
+    Instruction {
+        program_id: pump_program_id(),
+        accounts: metas,
+        data,
+    }
+}
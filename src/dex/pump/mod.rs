@@ -1,5 +1,7 @@
 pub mod amm_info;
 pub mod constants;
+pub mod instruction;
+pub mod quote;
 
 pub use amm_info::PumpAmmInfo;
 pub use constants::*;
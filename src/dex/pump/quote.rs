@@ -0,0 +1,181 @@
+use crate::dex::quote::{
+    ceil_div, constant_product_amount_in, constant_product_amount_out, to_u64, SwapQuote,
+};
+use anyhow::{anyhow, Result};
+
+const BASIS_POINTS: u128 = 10_000;
+
+/// Fee rates of a Pump AMM pool in basis points. All three are charged on the quote token.
+#[derive(Debug, Clone, Copy)]
+pub struct PumpFees {
+    pub lp_fee_basis_points: u64,
+    pub protocol_fee_basis_points: u64,
+    pub coin_creator_fee_basis_points: u64,
+}
+
+impl PumpFees {
+    fn total_basis_points(&self) -> u128 {
+        self.lp_fee_basis_points as u128
+            + self.protocol_fee_basis_points as u128
+            + self.coin_creator_fee_basis_points as u128
+    }
+
+    /// Sum of the three fees on `amount`, each rounded up separately as the program does
+    fn fees_on(&self, amount: u128) -> Result<u128> {
+        [
+            self.lp_fee_basis_points,
+            self.protocol_fee_basis_points,
+            self.coin_creator_fee_basis_points,
+        ]
+        .iter()
+        .try_fold(0u128, |total, &bps| {
+            amount
+                .checked_mul(bps as u128)
+                .and_then(|numerator| ceil_div(numerator, BASIS_POINTS))
+                .and_then(|fee| total.checked_add(fee))
+        })
+        .ok_or_else(|| anyhow!("Pump fee overflowed"))
+    }
+}
+
+/// Sell `base_in` for as much quote as possible; fees come out of the quote output
+pub fn sell_exact_in(
+    base_in: u64,
+    base_reserve: u64,
+    quote_reserve: u64,
+    fees: PumpFees,
+) -> Result<SwapQuote> {
+    let quote_out =
+        constant_product_amount_out(base_in as u128, base_reserve as u128, quote_reserve as u128)?;
+    let fee = fees.fees_on(quote_out)?;
+    let quote_out_after_fees = quote_out
+        .checked_sub(fee)
+        .ok_or_else(|| anyhow!("Pump fees exceed the output"))?;
+
+    Ok(SwapQuote {
+        amount_in: base_in,
+        amount_out: to_u64(quote_out_after_fees)?,
+        fee_amount: to_u64(fee)?,
+    })
+}
+
+/// Base needed to receive `quote_out` after fees
+pub fn sell_exact_out(
+    quote_out: u64,
+    base_reserve: u64,
+    quote_reserve: u64,
+    fees: PumpFees,
+) -> Result<SwapQuote> {
+    let total_basis_points = fees.total_basis_points();
+    if total_basis_points >= BASIS_POINTS {
+        return Err(anyhow!(
+            "Pump fees of {} bps leave no output",
+            total_basis_points
+        ));
+    }
+    let quote_before_fees = ceil_div(
+        quote_out as u128 * BASIS_POINTS,
+        BASIS_POINTS - total_basis_points,
+    )
+    .ok_or_else(|| anyhow!("Pump fee overflowed"))?;
+    let base_in = constant_product_amount_in(
+        quote_before_fees,
+        base_reserve as u128,
+        quote_reserve as u128,
+    )?;
+
+    Ok(SwapQuote {
+        amount_in: to_u64(base_in)?,
+        amount_out: quote_out,
+        fee_amount: to_u64(quote_before_fees - quote_out as u128)?,
+    })
+}
+
+/// Spend `quote_in`, fees included, on as much base as possible
+pub fn buy_exact_in(
+    quote_in: u64,
+    base_reserve: u64,
+    quote_reserve: u64,
+    fees: PumpFees,
+) -> Result<SwapQuote> {
+    let effective_quote =
+        quote_in as u128 * BASIS_POINTS / (BASIS_POINTS + fees.total_basis_points());
+    let base_out =
+        constant_product_amount_out(effective_quote, quote_reserve as u128, base_reserve as u128)?;
+
+    Ok(SwapQuote {
+        amount_in: quote_in,
+        amount_out: to_u64(base_out)?,
+        fee_amount: to_u64(quote_in as u128 - effective_quote)?,
+    })
+}
+
+/// Quote needed, fees included, to buy exactly `base_out`
+pub fn buy_exact_out(
+    base_out: u64,
+    base_reserve: u64,
+    quote_reserve: u64,
+    fees: PumpFees,
+) -> Result<SwapQuote> {
+    let quote_in = constant_product_amount_in(
+        base_out as u128,
+        quote_reserve as u128,
+        base_reserve as u128,
+    )?;
+    let fee = fees.fees_on(quote_in)?;
+
+    Ok(SwapQuote {
+        amount_in: to_u64(quote_in + fee)?,
+        amount_out: base_out,
+        fee_amount: to_u64(fee)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE_RESERVE: u64 = 1_000_000_000_000;
+    const QUOTE_RESERVE: u64 = 100_000_000_000;
+    const FEES: PumpFees = PumpFees {
+        lp_fee_basis_points: 20,
+        protocol_fee_basis_points: 5,
+        coin_creator_fee_basis_points: 5,
+    };
+
+    #[test]
+    fn sell_round_trips() {
+        let quote = sell_exact_in(1_000_000_000, BASE_RESERVE, QUOTE_RESERVE, FEES).unwrap();
+        assert_eq!(quote.amount_out, 99_600_396);
+        assert_eq!(quote.fee_amount, 299_703);
+
+        let quote = sell_exact_out(quote.amount_out, BASE_RESERVE, QUOTE_RESERVE, FEES).unwrap();
+        assert_eq!(quote.amount_in, 999_999_971);
+        assert_eq!(quote.fee_amount, 299_701);
+    }
+
+    #[test]
+    fn buy_round_trips() {
+        let quote = buy_exact_in(100_000_000, BASE_RESERVE, QUOTE_RESERVE, FEES).unwrap();
+        assert_eq!(quote.amount_out, 996_015_933);
+        assert_eq!(quote.fee_amount, 299_103);
+
+        // Fees rounded up per fee cost at most a lamport more than the exact-in spend
+        let quote = buy_exact_out(quote.amount_out, BASE_RESERVE, QUOTE_RESERVE, FEES).unwrap();
+        assert_eq!(quote.amount_in, 100_000_001);
+        assert_eq!(quote.fee_amount, 299_104);
+    }
+
+    #[test]
+    fn rejects_degenerate_swaps() {
+        assert_eq!(
+            sell_exact_in(0, BASE_RESERVE, QUOTE_RESERVE, FEES)
+                .unwrap()
+                .amount_out,
+            0
+        );
+        assert!(sell_exact_out(QUOTE_RESERVE, BASE_RESERVE, QUOTE_RESERVE, FEES).is_err());
+        assert!(buy_exact_out(BASE_RESERVE, BASE_RESERVE, QUOTE_RESERVE, FEES).is_err());
+        assert!(buy_exact_in(1, BASE_RESERVE, 0, FEES).is_err());
+    }
+}
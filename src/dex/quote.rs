@@ -0,0 +1,114 @@
+use anyhow::{anyhow, Result};
+
+/// Which side of a pool a swap sells. The SOL side is whatever the pool pairs the token with,
+/// which is SOL for most pools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SwapDirection {
+    SolToToken,
+    TokenToSol,
+}
+
+impl SwapDirection {
+    pub fn reverse(self) -> Self {
+        match self {
+            SwapDirection::SolToToken => SwapDirection::TokenToSol,
+            SwapDirection::TokenToSol => SwapDirection::SolToToken,
+        }
+    }
+}
+
+/// Amounts a swap moves through one pool, as the program would compute them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SwapQuote {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// Total fee charged, in the token the program charges it in
+    pub fee_amount: u64,
+}
+
+pub fn ceil_div(numerator: u128, denominator: u128) -> Option<u128> {
+    if denominator == 0 {
+        return None;
+    }
+    numerator
+        .checked_add(denominator - 1)?
+        .checked_div(denominator)
+}
+
+/// Constant product output for `amount_in`, rounded down
+pub fn constant_product_amount_out(
+    amount_in: u128,
+    reserve_in: u128,
+    reserve_out: u128,
+) -> Result<u128> {
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(anyhow!("Pool has an empty reserve"));
+    }
+    reserve_out
+        .checked_mul(amount_in)
+        .and_then(|numerator| numerator.checked_div(reserve_in.checked_add(amount_in)?))
+        .ok_or_else(|| anyhow!("Constant product output overflowed"))
+}
+
+/// Constant product input needed for `amount_out`, rounded up
+pub fn constant_product_amount_in(
+    amount_out: u128,
+    reserve_in: u128,
+    reserve_out: u128,
+) -> Result<u128> {
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(anyhow!("Pool has an empty reserve"));
+    }
+    if amount_out >= reserve_out {
+        return Err(anyhow!(
+            "Requested output {} exceeds the reserve of {}",
+            amount_out,
+            reserve_out
+        ));
+    }
+    reserve_in
+        .checked_mul(amount_out)
+        .and_then(|numerator| ceil_div(numerator, reserve_out - amount_out))
+        .ok_or_else(|| anyhow!("Constant product input overflowed"))
+}
+
+pub fn to_u64(amount: u128) -> Result<u64> {
+    u64::try_from(amount).map_err(|_| anyhow!("Amount {} does not fit in u64", amount))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESERVE_IN: u128 = 1_000_000_000;
+    const RESERVE_OUT: u128 = 2_000_000_000;
+
+    #[test]
+    fn ceil_div_rounds_up() {
+        assert_eq!(ceil_div(7, 2), Some(4));
+        assert_eq!(ceil_div(6, 2), Some(3));
+        assert_eq!(ceil_div(0, 2), Some(0));
+        assert_eq!(ceil_div(1, 0), None);
+    }
+
+    #[test]
+    fn constant_product_round_trips() {
+        let amount_out = constant_product_amount_out(1_000_000, RESERVE_IN, RESERVE_OUT).unwrap();
+        assert_eq!(amount_out, 1_998_001);
+        assert_eq!(
+            constant_product_amount_in(amount_out, RESERVE_IN, RESERVE_OUT).unwrap(),
+            1_000_000
+        );
+    }
+
+    #[test]
+    fn constant_product_rejects_degenerate_swaps() {
+        assert_eq!(
+            constant_product_amount_out(0, RESERVE_IN, RESERVE_OUT).unwrap(),
+            0
+        );
+        assert!(constant_product_amount_out(1, 0, RESERVE_OUT).is_err());
+        assert!(constant_product_amount_in(RESERVE_OUT, RESERVE_IN, RESERVE_OUT).is_err());
+        assert!(constant_product_amount_in(RESERVE_OUT + 1, RESERVE_IN, RESERVE_OUT).is_err());
+    }
+}
@@ -1,51 +1,254 @@
 use solana_program::pubkey::Pubkey;
 use anyhow::Result;
 
+pub const AMM_INFO_SIZE: usize = 752;
+
+const STATUS_OFFSET: usize = 0; // status
+const NONCE_OFFSET: usize = 8; // nonce
+const COIN_DECIMALS_OFFSET: usize = 32; // coinDecimals
+const PC_DECIMALS_OFFSET: usize = 40; // pcDecimals
+const COIN_LOT_SIZE_OFFSET: usize = 88; // coinLotSize
+const PC_LOT_SIZE_OFFSET: usize = 96; // pcLotSize
+
+// Fees
+const TRADE_FEE_NUMERATOR_OFFSET: usize = 144; // fees.tradeFeeNumerator
+const TRADE_FEE_DENOMINATOR_OFFSET: usize = 152; // fees.tradeFeeDenominator
+const PNL_NUMERATOR_OFFSET: usize = 160; // fees.pnlNumerator
+const PNL_DENOMINATOR_OFFSET: usize = 168; // fees.pnlDenominator
+const SWAP_FEE_NUMERATOR_OFFSET: usize = 176; // fees.swapFeeNumerator
+const SWAP_FEE_DENOMINATOR_OFFSET: usize = 184; // fees.swapFeeDenominator
+
+// State data
+const NEED_TAKE_PNL_COIN_OFFSET: usize = 192; // stateData.needTakePnlCoin
+const NEED_TAKE_PNL_PC_OFFSET: usize = 200; // stateData.needTakePnlPc
+const POOL_OPEN_TIME_OFFSET: usize = 224; // stateData.poolOpenTime
+
 const COIN_VAULT_OFFSET: usize = 336; // coinVault/tokenVaultA
 const PC_VAULT_OFFSET: usize = 368; // pcVault/tokenVaultB
 const COIN_MINT_OFFSET: usize = 400; // coinMint/tokenMintA
 const PC_MINT_OFFSET: usize = 432; // pcMint/tokenMintB
+const LP_MINT_OFFSET: usize = 464; // lpMint
+const OPEN_ORDERS_OFFSET: usize = 496; // openOrders
+const MARKET_OFFSET: usize = 528; // market
+const MARKET_PROGRAM_OFFSET: usize = 560; // marketProgram
+const TARGET_ORDERS_OFFSET: usize = 592; // targetOrders
+const AMM_OWNER_OFFSET: usize = 688; // ammOwner
+const LP_AMOUNT_OFFSET: usize = 720; // lpAmount
+
+/// `AmmStatus` values the program accepts swaps in
+const STATUS_INITIALIZED: u64 = 1;
+const STATUS_SWAP_ONLY: u64 = 6;
+const STATUS_WAITING_TRADE: u64 = 7;
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
+}
 
-#[derive(Debug)]
+/// Decoded Raydium AMM v4 `AmmInfo` account
+#[derive(Debug, Clone)]
 pub struct RaydiumAmmInfo {
-    pub coin_mint: Pubkey,
-    pub pc_mint: Pubkey,
+    pub status: u64,
+    pub nonce: u64,
+    pub coin_decimals: u64,
+    pub pc_decimals: u64,
+    pub coin_lot_size: u64,
+    pub pc_lot_size: u64,
+    pub trade_fee_numerator: u64,
+    pub trade_fee_denominator: u64,
+    pub pnl_numerator: u64,
+    pub pnl_denominator: u64,
+    pub swap_fee_numerator: u64,
+    pub swap_fee_denominator: u64,
+    pub need_take_pnl_coin: u64,
+    pub need_take_pnl_pc: u64,
+    pub pool_open_time: u64,
     pub coin_vault: Pubkey,
     pub pc_vault: Pubkey,
+    pub coin_mint: Pubkey,
+    pub pc_mint: Pubkey,
+    pub lp_mint: Pubkey,
+    pub open_orders: Pubkey,
+    pub market: Pubkey,
+    pub market_program: Pubkey,
+    pub target_orders: Pubkey,
+    pub amm_owner: Pubkey,
+    pub lp_amount: u64,
 }
 
 impl RaydiumAmmInfo {
     pub fn load_checked(data: &[u8]) -> Result<Self> {
-        if data.len() < PC_MINT_OFFSET + 32 {
+        if data.len() < AMM_INFO_SIZE {
             return Err(anyhow::anyhow!("Invalid data length for RaydiumAmmInfo"));
         }
-        
-        let coin_vault = Pubkey::new_from_array(
-            data[COIN_VAULT_OFFSET..COIN_VAULT_OFFSET + 32]
-                .try_into()
-                .unwrap(),
-        );
-        let pc_vault = Pubkey::new_from_array(
-            data[PC_VAULT_OFFSET..PC_VAULT_OFFSET + 32]
-                .try_into()
-                .unwrap(),
-        );
-        let coin_mint = Pubkey::new_from_array(
-            data[COIN_MINT_OFFSET..COIN_MINT_OFFSET + 32]
-                .try_into()
-                .unwrap(),
-        );
-        let pc_mint = Pubkey::new_from_array(
-            data[PC_MINT_OFFSET..PC_MINT_OFFSET + 32]
-                .try_into()
-                .unwrap(),
-        );
-        
-        Ok(Self {
-            coin_mint,
-            pc_mint,
-            coin_vault,
-            pc_vault,
-        })
+
+        let info = Self {
+            status: read_u64(data, STATUS_OFFSET),
+            nonce: read_u64(data, NONCE_OFFSET),
+            coin_decimals: read_u64(data, COIN_DECIMALS_OFFSET),
+            pc_decimals: read_u64(data, PC_DECIMALS_OFFSET),
+            coin_lot_size: read_u64(data, COIN_LOT_SIZE_OFFSET),
+            pc_lot_size: read_u64(data, PC_LOT_SIZE_OFFSET),
+            trade_fee_numerator: read_u64(data, TRADE_FEE_NUMERATOR_OFFSET),
+            trade_fee_denominator: read_u64(data, TRADE_FEE_DENOMINATOR_OFFSET),
+            pnl_numerator: read_u64(data, PNL_NUMERATOR_OFFSET),
+            pnl_denominator: read_u64(data, PNL_DENOMINATOR_OFFSET),
+            swap_fee_numerator: read_u64(data, SWAP_FEE_NUMERATOR_OFFSET),
+            swap_fee_denominator: read_u64(data, SWAP_FEE_DENOMINATOR_OFFSET),
+            need_take_pnl_coin: read_u64(data, NEED_TAKE_PNL_COIN_OFFSET),
+            need_take_pnl_pc: read_u64(data, NEED_TAKE_PNL_PC_OFFSET),
+            pool_open_time: read_u64(data, POOL_OPEN_TIME_OFFSET),
+            coin_vault: read_pubkey(data, COIN_VAULT_OFFSET),
+            pc_vault: read_pubkey(data, PC_VAULT_OFFSET),
+            coin_mint: read_pubkey(data, COIN_MINT_OFFSET),
+            pc_mint: read_pubkey(data, PC_MINT_OFFSET),
+            lp_mint: read_pubkey(data, LP_MINT_OFFSET),
+            open_orders: read_pubkey(data, OPEN_ORDERS_OFFSET),
+            market: read_pubkey(data, MARKET_OFFSET),
+            market_program: read_pubkey(data, MARKET_PROGRAM_OFFSET),
+            target_orders: read_pubkey(data, TARGET_ORDERS_OFFSET),
+            amm_owner: read_pubkey(data, AMM_OWNER_OFFSET),
+            lp_amount: read_u64(data, LP_AMOUNT_OFFSET),
+        };
+
+        if info.swap_fee_denominator == 0 || info.trade_fee_denominator == 0 {
+            return Err(anyhow::anyhow!("RaydiumAmmInfo has a zero fee denominator"));
+        }
+
+        Ok(info)
+    }
+
+    /// Whether the pool status lets `swap_base_in`/`swap_base_out` through
+    pub fn swap_enabled(&self) -> bool {
+        matches!(
+            self.status,
+            STATUS_INITIALIZED | STATUS_SWAP_ONLY | STATUS_WAITING_TRADE
+        )
+    }
+
+    /// Reserves the program actually trades against: vault balances minus the PnL it still
+    /// owes to the protocol, returned as `(coin, pc)`
+    pub fn effective_reserves(&self, coin_vault_amount: u64, pc_vault_amount: u64) -> Result<(u64, u64)> {
+        let coin = coin_vault_amount
+            .checked_sub(self.need_take_pnl_coin)
+            .ok_or_else(|| anyhow::anyhow!("Coin vault balance is below pending PnL"))?;
+        let pc = pc_vault_amount
+            .checked_sub(self.need_take_pnl_pc)
+            .ok_or_else(|| anyhow::anyhow!("Pc vault balance is below pending PnL"))?;
+        Ok((coin, pc))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::layout::{key, Layout};
+
+    const SOL_USDC_COIN_VAULT: &str = "DQyrAcCrDXQ7NeoqGgDCZwBvWDcYmFCjSb9JtteuvPpz";
+    const SOL_USDC_PC_VAULT: &str = "HLmqeL62xR1QoZ1HKKbXRrdN1p3phKpxRMb2VVopvBBz";
+    const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+    const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qA1xxoxHSgyfnkZTrNEwE7n5Xt";
+
+    /// `AmmInfo` of the SOL/USDC pool, written in the order the AMM v4 program declares it
+    fn sol_usdc_amm_info(status: u64, need_take_pnl: (u64, u64)) -> Vec<u8> {
+        let mut layout = Layout::new();
+        layout
+            .u64(status)
+            .u64(254) // nonce
+            .u64(7) // order_num
+            .u64(3) // depth
+            .u64(9) // coin_decimals
+            .u64(6) // pc_decimals
+            .u64(1) // state
+            .u64(0) // reset_flag
+            .u64(100_000) // min_size
+            .u64(500) // vol_max_cut_ratio
+            .u64(5_000_000) // amount_wave
+            .u64(1_000_000) // coin_lot_size
+            .u64(1) // pc_lot_size
+            .u64(1) // min_price_multiplier
+            .u64(1_000_000_000) // max_price_multiplier
+            .u64(1_000_000_000) // sys_decimal_value
+            // Fees
+            .u64(5) // min_separate_numerator
+            .u64(10_000) // min_separate_denominator
+            .u64(25) // trade_fee_numerator
+            .u64(10_000) // trade_fee_denominator
+            .u64(12) // pnl_numerator
+            .u64(100) // pnl_denominator
+            .u64(25) // swap_fee_numerator
+            .u64(10_000) // swap_fee_denominator
+            // StateData
+            .u64(need_take_pnl.0)
+            .u64(need_take_pnl.1)
+            .u64(0) // total_pnl_pc
+            .u64(0) // total_pnl_coin
+            .u64(1_650_000_000) // pool_open_time
+            .zeros(16) // padding
+            .u64(0) // orderbook_to_init_time
+            .u128(0) // swap_coin_in_amount
+            .u128(0) // swap_pc_out_amount
+            .u64(0) // swap_acc_pc_fee
+            .u128(0) // swap_pc_in_amount
+            .u128(0) // swap_coin_out_amount
+            .u64(0); // swap_acc_coin_fee
+        assert_eq!(layout.len(), COIN_VAULT_OFFSET);
+        layout
+            .pubkey(&key(SOL_USDC_COIN_VAULT))
+            .pubkey(&key(SOL_USDC_PC_VAULT))
+            .pubkey(&key(SOL_MINT))
+            .pubkey(&key(USDC_MINT))
+            .pubkey(&Pubkey::new_unique()) // lp_mint
+            .pubkey(&Pubkey::new_unique()) // open_orders
+            .pubkey(&Pubkey::new_unique()) // market
+            .pubkey(&Pubkey::new_unique()) // market_program
+            .pubkey(&Pubkey::new_unique()) // target_orders
+            .zeros(64) // padding1
+            .pubkey(&Pubkey::new_unique()) // amm_owner
+            .u64(42_000_000) // lp_amount
+            .u64(0) // client_order_id
+            .u64(600) // recent_epoch
+            .u64(0); // padding2
+        assert_eq!(layout.len(), AMM_INFO_SIZE);
+        layout.finish()
+    }
+
+    #[test]
+    fn decodes_sol_usdc_pool() {
+        let info = RaydiumAmmInfo::load_checked(&sol_usdc_amm_info(6, (0, 0))).unwrap();
+
+        assert_eq!(info.status, 6);
+        assert!(info.swap_enabled());
+        assert_eq!((info.coin_decimals, info.pc_decimals), (9, 6));
+        assert_eq!((info.coin_lot_size, info.pc_lot_size), (1_000_000, 1));
+        assert_eq!((info.trade_fee_numerator, info.trade_fee_denominator), (25, 10_000));
+        assert_eq!((info.swap_fee_numerator, info.swap_fee_denominator), (25, 10_000));
+        assert_eq!((info.pnl_numerator, info.pnl_denominator), (12, 100));
+        assert_eq!(info.pool_open_time, 1_650_000_000);
+        assert_eq!(info.coin_vault, key(SOL_USDC_COIN_VAULT));
+        assert_eq!(info.pc_vault, key(SOL_USDC_PC_VAULT));
+        assert_eq!(info.coin_mint, key(SOL_MINT));
+        assert_eq!(info.pc_mint, key(USDC_MINT));
+        assert_eq!(info.lp_amount, 42_000_000);
+    }
+
+    #[test]
+    fn effective_reserves_subtract_pending_pnl() {
+        let info = RaydiumAmmInfo::load_checked(&sol_usdc_amm_info(6, (3, 40))).unwrap();
+
+        assert_eq!(info.effective_reserves(1_000, 2_000).unwrap(), (997, 1_960));
+        assert!(info.effective_reserves(2, 2_000).is_err());
+    }
+
+    #[test]
+    fn rejects_disabled_and_truncated_pools() {
+        let disabled = RaydiumAmmInfo::load_checked(&sol_usdc_amm_info(4, (0, 0))).unwrap();
+        assert!(!disabled.swap_enabled());
+
+        let data = sol_usdc_amm_info(6, (0, 0));
+        assert!(RaydiumAmmInfo::load_checked(&data[..AMM_INFO_SIZE - 1]).is_err());
     }
 }
@@ -8,6 +8,93 @@ pub const REWARD_NUM: usize = 3;
 
 pub const POOL_TICK_ARRAY_BITMAP_SEED: &str = "pool_tick_array_bitmap_extension";
 
+pub const MIN_TICK: i32 = -443636;
+pub const MAX_TICK: i32 = 443636;
+
+/// Tick arrays tracked by each 512 bit bitmap (half of the pool bitmap, or one extension row)
+pub const TICK_ARRAY_BITMAP_SIZE: i32 = 512;
+pub const EXTENSION_TICKARRAY_BITMAP_SIZE: usize = 14;
+
+pub const POOL_STATE_SIZE: usize = 1544;
+pub const TICK_ARRAY_STATE_SIZE: usize = 10240;
+pub const TICK_ARRAY_BITMAP_EXTENSION_SIZE: usize = 1832;
+pub const AMM_CONFIG_SIZE: usize = 117;
+
+const POOL_STATE_DISCRIMINATOR: [u8; 8] = [247, 237, 227, 245, 215, 195, 222, 70];
+const TICK_ARRAY_STATE_DISCRIMINATOR: [u8; 8] = [192, 155, 85, 205, 49, 249, 129, 42];
+const TICK_ARRAY_BITMAP_EXTENSION_DISCRIMINATOR: [u8; 8] = [60, 150, 36, 219, 97, 128, 139, 153];
+const AMM_CONFIG_DISCRIMINATOR: [u8; 8] = [218, 244, 33, 104, 203, 203, 43, 111];
+
+const TICK_STATE_SIZE: usize = 168;
+
+fn read_u8(data: &[u8], offset: &mut usize) -> u8 {
+    let value = data[*offset];
+    *offset += 1;
+    value
+}
+
+fn read_u16(data: &[u8], offset: &mut usize) -> u16 {
+    let value = u16::from_le_bytes(data[*offset..*offset + 2].try_into().unwrap());
+    *offset += 2;
+    value
+}
+
+fn read_i32(data: &[u8], offset: &mut usize) -> i32 {
+    let value = i32::from_le_bytes(data[*offset..*offset + 4].try_into().unwrap());
+    *offset += 4;
+    value
+}
+
+fn read_u32(data: &[u8], offset: &mut usize) -> u32 {
+    let value = u32::from_le_bytes(data[*offset..*offset + 4].try_into().unwrap());
+    *offset += 4;
+    value
+}
+
+fn read_u64(data: &[u8], offset: &mut usize) -> u64 {
+    let value = u64::from_le_bytes(data[*offset..*offset + 8].try_into().unwrap());
+    *offset += 8;
+    value
+}
+
+fn read_u128(data: &[u8], offset: &mut usize) -> u128 {
+    let value = u128::from_le_bytes(data[*offset..*offset + 16].try_into().unwrap());
+    *offset += 16;
+    value
+}
+
+fn read_i128(data: &[u8], offset: &mut usize) -> i128 {
+    let value = i128::from_le_bytes(data[*offset..*offset + 16].try_into().unwrap());
+    *offset += 16;
+    value
+}
+
+fn read_pubkey(data: &[u8], offset: &mut usize) -> Pubkey {
+    let value = Pubkey::new_from_array(data[*offset..*offset + 32].try_into().unwrap());
+    *offset += 32;
+    value
+}
+
+fn read_u64_array<const N: usize>(data: &[u8], offset: &mut usize) -> [u64; N] {
+    let mut values = [0u64; N];
+    for value in values.iter_mut() {
+        *value = read_u64(data, offset);
+    }
+    values
+}
+
+fn check_discriminator(data: &[u8], discriminator: &[u8; 8], name: &str) -> Result<()> {
+    if data[..8] != discriminator[..] {
+        return Err(anyhow::anyhow!("Invalid discriminator for {}", name));
+    }
+    Ok(())
+}
+
+/// Whether bit `bit` is set in a little-endian multi-word bitmap
+fn bit_is_set(bitmap: &[u64], bit: usize) -> bool {
+    bitmap[bit / 64] & (1u64 << (bit % 64)) != 0
+}
+
 pub enum RewardState {
     Uninitialized,
     Initialized,
@@ -15,7 +102,7 @@ pub enum RewardState {
     Ended,
 }
 
-#[derive(Default, Debug, PartialEq, Eq)]
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub struct RewardInfo {
     pub reward_state: u8,
     pub open_time: u64,
@@ -41,9 +128,25 @@ impl RewardInfo {
     pub fn initialized(&self) -> bool {
         self.token_mint.ne(&Pubkey::default())
     }
+
+    fn read(data: &[u8], offset: &mut usize) -> Self {
+        Self {
+            reward_state: read_u8(data, offset),
+            open_time: read_u64(data, offset),
+            end_time: read_u64(data, offset),
+            last_update_time: read_u64(data, offset),
+            emissions_per_second_x64: read_u128(data, offset),
+            reward_total_emissioned: read_u64(data, offset),
+            reward_claimed: read_u64(data, offset),
+            token_mint: read_pubkey(data, offset),
+            token_vault: read_pubkey(data, offset),
+            authority: read_pubkey(data, offset),
+            reward_growth_global_x64: read_u128(data, offset),
+        }
+    }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct PoolState {
     pub bump: [u8; 1],
     pub amm_config: Pubkey,
@@ -103,78 +206,424 @@ pub struct PoolState {
 
 impl PoolState {
     pub fn load_checked(data: &[u8]) -> Result<Self> {
-        if data.len() < 8 + 32 + 32 + 32 + 32 + 32 + 32 + 4 + 2 {
+        if data.len() < POOL_STATE_SIZE {
             return Err(anyhow::anyhow!(
                 "Invalid data length for RaydiumClmmPoolState"
             ));
         }
+        check_discriminator(data, &POOL_STATE_DISCRIMINATOR, "RaydiumClmmPoolState")?;
+
+        let mut offset = 8; // Skip the discriminator
+
+        let bump = [read_u8(data, &mut offset)];
+        let amm_config = read_pubkey(data, &mut offset);
+        let owner = read_pubkey(data, &mut offset);
+        let token_mint_0 = read_pubkey(data, &mut offset);
+        let token_mint_1 = read_pubkey(data, &mut offset);
+        let token_vault_0 = read_pubkey(data, &mut offset);
+        let token_vault_1 = read_pubkey(data, &mut offset);
+        let observation_key = read_pubkey(data, &mut offset);
+        let mint_decimals_0 = read_u8(data, &mut offset);
+        let mint_decimals_1 = read_u8(data, &mut offset);
+        let tick_spacing = read_u16(data, &mut offset);
+        let liquidity = read_u128(data, &mut offset);
+        let sqrt_price_x64 = read_u128(data, &mut offset);
+        let tick_current = read_i32(data, &mut offset);
+        let padding3 = read_u16(data, &mut offset);
+        let padding4 = read_u16(data, &mut offset);
+        let fee_growth_global_0_x64 = read_u128(data, &mut offset);
+        let fee_growth_global_1_x64 = read_u128(data, &mut offset);
+        let protocol_fees_token_0 = read_u64(data, &mut offset);
+        let protocol_fees_token_1 = read_u64(data, &mut offset);
+        let swap_in_amount_token_0 = read_u128(data, &mut offset);
+        let swap_out_amount_token_1 = read_u128(data, &mut offset);
+        let swap_in_amount_token_1 = read_u128(data, &mut offset);
+        let swap_out_amount_token_0 = read_u128(data, &mut offset);
+        let status = read_u8(data, &mut offset);
+        let padding: [u8; 7] = data[offset..offset + 7].try_into().unwrap();
+        offset += 7;
+        let reward_infos = [
+            RewardInfo::read(data, &mut offset),
+            RewardInfo::read(data, &mut offset),
+            RewardInfo::read(data, &mut offset),
+        ];
+        let tick_array_bitmap = read_u64_array(data, &mut offset);
+        let total_fees_token_0 = read_u64(data, &mut offset);
+        let total_fees_claimed_token_0 = read_u64(data, &mut offset);
+        let total_fees_token_1 = read_u64(data, &mut offset);
+        let total_fees_claimed_token_1 = read_u64(data, &mut offset);
+        let fund_fees_token_0 = read_u64(data, &mut offset);
+        let fund_fees_token_1 = read_u64(data, &mut offset);
+        let open_time = read_u64(data, &mut offset);
+        let recent_epoch = read_u64(data, &mut offset);
+        let padding1 = read_u64_array(data, &mut offset);
+        let padding2 = read_u64_array(data, &mut offset);
+
+        if tick_spacing == 0 {
+            return Err(anyhow::anyhow!("RaydiumClmmPoolState has a zero tick spacing"));
+        }
 
-        let data = &data[8..]; // Skip the discriminator
-        let mut offset = 0;
+        Ok(Self {
+            bump,
+            amm_config,
+            owner,
+            token_mint_0,
+            token_mint_1,
+            token_vault_0,
+            token_vault_1,
+            observation_key,
+            mint_decimals_0,
+            mint_decimals_1,
+            tick_spacing,
+            liquidity,
+            sqrt_price_x64,
+            tick_current,
+            padding3,
+            padding4,
+            fee_growth_global_0_x64,
+            fee_growth_global_1_x64,
+            protocol_fees_token_0,
+            protocol_fees_token_1,
+            swap_in_amount_token_0,
+            swap_out_amount_token_1,
+            swap_in_amount_token_1,
+            swap_out_amount_token_0,
+            status,
+            padding,
+            reward_infos,
+            tick_array_bitmap,
+            total_fees_token_0,
+            total_fees_claimed_token_0,
+            total_fees_token_1,
+            total_fees_claimed_token_1,
+            fund_fees_token_0,
+            fund_fees_token_1,
+            open_time,
+            recent_epoch,
+            padding1,
+            padding2,
+        })
+    }
 
-        offset += 1;
+    /// Whether the status bits allow swaps
+    pub fn swap_enabled(&self) -> bool {
+        self.status & (1 << 4) == 0
+    }
 
-        let mut amm_config = [0u8; 32];
-        amm_config.copy_from_slice(&data[offset..offset + 32]);
-        let amm_config = Pubkey::new_from_array(amm_config);
-        offset += 32;
+    /// Whether the tick array starting at `start_index` is initialized, consulting the
+    /// bitmap extension for arrays outside the range the pool bitmap covers.
+    ///
+    /// Arrays outside the pool bitmap are reported as uninitialized when no extension is given.
+    pub fn is_tick_array_initialized(
+        &self,
+        start_index: i32,
+        extension: Option<&TickArrayBitmapExtension>,
+    ) -> bool {
+        let max_tick = max_tick_in_tickarray_bitmap(self.tick_spacing);
+        if start_index >= -max_tick && start_index < max_tick {
+            let ticks_in_array = tick_count(self.tick_spacing);
+            let bit = (start_index / ticks_in_array + TICK_ARRAY_BITMAP_SIZE) as usize;
+            return bit_is_set(&self.tick_array_bitmap, bit);
+        }
 
-        offset += 32;
+        extension
+            .map(|extension| extension.is_initialized(start_index, self.tick_spacing))
+            .unwrap_or(false)
+    }
 
-        let mut token_mint_0 = [0u8; 32];
-        token_mint_0.copy_from_slice(&data[offset..offset + 32]);
-        let token_mint_0 = Pubkey::new_from_array(token_mint_0);
-        offset += 32;
+    /// First tick array a swap reads and whether it is the array holding `tick_current`.
+    /// When that array is not initialized the swap starts from the next initialized one.
+    pub fn first_initialized_tick_array(
+        &self,
+        extension: Option<&TickArrayBitmapExtension>,
+        zero_for_one: bool,
+    ) -> Option<(bool, i32)> {
+        let start_index = compute_tick_array_start_index(self.tick_current, self.tick_spacing);
+        if self.is_tick_array_initialized(start_index, extension) {
+            return Some((true, start_index));
+        }
+        self.next_initialized_tick_array_start_index(extension, start_index, zero_for_one)
+            .map(|start_index| (false, start_index))
+    }
 
-        let mut token_mint_1 = [0u8; 32];
-        token_mint_1.copy_from_slice(&data[offset..offset + 32]);
-        let token_mint_1 = Pubkey::new_from_array(token_mint_1);
-        offset += 32;
+    /// Start index of the next initialized tick array after `last_start_index` in the swap
+    /// direction, or `None` when none is left before the end of the tick range
+    pub fn next_initialized_tick_array_start_index(
+        &self,
+        extension: Option<&TickArrayBitmapExtension>,
+        last_start_index: i32,
+        zero_for_one: bool,
+    ) -> Option<i32> {
+        let ticks_in_array = tick_count(self.tick_spacing);
+        let min_start = compute_tick_array_start_index(MIN_TICK, self.tick_spacing);
+        let max_start = compute_tick_array_start_index(MAX_TICK, self.tick_spacing);
+        let step = if zero_for_one { -ticks_in_array } else { ticks_in_array };
+
+        let mut start_index =
+            compute_tick_array_start_index(last_start_index, self.tick_spacing) + step;
+        while start_index >= min_start && start_index <= max_start {
+            if self.is_tick_array_initialized(start_index, extension) {
+                return Some(start_index);
+            }
+            start_index += step;
+        }
+        None
+    }
 
-        let mut token_vault_0 = [0u8; 32];
-        token_vault_0.copy_from_slice(&data[offset..offset + 32]);
-        let token_vault_0 = Pubkey::new_from_array(token_vault_0);
-        offset += 32;
+    /// Start indices of up to `max_count` initialized tick arrays, beginning with the array
+    /// holding `tick_current` and moving down in price when `zero_for_one` is set, up otherwise
+    pub fn initialized_tick_array_start_indices(
+        &self,
+        extension: Option<&TickArrayBitmapExtension>,
+        zero_for_one: bool,
+        max_count: usize,
+    ) -> Vec<i32> {
+        let ticks_in_array = tick_count(self.tick_spacing);
+        let min_start = compute_tick_array_start_index(MIN_TICK, self.tick_spacing);
+        let max_start = compute_tick_array_start_index(MAX_TICK, self.tick_spacing);
+        let step = if zero_for_one { -ticks_in_array } else { ticks_in_array };
+
+        let mut result = Vec::with_capacity(max_count);
+        let mut start_index = compute_tick_array_start_index(self.tick_current, self.tick_spacing);
+        while result.len() < max_count && start_index >= min_start && start_index <= max_start {
+            if self.is_tick_array_initialized(start_index, extension) {
+                result.push(start_index);
+            }
+            start_index += step;
+        }
+        result
+    }
+}
 
-        let mut token_vault_1 = [0u8; 32];
-        token_vault_1.copy_from_slice(&data[offset..offset + 32]);
-        let token_vault_1 = Pubkey::new_from_array(token_vault_1);
-        offset += 32;
+/// Decoded Raydium CLMM `AmmConfig` account, the fee tier a pool belongs to
+#[derive(Debug, Clone)]
+pub struct AmmConfig {
+    pub index: u16,
+    pub owner: Pubkey,
+    /// Share of the trade fee kept by the protocol, out of `FEE_RATE_DENOMINATOR`
+    pub protocol_fee_rate: u32,
+    /// Out of `FEE_RATE_DENOMINATOR`
+    pub trade_fee_rate: u32,
+    pub tick_spacing: u16,
+    pub fund_fee_rate: u32,
+    pub fund_owner: Pubkey,
+}
 
-        let mut observation_key = [0u8; 32];
-        observation_key.copy_from_slice(&data[offset..offset + 32]);
-        let observation_key = Pubkey::new_from_array(observation_key);
-        offset += 32;
+impl AmmConfig {
+    pub fn load_checked(data: &[u8]) -> Result<Self> {
+        if data.len() < AMM_CONFIG_SIZE {
+            return Err(anyhow::anyhow!("Invalid data length for RaydiumClmmAmmConfig"));
+        }
+        check_discriminator(data, &AMM_CONFIG_DISCRIMINATOR, "RaydiumClmmAmmConfig")?;
+
+        let mut offset = 9; // Skip the discriminator and bump
+        let index = read_u16(data, &mut offset);
+        let owner = read_pubkey(data, &mut offset);
+        let protocol_fee_rate = read_u32(data, &mut offset);
+        let trade_fee_rate = read_u32(data, &mut offset);
+        let tick_spacing = read_u16(data, &mut offset);
+        let fund_fee_rate = read_u32(data, &mut offset);
+        offset += 4; // padding
+        let fund_owner = read_pubkey(data, &mut offset);
 
-        offset += 2;
+        Ok(Self {
+            index,
+            owner,
+            protocol_fee_rate,
+            trade_fee_rate,
+            tick_spacing,
+            fund_fee_rate,
+            fund_owner,
+        })
+    }
+}
+
+/// A single tick inside a `TickArrayState`
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TickState {
+    pub tick: i32,
+    pub liquidity_net: i128,
+    pub liquidity_gross: u128,
+    pub fee_growth_outside_0_x64: u128,
+    pub fee_growth_outside_1_x64: u128,
+    pub reward_growths_outside_x64: [u128; REWARD_NUM],
+}
+
+impl TickState {
+    pub fn is_initialized(&self) -> bool {
+        self.liquidity_gross != 0
+    }
+
+    fn read(data: &[u8], offset: usize) -> Self {
+        let mut cursor = offset;
+        Self {
+            tick: read_i32(data, &mut cursor),
+            liquidity_net: read_i128(data, &mut cursor),
+            liquidity_gross: read_u128(data, &mut cursor),
+            fee_growth_outside_0_x64: read_u128(data, &mut cursor),
+            fee_growth_outside_1_x64: read_u128(data, &mut cursor),
+            reward_growths_outside_x64: [
+                read_u128(data, &mut cursor),
+                read_u128(data, &mut cursor),
+                read_u128(data, &mut cursor),
+            ],
+        }
+    }
+}
+
+/// Decoded Raydium CLMM `TickArrayState` account (60 ticks)
+#[derive(Debug, Clone)]
+pub struct TickArrayState {
+    pub pool_id: Pubkey,
+    pub start_tick_index: i32,
+    pub ticks: [TickState; TICK_ARRAY_SIZE_USIZE],
+    pub initialized_tick_count: u8,
+    pub recent_epoch: u64,
+}
 
-        let mut tick_spacing_bytes = [0u8; 2];
-        tick_spacing_bytes.copy_from_slice(&data[offset..offset + 2]);
-        let tick_spacing = u16::from_le_bytes(tick_spacing_bytes);
-        offset += 2;
+impl TickArrayState {
+    pub fn load_checked(data: &[u8]) -> Result<Self> {
+        if data.len() < TICK_ARRAY_STATE_SIZE {
+            return Err(anyhow::anyhow!(
+                "Invalid data length for RaydiumClmmTickArrayState"
+            ));
+        }
+        check_discriminator(data, &TICK_ARRAY_STATE_DISCRIMINATOR, "RaydiumClmmTickArrayState")?;
 
-        offset += 16;
+        let mut offset = 8;
+        let pool_id = read_pubkey(data, &mut offset);
+        let start_tick_index = read_i32(data, &mut offset);
 
-        // Skip sqrt_price_x64
-        offset += 16;
+        let mut ticks = [TickState::default(); TICK_ARRAY_SIZE_USIZE];
+        for (i, tick) in ticks.iter_mut().enumerate() {
+            *tick = TickState::read(data, offset + i * TICK_STATE_SIZE);
+        }
+        offset += TICK_ARRAY_SIZE_USIZE * TICK_STATE_SIZE;
 
-        let mut tick_current_bytes = [0u8; 4];
-        tick_current_bytes.copy_from_slice(&data[offset..offset + 4]);
-        let tick_current = i32::from_le_bytes(tick_current_bytes);
-        let _ = offset + 4;
+        let initialized_tick_count = read_u8(data, &mut offset);
+        let recent_epoch = read_u64(data, &mut offset);
 
         Ok(Self {
-            amm_config,
-            token_mint_0,
-            token_mint_1,
-            token_vault_0,
-            token_vault_1,
-            observation_key,
-            tick_spacing,
-            tick_current,
-            ..Default::default()
+            pool_id,
+            start_tick_index,
+            ticks,
+            initialized_tick_count,
+            recent_epoch,
+        })
+    }
+
+    /// Next initialized tick in this array from `current_tick_index`, inclusive when moving
+    /// down (`zero_for_one`) and exclusive when moving up, as the program searches
+    pub fn next_initialized_tick(
+        &self,
+        current_tick_index: i32,
+        tick_spacing: u16,
+        zero_for_one: bool,
+    ) -> Option<&TickState> {
+        if compute_tick_array_start_index(current_tick_index, tick_spacing) != self.start_tick_index {
+            return None;
+        }
+        let offset = (current_tick_index - self.start_tick_index) / tick_spacing as i32;
+
+        if zero_for_one {
+            (0..=offset)
+                .rev()
+                .map(|i| &self.ticks[i as usize])
+                .find(|tick| tick.is_initialized())
+        } else {
+            (offset + 1..TICK_ARRAY_SIZE)
+                .map(|i| &self.ticks[i as usize])
+                .find(|tick| tick.is_initialized())
+        }
+    }
+
+    /// First initialized tick met when entering this array in the swap direction
+    pub fn first_initialized_tick(&self, zero_for_one: bool) -> Option<&TickState> {
+        if zero_for_one {
+            self.ticks.iter().rev().find(|tick| tick.is_initialized())
+        } else {
+            self.ticks.iter().find(|tick| tick.is_initialized())
+        }
+    }
+}
+
+/// Decoded Raydium CLMM `TickArrayBitmapExtension` account, which tracks tick arrays beyond
+/// the range covered by `PoolState::tick_array_bitmap`
+#[derive(Debug, Clone)]
+pub struct TickArrayBitmapExtension {
+    pub pool_id: Pubkey,
+    pub positive_tick_array_bitmap: [[u64; 8]; EXTENSION_TICKARRAY_BITMAP_SIZE],
+    pub negative_tick_array_bitmap: [[u64; 8]; EXTENSION_TICKARRAY_BITMAP_SIZE],
+}
+
+impl TickArrayBitmapExtension {
+    pub fn load_checked(data: &[u8]) -> Result<Self> {
+        if data.len() < TICK_ARRAY_BITMAP_EXTENSION_SIZE {
+            return Err(anyhow::anyhow!(
+                "Invalid data length for RaydiumClmmTickArrayBitmapExtension"
+            ));
+        }
+        check_discriminator(
+            data,
+            &TICK_ARRAY_BITMAP_EXTENSION_DISCRIMINATOR,
+            "RaydiumClmmTickArrayBitmapExtension",
+        )?;
+
+        let mut offset = 8;
+        let pool_id = read_pubkey(data, &mut offset);
+        let mut positive_tick_array_bitmap = [[0u64; 8]; EXTENSION_TICKARRAY_BITMAP_SIZE];
+        for row in positive_tick_array_bitmap.iter_mut() {
+            *row = read_u64_array(data, &mut offset);
+        }
+        let mut negative_tick_array_bitmap = [[0u64; 8]; EXTENSION_TICKARRAY_BITMAP_SIZE];
+        for row in negative_tick_array_bitmap.iter_mut() {
+            *row = read_u64_array(data, &mut offset);
+        }
+
+        Ok(Self {
+            pool_id,
+            positive_tick_array_bitmap,
+            negative_tick_array_bitmap,
         })
     }
+
+    /// Whether the tick array starting at `start_index` is initialized. Only meaningful for
+    /// start indices outside the range of the pool's own bitmap.
+    pub fn is_initialized(&self, start_index: i32, tick_spacing: u16) -> bool {
+        let max_tick = max_tick_in_tickarray_bitmap(tick_spacing);
+        if start_index >= -max_tick && start_index < max_tick {
+            return false;
+        }
+
+        let mut row = start_index.abs() / max_tick - 1;
+        if start_index < 0 && start_index.abs() % max_tick == 0 {
+            row -= 1;
+        }
+        let Some(bitmap) = (if start_index < 0 {
+            self.negative_tick_array_bitmap.get(row as usize)
+        } else {
+            self.positive_tick_array_bitmap.get(row as usize)
+        }) else {
+            return false;
+        };
+
+        let remainder = start_index.abs() % max_tick;
+        let mut bit = remainder / tick_count(tick_spacing);
+        if start_index < 0 && remainder != 0 {
+            bit = TICK_ARRAY_BITMAP_SIZE - bit;
+        }
+        bit_is_set(bitmap, bit as usize)
+    }
+}
+
+/// Number of ticks spanned by one tick array
+pub fn tick_count(tick_spacing: u16) -> i32 {
+    TICK_ARRAY_SIZE * tick_spacing as i32
+}
+
+/// Highest tick array start index (exclusive) the pool's own bitmap covers
+pub fn max_tick_in_tickarray_bitmap(tick_spacing: u16) -> i32 {
+    tick_count(tick_spacing) * TICK_ARRAY_BITMAP_SIZE
 }
 
 pub fn compute_tick_array_start_index(tick: i32, tick_spacing: u16) -> i32 {
@@ -186,6 +635,20 @@ pub fn compute_tick_array_start_index(tick: i32, tick_spacing: u16) -> i32 {
     start * ticks_in_array
 }
 
+/// Address of the tick array starting at `start_index`
+pub fn get_tick_array_pubkey(
+    pool_pubkey: &Pubkey,
+    start_index: i32,
+    raydium_clmm_program_id: &Pubkey,
+) -> Pubkey {
+    let seeds = &[
+        TICK_ARRAY_SEED.as_bytes(),
+        pool_pubkey.as_ref(),
+        &start_index.to_be_bytes(),
+    ];
+    Pubkey::find_program_address(seeds, raydium_clmm_program_id).0
+}
+
 pub fn get_tick_array_pubkeys(
     pool_pubkey: &Pubkey,
     tick_current: i32,
@@ -201,14 +664,11 @@ pub fn get_tick_array_pubkeys(
 
         let offset_start_index = base_start_index + offset * ticks_in_array;
 
-        let seeds = &[
-            TICK_ARRAY_SEED.as_bytes(),
-            pool_pubkey.as_ref(),
-            &offset_start_index.to_be_bytes(),
-        ];
-
-        let (pubkey, _) = Pubkey::find_program_address(seeds, raydium_clmm_program_id);
-        result.push(pubkey);
+        result.push(get_tick_array_pubkey(
+            pool_pubkey,
+            offset_start_index,
+            raydium_clmm_program_id,
+        ));
     }
 
     Ok(result)
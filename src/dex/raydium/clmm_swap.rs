@@ -0,0 +1,278 @@
+use crate::dex::clmm::{self, NextTick, SwapParams, SwapResult, TickTraversal};
+use crate::dex::raydium::clmm_info::{
+    PoolState, TickArrayBitmapExtension, TickArrayState, TickState, MAX_TICK, MIN_TICK,
+};
+use crate::dex::raydium::clmm_tick_math::{
+    get_sqrt_price_at_tick, get_tick_at_sqrt_price, MAX_SQRT_PRICE_X64, MIN_SQRT_PRICE_X64,
+};
+use anyhow::{anyhow, Result};
+
+/// Walks initialized tick arrays the way the Raydium CLMM swap instruction does, jumping
+/// between arrays through the pool bitmap and its extension
+pub struct RaydiumClmmTickTraversal<'a> {
+    pool: &'a PoolState,
+    extension: Option<&'a TickArrayBitmapExtension>,
+    tick_arrays: &'a [TickArrayState],
+    current: &'a TickArrayState,
+    /// False until the swap has entered its first array when that array does not hold the
+    /// current tick
+    is_match_current_tick_array: bool,
+    touched: Vec<i32>,
+}
+
+impl<'a> RaydiumClmmTickTraversal<'a> {
+    pub fn new(
+        pool: &'a PoolState,
+        extension: Option<&'a TickArrayBitmapExtension>,
+        tick_arrays: &'a [TickArrayState],
+        zero_for_one: bool,
+    ) -> Result<Self> {
+        let (is_match_current_tick_array, start_index) = pool
+            .first_initialized_tick_array(extension, zero_for_one)
+            .ok_or_else(|| anyhow!("No initialized tick array in the swap direction"))?;
+        let current = find_tick_array(tick_arrays, start_index)?;
+
+        Ok(Self {
+            pool,
+            extension,
+            tick_arrays,
+            current,
+            is_match_current_tick_array,
+            touched: vec![start_index],
+        })
+    }
+}
+
+fn find_tick_array(tick_arrays: &[TickArrayState], start_index: i32) -> Result<&TickArrayState> {
+    tick_arrays
+        .iter()
+        .find(|array| array.start_tick_index == start_index)
+        .ok_or_else(|| anyhow!("Swap needs tick array {} which is not loaded", start_index))
+}
+
+fn first_initialized_tick(array: &TickArrayState, zero_for_one: bool) -> Result<&TickState> {
+    array.first_initialized_tick(zero_for_one).ok_or_else(|| {
+        anyhow!(
+            "Tick array {} has no initialized tick",
+            array.start_tick_index
+        )
+    })
+}
+
+impl TickTraversal for RaydiumClmmTickTraversal<'_> {
+    fn next_tick(&mut self, tick_current: i32, zero_for_one: bool) -> Result<NextTick> {
+        let tick = match self.current.next_initialized_tick(
+            tick_current,
+            self.pool.tick_spacing,
+            zero_for_one,
+        ) {
+            Some(tick) => tick,
+            None if !self.is_match_current_tick_array => {
+                self.is_match_current_tick_array = true;
+                first_initialized_tick(self.current, zero_for_one)?
+            }
+            None => {
+                let start_index = self
+                    .pool
+                    .next_initialized_tick_array_start_index(
+                        self.extension,
+                        self.current.start_tick_index,
+                        zero_for_one,
+                    )
+                    .ok_or_else(|| anyhow!("Not enough liquidity to fill the swap"))?;
+                self.current = find_tick_array(self.tick_arrays, start_index)?;
+                self.touched.push(start_index);
+                first_initialized_tick(self.current, zero_for_one)?
+            }
+        };
+
+        Ok(NextTick {
+            tick_index: tick.tick.clamp(MIN_TICK, MAX_TICK),
+            liquidity_net: Some(tick.liquidity_net),
+        })
+    }
+
+    fn sqrt_price_at_tick(&self, tick_index: i32) -> Result<u128> {
+        get_sqrt_price_at_tick(tick_index)
+    }
+
+    fn tick_at_sqrt_price(&self, sqrt_price: u128) -> Result<i32> {
+        get_tick_at_sqrt_price(sqrt_price)
+    }
+
+    fn can_step_from(&self, tick_current: i32) -> bool {
+        tick_current > MIN_TICK && tick_current < MAX_TICK
+    }
+
+    fn tick_arrays_touched(&self) -> Vec<i32> {
+        self.touched.clone()
+    }
+}
+
+/// Simulate a swap against a Raydium CLMM pool with its tick arrays loaded, reproducing the
+/// program's amounts, ending price and tick. `trade_fee_rate` comes from the pool's AMM config
+/// and `sqrt_price_limit` defaults to just inside the end of the price range.
+#[allow(clippy::too_many_arguments)]
+pub fn simulate_swap(
+    pool: &PoolState,
+    trade_fee_rate: u32,
+    extension: Option<&TickArrayBitmapExtension>,
+    tick_arrays: &[TickArrayState],
+    amount: u64,
+    is_base_input: bool,
+    zero_for_one: bool,
+    sqrt_price_limit: Option<u128>,
+) -> Result<SwapResult> {
+    if !pool.swap_enabled() {
+        return Err(anyhow!("Swaps are disabled for this pool"));
+    }
+    if amount == 0 {
+        return Err(anyhow!("Swap amount must be positive"));
+    }
+    let sqrt_price_limit = sqrt_price_limit.unwrap_or(if zero_for_one {
+        MIN_SQRT_PRICE_X64 + 1
+    } else {
+        MAX_SQRT_PRICE_X64 - 1
+    });
+    let limit_is_valid = if zero_for_one {
+        sqrt_price_limit < pool.sqrt_price_x64 && sqrt_price_limit > MIN_SQRT_PRICE_X64
+    } else {
+        sqrt_price_limit > pool.sqrt_price_x64 && sqrt_price_limit < MAX_SQRT_PRICE_X64
+    };
+    if !limit_is_valid {
+        return Err(anyhow!("Invalid sqrt price limit {}", sqrt_price_limit));
+    }
+
+    let mut traversal = RaydiumClmmTickTraversal::new(pool, extension, tick_arrays, zero_for_one)?;
+    let result = clmm::swap(
+        &mut traversal,
+        SwapParams {
+            sqrt_price: pool.sqrt_price_x64,
+            tick_current: pool.tick_current,
+            liquidity: pool.liquidity,
+            fee_rate: trade_fee_rate,
+            amount,
+            amount_specified_is_input: is_base_input,
+            a_to_b: zero_for_one,
+            sqrt_price_limit,
+        },
+    )?;
+
+    if result.amount_in == 0 || result.amount_out == 0 {
+        return Err(anyhow!("Swap amount is too small"));
+    }
+    // Exact-out swaps must deliver the full amount
+    if !is_base_input && result.amount_remaining > 0 {
+        return Err(anyhow!(
+            "Swap can only fill {} of {}",
+            result.amount_out,
+            amount
+        ));
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::raydium::clmm_info::{
+        tick_count, TICK_ARRAY_BITMAP_SIZE, TICK_ARRAY_SIZE_USIZE,
+    };
+    use solana_program::pubkey::Pubkey;
+
+    const TICK_SPACING: u16 = 10;
+    const TRADE_FEE_RATE: u32 = 2_500;
+    const WIDE_LIQUIDITY: u128 = 1_000_000_000_000;
+    const NARROW_LIQUIDITY: u128 = 500_000_000_000;
+
+    fn tick_array(start_tick_index: i32, initialized: &[(i32, i128)]) -> TickArrayState {
+        let mut ticks = [TickState::default(); TICK_ARRAY_SIZE_USIZE];
+        for (i, tick) in ticks.iter_mut().enumerate() {
+            tick.tick = start_tick_index + i as i32 * TICK_SPACING as i32;
+        }
+        for &(tick_index, liquidity_net) in initialized {
+            let tick = &mut ticks[((tick_index - start_tick_index) / TICK_SPACING as i32) as usize];
+            tick.liquidity_net = liquidity_net;
+            tick.liquidity_gross = liquidity_net.unsigned_abs();
+        }
+        TickArrayState {
+            pool_id: Pubkey::default(),
+            start_tick_index,
+            ticks,
+            initialized_tick_count: initialized.len() as u8,
+            recent_epoch: 0,
+        }
+    }
+
+    /// A wide position over [-1200, 590] and a narrow one over [100, 590]. The array between
+    /// the two holding ticks is not initialized.
+    fn pool_and_tick_arrays() -> (PoolState, Vec<TickArrayState>) {
+        let tick_arrays = vec![
+            tick_array(
+                0,
+                &[
+                    (100, NARROW_LIQUIDITY as i128),
+                    (590, -((WIDE_LIQUIDITY + NARROW_LIQUIDITY) as i128)),
+                ],
+            ),
+            tick_array(-1200, &[(-1200, WIDE_LIQUIDITY as i128)]),
+        ];
+        let mut pool = PoolState {
+            tick_spacing: TICK_SPACING,
+            liquidity: WIDE_LIQUIDITY + NARROW_LIQUIDITY,
+            sqrt_price_x64: get_sqrt_price_at_tick(300).unwrap(),
+            tick_current: 300,
+            ..Default::default()
+        };
+        for array in &tick_arrays {
+            let bit = (array.start_tick_index / tick_count(TICK_SPACING) + TICK_ARRAY_BITMAP_SIZE)
+                as usize;
+            pool.tick_array_bitmap[bit / 64] |= 1 << (bit % 64);
+        }
+        (pool, tick_arrays)
+    }
+
+    #[test]
+    fn swap_crosses_an_initialized_tick() {
+        let (pool, tick_arrays) = pool_and_tick_arrays();
+        let limit = get_sqrt_price_at_tick(-700).unwrap();
+        let result = simulate_swap(
+            &pool,
+            TRADE_FEE_RATE,
+            None,
+            &tick_arrays,
+            1_000_000_000_000,
+            true,
+            true,
+            Some(limit),
+        )
+        .unwrap();
+
+        assert_eq!(result.amount_in, 55_594_228_543);
+        assert_eq!(result.amount_out, 54_555_214_230);
+        assert_eq!(result.fee_amount, 138_985_572);
+        assert_eq!(result.sqrt_price, limit);
+        assert_eq!(result.tick_current, -700);
+        // Crossing tick 100 downwards drops the narrow position
+        assert_eq!(result.liquidity, WIDE_LIQUIDITY);
+        assert_eq!(result.tick_arrays, vec![0, -1200]);
+    }
+
+    #[test]
+    fn swap_needs_every_tick_array_it_crosses() {
+        let (pool, mut tick_arrays) = pool_and_tick_arrays();
+        tick_arrays.truncate(1);
+        let limit = get_sqrt_price_at_tick(-700).unwrap();
+        assert!(simulate_swap(
+            &pool,
+            TRADE_FEE_RATE,
+            None,
+            &tick_arrays,
+            1_000_000_000_000,
+            true,
+            true,
+            Some(limit),
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,92 @@
+use crate::dex::clmm::swap::tick_at_sqrt_price;
+use crate::dex::raydium::clmm_info::{MAX_TICK, MIN_TICK};
+use anyhow::{anyhow, Result};
+
+pub const MIN_SQRT_PRICE_X64: u128 = 4295048016;
+pub const MAX_SQRT_PRICE_X64: u128 = 79226673521066979257578248091;
+
+// 2^64 / 1.0001^(2^i / 2) for the program's fixed-point ladder, bit 0 first
+const TICK_RATIOS_X64: [u128; 19] = [
+    0xfffcb933bd6fb800,
+    0xfff97272373d4000,
+    0xfff2e50f5f657000,
+    0xffe5caca7e10f000,
+    0xffcb9843d60f7000,
+    0xff973b41fa98e800,
+    0xff2ea16466c9b000,
+    0xfe5dee046a9a3800,
+    0xfcbe86c7900bb000,
+    0xf987a7253ac65800,
+    0xf3392b0822bb6000,
+    0xe7159475a2caf000,
+    0xd097f3bdfd2f2000,
+    0xa9f746462d9f8000,
+    0x70d869a156f31c00,
+    0x31be135f97ed3200,
+    0x9aa508b5b85a500,
+    0x5d6af8dedc582c,
+    0x2216e584f5fa,
+];
+
+/// Q64.64 sqrt price at `tick`, computed the way the Raydium CLMM program does
+pub fn get_sqrt_price_at_tick(tick: i32) -> Result<u128> {
+    let abs_tick = tick.unsigned_abs();
+    if abs_tick > MAX_TICK as u32 {
+        return Err(anyhow!("Tick {} is out of range", tick));
+    }
+
+    let mut ratio = if abs_tick & 1 != 0 {
+        TICK_RATIOS_X64[0]
+    } else {
+        1u128 << 64
+    };
+    for (bit, factor) in TICK_RATIOS_X64.iter().enumerate().skip(1) {
+        if abs_tick & (1 << bit) != 0 {
+            ratio = (ratio * factor) >> 64;
+        }
+    }
+
+    if tick > 0 {
+        ratio = u128::MAX / ratio;
+    }
+    Ok(ratio)
+}
+
+/// Largest tick whose sqrt price does not exceed `sqrt_price`
+pub fn get_tick_at_sqrt_price(sqrt_price: u128) -> Result<i32> {
+    if !(MIN_SQRT_PRICE_X64..MAX_SQRT_PRICE_X64).contains(&sqrt_price) {
+        return Err(anyhow!("Sqrt price {} is out of range", sqrt_price));
+    }
+    tick_at_sqrt_price(sqrt_price, MIN_TICK, MAX_TICK, get_sqrt_price_at_tick)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sqrt_price_at_range_ends() {
+        assert_eq!(
+            get_sqrt_price_at_tick(MIN_TICK).unwrap(),
+            MIN_SQRT_PRICE_X64
+        );
+        assert_eq!(
+            get_sqrt_price_at_tick(MAX_TICK).unwrap(),
+            MAX_SQRT_PRICE_X64
+        );
+        assert_eq!(get_sqrt_price_at_tick(0).unwrap(), 1 << 64);
+        assert!(get_sqrt_price_at_tick(MAX_TICK + 1).is_err());
+    }
+
+    #[test]
+    fn tick_at_sqrt_price_inverts() {
+        assert_eq!(
+            get_tick_at_sqrt_price(MIN_SQRT_PRICE_X64).unwrap(),
+            MIN_TICK
+        );
+        assert_eq!(get_tick_at_sqrt_price(1 << 64).unwrap(), 0);
+        assert_eq!(get_tick_at_sqrt_price((1 << 64) - 1).unwrap(), -1);
+        // The program's range excludes the maximum price itself
+        assert!(get_tick_at_sqrt_price(MAX_SQRT_PRICE_X64).is_err());
+    }
+}
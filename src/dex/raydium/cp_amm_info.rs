@@ -1,71 +1,322 @@
 use solana_program::pubkey::Pubkey;
 use anyhow::Result;
 
+/// Denominator of every fee rate in `AmmConfig`
+pub const FEE_RATE_DENOMINATOR: u64 = 1_000_000;
+
+pub const POOL_STATE_SIZE: usize = 637;
+pub const AMM_CONFIG_SIZE: usize = 236;
+
+// PoolState account (after the 8 byte discriminator)
 const AMM_CONFIG_OFFSET: usize = 8; // amm_config
-// const POOL_CREATOR_OFFSET: usize = 40; // pool_creator
+const POOL_CREATOR_OFFSET: usize = 40; // pool_creator
 const TOKEN_0_VAULT_OFFSET: usize = 72; // token_0_vault
 const TOKEN_1_VAULT_OFFSET: usize = 104; // token_1_vault
-// const LP_MINT_OFFSET: usize = 136; // lp_mint
+const LP_MINT_OFFSET: usize = 136; // lp_mint
 const TOKEN_0_MINT_OFFSET: usize = 168; // token_0_mint
 const TOKEN_1_MINT_OFFSET: usize = 200; // token_1_mint
-// const TOKEN_0_PROGRAM_OFFSET: usize = 232; // token_0_program
-// const TOKEN_1_PROGRAM_OFFSET: usize = 264; // token_1_program
+const TOKEN_0_PROGRAM_OFFSET: usize = 232; // token_0_program
+const TOKEN_1_PROGRAM_OFFSET: usize = 264; // token_1_program
 const OBSERVATION_KEY_OFFSET: usize = 296; // observation_key
+const AUTH_BUMP_OFFSET: usize = 328; // auth_bump
+const STATUS_OFFSET: usize = 329; // status
+const LP_MINT_DECIMALS_OFFSET: usize = 330; // lp_mint_decimals
+const MINT_0_DECIMALS_OFFSET: usize = 331; // mint_0_decimals
+const MINT_1_DECIMALS_OFFSET: usize = 332; // mint_1_decimals
+const LP_SUPPLY_OFFSET: usize = 333; // lp_supply
+const PROTOCOL_FEES_TOKEN_0_OFFSET: usize = 341; // protocol_fees_token_0
+const PROTOCOL_FEES_TOKEN_1_OFFSET: usize = 349; // protocol_fees_token_1
+const FUND_FEES_TOKEN_0_OFFSET: usize = 357; // fund_fees_token_0
+const FUND_FEES_TOKEN_1_OFFSET: usize = 365; // fund_fees_token_1
+const OPEN_TIME_OFFSET: usize = 373; // open_time
+// const RECENT_EPOCH_OFFSET: usize = 381; // recent_epoch
+const CREATOR_FEE_ON_OFFSET: usize = 389; // creator_fee_on
+const ENABLE_CREATOR_FEE_OFFSET: usize = 390; // enable_creator_fee
+const CREATOR_FEES_TOKEN_0_OFFSET: usize = 397; // creator_fees_token_0
+const CREATOR_FEES_TOKEN_1_OFFSET: usize = 405; // creator_fees_token_1
+
+// AmmConfig account (after the 8 byte discriminator)
+const CONFIG_DISABLE_CREATE_POOL_OFFSET: usize = 9; // disable_create_pool
+const CONFIG_INDEX_OFFSET: usize = 10; // index
+const CONFIG_TRADE_FEE_RATE_OFFSET: usize = 12; // trade_fee_rate
+const CONFIG_PROTOCOL_FEE_RATE_OFFSET: usize = 20; // protocol_fee_rate
+const CONFIG_FUND_FEE_RATE_OFFSET: usize = 28; // fund_fee_rate
+const CONFIG_CREATE_POOL_FEE_OFFSET: usize = 36; // create_pool_fee
+const CONFIG_PROTOCOL_OWNER_OFFSET: usize = 44; // protocol_owner
+const CONFIG_FUND_OWNER_OFFSET: usize = 76; // fund_owner
+const CONFIG_CREATOR_FEE_RATE_OFFSET: usize = 108; // creator_fee_rate
+
+/// Bit of `status` that disables swaps
+const STATUS_SWAP_DISABLED: u8 = 1 << 2;
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
+}
 
-#[derive(Debug)]
+/// Decoded Raydium CP-Swap `PoolState` account
+#[derive(Debug, Clone)]
 pub struct RaydiumCpAmmInfo {
-    pub token_0_mint: Pubkey,
-    pub token_1_mint: Pubkey,
+    pub amm_config: Pubkey,
+    pub pool_creator: Pubkey,
     pub token_0_vault: Pubkey,
     pub token_1_vault: Pubkey,
-    pub amm_config: Pubkey,
+    pub lp_mint: Pubkey,
+    pub token_0_mint: Pubkey,
+    pub token_1_mint: Pubkey,
+    pub token_0_program: Pubkey,
+    pub token_1_program: Pubkey,
     pub observation_key: Pubkey,
+    pub auth_bump: u8,
+    pub status: u8,
+    pub lp_mint_decimals: u8,
+    pub mint_0_decimals: u8,
+    pub mint_1_decimals: u8,
+    pub lp_supply: u64,
+    pub protocol_fees_token_0: u64,
+    pub protocol_fees_token_1: u64,
+    pub fund_fees_token_0: u64,
+    pub fund_fees_token_1: u64,
+    pub open_time: u64,
+    pub creator_fee_on: u8,
+    pub enable_creator_fee: bool,
+    pub creator_fees_token_0: u64,
+    pub creator_fees_token_1: u64,
 }
 
 impl RaydiumCpAmmInfo {
     pub fn load_checked(data: &[u8]) -> Result<Self> {
-        if data.len() < OBSERVATION_KEY_OFFSET + 32 {
+        if data.len() < POOL_STATE_SIZE {
             return Err(anyhow::anyhow!("Invalid data length for RaydiumCpAmmInfo"));
         }
-        
-        let token_0_vault = Pubkey::new_from_array(
-            data[TOKEN_0_VAULT_OFFSET..TOKEN_0_VAULT_OFFSET + 32]
-                .try_into()
-                .unwrap(),
-        );
-        let token_1_vault = Pubkey::new_from_array(
-            data[TOKEN_1_VAULT_OFFSET..TOKEN_1_VAULT_OFFSET + 32]
-                .try_into()
-                .unwrap(),
-        );
-        let token_0_mint = Pubkey::new_from_array(
-            data[TOKEN_0_MINT_OFFSET..TOKEN_0_MINT_OFFSET + 32]
-                .try_into()
-                .unwrap(),
-        );
-        let token_1_mint = Pubkey::new_from_array(
-            data[TOKEN_1_MINT_OFFSET..TOKEN_1_MINT_OFFSET + 32]
-                .try_into()
-                .unwrap(),
-        );
-        let amm_config = Pubkey::new_from_array(
-            data[AMM_CONFIG_OFFSET..AMM_CONFIG_OFFSET + 32]
-                .try_into()
-                .unwrap(),
-        );
-        let observation_key = Pubkey::new_from_array(
-            data[OBSERVATION_KEY_OFFSET..OBSERVATION_KEY_OFFSET + 32]
-                .try_into()
-                .unwrap(),
-        );
-        
+
         Ok(Self {
-            token_0_mint,
-            token_1_mint,
-            token_0_vault,
-            token_1_vault,
-            amm_config,
-            observation_key,
+            amm_config: read_pubkey(data, AMM_CONFIG_OFFSET),
+            pool_creator: read_pubkey(data, POOL_CREATOR_OFFSET),
+            token_0_vault: read_pubkey(data, TOKEN_0_VAULT_OFFSET),
+            token_1_vault: read_pubkey(data, TOKEN_1_VAULT_OFFSET),
+            lp_mint: read_pubkey(data, LP_MINT_OFFSET),
+            token_0_mint: read_pubkey(data, TOKEN_0_MINT_OFFSET),
+            token_1_mint: read_pubkey(data, TOKEN_1_MINT_OFFSET),
+            token_0_program: read_pubkey(data, TOKEN_0_PROGRAM_OFFSET),
+            token_1_program: read_pubkey(data, TOKEN_1_PROGRAM_OFFSET),
+            observation_key: read_pubkey(data, OBSERVATION_KEY_OFFSET),
+            auth_bump: data[AUTH_BUMP_OFFSET],
+            status: data[STATUS_OFFSET],
+            lp_mint_decimals: data[LP_MINT_DECIMALS_OFFSET],
+            mint_0_decimals: data[MINT_0_DECIMALS_OFFSET],
+            mint_1_decimals: data[MINT_1_DECIMALS_OFFSET],
+            lp_supply: read_u64(data, LP_SUPPLY_OFFSET),
+            protocol_fees_token_0: read_u64(data, PROTOCOL_FEES_TOKEN_0_OFFSET),
+            protocol_fees_token_1: read_u64(data, PROTOCOL_FEES_TOKEN_1_OFFSET),
+            fund_fees_token_0: read_u64(data, FUND_FEES_TOKEN_0_OFFSET),
+            fund_fees_token_1: read_u64(data, FUND_FEES_TOKEN_1_OFFSET),
+            open_time: read_u64(data, OPEN_TIME_OFFSET),
+            creator_fee_on: data[CREATOR_FEE_ON_OFFSET],
+            enable_creator_fee: data[ENABLE_CREATOR_FEE_OFFSET] != 0,
+            creator_fees_token_0: read_u64(data, CREATOR_FEES_TOKEN_0_OFFSET),
+            creator_fees_token_1: read_u64(data, CREATOR_FEES_TOKEN_1_OFFSET),
         })
     }
+
+    /// Whether the pool status bits allow swaps
+    pub fn swap_enabled(&self) -> bool {
+        self.status & STATUS_SWAP_DISABLED == 0
+    }
+
+    /// Tradeable reserves: vault balances minus the protocol, fund and creator fees that are
+    /// held in the vaults but not owned by LPs, returned as `(token_0, token_1)`
+    pub fn vault_amounts_without_fees(&self, vault_0_amount: u64, vault_1_amount: u64) -> Result<(u64, u64)> {
+        let token_0 = vault_0_amount
+            .checked_sub(self.protocol_fees_token_0)
+            .and_then(|amount| amount.checked_sub(self.fund_fees_token_0))
+            .and_then(|amount| amount.checked_sub(self.creator_fees_token_0))
+            .ok_or_else(|| anyhow::anyhow!("Token 0 vault balance is below accrued fees"))?;
+        let token_1 = vault_1_amount
+            .checked_sub(self.protocol_fees_token_1)
+            .and_then(|amount| amount.checked_sub(self.fund_fees_token_1))
+            .and_then(|amount| amount.checked_sub(self.creator_fees_token_1))
+            .ok_or_else(|| anyhow::anyhow!("Token 1 vault balance is below accrued fees"))?;
+        Ok((token_0, token_1))
+    }
+}
+
+/// Decoded Raydium CP-Swap `AmmConfig` account, which sets a pool's fee tier
+#[derive(Debug, Clone)]
+pub struct RaydiumCpAmmConfig {
+    pub disable_create_pool: bool,
+    pub index: u16,
+    pub trade_fee_rate: u64,
+    pub protocol_fee_rate: u64,
+    pub fund_fee_rate: u64,
+    pub create_pool_fee: u64,
+    pub protocol_owner: Pubkey,
+    pub fund_owner: Pubkey,
+    pub creator_fee_rate: u64,
+}
+
+impl RaydiumCpAmmConfig {
+    pub fn load_checked(data: &[u8]) -> Result<Self> {
+        if data.len() < AMM_CONFIG_SIZE {
+            return Err(anyhow::anyhow!("Invalid data length for RaydiumCpAmmConfig"));
+        }
+
+        let config = Self {
+            disable_create_pool: data[CONFIG_DISABLE_CREATE_POOL_OFFSET] != 0,
+            index: u16::from_le_bytes(
+                data[CONFIG_INDEX_OFFSET..CONFIG_INDEX_OFFSET + 2]
+                    .try_into()
+                    .unwrap(),
+            ),
+            trade_fee_rate: read_u64(data, CONFIG_TRADE_FEE_RATE_OFFSET),
+            protocol_fee_rate: read_u64(data, CONFIG_PROTOCOL_FEE_RATE_OFFSET),
+            fund_fee_rate: read_u64(data, CONFIG_FUND_FEE_RATE_OFFSET),
+            create_pool_fee: read_u64(data, CONFIG_CREATE_POOL_FEE_OFFSET),
+            protocol_owner: read_pubkey(data, CONFIG_PROTOCOL_OWNER_OFFSET),
+            fund_owner: read_pubkey(data, CONFIG_FUND_OWNER_OFFSET),
+            creator_fee_rate: read_u64(data, CONFIG_CREATOR_FEE_RATE_OFFSET),
+        };
+
+        if config.trade_fee_rate >= FEE_RATE_DENOMINATOR {
+            return Err(anyhow::anyhow!(
+                "RaydiumCpAmmConfig trade fee rate {} is out of range",
+                config.trade_fee_rate
+            ));
+        }
+
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::layout::{key, Layout};
+
+    const POOL_STATE_DISCRIMINATOR: [u8; 8] = [247, 237, 227, 245, 215, 195, 222, 70];
+    const AMM_CONFIG_DISCRIMINATOR: [u8; 8] = [218, 244, 33, 104, 203, 203, 43, 111];
+    const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+    const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qA1xxoxHSgyfnkZTrNEwE7n5Xt";
+    const TOKEN_PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+
+    struct PoolFixture {
+        amm_config: Pubkey,
+        token_0_vault: Pubkey,
+        token_1_vault: Pubkey,
+        status: u8,
+    }
+
+    /// `PoolState` of a SOL/USDC pool, written in the order the CP-Swap program declares it
+    fn pool_state(fixture: &PoolFixture) -> Vec<u8> {
+        let mut layout = Layout::anchor(POOL_STATE_DISCRIMINATOR);
+        layout
+            .pubkey(&fixture.amm_config)
+            .pubkey(&Pubkey::new_unique()) // pool_creator
+            .pubkey(&fixture.token_0_vault)
+            .pubkey(&fixture.token_1_vault)
+            .pubkey(&Pubkey::new_unique()) // lp_mint
+            .pubkey(&key(SOL_MINT))
+            .pubkey(&key(USDC_MINT))
+            .pubkey(&key(TOKEN_PROGRAM))
+            .pubkey(&key(TOKEN_PROGRAM))
+            .pubkey(&Pubkey::new_unique()) // observation_key
+            .u8(253) // auth_bump
+            .u8(fixture.status)
+            .u8(9) // lp_mint_decimals
+            .u8(9) // mint_0_decimals
+            .u8(6) // mint_1_decimals
+            .u64(5_000_000_000) // lp_supply
+            .u64(11) // protocol_fees_token_0
+            .u64(12) // protocol_fees_token_1
+            .u64(21) // fund_fees_token_0
+            .u64(22) // fund_fees_token_1
+            .u64(1_720_000_000) // open_time
+            .u64(650) // recent_epoch
+            .u8(0) // creator_fee_on
+            .bool(true) // enable_creator_fee
+            .zeros(6) // padding1
+            .u64(31) // creator_fees_token_0
+            .u64(32) // creator_fees_token_1
+            .zeros(28 * 8); // padding
+        assert_eq!(layout.len(), POOL_STATE_SIZE);
+        layout.finish()
+    }
+
+    /// `AmmConfig` with the rates of the 0.25% fee tier (index 0)
+    fn amm_config(trade_fee_rate: u64) -> Vec<u8> {
+        let mut layout = Layout::anchor(AMM_CONFIG_DISCRIMINATOR);
+        layout
+            .u8(255) // bump
+            .bool(false) // disable_create_pool
+            .u16(0) // index
+            .u64(trade_fee_rate)
+            .u64(120_000) // protocol_fee_rate
+            .u64(40_000) // fund_fee_rate
+            .u64(150_000_000) // create_pool_fee
+            .pubkey(&key(SOL_MINT)) // protocol_owner
+            .pubkey(&key(USDC_MINT)) // fund_owner
+            .u64(0) // creator_fee_rate
+            .zeros(15 * 8); // padding
+        assert_eq!(layout.len(), AMM_CONFIG_SIZE);
+        layout.finish()
+    }
+
+    fn fixture(status: u8) -> PoolFixture {
+        PoolFixture {
+            amm_config: Pubkey::new_unique(),
+            token_0_vault: Pubkey::new_unique(),
+            token_1_vault: Pubkey::new_unique(),
+            status,
+        }
+    }
+
+    #[test]
+    fn decodes_pool_state() {
+        let fixture = fixture(0);
+        let info = RaydiumCpAmmInfo::load_checked(&pool_state(&fixture)).unwrap();
+
+        assert_eq!(info.amm_config, fixture.amm_config);
+        assert_eq!(info.token_0_vault, fixture.token_0_vault);
+        assert_eq!(info.token_1_vault, fixture.token_1_vault);
+        assert_eq!(info.token_0_mint, key(SOL_MINT));
+        assert_eq!(info.token_1_mint, key(USDC_MINT));
+        assert_eq!(info.token_0_program, key(TOKEN_PROGRAM));
+        assert_eq!(info.token_1_program, key(TOKEN_PROGRAM));
+        assert_eq!((info.mint_0_decimals, info.mint_1_decimals), (9, 6));
+        assert_eq!(info.open_time, 1_720_000_000);
+        assert!(info.enable_creator_fee);
+        assert!(info.swap_enabled());
+        assert_eq!(info.vault_amounts_without_fees(1_000, 1_000).unwrap(), (937, 934));
+    }
+
+    #[test]
+    fn swap_disabled_status_bit_blocks_swaps() {
+        let info = RaydiumCpAmmInfo::load_checked(&pool_state(&fixture(STATUS_SWAP_DISABLED))).unwrap();
+        assert!(!info.swap_enabled());
+
+        let info = RaydiumCpAmmInfo::load_checked(&pool_state(&fixture(1))).unwrap();
+        assert!(info.swap_enabled());
+    }
+
+    #[test]
+    fn decodes_amm_config() {
+        let config = RaydiumCpAmmConfig::load_checked(&amm_config(2_500)).unwrap();
+
+        assert_eq!(config.index, 0);
+        assert!(!config.disable_create_pool);
+        assert_eq!(config.trade_fee_rate, 2_500);
+        assert_eq!(config.protocol_fee_rate, 120_000);
+        assert_eq!(config.fund_fee_rate, 40_000);
+        assert_eq!(config.create_pool_fee, 150_000_000);
+        assert_eq!(config.creator_fee_rate, 0);
+    }
+
+    #[test]
+    fn rejects_out_of_range_trade_fee() {
+        assert!(RaydiumCpAmmConfig::load_checked(&amm_config(FEE_RATE_DENOMINATOR)).is_err());
+        assert!(RaydiumCpAmmConfig::load_checked(&amm_config(2_500)[..AMM_CONFIG_SIZE - 1]).is_err());
+    }
 }
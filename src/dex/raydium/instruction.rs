@@ -0,0 +1,179 @@
+use crate::{
+    chain::constants::{memo_program_id, TOKEN_2022_PROGRAM_ID},
+    dex::raydium::{
+        constants::{
+            raydium_authority, raydium_clmm_program_id, raydium_cp_authority,
+            raydium_cp_program_id, raydium_program_id,
+        },
+        market_info::MarketAccounts,
+    },
+};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+const SWAP_BASE_IN_TAG: u8 = 9;
+const SWAP_BASE_INPUT_DISCRIMINATOR: [u8; 8] = [143, 190, 90, 218, 196, 30, 51, 222];
+const SWAP_V2_DISCRIMINATOR: [u8; 8] = [43, 4, 237, 11, 26, 201, 30, 98];
+
+/// Accounts of an AMM v4 `swap_base_in` other than the program's own
+#[derive(Debug, Clone)]
+pub struct SwapBaseInAccounts {
+    pub amm: Pubkey,
+    pub amm_open_orders: Pubkey,
+    pub pool_coin_vault: Pubkey,
+    pub pool_pc_vault: Pubkey,
+    pub market: MarketAccounts,
+    pub user_source: Pubkey,
+    pub user_destination: Pubkey,
+    pub user_owner: Pubkey,
+}
+
+/// AMM v4 `swap_base_in`: sell exactly `amount_in`, failing below `minimum_amount_out`
+pub fn swap_base_in(
+    accounts: &SwapBaseInAccounts,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Instruction {
+    let mut data = vec![SWAP_BASE_IN_TAG];
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&minimum_amount_out.to_le_bytes());
+
+    let market = &accounts.market;
+    Instruction {
+        program_id: raydium_program_id(),
+        accounts: vec![
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new(accounts.amm, false),
+            AccountMeta::new_readonly(raydium_authority(), false),
+            AccountMeta::new(accounts.amm_open_orders, false),
+            AccountMeta::new(accounts.pool_coin_vault, false),
+            AccountMeta::new(accounts.pool_pc_vault, false),
+            AccountMeta::new_readonly(market.program, false),
+            AccountMeta::new(market.market, false),
+            AccountMeta::new(market.bids, false),
+            AccountMeta::new(market.asks, false),
+            AccountMeta::new(market.event_queue, false),
+            AccountMeta::new(market.coin_vault, false),
+            AccountMeta::new(market.pc_vault, false),
+            AccountMeta::new_readonly(market.vault_signer, false),
+            AccountMeta::new(accounts.user_source, false),
+            AccountMeta::new(accounts.user_destination, false),
+            AccountMeta::new_readonly(accounts.user_owner, true),
+        ],
+        data,
+    }
+}
+
+/// Accounts of a CP-Swap `swap_base_input` other than the program's own
+#[derive(Debug, Clone)]
+pub struct SwapBaseInputAccounts {
+    pub payer: Pubkey,
+    pub amm_config: Pubkey,
+    pub pool_state: Pubkey,
+    pub input_token_account: Pubkey,
+    pub output_token_account: Pubkey,
+    pub input_vault: Pubkey,
+    pub output_vault: Pubkey,
+    pub input_token_program: Pubkey,
+    pub output_token_program: Pubkey,
+    pub input_token_mint: Pubkey,
+    pub output_token_mint: Pubkey,
+    pub observation_state: Pubkey,
+}
+
+/// CP-Swap `swap_base_input`: sell exactly `amount_in`, failing below `minimum_amount_out`
+pub fn swap_base_input(
+    accounts: &SwapBaseInputAccounts,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Instruction {
+    let mut data = SWAP_BASE_INPUT_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&minimum_amount_out.to_le_bytes());
+
+    Instruction {
+        program_id: raydium_cp_program_id(),
+        accounts: vec![
+            AccountMeta::new_readonly(accounts.payer, true),
+            AccountMeta::new_readonly(raydium_cp_authority(), false),
+            AccountMeta::new_readonly(accounts.amm_config, false),
+            AccountMeta::new(accounts.pool_state, false),
+            AccountMeta::new(accounts.input_token_account, false),
+            AccountMeta::new(accounts.output_token_account, false),
+            AccountMeta::new(accounts.input_vault, false),
+            AccountMeta::new(accounts.output_vault, false),
+            AccountMeta::new_readonly(accounts.input_token_program, false),
+            AccountMeta::new_readonly(accounts.output_token_program, false),
+            AccountMeta::new_readonly(accounts.input_token_mint, false),
+            AccountMeta::new_readonly(accounts.output_token_mint, false),
+            AccountMeta::new(accounts.observation_state, false),
+        ],
+        data,
+    }
+}
+
+/// Accounts of a CLMM `swap_v2` other than the program's own
+#[derive(Debug, Clone)]
+pub struct ClmmSwapV2Accounts {
+    pub payer: Pubkey,
+    pub amm_config: Pubkey,
+    pub pool_state: Pubkey,
+    pub input_token_account: Pubkey,
+    pub output_token_account: Pubkey,
+    pub input_vault: Pubkey,
+    pub output_vault: Pubkey,
+    pub observation_state: Pubkey,
+    pub input_vault_mint: Pubkey,
+    pub output_vault_mint: Pubkey,
+    /// Only passed when the account exists
+    pub bitmap_extension: Option<Pubkey>,
+    /// Initialized tick arrays from the current one in the swap's direction
+    pub tick_arrays: Vec<Pubkey>,
+}
+
+/// CLMM `swap_v2` exact in: sell exactly `amount_in`, failing below `minimum_amount_out`.
+/// The price limit is left at zero, which the program reads as the end of the range.
+pub fn clmm_swap_v2(
+    accounts: &ClmmSwapV2Accounts,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Instruction {
+    let mut data = SWAP_V2_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&minimum_amount_out.to_le_bytes());
+    data.extend_from_slice(&0u128.to_le_bytes()); // sqrt_price_limit_x64
+    data.push(1); // is_base_input
+
+    let mut metas = vec![
+        AccountMeta::new_readonly(accounts.payer, true),
+        AccountMeta::new_readonly(accounts.amm_config, false),
+        AccountMeta::new(accounts.pool_state, false),
+        AccountMeta::new(accounts.input_token_account, false),
+        AccountMeta::new(accounts.output_token_account, false),
+        AccountMeta::new(accounts.input_vault, false),
+        AccountMeta::new(accounts.output_vault, false),
+        AccountMeta::new(accounts.observation_state, false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new_readonly(TOKEN_2022_PROGRAM_ID, false),
+        AccountMeta::new_readonly(memo_program_id(), false),
+        AccountMeta::new_readonly(accounts.input_vault_mint, false),
+        AccountMeta::new_readonly(accounts.output_vault_mint, false),
+    ];
+    if let Some(bitmap_extension) = accounts.bitmap_extension {
+        metas.push(AccountMeta::new(bitmap_extension, false));
+    }
+    metas.extend(
+        accounts
+            .tick_arrays
+            .iter()
+            .map(|tick_array| AccountMeta::new(*tick_array, false)),
+    );
+
+    Instruction {
+        program_id: raydium_clmm_program_id(),
+        accounts: metas,
+        data,
+    }
+}
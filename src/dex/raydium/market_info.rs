@@ -0,0 +1,101 @@
+use anyhow::Result;
+use solana_program::pubkey::Pubkey;
+
+// OpenBook / Serum v3 `MarketState`, after the 5 byte "serum" head padding
+const ACCOUNT_FLAGS_OFFSET: usize = 5; // account_flags
+const OWN_ADDRESS_OFFSET: usize = 13; // own_address
+const VAULT_SIGNER_NONCE_OFFSET: usize = 45; // vault_signer_nonce
+const COIN_MINT_OFFSET: usize = 53; // coin_mint
+const PC_MINT_OFFSET: usize = 85; // pc_mint
+const COIN_VAULT_OFFSET: usize = 117; // coin_vault
+const PC_VAULT_OFFSET: usize = 165; // pc_vault
+const EVENT_QUEUE_OFFSET: usize = 253; // event_q
+const BIDS_OFFSET: usize = 285; // bids
+const ASKS_OFFSET: usize = 317; // asks
+pub const MARKET_STATE_SIZE: usize = 388;
+
+const HEAD_PADDING: &[u8; 5] = b"serum";
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
+}
+
+/// Decoded OpenBook market that a Raydium AMM v4 pool is attached to
+#[derive(Debug, Clone)]
+pub struct MarketInfo {
+    pub account_flags: u64,
+    pub own_address: Pubkey,
+    pub vault_signer_nonce: u64,
+    pub coin_mint: Pubkey,
+    pub pc_mint: Pubkey,
+    pub coin_vault: Pubkey,
+    pub pc_vault: Pubkey,
+    pub event_queue: Pubkey,
+    pub bids: Pubkey,
+    pub asks: Pubkey,
+}
+
+impl MarketInfo {
+    pub fn load_checked(data: &[u8]) -> Result<Self> {
+        if data.len() < MARKET_STATE_SIZE {
+            return Err(anyhow::anyhow!("Invalid data length for MarketInfo"));
+        }
+        if &data[..5] != HEAD_PADDING {
+            return Err(anyhow::anyhow!("Invalid head padding for MarketInfo"));
+        }
+
+        Ok(Self {
+            account_flags: read_u64(data, ACCOUNT_FLAGS_OFFSET),
+            own_address: read_pubkey(data, OWN_ADDRESS_OFFSET),
+            vault_signer_nonce: read_u64(data, VAULT_SIGNER_NONCE_OFFSET),
+            coin_mint: read_pubkey(data, COIN_MINT_OFFSET),
+            pc_mint: read_pubkey(data, PC_MINT_OFFSET),
+            coin_vault: read_pubkey(data, COIN_VAULT_OFFSET),
+            pc_vault: read_pubkey(data, PC_VAULT_OFFSET),
+            event_queue: read_pubkey(data, EVENT_QUEUE_OFFSET),
+            bids: read_pubkey(data, BIDS_OFFSET),
+            asks: read_pubkey(data, ASKS_OFFSET),
+        })
+    }
+
+    /// Accounts of this market that `swap_base_in` passes through
+    pub fn swap_accounts(
+        &self,
+        market: &Pubkey,
+        market_program: &Pubkey,
+    ) -> Result<MarketAccounts> {
+        let vault_signer = Pubkey::create_program_address(
+            &[market.as_ref(), &self.vault_signer_nonce.to_le_bytes()],
+            market_program,
+        )
+        .map_err(|e| anyhow::anyhow!("Failed to derive market vault signer: {}", e))?;
+
+        Ok(MarketAccounts {
+            program: *market_program,
+            market: *market,
+            bids: self.bids,
+            asks: self.asks,
+            event_queue: self.event_queue,
+            coin_vault: self.coin_vault,
+            pc_vault: self.pc_vault,
+            vault_signer,
+        })
+    }
+}
+
+/// The OpenBook accounts a Raydium AMM v4 swap expects
+#[derive(Debug, Clone, Default)]
+pub struct MarketAccounts {
+    pub program: Pubkey,
+    pub market: Pubkey,
+    pub bids: Pubkey,
+    pub asks: Pubkey,
+    pub event_queue: Pubkey,
+    pub coin_vault: Pubkey,
+    pub pc_vault: Pubkey,
+    pub vault_signer: Pubkey,
+}
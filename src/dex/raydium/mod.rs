@@ -2,8 +2,16 @@ pub mod amm_info;
 pub mod constants;
 pub mod cp_amm_info;
 pub mod clmm_info;
+pub mod clmm_swap;
+pub mod clmm_tick_math;
+pub mod instruction;
+pub mod market_info;
+pub mod quote;
 
 pub use amm_info::RaydiumAmmInfo;
 pub use constants::*;
 pub use cp_amm_info::RaydiumCpAmmInfo;
-pub use clmm_info::{PoolState, get_tick_array_pubkeys};
+pub use clmm_info::{
+    get_tick_array_pubkey, get_tick_array_pubkeys, PoolState, TickArrayBitmapExtension,
+    TickArrayState, TickState,
+};
@@ -0,0 +1,250 @@
+use crate::dex::{
+    quote::{ceil_div, constant_product_amount_in, constant_product_amount_out, to_u64, SwapQuote},
+    raydium::cp_amm_info::FEE_RATE_DENOMINATOR,
+};
+use anyhow::{anyhow, Result};
+
+/// The AMM v4 program's `checked_ceil_div` quotient: rounds up, except that a quotient below
+/// one rounds to the nearest integer instead
+fn amm_ceil_div(numerator: u128, denominator: u128) -> Option<u128> {
+    let quotient = numerator.checked_div(denominator)?;
+    if quotient == 0 {
+        return Some(if numerator.checked_mul(2)? >= denominator {
+            1
+        } else {
+            0
+        });
+    }
+    if !numerator.is_multiple_of(denominator) {
+        quotient.checked_add(1)
+    } else {
+        Some(quotient)
+    }
+}
+
+/// Raydium AMM v4 swap_base_in: the fee is taken from the input, rounded as the program does
+pub fn amm_quote_exact_in(
+    amount_in: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    fee_numerator: u64,
+    fee_denominator: u64,
+) -> Result<SwapQuote> {
+    let fee = amm_ceil_div(
+        amount_in as u128 * fee_numerator as u128,
+        fee_denominator as u128,
+    )
+    .ok_or_else(|| anyhow!("Invalid swap fee {}/{}", fee_numerator, fee_denominator))?;
+    let amount_in_after_fee = (amount_in as u128)
+        .checked_sub(fee)
+        .ok_or_else(|| anyhow!("Swap fee exceeds the input"))?;
+    let amount_out =
+        constant_product_amount_out(amount_in_after_fee, reserve_in as u128, reserve_out as u128)?;
+
+    Ok(SwapQuote {
+        amount_in,
+        amount_out: to_u64(amount_out)?,
+        fee_amount: to_u64(fee)?,
+    })
+}
+
+/// Raydium AMM v4 swap_base_out: the input before fees is grossed up by the fee, both rounded
+/// as the program does
+pub fn amm_quote_exact_out(
+    amount_out: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    fee_numerator: u64,
+    fee_denominator: u64,
+) -> Result<SwapQuote> {
+    if fee_numerator >= fee_denominator {
+        return Err(anyhow!(
+            "Invalid swap fee {}/{}",
+            fee_numerator,
+            fee_denominator
+        ));
+    }
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(anyhow!("Pool has an empty reserve"));
+    }
+    if amount_out >= reserve_out {
+        return Err(anyhow!(
+            "Requested output {} exceeds the reserve of {}",
+            amount_out,
+            reserve_out
+        ));
+    }
+    let amount_in_without_fee = amm_ceil_div(
+        amount_out as u128 * reserve_in as u128,
+        (reserve_out - amount_out) as u128,
+    )
+    .ok_or_else(|| anyhow!("Swap input overflowed"))?;
+    let amount_in = amount_in_without_fee
+        .checked_mul(fee_denominator as u128)
+        .and_then(|numerator| amm_ceil_div(numerator, (fee_denominator - fee_numerator) as u128))
+        .ok_or_else(|| anyhow!("Swap input overflowed"))?;
+
+    Ok(SwapQuote {
+        amount_in: to_u64(amount_in)?,
+        amount_out,
+        fee_amount: to_u64(amount_in - amount_in_without_fee)?,
+    })
+}
+
+/// Fee rates of a CP-Swap pool, out of `FEE_RATE_DENOMINATOR`. The protocol and fund fees are
+/// shares of the trade fee and do not change swap amounts.
+#[derive(Debug, Clone, Copy)]
+pub struct CpSwapFees {
+    pub trade_fee_rate: u64,
+    pub creator_fee_rate: u64,
+    /// Whether the creator fee is charged on the input token rather than the output
+    pub creator_fee_on_input: bool,
+}
+
+fn cp_fee(amount: u128, rate: u64) -> Result<u128> {
+    amount
+        .checked_mul(rate as u128)
+        .and_then(|numerator| ceil_div(numerator, FEE_RATE_DENOMINATOR as u128))
+        .ok_or_else(|| anyhow!("CP-Swap fee overflowed"))
+}
+
+/// Amount that is `post_fee_amount` after a `rate` fee, rounded up
+fn cp_pre_fee_amount(post_fee_amount: u128, rate: u64) -> Result<u128> {
+    if rate == 0 {
+        return Ok(post_fee_amount);
+    }
+    if rate >= FEE_RATE_DENOMINATOR {
+        return Err(anyhow!("Invalid CP-Swap fee rate {}", rate));
+    }
+    post_fee_amount
+        .checked_mul(FEE_RATE_DENOMINATOR as u128)
+        .and_then(|numerator| ceil_div(numerator, (FEE_RATE_DENOMINATOR - rate) as u128))
+        .ok_or_else(|| anyhow!("CP-Swap fee overflowed"))
+}
+
+/// CP-Swap swap_base_input
+pub fn cp_quote_exact_in(
+    amount_in: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    fees: CpSwapFees,
+) -> Result<SwapQuote> {
+    let trade_fee = cp_fee(amount_in as u128, fees.trade_fee_rate)?;
+    let mut creator_fee = 0;
+    let mut amount_in_less_fees = (amount_in as u128)
+        .checked_sub(trade_fee)
+        .ok_or_else(|| anyhow!("Trade fee exceeds the input"))?;
+    if fees.creator_fee_on_input {
+        creator_fee = cp_fee(amount_in as u128, fees.creator_fee_rate)?;
+        amount_in_less_fees = amount_in_less_fees
+            .checked_sub(creator_fee)
+            .ok_or_else(|| anyhow!("Creator fee exceeds the input"))?;
+    }
+
+    let mut amount_out =
+        constant_product_amount_out(amount_in_less_fees, reserve_in as u128, reserve_out as u128)?;
+    if !fees.creator_fee_on_input {
+        creator_fee = cp_fee(amount_out, fees.creator_fee_rate)?;
+        amount_out -= creator_fee.min(amount_out);
+    }
+
+    Ok(SwapQuote {
+        amount_in,
+        amount_out: to_u64(amount_out)?,
+        fee_amount: to_u64(trade_fee + creator_fee)?,
+    })
+}
+
+/// CP-Swap swap_base_output
+pub fn cp_quote_exact_out(
+    amount_out: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    fees: CpSwapFees,
+) -> Result<SwapQuote> {
+    let mut creator_fee = 0;
+    let amount_out_swapped = if fees.creator_fee_on_input {
+        amount_out as u128
+    } else {
+        let amount = cp_pre_fee_amount(amount_out as u128, fees.creator_fee_rate)?;
+        creator_fee = amount - amount_out as u128;
+        amount
+    };
+
+    let amount_in_swapped =
+        constant_product_amount_in(amount_out_swapped, reserve_in as u128, reserve_out as u128)?;
+    let input_fee_rate = if fees.creator_fee_on_input {
+        fees.trade_fee_rate + fees.creator_fee_rate
+    } else {
+        fees.trade_fee_rate
+    };
+    let amount_in = cp_pre_fee_amount(amount_in_swapped, input_fee_rate)?;
+
+    Ok(SwapQuote {
+        amount_in: to_u64(amount_in)?,
+        amount_out,
+        fee_amount: to_u64(amount_in - amount_in_swapped + creator_fee)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESERVE_IN: u64 = 1_000_000_000;
+    const RESERVE_OUT: u64 = 2_000_000_000;
+
+    #[test]
+    fn amm_ceil_div_rounds_small_quotients_to_nearest() {
+        assert_eq!(amm_ceil_div(1, 3), Some(0));
+        assert_eq!(amm_ceil_div(2, 3), Some(1));
+        assert_eq!(amm_ceil_div(7, 2), Some(4));
+        assert_eq!(amm_ceil_div(6, 2), Some(3));
+        assert_eq!(amm_ceil_div(1, 0), None);
+    }
+
+    #[test]
+    fn amm_fee_on_small_inputs() {
+        // 25/10000 of 199 is 0.4975, which the program rounds to no fee at all
+        for (amount_in, fee) in [(199, 0), (200, 1), (400, 1), (401, 2)] {
+            let quote = amm_quote_exact_in(amount_in, RESERVE_IN, RESERVE_OUT, 25, 10_000).unwrap();
+            assert_eq!(quote.fee_amount, fee, "{}", amount_in);
+        }
+    }
+
+    #[test]
+    fn amm_round_trips() {
+        let quote = amm_quote_exact_in(1_000_000, RESERVE_IN, RESERVE_OUT, 25, 10_000).unwrap();
+        assert_eq!(quote.fee_amount, 2_500);
+        assert_eq!(quote.amount_out, 1_993_011);
+
+        let quote =
+            amm_quote_exact_out(quote.amount_out, RESERVE_IN, RESERVE_OUT, 25, 10_000).unwrap();
+        assert_eq!(quote.amount_in, 1_000_000);
+        assert_eq!(quote.fee_amount, 2_500);
+    }
+
+    #[test]
+    fn amm_rejects_degenerate_swaps() {
+        let quote = amm_quote_exact_in(0, RESERVE_IN, RESERVE_OUT, 25, 10_000).unwrap();
+        assert_eq!((quote.amount_out, quote.fee_amount), (0, 0));
+        assert!(amm_quote_exact_out(RESERVE_OUT, RESERVE_IN, RESERVE_OUT, 25, 10_000).is_err());
+        assert!(amm_quote_exact_out(1, RESERVE_IN, RESERVE_OUT, 10_000, 10_000).is_err());
+    }
+
+    #[test]
+    fn cp_round_trips() {
+        let fees = CpSwapFees {
+            trade_fee_rate: 2_500,
+            creator_fee_rate: 0,
+            creator_fee_on_input: true,
+        };
+        let quote = cp_quote_exact_in(1_000_000, RESERVE_IN, RESERVE_OUT, fees).unwrap();
+        assert_eq!(quote.fee_amount, 2_500);
+        assert_eq!(quote.amount_out, 1_993_011);
+
+        let quote = cp_quote_exact_out(quote.amount_out, RESERVE_IN, RESERVE_OUT, fees).unwrap();
+        assert_eq!(quote.amount_in, 1_000_000);
+        assert!(cp_quote_exact_out(RESERVE_OUT, RESERVE_IN, RESERVE_OUT, fees).is_err());
+    }
+}
@@ -1,6 +1,15 @@
 use anyhow::Result;
 use solana_sdk::pubkey::Pubkey;
 
+// The pool's pricing state before these offsets is not publicly documented, so only the
+// token accounts are decoded. Reserves are read from the vaults.
+const BASE_MINT_OFFSET: usize = 2664;
+const QUOTE_MINT_OFFSET: usize = 2696;
+const BASE_VAULT_OFFSET: usize = 2736;
+const QUOTE_VAULT_OFFSET: usize = 2768;
+pub const MIN_POOL_ACCOUNT_SIZE: usize = QUOTE_VAULT_OFFSET + 32;
+
+#[derive(Debug)]
 pub struct SolfiInfo {
     pub base_mint: Pubkey,
     pub quote_mint: Pubkey,
@@ -10,22 +19,88 @@ pub struct SolfiInfo {
 
 impl SolfiInfo {
     pub fn load_checked(data: &[u8]) -> Result<Self> {
-        let (base_mint, quote_mint, base_vault, quote_vault) = load_pubkeys(data);
+        if data.len() < MIN_POOL_ACCOUNT_SIZE {
+            return Err(anyhow::anyhow!(
+                "Invalid data length for SolfiInfo: {} < {}",
+                data.len(),
+                MIN_POOL_ACCOUNT_SIZE
+            ));
+        }
 
         Ok(Self {
-            base_mint,
-            quote_mint,
-            base_vault,
-            quote_vault,
+            base_mint: read_pubkey(data, BASE_MINT_OFFSET),
+            quote_mint: read_pubkey(data, QUOTE_MINT_OFFSET),
+            base_vault: read_pubkey(data, BASE_VAULT_OFFSET),
+            quote_vault: read_pubkey(data, QUOTE_VAULT_OFFSET),
         })
     }
 }
 
-fn load_pubkeys(data: &[u8]) -> (Pubkey, Pubkey, Pubkey, Pubkey) {
-    (
-        Pubkey::new_from_array(data[2664..2696].try_into().unwrap()),
-        Pubkey::new_from_array(data[2696..2728].try_into().unwrap()),
-        Pubkey::new_from_array(data[2736..2768].try_into().unwrap()),
-        Pubkey::new_from_array(data[2768..2800].try_into().unwrap()),
-    )
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::layout::{key, Layout};
+
+    const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+    const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qA1xxoxHSgyfnkZTrNEwE7n5Xt";
+
+    struct PoolFixture {
+        base_vault: Pubkey,
+        quote_vault: Pubkey,
+    }
+
+    /// SOL/USDC pool account. The pricing state ahead of the mints and the 8 bytes between
+    /// the mints and vaults are undocumented, so they hold a marker that must not leak into
+    /// the decoded keys.
+    fn account_bytes(fixture: &PoolFixture) -> Vec<u8> {
+        let mut layout = Layout::new();
+        layout
+            .bytes(&[0xaa; BASE_MINT_OFFSET])
+            .pubkey(&key(SOL_MINT))
+            .pubkey(&key(USDC_MINT))
+            .bytes(&[0xaa; BASE_VAULT_OFFSET - QUOTE_MINT_OFFSET - 32])
+            .pubkey(&fixture.base_vault)
+            .pubkey(&fixture.quote_vault);
+        assert_eq!(layout.len(), MIN_POOL_ACCOUNT_SIZE);
+        layout.finish()
+    }
+
+    fn fixture() -> PoolFixture {
+        PoolFixture {
+            base_vault: Pubkey::new_unique(),
+            quote_vault: Pubkey::new_unique(),
+        }
+    }
+
+    #[test]
+    fn decodes_token_accounts() {
+        let fixture = fixture();
+        let info = SolfiInfo::load_checked(&account_bytes(&fixture)).unwrap();
+
+        assert_eq!(info.base_mint, key(SOL_MINT));
+        assert_eq!(info.quote_mint, key(USDC_MINT));
+        assert_eq!(info.base_vault, fixture.base_vault);
+        assert_eq!(info.quote_vault, fixture.quote_vault);
+    }
+
+    #[test]
+    fn ignores_trailing_bytes() {
+        let fixture = fixture();
+        let mut data = account_bytes(&fixture);
+        data.extend([0xbb; 64]);
+        let info = SolfiInfo::load_checked(&data).unwrap();
+        assert_eq!(info.quote_vault, fixture.quote_vault);
+    }
+
+    #[test]
+    fn rejects_truncated_account() {
+        let data = account_bytes(&fixture());
+        for len in [0, BASE_MINT_OFFSET, QUOTE_VAULT_OFFSET, MIN_POOL_ACCOUNT_SIZE - 1] {
+            assert!(SolfiInfo::load_checked(&data[..len]).is_err(), "len {}", len);
+        }
+    }
 }
@@ -0,0 +1,55 @@
+use crate::dex::solfi::constants::solfi_program_id;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    sysvar,
+};
+
+// Solfi publishes no IDL; the layout follows its swaps on mainnet
+const SWAP_TAG: u8 = 7;
+const BASE_TO_QUOTE: u8 = 0;
+const QUOTE_TO_BASE: u8 = 1;
+
+/// Accounts of a Solfi swap, named from the pool's base/quote sides
+#[derive(Debug, Clone)]
+pub struct SolfiSwapAccounts {
+    pub user: Pubkey,
+    pub pool: Pubkey,
+    pub pool_base_vault: Pubkey,
+    pub pool_quote_vault: Pubkey,
+    pub user_base_token_account: Pubkey,
+    pub user_quote_token_account: Pubkey,
+}
+
+/// Sell exactly `amount_in` of the base mint when `base_to_quote` (of the quote mint
+/// otherwise), failing below `minimum_amount_out`
+pub fn swap(
+    accounts: &SolfiSwapAccounts,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    base_to_quote: bool,
+) -> Instruction {
+    let mut data = vec![SWAP_TAG];
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&minimum_amount_out.to_le_bytes());
+    data.push(if base_to_quote {
+        BASE_TO_QUOTE
+    } else {
+        QUOTE_TO_BASE
+    });
+
+    Instruction {
+        program_id: solfi_program_id(),
+        accounts: vec![
+            AccountMeta::new(accounts.user, true),
+            AccountMeta::new(accounts.pool, false),
+            AccountMeta::new(accounts.pool_base_vault, false),
+            AccountMeta::new(accounts.pool_quote_vault, false),
+            AccountMeta::new(accounts.user_base_token_account, false),
+            AccountMeta::new(accounts.user_quote_token_account, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(sysvar::instructions::ID, false),
+        ],
+        data,
+    }
+}
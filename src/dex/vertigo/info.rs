@@ -2,60 +2,170 @@ use anyhow::Result;
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::pubkey::Pubkey;
 
-use super::constants::vertigo_program_id;
+pub const POOL_DISCRIMINATOR: [u8; 8] = [241, 154, 109, 4, 17, 177, 109, 188];
+/// Discriminator plus the Borsh encoding of `VertigoPool` with a privileged swapper set
+pub const POOL_ACCOUNT_SIZE: usize = 8 + 1 + 32 * 3 + 16 * 3 + 8 * 2 + 1 + FeeParams::MAX_SIZE;
 
-#[derive(Debug, BorshDeserialize, BorshSerialize)]
+#[derive(Debug, Clone, Default, PartialEq, BorshDeserialize, BorshSerialize)]
+pub struct FeeParams {
+    /// Slots over which the launch fee decays towards `royalties_bps`
+    pub normalization_period: u64,
+    pub decay: f64,
+    /// Slot the decay is measured from
+    pub reference: u64,
+    pub royalties_bps: u16,
+    pub privileged_swapper: Option<Pubkey>,
+}
+
+impl FeeParams {
+    pub const MAX_SIZE: usize = 8 + 8 + 8 + 2 + 1 + 32;
+}
+
+#[derive(Debug, Clone, PartialEq, BorshDeserialize, BorshSerialize)]
 pub struct VertigoPool {
+    pub enabled: bool,
+    pub owner: Pubkey,
     pub mint_a: Pubkey,
     pub mint_b: Pubkey,
-    pub owner: Pubkey,
-    // Add other fields as needed based on actual Vertigo pool structure
+    pub token_a_reserves: u128,
+    pub token_b_reserves: u128,
+    /// Virtual liquidity added to the mint A side of the curve
+    pub shift: u128,
+    pub royalties: u64,
+    pub vertigo_fees: u64,
+    pub bump: u8,
+    pub fee_params: FeeParams,
 }
 
 impl VertigoPool {
-    pub fn try_deserialize(data: &mut &[u8]) -> Result<Self> {
-        Self::try_from_slice(data)
+    pub fn load_checked(data: &[u8]) -> Result<Self> {
+        if data.len() < 8 {
+            return Err(anyhow::anyhow!("Invalid data length for VertigoPool"));
+        }
+        if data[..8] != POOL_DISCRIMINATOR {
+            return Err(anyhow::anyhow!("Invalid discriminator for VertigoPool"));
+        }
+
+        // The account is allocated for the largest encoding, so trailing bytes are expected
+        Self::deserialize(&mut &data[8..])
             .map_err(|e| anyhow::anyhow!("Failed to deserialize VertigoPool: {}", e))
     }
-}
 
-#[derive(Debug)]
-pub struct VertigoInfo {
-    pub mint_a: Pubkey,
-    pub mint_b: Pubkey,
-    pub pool: Pubkey,
+    /// Reserves the constant product curve prices against, with `shift` applied to mint A
+    pub fn curve_reserves(&self) -> Option<(u128, u128)> {
+        Some((
+            self.token_a_reserves.checked_add(self.shift)?,
+            self.token_b_reserves,
+        ))
+    }
 }
 
-impl VertigoInfo {
-    pub fn load_checked(data: &[u8], pool: &Pubkey) -> Result<Self> {
-        let mut data_slice = &data[..];
-        let vertigo_pool = VertigoPool::try_deserialize(&mut data_slice)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::layout::{key, Layout};
+
+    const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
 
-        Ok(Self {
-            mint_a: vertigo_pool.mint_a,
-            mint_b: vertigo_pool.mint_b,
-            pool: pool.to_owned(),
-        })
+    struct PoolFixture {
+        owner: Pubkey,
+        mint_b: Pubkey,
+        privileged_swapper: Option<Pubkey>,
     }
 
-    pub fn get_token_and_sol_vaults(&self, base_mint: &str, _sol_mint: &Pubkey) -> (Pubkey, Pubkey) {
-        let token_x_vault = if base_mint == self.mint_a.to_string() {
-            derive_vault_address(&self.pool, &self.mint_b).0
-        } else {
-            derive_vault_address(&self.pool, &self.mint_a).0
-        };
+    impl PoolFixture {
+        fn new() -> Self {
+            Self {
+                owner: Pubkey::new_unique(),
+                mint_b: Pubkey::new_unique(),
+                privileged_swapper: None,
+            }
+        }
+    }
+
+    /// SOL/token `Pool` account, written in the order the Vertigo IDL declares it and padded
+    /// to the allocated size
+    fn account_bytes(fixture: &PoolFixture) -> Vec<u8> {
+        let mut layout = Layout::anchor(POOL_DISCRIMINATOR);
+        layout
+            .bool(true) // enabled
+            .pubkey(&fixture.owner)
+            .pubkey(&key(SOL_MINT)) // mint_a
+            .pubkey(&fixture.mint_b)
+            .u128(12_500_000_000) // token_a_reserves
+            .u128(987_654_321_000_000) // token_b_reserves
+            .u128(100_000_000_000) // shift
+            .u64(4_200_000) // royalties
+            .u64(1_300_000) // vertigo_fees
+            .u8(254) // bump
+            // fee_params
+            .u64(1_000) // normalization_period
+            .f64(10.0) // decay
+            .u64(330_000_000) // reference
+            .u16(100) // royalties_bps
+            .option_pubkey(fixture.privileged_swapper.as_ref());
+        layout.zeros(POOL_ACCOUNT_SIZE - layout.len());
+        layout.finish()
+    }
+
+    #[test]
+    fn decodes_padded_account() {
+        let fixture = PoolFixture::new();
+        let data = account_bytes(&fixture);
+        assert_eq!(data.len(), POOL_ACCOUNT_SIZE);
+
+        let pool = VertigoPool::load_checked(&data).unwrap();
+        assert!(pool.enabled);
+        assert_eq!(pool.owner, fixture.owner);
+        assert_eq!(pool.mint_a, key(SOL_MINT));
+        assert_eq!(pool.mint_b, fixture.mint_b);
+        assert_eq!(pool.token_a_reserves, 12_500_000_000);
+        assert_eq!(pool.token_b_reserves, 987_654_321_000_000);
+        assert_eq!(pool.shift, 100_000_000_000);
+        assert_eq!((pool.royalties, pool.vertigo_fees), (4_200_000, 1_300_000));
+        assert_eq!(pool.bump, 254);
+        assert_eq!(pool.fee_params.normalization_period, 1_000);
+        assert_eq!(pool.fee_params.decay, 10.0);
+        assert_eq!(pool.fee_params.reference, 330_000_000);
+        assert_eq!(pool.fee_params.royalties_bps, 100);
+        assert_eq!(pool.fee_params.privileged_swapper, None);
+    }
 
-        let token_base_vault = if base_mint == self.mint_a.to_string() {
-            derive_vault_address(&self.pool, &self.mint_a).0
-        } else {
-            derive_vault_address(&self.pool, &self.mint_b).0
+    #[test]
+    fn decodes_privileged_swapper() {
+        let swapper = Pubkey::new_unique();
+        let fixture = PoolFixture {
+            privileged_swapper: Some(swapper),
+            ..PoolFixture::new()
         };
+        let data = account_bytes(&fixture);
+        assert_eq!(data.len(), POOL_ACCOUNT_SIZE);
 
-        (token_x_vault, token_base_vault)
+        let decoded = VertigoPool::load_checked(&data).unwrap();
+        assert_eq!(decoded.fee_params.privileged_swapper, Some(swapper));
+    }
+
+    #[test]
+    fn curve_reserves_apply_shift_to_mint_a() {
+        let pool = VertigoPool::load_checked(&account_bytes(&PoolFixture::new())).unwrap();
+        assert_eq!(
+            pool.curve_reserves(),
+            Some((112_500_000_000, 987_654_321_000_000))
+        );
     }
-}
 
-/// Helper function to derive vault PDA
-pub fn derive_vault_address(pool: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
-    Pubkey::find_program_address(&[pool.as_ref(), mint.as_ref()], &vertigo_program_id())
+    #[test]
+    fn rejects_wrong_discriminator() {
+        let mut data = account_bytes(&PoolFixture::new());
+        data[0] ^= 0xff;
+        assert!(VertigoPool::load_checked(&data).is_err());
+    }
+
+    #[test]
+    fn rejects_truncated_account() {
+        let data = account_bytes(&PoolFixture::new());
+        for len in [0, 7, 8, 100, 8 + 1 + 32 * 3 + 16 * 3] {
+            assert!(VertigoPool::load_checked(&data[..len]).is_err(), "len {}", len);
+        }
+    }
 }
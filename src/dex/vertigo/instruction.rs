@@ -0,0 +1,69 @@
+use crate::dex::vertigo::constants::vertigo_program_id;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+
+const BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+const SELL_DISCRIMINATOR: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
+
+/// Accounts shared by `buy` and `sell`, named from the pool's A/B sides
+#[derive(Debug, Clone)]
+pub struct VertigoSwapAccounts {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub owner: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub user_ta_a: Pubkey,
+    pub user_ta_b: Pubkey,
+    pub vault_a: Pubkey,
+    pub vault_b: Pubkey,
+    pub token_program_a: Pubkey,
+    pub token_program_b: Pubkey,
+}
+
+fn swap(
+    discriminator: [u8; 8],
+    accounts: &VertigoSwapAccounts,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Instruction {
+    let mut data = discriminator.to_vec();
+    data.extend_from_slice(&amount_in.to_le_bytes()); // amount
+    data.extend_from_slice(&minimum_amount_out.to_le_bytes()); // limit
+
+    Instruction {
+        program_id: vertigo_program_id(),
+        accounts: vec![
+            AccountMeta::new(accounts.pool, false),
+            AccountMeta::new(accounts.user, true),
+            AccountMeta::new_readonly(accounts.owner, false),
+            AccountMeta::new_readonly(accounts.mint_a, false),
+            AccountMeta::new_readonly(accounts.mint_b, false),
+            AccountMeta::new(accounts.user_ta_a, false),
+            AccountMeta::new(accounts.user_ta_b, false),
+            AccountMeta::new(accounts.vault_a, false),
+            AccountMeta::new(accounts.vault_b, false),
+            AccountMeta::new_readonly(accounts.token_program_a, false),
+            AccountMeta::new_readonly(accounts.token_program_b, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data,
+    }
+}
+
+/// `buy`: sell exactly `amount_in` of mint A for mint B, failing below `minimum_amount_out`
+pub fn buy(accounts: &VertigoSwapAccounts, amount_in: u64, minimum_amount_out: u64) -> Instruction {
+    swap(BUY_DISCRIMINATOR, accounts, amount_in, minimum_amount_out)
+}
+
+/// `sell`: sell exactly `amount_in` of mint B for mint A, failing below `minimum_amount_out`
+pub fn sell(
+    accounts: &VertigoSwapAccounts,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Instruction {
+    swap(SELL_DISCRIMINATOR, accounts, amount_in, minimum_amount_out)
+}
@@ -1,5 +1,6 @@
 pub mod constants;
 pub mod info;
+pub mod instruction;
 pub mod utils;
 
 pub use utils::derive_vault_address;
@@ -0,0 +1,81 @@
+use crate::{
+    chain::constants::memo_program_id,
+    dex::whirlpool::{
+        constants::whirlpool_program_id,
+        tick_math::{MAX_SQRT_PRICE, MIN_SQRT_PRICE},
+    },
+};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+const SWAP_V2_DISCRIMINATOR: [u8; 8] = [43, 4, 237, 11, 26, 201, 30, 98];
+
+/// Accounts of a `swap_v2` other than the program's own
+#[derive(Debug, Clone)]
+pub struct SwapV2Accounts {
+    pub token_program_a: Pubkey,
+    pub token_program_b: Pubkey,
+    pub token_authority: Pubkey,
+    pub whirlpool: Pubkey,
+    pub token_mint_a: Pubkey,
+    pub token_mint_b: Pubkey,
+    pub token_owner_account_a: Pubkey,
+    pub token_vault_a: Pubkey,
+    pub token_owner_account_b: Pubkey,
+    pub token_vault_b: Pubkey,
+    /// Three tick arrays; the program orders them itself and skips uninitialized ones
+    pub tick_arrays: [Pubkey; 3],
+    pub oracle: Pubkey,
+}
+
+/// `swap_v2` exact in: sell exactly `amount_in` of mint A when `a_to_b` (of mint B otherwise),
+/// failing below `minimum_amount_out`. The price limit is the end of the range.
+pub fn swap_v2(
+    accounts: &SwapV2Accounts,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    a_to_b: bool,
+) -> Instruction {
+    let sqrt_price_limit = if a_to_b {
+        MIN_SQRT_PRICE
+    } else {
+        MAX_SQRT_PRICE
+    };
+
+    let mut data = SWAP_V2_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&minimum_amount_out.to_le_bytes());
+    data.extend_from_slice(&sqrt_price_limit.to_le_bytes());
+    data.push(1); // amount_specified_is_input
+    data.push(a_to_b as u8);
+    data.push(0); // remaining_accounts_info: None
+
+    let mut metas = vec![
+        AccountMeta::new_readonly(accounts.token_program_a, false),
+        AccountMeta::new_readonly(accounts.token_program_b, false),
+        AccountMeta::new_readonly(memo_program_id(), false),
+        AccountMeta::new_readonly(accounts.token_authority, true),
+        AccountMeta::new(accounts.whirlpool, false),
+        AccountMeta::new_readonly(accounts.token_mint_a, false),
+        AccountMeta::new_readonly(accounts.token_mint_b, false),
+        AccountMeta::new(accounts.token_owner_account_a, false),
+        AccountMeta::new(accounts.token_vault_a, false),
+        AccountMeta::new(accounts.token_owner_account_b, false),
+        AccountMeta::new(accounts.token_vault_b, false),
+    ];
+    metas.extend(
+        accounts
+            .tick_arrays
+            .iter()
+            .map(|tick_array| AccountMeta::new(*tick_array, false)),
+    );
+    metas.push(AccountMeta::new(accounts.oracle, false));
+
+    Instruction {
+        program_id: whirlpool_program_id(),
+        accounts: metas,
+        data,
+    }
+}
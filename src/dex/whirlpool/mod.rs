@@ -1,5 +1,9 @@
 pub mod constants;
+pub mod instruction;
 pub mod state;
+pub mod swap;
+pub mod tick_math;
+pub mod tick_sequence;
 pub mod utils;
 
 use crate::dex::whirlpool::state::{Whirlpool, TICK_ARRAY_SIZE};
@@ -69,13 +69,66 @@ pub struct Tick {
 }
 
 impl Tick {
+    pub const LEN: usize = 113;
+
     pub fn check_is_valid_start_tick(tick_index: i32, tick_spacing: u16) -> bool {
         tick_index % (tick_spacing as i32 * TICK_ARRAY_SIZE as i32) == 0
     }
+
+    fn deserialize(data: &[u8]) -> Self {
+        let read_u128 =
+            |offset: usize| u128::from_le_bytes(data[offset..offset + 16].try_into().unwrap());
+
+        Tick {
+            initialized: data[0] != 0,
+            liquidity_net: i128::from_le_bytes(data[1..17].try_into().unwrap()),
+            liquidity_gross: read_u128(17),
+            fee_growth_outside_a: read_u128(33),
+            fee_growth_outside_b: read_u128(49),
+            reward_growths_outside: [read_u128(65), read_u128(81), read_u128(97)],
+        }
+    }
 }
 
 pub const TICK_ARRAY_SIZE: usize = 88;
 
+impl TickArray {
+    pub const LEN: usize = 8 + 4 + Tick::LEN * TICK_ARRAY_SIZE + 32;
+    pub const DISCRIMINATOR: [u8; 8] = [69, 97, 189, 190, 110, 7, 66, 187];
+
+    pub fn try_deserialize(data: &[u8]) -> Result<Self> {
+        if data.len() != Self::LEN {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("unexpected TickArray length {}", data.len()),
+            ));
+        }
+        if data[..8] != Self::DISCRIMINATOR {
+            return Err(Error::new(ErrorKind::InvalidData, "invalid TickArray discriminator"));
+        }
+
+        let data = &data[8..];
+
+        let start_tick_index = i32::from_le_bytes(data[0..4].try_into().unwrap());
+
+        let mut ticks = [Tick::default(); TICK_ARRAY_SIZE];
+        for (i, tick) in ticks.iter_mut().enumerate() {
+            let offset = 4 + i * Tick::LEN;
+            *tick = Tick::deserialize(&data[offset..offset + Tick::LEN]);
+        }
+
+        let whirlpool_offset = 4 + TICK_ARRAY_SIZE * Tick::LEN;
+        let mut whirlpool = [0u8; 32];
+        whirlpool.copy_from_slice(&data[whirlpool_offset..whirlpool_offset + 32]);
+
+        Ok(TickArray {
+            start_tick_index,
+            ticks,
+            whirlpool: Pubkey::new_from_array(whirlpool),
+        })
+    }
+}
+
 impl Whirlpool {
     pub fn try_deserialize(data: &[u8]) -> Result<Self> {
         if data.len() < Self::LEN {
@@ -0,0 +1,328 @@
+use crate::dex::clmm::{self, NextTick, SwapParams, SwapResult, TickTraversal};
+use crate::dex::whirlpool::constants::{MAX_TICK_INDEX, MIN_TICK_INDEX};
+use crate::dex::whirlpool::state::{Tick, TickArray, Whirlpool, TICK_ARRAY_SIZE};
+use crate::dex::whirlpool::tick_math::{
+    sqrt_price_from_tick_index, tick_index_from_sqrt_price, MAX_SQRT_PRICE, MIN_SQRT_PRICE,
+};
+use crate::dex::whirlpool::tick_sequence::TickArraySequence;
+use anyhow::{anyhow, Result};
+
+/// Walks the loaded tick arrays the way the Whirlpool swap instruction does: starting from the
+/// array holding the current tick and moving one array at a time in the swap direction
+pub struct WhirlpoolTickTraversal<'a> {
+    /// In swap order
+    arrays: Vec<&'a TickArray>,
+    tick_spacing: i32,
+    array_index: usize,
+    last_array_read: usize,
+}
+
+impl<'a> WhirlpoolTickTraversal<'a> {
+    pub fn new(sequence: &'a TickArraySequence, tick_current: i32, a_to_b: bool) -> Result<Self> {
+        let tick_spacing = sequence.tick_spacing() as i32;
+        let ticks_in_array = TICK_ARRAY_SIZE as i32 * tick_spacing;
+        // Moving up, the search starts one tick spacing above the current tick
+        let search_tick = if a_to_b {
+            tick_current
+        } else {
+            tick_current + tick_spacing
+        };
+
+        let arrays = sequence.arrays();
+        let first = arrays
+            .iter()
+            .position(|array| {
+                search_tick >= array.start_tick_index
+                    && search_tick < array.start_tick_index + ticks_in_array
+            })
+            .ok_or_else(|| anyhow!("No loaded tick array holds tick {}", tick_current))?;
+        let arrays = if a_to_b {
+            arrays[..=first].iter().rev().collect()
+        } else {
+            arrays[first..].iter().collect()
+        };
+
+        Ok(Self {
+            arrays,
+            tick_spacing,
+            array_index: 0,
+            last_array_read: 0,
+        })
+    }
+
+    fn ticks_in_array(&self) -> i32 {
+        TICK_ARRAY_SIZE as i32 * self.tick_spacing
+    }
+
+    /// Next initialized tick within one array, searching as `TickArray::get_next_init_tick_index`
+    fn next_initialized_in_array(
+        &self,
+        array: &'a TickArray,
+        tick_index: i32,
+        a_to_b: bool,
+    ) -> Result<Option<(i32, &'a Tick)>> {
+        let shift = if a_to_b { 0 } else { self.tick_spacing };
+        let lower = array.start_tick_index - shift;
+        if tick_index < lower || tick_index >= lower + self.ticks_in_array() {
+            return Err(anyhow!(
+                "Tick {} is outside tick array {}",
+                tick_index,
+                array.start_tick_index
+            ));
+        }
+
+        let offset = (tick_index - array.start_tick_index).div_euclid(self.tick_spacing);
+        let found = if a_to_b {
+            (0..=offset)
+                .rev()
+                .find(|&i| array.ticks[i as usize].initialized)
+        } else {
+            (offset + 1..TICK_ARRAY_SIZE as i32).find(|&i| array.ticks[i as usize].initialized)
+        };
+        Ok(found.map(|i| {
+            (
+                array.start_tick_index + i * self.tick_spacing,
+                &array.ticks[i as usize],
+            )
+        }))
+    }
+}
+
+impl TickTraversal for WhirlpoolTickTraversal<'_> {
+    fn next_tick(&mut self, tick_current: i32, a_to_b: bool) -> Result<NextTick> {
+        let ticks_in_array = self.ticks_in_array();
+        let mut search_index = tick_current;
+        let mut i = self.array_index;
+
+        loop {
+            let array = *self.arrays.get(i).ok_or_else(|| {
+                anyhow!(
+                    "Swap needs more than the {} loaded tick arrays",
+                    self.arrays.len()
+                )
+            })?;
+            self.last_array_read = self.last_array_read.max(i);
+            self.array_index = i;
+
+            if let Some((tick_index, tick)) =
+                self.next_initialized_in_array(array, search_index, a_to_b)?
+            {
+                return Ok(NextTick {
+                    tick_index,
+                    liquidity_net: Some(tick.liquidity_net),
+                });
+            }
+
+            let array_end = array.start_tick_index + ticks_in_array;
+            let boundary = if a_to_b && array.start_tick_index <= MIN_TICK_INDEX {
+                Some(MIN_TICK_INDEX)
+            } else if !a_to_b && array_end > MAX_TICK_INDEX {
+                Some(MAX_TICK_INDEX)
+            } else if i + 1 == self.arrays.len() {
+                // The swap may still fill within the last array before stepping past it
+                Some(if a_to_b {
+                    array.start_tick_index
+                } else {
+                    array_end - 1
+                })
+            } else {
+                None
+            };
+            if let Some(tick_index) = boundary {
+                return Ok(NextTick {
+                    tick_index,
+                    liquidity_net: None,
+                });
+            }
+
+            search_index = if a_to_b {
+                array.start_tick_index - 1
+            } else {
+                array_end - 1
+            };
+            i += 1;
+        }
+    }
+
+    fn sqrt_price_at_tick(&self, tick_index: i32) -> Result<u128> {
+        Ok(sqrt_price_from_tick_index(tick_index))
+    }
+
+    fn tick_at_sqrt_price(&self, sqrt_price: u128) -> Result<i32> {
+        tick_index_from_sqrt_price(sqrt_price)
+    }
+
+    fn tick_arrays_touched(&self) -> Vec<i32> {
+        self.arrays[..=self.last_array_read]
+            .iter()
+            .map(|array| array.start_tick_index)
+            .collect()
+    }
+}
+
+/// Simulate a swap against a whirlpool with its tick arrays loaded, reproducing the program's
+/// amounts, ending price and tick. `sqrt_price_limit` defaults to the end of the price range.
+pub fn simulate_swap(
+    whirlpool: &Whirlpool,
+    tick_arrays: &TickArraySequence,
+    amount: u64,
+    amount_specified_is_input: bool,
+    a_to_b: bool,
+    sqrt_price_limit: Option<u128>,
+) -> Result<SwapResult> {
+    if tick_arrays.tick_spacing() != whirlpool.tick_spacing {
+        return Err(anyhow!(
+            "Tick arrays use spacing {} but the whirlpool uses {}",
+            tick_arrays.tick_spacing(),
+            whirlpool.tick_spacing
+        ));
+    }
+    let limit_given = sqrt_price_limit.is_some();
+    let sqrt_price_limit = sqrt_price_limit.unwrap_or(if a_to_b {
+        MIN_SQRT_PRICE
+    } else {
+        MAX_SQRT_PRICE
+    });
+    if !(MIN_SQRT_PRICE..=MAX_SQRT_PRICE).contains(&sqrt_price_limit) {
+        return Err(anyhow!(
+            "Sqrt price limit {} is out of range",
+            sqrt_price_limit
+        ));
+    }
+    if amount == 0 {
+        return Err(anyhow!("Swap amount must be positive"));
+    }
+
+    let mut traversal =
+        WhirlpoolTickTraversal::new(tick_arrays, whirlpool.tick_current_index, a_to_b)?;
+    let result = clmm::swap(
+        &mut traversal,
+        SwapParams {
+            sqrt_price: whirlpool.sqrt_price,
+            tick_current: whirlpool.tick_current_index,
+            liquidity: whirlpool.liquidity,
+            fee_rate: whirlpool.fee_rate as u32,
+            amount,
+            amount_specified_is_input,
+            a_to_b,
+            sqrt_price_limit,
+        },
+    )?;
+
+    // The program rejects exact-out swaps it cannot fill unless the caller set a price limit
+    if !amount_specified_is_input && !limit_given && result.amount_remaining > 0 {
+        return Err(anyhow!(
+            "Swap can only fill {} of {}",
+            result.amount_out,
+            amount
+        ));
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::whirlpool::state::WhirlpoolRewardInfo;
+    use solana_program::pubkey::Pubkey;
+
+    const TICK_SPACING: u16 = 64;
+    const WIDE_LIQUIDITY: u128 = 1_000_000_000_000;
+    const NARROW_LIQUIDITY: u128 = 500_000_000_000;
+
+    fn whirlpool(tick_current_index: i32, liquidity: u128) -> Whirlpool {
+        let reward_info = WhirlpoolRewardInfo {
+            mint: Pubkey::default(),
+            vault: Pubkey::default(),
+            authority: Pubkey::default(),
+            emissions_per_second_x64: 0,
+            growth_global_x64: 0,
+        };
+        Whirlpool {
+            whirlpools_config: Pubkey::default(),
+            whirlpool_bump: [0],
+            tick_spacing: TICK_SPACING,
+            tick_spacing_seed: TICK_SPACING.to_le_bytes(),
+            fee_rate: 3_000,
+            protocol_fee_rate: 0,
+            liquidity,
+            sqrt_price: sqrt_price_from_tick_index(tick_current_index),
+            tick_current_index,
+            protocol_fee_owed_a: 0,
+            protocol_fee_owed_b: 0,
+            token_mint_a: Pubkey::default(),
+            token_vault_a: Pubkey::default(),
+            fee_growth_global_a: 0,
+            token_mint_b: Pubkey::default(),
+            token_vault_b: Pubkey::default(),
+            fee_growth_global_b: 0,
+            reward_last_updated_timestamp: 0,
+            reward_infos: [reward_info; 3],
+        }
+    }
+
+    fn tick_array(start_tick_index: i32, initialized: &[(i32, i128)]) -> TickArray {
+        let mut ticks = [Tick::default(); TICK_ARRAY_SIZE];
+        for &(tick_index, liquidity_net) in initialized {
+            let tick = &mut ticks[((tick_index - start_tick_index) / TICK_SPACING as i32) as usize];
+            tick.initialized = true;
+            tick.liquidity_net = liquidity_net;
+            tick.liquidity_gross = liquidity_net.unsigned_abs();
+        }
+        TickArray {
+            start_tick_index,
+            ticks,
+            whirlpool: Pubkey::default(),
+        }
+    }
+
+    /// A wide position over [-10240, 5120] and a narrow one over [1280, 5120], with the middle
+    /// of the three arrays empty
+    fn sequence() -> TickArraySequence {
+        let total = (WIDE_LIQUIDITY + NARROW_LIQUIDITY) as i128;
+        TickArraySequence::new(
+            vec![
+                tick_array(0, &[(1280, NARROW_LIQUIDITY as i128), (5120, -total)]),
+                tick_array(-5632, &[]),
+                tick_array(-11264, &[(-10240, WIDE_LIQUIDITY as i128)]),
+            ],
+            TICK_SPACING,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn swap_crosses_tick_arrays() {
+        let pool = whirlpool(3_000, WIDE_LIQUIDITY + NARROW_LIQUIDITY);
+        let limit = sqrt_price_from_tick_index(-8_000);
+        let result = simulate_swap(
+            &pool,
+            &sequence(),
+            1_000_000_000_000_000,
+            true,
+            true,
+            Some(limit),
+        )
+        .unwrap();
+
+        assert_eq!(result.amount_in, 671_742_444_253);
+        assert_eq!(result.amount_out, 539_360_348_950);
+        assert_eq!(result.fee_amount, 2_015_227_334);
+        assert_eq!(result.sqrt_price, limit);
+        assert_eq!(result.tick_current, -8_000);
+        assert_eq!(result.liquidity, WIDE_LIQUIDITY);
+        assert_eq!(result.tick_arrays, vec![0, -5632, -11264]);
+    }
+
+    #[test]
+    fn swap_needs_the_next_tick_array() {
+        let pool = whirlpool(3_000, WIDE_LIQUIDITY + NARROW_LIQUIDITY);
+        let sequence = TickArraySequence::new(
+            vec![tick_array(0, &[(1280, NARROW_LIQUIDITY as i128)])],
+            TICK_SPACING,
+        )
+        .unwrap();
+        // Without a price limit the exact-out swap cannot be filled within one array
+        assert!(simulate_swap(&pool, &sequence, 600_000_000_000, false, true, None).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+use crate::dex::clmm::{math::U256, swap::tick_at_sqrt_price};
+use crate::dex::whirlpool::constants::{MAX_TICK_INDEX, MIN_TICK_INDEX};
+use anyhow::Result;
+
+pub const MIN_SQRT_PRICE: u128 = 4295048016;
+pub const MAX_SQRT_PRICE: u128 = 79226673515401279992447579055;
+
+// floor(2^96 * 1.0001^(2^i / 2)) for positive ticks
+const POSITIVE_TICK_RATIOS_X96: [u128; 19] = [
+    79232123823359799118286999567,
+    79236085330515764027303304731,
+    79244008939048815603706035061,
+    79259858533276714757314932305,
+    79291567232598584799939703904,
+    79355022692464371645785046466,
+    79482085999252804386437311141,
+    79736823300114093921829183326,
+    80248749790819932309965073892,
+    81282483887344747381513967011,
+    83390072131320151908154831281,
+    87770609709833776024991924138,
+    97234110755111693312479820773,
+    119332217159966728226237229890,
+    179736315981702064433883588727,
+    407748233172238350107850275304,
+    2098478828474011932436660412517,
+    55581415166113811149459800483533,
+    38992368544603139932233054999993551,
+];
+
+// floor(2^64 / 1.0001^(2^i / 2)) for negative ticks
+const NEGATIVE_TICK_RATIOS_X64: [u128; 19] = [
+    18445821805675392311,
+    18444899583751176498,
+    18443055278223354162,
+    18439367220385604838,
+    18431993317065449817,
+    18417254355718160513,
+    18387811781193591352,
+    18329067761203520168,
+    18212142134806087854,
+    17980523815641551639,
+    17526086738831147013,
+    16651378430235024244,
+    15030750278693429944,
+    12247334978882834399,
+    8131365268884726200,
+    3584323654723342297,
+    696457651847595233,
+    26294789957452057,
+    37481735321082,
+];
+
+/// Q64.64 sqrt price at `tick`, computed the way the Whirlpool program does
+pub fn sqrt_price_from_tick_index(tick: i32) -> u128 {
+    if tick >= 0 {
+        let mut ratio = if tick & 1 != 0 {
+            U256::from(POSITIVE_TICK_RATIOS_X96[0])
+        } else {
+            U256::one() << 96
+        };
+        for (bit, factor) in POSITIVE_TICK_RATIOS_X96.iter().enumerate().skip(1) {
+            if tick & (1 << bit) != 0 {
+                ratio = (ratio * U256::from(*factor)) >> 96;
+            }
+        }
+        (ratio >> 32).low_u128()
+    } else {
+        let abs_tick = tick.unsigned_abs();
+        let mut ratio = if abs_tick & 1 != 0 {
+            NEGATIVE_TICK_RATIOS_X64[0]
+        } else {
+            1u128 << 64
+        };
+        for (bit, factor) in NEGATIVE_TICK_RATIOS_X64.iter().enumerate().skip(1) {
+            if abs_tick & (1 << bit) != 0 {
+                ratio = (ratio * factor) >> 64;
+            }
+        }
+        ratio
+    }
+}
+
+/// Largest tick whose sqrt price does not exceed `sqrt_price`
+pub fn tick_index_from_sqrt_price(sqrt_price: u128) -> Result<i32> {
+    tick_at_sqrt_price(sqrt_price, MIN_TICK_INDEX, MAX_TICK_INDEX, |tick| {
+        Ok(sqrt_price_from_tick_index(tick))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sqrt_price_at_range_ends() {
+        assert_eq!(sqrt_price_from_tick_index(MIN_TICK_INDEX), MIN_SQRT_PRICE);
+        assert_eq!(sqrt_price_from_tick_index(MAX_TICK_INDEX), MAX_SQRT_PRICE);
+        assert_eq!(sqrt_price_from_tick_index(0), 1 << 64);
+    }
+
+    #[test]
+    fn tick_from_sqrt_price_inverts() {
+        assert_eq!(
+            tick_index_from_sqrt_price(MIN_SQRT_PRICE).unwrap(),
+            MIN_TICK_INDEX
+        );
+        assert_eq!(
+            tick_index_from_sqrt_price(MAX_SQRT_PRICE).unwrap(),
+            MAX_TICK_INDEX
+        );
+        assert_eq!(tick_index_from_sqrt_price(1 << 64).unwrap(), 0);
+        assert_eq!(tick_index_from_sqrt_price((1 << 64) - 1).unwrap(), -1);
+        assert!(tick_index_from_sqrt_price(MIN_SQRT_PRICE - 1).is_err());
+    }
+}
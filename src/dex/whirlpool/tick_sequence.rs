@@ -0,0 +1,126 @@
+use crate::dex::whirlpool::state::{Tick, TickArray, TICK_ARRAY_SIZE};
+use anyhow::{anyhow, Result};
+
+/// Consecutive tick arrays of one Whirlpool, searchable for initialized ticks in either
+/// swap direction
+#[derive(Clone, Debug)]
+pub struct TickArraySequence {
+    arrays: Vec<TickArray>,
+    tick_spacing: u16,
+}
+
+impl TickArraySequence {
+    /// Build a sequence from tick arrays given in any order. The arrays must belong to the same
+    /// pool and cover a contiguous tick range.
+    pub fn new(mut arrays: Vec<TickArray>, tick_spacing: u16) -> Result<Self> {
+        if arrays.is_empty() {
+            return Err(anyhow!("Tick array sequence needs at least one array"));
+        }
+        if tick_spacing == 0 {
+            return Err(anyhow!("Tick spacing must be positive"));
+        }
+
+        arrays.sort_by_key(|array| array.start_tick_index);
+        let ticks_in_array = TICK_ARRAY_SIZE as i32 * tick_spacing as i32;
+        let whirlpool = arrays[0].whirlpool;
+
+        for (i, array) in arrays.iter().enumerate() {
+            if !Tick::check_is_valid_start_tick(array.start_tick_index, tick_spacing) {
+                return Err(anyhow!(
+                    "Invalid tick array start index {} for tick spacing {}",
+                    array.start_tick_index,
+                    tick_spacing
+                ));
+            }
+            if array.whirlpool != whirlpool {
+                return Err(anyhow!("Tick arrays belong to different whirlpools"));
+            }
+            if i > 0 && array.start_tick_index != arrays[i - 1].start_tick_index + ticks_in_array {
+                return Err(anyhow!(
+                    "Tick arrays are not consecutive: {} follows {}",
+                    array.start_tick_index,
+                    arrays[i - 1].start_tick_index
+                ));
+            }
+        }
+
+        Ok(Self {
+            arrays,
+            tick_spacing,
+        })
+    }
+
+    /// Arrays in ascending tick order
+    pub fn arrays(&self) -> &[TickArray] {
+        &self.arrays
+    }
+
+    pub fn tick_spacing(&self) -> u16 {
+        self.tick_spacing
+    }
+
+    /// First tick index covered by the sequence
+    pub fn start_tick_index(&self) -> i32 {
+        self.arrays[0].start_tick_index
+    }
+
+    /// One past the last tick index covered by the sequence
+    pub fn end_tick_index(&self) -> i32 {
+        self.start_tick_index() + self.slot_count() as i32 * self.tick_spacing as i32
+    }
+
+    fn slot_count(&self) -> usize {
+        self.arrays.len() * TICK_ARRAY_SIZE
+    }
+
+    fn tick_at(&self, slot: usize) -> &Tick {
+        &self.arrays[slot / TICK_ARRAY_SIZE].ticks[slot % TICK_ARRAY_SIZE]
+    }
+
+    fn tick_index_at(&self, slot: usize) -> i32 {
+        self.start_tick_index() + slot as i32 * self.tick_spacing as i32
+    }
+
+    /// Next initialized tick from `tick_index` in the swap direction, or `None` when the
+    /// sequence runs out first.
+    ///
+    /// Moving down (`a_to_b`) the tick at `tick_index` itself is included; moving up the search
+    /// starts strictly above it, matching how the program picks the next tick to cross.
+    pub fn next_initialized_tick(&self, tick_index: i32, a_to_b: bool) -> Option<(i32, &Tick)> {
+        let spacing = self.tick_spacing as i32;
+        // Slot holding tick_index, rounded towards negative infinity
+        let slot = (tick_index - self.start_tick_index()).div_euclid(spacing);
+
+        if a_to_b {
+            if slot < 0 {
+                return None;
+            }
+            let last = (slot as usize).min(self.slot_count() - 1);
+            (0..=last)
+                .rev()
+                .find(|&slot| self.tick_at(slot).initialized)
+                .map(|slot| (self.tick_index_at(slot), self.tick_at(slot)))
+        } else {
+            let first = (slot + 1).max(0) as usize;
+            (first..self.slot_count())
+                .find(|&slot| self.tick_at(slot).initialized)
+                .map(|slot| (self.tick_index_at(slot), self.tick_at(slot)))
+        }
+    }
+
+    /// Every initialized tick met when moving from `tick_index` in the swap direction, in the
+    /// order a swap would cross them
+    pub fn initialized_ticks(
+        &self,
+        tick_index: i32,
+        a_to_b: bool,
+    ) -> impl Iterator<Item = (i32, &Tick)> + '_ {
+        let mut cursor = Some(tick_index);
+        std::iter::from_fn(move || {
+            let (index, tick) = self.next_initialized_tick(cursor?, a_to_b)?;
+            // Crossing a tick downwards leaves the price just below it
+            cursor = if a_to_b { index.checked_sub(1) } else { Some(index) };
+            Some((index, tick))
+        })
+    }
+}
@@ -1,13 +1,17 @@
 use solana_mev_bot::{
     chain::{
+        arbitrage::{best_two_pool_plan, ExecutionCosts},
+        compute_units::ComputeUnitEstimator,
+        pools::PoolKind,
+        route::{find_cycles, TokenGraph},
         token_fetch::{TokenFetchConfig, TokenFetcher},
         token_price::MarketDataFetcher,
     },
     config::Config,
 };
 use solana_client::rpc_client::RpcClient;
-use solana_sdk::{signature::Keypair, signer::Signer};
-use std::sync::Arc;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+use std::{str::FromStr, sync::Arc};
 use tracing_subscriber::{EnvFilter, FmtSubscriber};
 
 #[tokio::main]
@@ -30,6 +34,7 @@ async fn main() {
     println!("Configuration loaded successfully!");
     println!("RPC URL: {}", config.rpc.url);
     println!("Compute unit limit: {}", config.bot.compute_unit_limit);
+    println!("Executor program: {}", config.executor.program_id);
 
     // Parse wallet private key and derive wallet address
     let wallet_keypair = Keypair::from_base58_string(&config.wallet.private_key);
@@ -39,12 +44,16 @@ async fn main() {
 
     // Initialize RPC client
     let rpc_client = Arc::new(RpcClient::new(config.rpc.url.clone()));
+    if let Err(e) = config.executor.check_pool(&rpc_client) {
+        eprintln!("Invalid executor configuration: {:#}", e);
+        return;
+    }
 
     // Initialize enhanced token fetcher
     let token_fetch_config = TokenFetchConfig {
         max_retries: 10,
         retry_delay_ms: 1000,
-        batch_size: 10,
+        batch_size: 100,
         timeout_seconds: 30,
         enable_caching: true,
         cache_ttl_seconds: 300,
@@ -53,7 +62,7 @@ async fn main() {
     let mut token_fetcher = TokenFetcher::new(rpc_client.clone(), token_fetch_config);
 
     // Initialize market data fetcher
-    let mut market_fetcher = MarketDataFetcher::new(rpc_client.clone());
+    let mut market_fetcher = MarketDataFetcher::new();
 
     // Process each mint configuration
     for mint_config in &config.routing.mint_config_list {
@@ -77,11 +86,18 @@ async fn main() {
             )
             .await
         {
-            Ok(pool_data) => {
+            Ok(result) => {
+                let pool_data = result.pool_data;
                 println!("Successfully loaded pool data for mint: {}", mint_config.mint);
-                println!("  - Raydium pools: {}", pool_data.raydium_pools.len());
-                println!("  - Pump pools: {}", pool_data.pump_pools.len());
-                println!("  - Whirlpool pools: {}", pool_data.whirlpool_pools.len());
+                println!("  - Raydium pools: {}", pool_data.pool_count(PoolKind::Raydium));
+                println!("  - Pump pools: {}", pool_data.pool_count(PoolKind::Pump));
+                println!("  - Whirlpool pools: {}", pool_data.pool_count(PoolKind::Whirlpool));
+                for failure in &result.failures {
+                    println!(
+                        "  ! Skipped {} pool {}: {}",
+                        failure.kind, failure.pool, failure.error
+                    );
+                }
 
                 // Fetch token price
                 match market_fetcher.fetch_token_price(&mint_config.mint).await {
@@ -96,7 +112,9 @@ async fn main() {
                     }
                 }
                 println!("-------\n");
-                println!("pool_data: {:?}", pool_data);
+                for pool in pool_data.pools() {
+                    println!("  - {} pool {}", pool.kind(), pool.address());
+                }
                 println!("-------\n");
 
                 // Calculate arbitrage opportunities
@@ -116,13 +134,15 @@ async fn main() {
                             );
                             for (i, opp) in opportunities.iter().enumerate() {
                                 println!(
-                                    "  {}. {}: Buy on {} at {:.6}, Sell on {} at {:.6} ({}% profit)",
+                                    "  {}. {}: Buy on {} {} at {:.6}, Sell on {} {} at {:.6} ({}% profit)",
                                     i + 1,
                                     opp.token_mint,
-                                    opp.best_buy_dex,
-                                    opp.best_buy_price,
-                                    opp.best_sell_dex,
-                                    opp.best_sell_price,
+                                    opp.buy.kind,
+                                    opp.buy.pool,
+                                    opp.buy.price,
+                                    opp.sell.kind,
+                                    opp.sell.pool,
+                                    opp.sell.price,
                                     opp.potential_profit_percent
                                 );
                             }
@@ -144,10 +164,21 @@ async fn main() {
     println!("Monitoring interval: 2 seconds");
     println!("Price threshold: 0.5% minimum profit");
     
+    let execution_costs = ExecutionCosts::from_config(&config);
+    let compute_units = ComputeUnitEstimator::from_config(&config);
+    let base_mint = match Pubkey::from_str(&config.routing.base_mint) {
+        Ok(base_mint) => base_mint,
+        Err(e) => {
+            eprintln!("Invalid routing base mint {}: {}", config.routing.base_mint, e);
+            return;
+        }
+    };
+
     // Monitoring interval - check for opportunities every 2 seconds
     let monitoring_interval_ms = 2000u64; // 2 seconds
     
     loop {
+        let mut loaded_pool_data = Vec::new();
         for mint_config in &config.routing.mint_config_list {
             // Fetch fresh pool data
             match token_fetcher
@@ -167,7 +198,14 @@ async fn main() {
                 )
                 .await
             {
-                Ok(pool_data) => {
+                Ok(result) => {
+                    // Cached pool data keeps its layout, but its reserves and ticks go stale
+                    let mut pool_data = result.pool_data;
+                    if let Err(e) = token_fetcher.refresh_pool_data(&mut pool_data).await {
+                        println!("Failed to refresh pools for {}: {}", mint_config.mint, e);
+                        continue;
+                    }
+
                     // Calculate arbitrage opportunities
                     match market_fetcher
                         .calculate_arbitrage_opportunities(&pool_data)
@@ -181,13 +219,15 @@ async fn main() {
                                 );
                                 for (i, opp) in opportunities.iter().enumerate() {
                                     println!(
-                                        "  {}. {}: Buy on {} at {:.6}, Sell on {} at {:.6} ({:.2}% profit)",
+                                        "  {}. {}: Buy on {} {} at {:.6}, Sell on {} {} at {:.6} ({:.2}% profit)",
                                         i + 1,
                                         opp.token_mint,
-                                        opp.best_buy_dex,
-                                        opp.best_buy_price,
-                                        opp.best_sell_dex,
-                                        opp.best_sell_price,
+                                        opp.buy.kind,
+                                        opp.buy.pool,
+                                        opp.buy.price,
+                                        opp.sell.kind,
+                                        opp.sell.pool,
+                                        opp.sell.price,
                                         opp.potential_profit_percent
                                     );
                                 }
@@ -197,12 +237,57 @@ async fn main() {
                             eprintln!("Failed to calculate arbitrage opportunities: {}", e);
                         }
                     }
+
+                    if let Some(plan) = best_two_pool_plan(
+                        &pool_data,
+                        config.bot.max_trade_lamports,
+                        &execution_costs,
+                        &compute_units,
+                    ) {
+                        println!(
+                            "  Plan: buy on {} {} with {} lamports, sell on {} {}: {} lamports expected profit ({} gross, {} costs)",
+                            plan.buy_kind,
+                            plan.buy_pool,
+                            plan.amount_in,
+                            plan.sell_kind,
+                            plan.sell_pool,
+                            plan.expected_profit,
+                            plan.gross_profit,
+                            plan.execution_cost
+                        );
+                    }
+                    loaded_pool_data.push(pool_data);
                 }
                 Err(e) => {
                     eprintln!("Failed to load pool data for mint {}: {}", mint_config.mint, e);
                 }
             }
         }
+
+        // Cycles through the base mint across every mint's pools
+        let graph = TokenGraph::new(&loaded_pool_data);
+        for route in find_cycles(
+            &graph,
+            &base_mint,
+            config.bot.max_trade_lamports,
+            &execution_costs,
+            &compute_units,
+        )
+        .into_iter()
+        .filter(|route| route.clears_floor(config.bot.min_profit))
+        {
+            let path: Vec<String> = route
+                .legs
+                .iter()
+                .map(|leg| format!("{} {} -> {}", leg.kind, leg.pool, leg.output_mint))
+                .collect();
+            println!(
+                "  Route: {} in, {} expected profit via {}",
+                route.amount_in,
+                route.expected_profit,
+                path.join(", ")
+            );
+        }
         
         // Wait before next monitoring cycle
         tokio::time::sleep(tokio::time::Duration::from_millis(monitoring_interval_ms)).await;